@@ -0,0 +1,12 @@
+#! /usr/bin/env topal
+use language (
+  version is v0.1
+)
+
+# Demonstrates conditional, counted, logging, and function-entry breakpoints.
+double is fn ( x : Int ) -> Int
+  x * 2
+a is double 3
+b is double 4
+c is double 5
+a + b + c
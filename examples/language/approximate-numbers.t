@@ -0,0 +1,14 @@
+#!/usr/bin/env topal
+use language (
+  version is v0.1
+)
+# Demonstrates Binary64 approximate numbers: exact checked construction, one
+# rounding per operation, a zero reached from below selecting the infinity of a
+# quotient, named lossy functions, and an order-independent exact sum.
+one is Binary64 1
+third is one / ( Binary64 3 )
+below is Binary64 -0
+sample is 0.1 round-to-even Binary64
+limit is ( Binary64 +Infinity ) saturate ( Finite Binary64 )
+entries : List Binary64 is Entry ( sample, Entry ( Binary64 0.5, Entry ( third, Empty ) ) )
+(third, one / below, 2.5 round-to-even Int, -3.9 floor Int, limit < one, entries reproducible-sum Binary64)
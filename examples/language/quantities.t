@@ -0,0 +1,48 @@
+#!/usr/bin/env topal
+use language (
+  version is v0.1
+)
+# Demonstrates dimensioned quantities: SI-prefixed and derived linear units,
+# an affine temperature with its compiler-derived delta unit, point and delta
+# arithmetic, and explicit conversion between point and delta units.
+Mass is Dimension
+Distance is Dimension
+Duration is Dimension
+Temperature is AffineDimension
+Gram is MeasurementUnit (
+  symbol is g,
+  prefixes is SI,
+  dimension is Mass
+)
+Metre is MeasurementUnit (
+  symbol is m,
+  prefixes is SI,
+  dimension is Distance
+)
+Second is MeasurementUnit (
+  symbol is s,
+  prefixes is SI,
+  dimension is Duration
+)
+Newton is MeasurementUnit (
+  symbol is N,
+  prefixes is SI,
+  scale is 1[kg * m / (s ^ 2)]
+)
+Kelvin is MeasurementUnit (
+  symbol is K,
+  prefixes is SI,
+  dimension is Temperature
+)
+Celsius is MeasurementUnit (
+  symbol is °C,
+  prefixes is none,
+  reference is Kelvin,
+  scale is 1,
+  offset is 273.15[ΔK]
+)
+force is 2[kg] * 4[m] / (1[s] ^ 2)
+freezing : Temperature is 0[°C]
+boiling : Temperature is freezing + 100[Δ°C]
+difference : Delta Temperature is boiling - freezing
+(force = 8[N], 2[kg] + 500[g], difference as Delta Kelvin, boiling as Kelvin, 1[Centimetre] < 1[Metre])
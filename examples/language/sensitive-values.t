@@ -0,0 +1,16 @@
+#!/usr/bin/env topal
+use language (
+  version is v0.1
+)
+# Demonstrates sensitive-value provenance: a `Sensitive String` keeps its
+# classification through copies, records, and a declared `Sensitive` function
+# result, while a comparison is a public conclusion that may be printed.
+password : Sensitive String is "correct horse"
+copy is password
+login is ( credential is copy, user is "ada" )
+normalize is fn (value : Sensitive String) -> Sensitive String
+  value
+matches is fn (supplied : Sensitive String, expected : Sensitive String) -> Boolean
+  supplied = expected
+normalized is normalize password
+(matches (normalized, password), password = "guess")
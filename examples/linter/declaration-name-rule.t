@@ -0,0 +1,9 @@
+use language (
+  version is v0.1,
+  features is ( lint )
+)
+# Demonstrates a catalog rule over the generic read-only declaration view. The
+# host supplies each declaration's kind and name; placeholder names are
+# rejected whatever they declare.
+rule is fn static (kind : String, name : String) -> Boolean
+  name != "tmp"
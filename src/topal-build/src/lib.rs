@@ -1,15 +1,23 @@
 //! Native capabilities for the source-level Topal incremental-build policy.
 
 use std::collections::{BTreeMap, BTreeSet};
-use std::fs;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::panic::{self, AssertUnwindSafe};
 use std::path::{Component, Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
 use std::time::UNIX_EPOCH;
 
 use serde::{Deserialize, Serialize};
-use topal_language::{Session, Value, load_module_tree};
+use topal_language::{ResourceLimits, Session, Value, load_module_tree, with_evaluation_stack};
 
 const STATE_SCHEMA: u32 = 1;
+const POLICY_BASE_FUEL: u64 = 100_000;
+const POLICY_FUEL_PER_STEP: u64 = 64;
+const POLICY_VALUE_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+const POLICY_CALL_DEPTH: usize = 256;
 
 #[derive(Clone, Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -59,6 +67,10 @@ pub struct Options {
     pub library_root: PathBuf,
     pub manifest: PathBuf,
     pub dry_run: bool,
+    /// Units that may execute at once.
+    pub jobs: usize,
+    /// Keep executing units that do not depend on a failed unit.
+    pub keep_going: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -125,21 +137,38 @@ pub fn run(options: &Options) -> Result<Outcome, String> {
         .collect::<Vec<_>>();
 
     if !options.dry_run {
-        for unit in manifest
+        let units = manifest
             .units
             .iter()
             .filter(|unit| selected_set.contains(&unit.id))
-        {
-            execute(unit, options)?;
+            .collect::<Vec<_>>();
+        let (succeeded, failures) = schedule(&units, options)?;
+        if failures.is_empty() || !succeeded.is_empty() {
+            let mut inputs = current_inputs;
+            let mut manifest_stamp = Some(current_manifest);
+            // Forget the observations of units that did not succeed so the
+            // next build selects them again; one without inputs is only
+            // selected again through the manifest.
+            for unit in units.iter().filter(|unit| !succeeded.contains(&unit.id)) {
+                if unit.inputs.is_empty() {
+                    manifest_stamp = None;
+                }
+                for input in &unit.inputs {
+                    inputs.remove(&normalized_relative(input)?);
+                }
+            }
+            write_state(
+                &state_path,
+                &State {
+                    schema: STATE_SCHEMA,
+                    manifest: manifest_stamp,
+                    inputs,
+                },
+            )?;
+        }
+        if !failures.is_empty() {
+            return Err(failures.join("\n"));
         }
-        write_state(
-            &state_path,
-            &State {
-                schema: STATE_SCHEMA,
-                manifest: Some(current_manifest),
-                inputs: current_inputs,
-            },
-        )?;
     }
     Ok(Outcome { selected })
 }
@@ -182,8 +211,22 @@ fn select_with_topal(
     library_root: &Path,
     manifest: &Manifest,
     changed: &[String],
+) -> Result<Vec<String>, String> {
+    let limits = policy_limits(manifest);
+    with_evaluation_stack(limits, || {
+        evaluate_policy(library_root, manifest, changed, limits)
+    })
+}
+
+/// Evaluate the selection policy in a session bounded by `limits`.
+fn evaluate_policy(
+    library_root: &Path,
+    manifest: &Manifest,
+    changed: &[String],
+    limits: ResourceLimits,
 ) -> Result<Vec<String>, String> {
     let mut session = Session::new();
+    session.set_resource_limits(limits);
     let mut trace = Vec::new();
     load_module_tree(&mut session, library_root, &mut trace)?;
     let edges = manifest.units.iter().flat_map(|unit| {
@@ -217,17 +260,125 @@ fn select_with_topal(
         .collect()
 }
 
+/// Budgets for the build policy. Its propagation visits every dependency edge
+/// once per unit, so fuel grows with that product.
+fn policy_limits(manifest: &Manifest) -> ResourceLimits {
+    let units = manifest.units.len() as u64;
+    let edges = manifest
+        .units
+        .iter()
+        .map(|unit| unit.dependencies.len() as u64)
+        .sum::<u64>();
+    ResourceLimits {
+        fuel: Some(
+            POLICY_FUEL_PER_STEP
+                .saturating_mul(units)
+                .saturating_mul(edges + 1)
+                .saturating_add(POLICY_BASE_FUEL),
+        ),
+        value_memory: Some(POLICY_VALUE_MEMORY_BYTES),
+        call_depth: Some(POLICY_CALL_DEPTH),
+    }
+}
+
+/// Execute `units`, given in manifest order, with at most `options.jobs`
+/// running at once. A unit starts once every selected dependency succeeded
+/// and never starts after a dependency failed. After the first failure no
+/// further unit starts unless `options.keep_going` is set; running units
+/// always finish. Returns the units that succeeded and each failure.
+fn schedule(units: &[&Unit], options: &Options) -> Result<(BTreeSet<String>, Vec<String>), String> {
+    if options.jobs == 0 {
+        return Err("a build requires at least one job".into());
+    }
+    let logs = options.build_root.join("logs");
+    fs::create_dir_all(&logs)
+        .map_err(|error| format!("cannot create {}: {error}", logs.display()))?;
+    let selected = units
+        .iter()
+        .map(|unit| unit.id.as_str())
+        .collect::<BTreeSet<_>>();
+    let mut pending = units.to_vec();
+    let mut succeeded = BTreeSet::new();
+    let mut failed = BTreeSet::new();
+    let mut failures = Vec::new();
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        let mut running = 0;
+        loop {
+            pending.retain(|unit| {
+                let blocked = unit
+                    .dependencies
+                    .iter()
+                    .any(|dependency| failed.contains(dependency.as_str()));
+                if blocked {
+                    failed.insert(unit.id.as_str());
+                }
+                !blocked
+            });
+            while running < options.jobs && (failures.is_empty() || options.keep_going) {
+                let Some(index) = pending.iter().position(|unit| {
+                    unit.dependencies.iter().all(|dependency| {
+                        !selected.contains(dependency.as_str())
+                            || succeeded.contains(dependency.as_str())
+                    })
+                }) else {
+                    break;
+                };
+                let unit = pending.remove(index);
+                let sender = sender.clone();
+                scope.spawn(move || {
+                    // A panicking worker still reports, so the scheduler
+                    // fails the unit instead of waiting for it forever.
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| execute(unit, options)))
+                        .unwrap_or_else(|_| Err(format!("unit `{}` panicked", unit.id)));
+                    sender.send((unit, result))
+                });
+                running += 1;
+            }
+            if running == 0 {
+                break;
+            }
+            let (unit, result) = receiver.recv().expect("a running unit reports");
+            running -= 1;
+            match result {
+                Ok(()) => {
+                    succeeded.insert(unit.id.as_str());
+                }
+                Err(failure) => {
+                    failed.insert(unit.id.as_str());
+                    failures.push(failure);
+                }
+            }
+        }
+    });
+    Ok((succeeded.into_iter().map(str::to_owned).collect(), failures))
+}
+
+/// Run one unit with its standard output and error streamed to its log.
 fn execute(unit: &Unit, options: &Options) -> Result<(), String> {
     let (program, arguments) = unit.command.split_first().expect("validated command");
+    let log_path = log_path(&options.build_root, &unit.id);
+    let log = File::create(&log_path)
+        .map_err(|error| format!("cannot create {}: {error}", log_path.display()))?;
+    let errors = log
+        .try_clone()
+        .map_err(|error| format!("cannot share {}: {error}", log_path.display()))?;
     let status = Command::new(program)
         .args(arguments)
         .current_dir(&options.source_root)
         .env("TOPAL_SOURCE_ROOT", &options.source_root)
         .env("TOPAL_BUILD_ROOT", &options.build_root)
+        .stdin(Stdio::null())
+        .stdout(log)
+        .stderr(errors)
         .status()
         .map_err(|error| format!("cannot execute unit `{}`: {error}", unit.id))?;
     if !status.success() {
-        return Err(format!("unit `{}` failed with {status}", unit.id));
+        return Err(format!(
+            "unit `{}` failed with {status}; see {}",
+            unit.id,
+            log_path.display()
+        ));
     }
     for output in &unit.outputs {
         let path = resolve_build_path(&options.build_root, output)?;
@@ -242,6 +393,20 @@ fn execute(unit: &Unit, options: &Options) -> Result<(), String> {
     Ok(())
 }
 
+/// The log of unit `identity`. Bytes other than ASCII letters, digits, `-`,
+/// and `.` are percent-encoded, so distinct identities never share a log.
+fn log_path(build_root: &Path, identity: &str) -> PathBuf {
+    let mut name = String::with_capacity(identity.len());
+    for byte in identity.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.') {
+            name.push(char::from(byte));
+        } else {
+            let _ = write!(name, "%{byte:02X}");
+        }
+    }
+    build_root.join("logs").join(format!("{name}.log"))
+}
+
 fn stamp(path: &Path) -> Result<Stamp, String> {
     let modified = fs::metadata(path)
         .and_then(|metadata| metadata.modified())
@@ -16,6 +16,8 @@ fn execute() -> Result<(), String> {
     let mut library_root = None;
     let mut manifest = PathBuf::from("topal-build.json");
     let mut dry_run = false;
+    let mut jobs = 1;
+    let mut keep_going = false;
     let mut arguments = env::args().skip(1);
     while let Some(argument) = arguments.next() {
         match argument.as_str() {
@@ -26,10 +28,19 @@ fn execute() -> Result<(), String> {
             }
             "--manifest" => manifest = value(&mut arguments, &argument)?.into(),
             "--dry-run" => dry_run = true,
+            "--jobs" => {
+                jobs = value(&mut arguments, &argument)?
+                    .parse()
+                    .ok()
+                    .filter(|jobs| *jobs > 0)
+                    .ok_or("--jobs requires a positive count")?;
+            }
+            "--keep-going" => keep_going = true,
             "--help" => {
                 println!(
                     "usage: topal-build [--source-root PATH] [--build-root PATH] \
-                     [--library-root PATH] [--manifest PATH] [--dry-run]"
+                     [--library-root PATH] [--manifest PATH] [--dry-run] \
+                     [--jobs N] [--keep-going]"
                 );
                 return Ok(());
             }
@@ -44,6 +55,8 @@ fn execute() -> Result<(), String> {
         library_root,
         manifest,
         dry_run,
+        jobs,
+        keep_going,
     })?;
     for identity in outcome.selected {
         println!("selected {identity}");
@@ -1,6 +1,9 @@
 use std::env;
-use std::fs;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
 use std::path::Path;
+use std::thread;
+use std::time::Duration;
 
 fn main() {
     let arguments = env::args().skip(1).collect::<Vec<_>>();
@@ -11,7 +14,18 @@ fn main() {
     let source_root = env::var_os("TOPAL_SOURCE_ROOT").expect("source root");
     let build_root = env::var_os("TOPAL_BUILD_ROOT").expect("build root");
     let input_text = fs::read_to_string(Path::new(&source_root).join(input)).expect("input");
+    println!("building {identity}");
+    if let Some(milliseconds) = input_text
+        .strip_prefix("sleep ")
+        .and_then(|rest| rest.trim().parse().ok())
+    {
+        thread::sleep(Duration::from_millis(milliseconds));
+    }
+    if let Some(other) = input_text.strip_prefix("await ") {
+        await_unit(Path::new(&build_root), identity, other.trim());
+    }
     if input_text.contains("fail") {
+        eprintln!("{identity} failed");
         std::process::exit(3);
     }
     let output = Path::new(&build_root).join(output);
@@ -19,9 +33,24 @@ fn main() {
         fs::create_dir_all(parent).expect("output parent");
     }
     fs::write(&output, format!("{identity}:{input_text}")).expect("output");
-    let log = Path::new(&build_root).join("executed.log");
-    let mut contents = fs::read_to_string(&log).unwrap_or_default();
-    contents.push_str(identity);
-    contents.push('\n');
-    fs::write(log, contents).expect("log");
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(Path::new(&build_root).join("executed.log"))
+        .and_then(|mut log| writeln!(log, "{identity}"))
+        .expect("log");
+}
+
+/// Announce that `identity` started and wait until `other` has too, so a
+/// build only succeeds when both run at once.
+fn await_unit(build_root: &Path, identity: &str, other: &str) {
+    fs::write(build_root.join(format!("{identity}.started")), "").expect("start marker");
+    for _ in 0..1000 {
+        if build_root.join(format!("{other}.started")).exists() {
+            return;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    eprintln!("{identity} never ran alongside {other}");
+    std::process::exit(4);
 }
@@ -75,6 +75,8 @@ impl Fixture {
             library_root: self.library.clone(),
             manifest: "topal-build.json".into(),
             dry_run: false,
+            jobs: 1,
+            keep_going: false,
         }
     }
 
@@ -218,3 +220,81 @@ fn missing_declared_output_does_not_publish_state() {
     assert!(run(&options).unwrap_err().contains("did not produce"));
     assert!(!fixture.build.join("state.json").exists());
 }
+
+#[test]
+fn jobs_run_independent_units_concurrently_after_their_dependencies() {
+    let fixture = Fixture::new(true);
+    write_at(&fixture.source.join("core.t"), "await other", 12);
+    write_at(&fixture.source.join("other.t"), "await core", 13);
+    let mut options = fixture.options();
+    options.jobs = 2;
+    assert_eq!(run(&options).unwrap().selected.len(), 4);
+    let executed = fixture.executed();
+    let position = |identity: &str| executed.iter().position(|unit| unit == identity).unwrap();
+    assert!(position("core") < position("app"));
+    assert!(position("app") < position("test-app"));
+    assert!(
+        fs::read_to_string(fixture.build.join("logs/app.log"))
+            .unwrap()
+            .contains("building app")
+    );
+}
+
+#[test]
+fn keep_going_records_state_only_for_units_that_succeeded() {
+    let fixture = Fixture::new(true);
+    run(&fixture.options()).unwrap();
+    fixture.clear_log();
+    write_at(&fixture.source.join("core.t"), "fail", 40);
+    write_at(&fixture.source.join("other.t"), "changed", 41);
+    let error = run(&fixture.options()).unwrap_err();
+    assert!(error.contains("unit `core` failed"), "{error}");
+    assert!(fixture.executed().is_empty());
+
+    let mut options = fixture.options();
+    options.keep_going = true;
+    let error = run(&options).unwrap_err();
+    assert!(error.contains("logs/core.log"), "{error}");
+    assert_eq!(fixture.executed(), ["other"]);
+    assert!(
+        fs::read_to_string(fixture.build.join("logs/core.log"))
+            .unwrap()
+            .contains("core failed")
+    );
+
+    fixture.clear_log();
+    write_at(&fixture.source.join("core.t"), "recovered", 42);
+    assert_eq!(run(&options).unwrap().selected, ["core", "app", "test-app"]);
+    assert_eq!(fixture.executed(), ["core", "app", "test-app"]);
+}
+
+#[test]
+fn distinct_unit_identities_never_share_a_log() {
+    let fixture = Fixture::new(true);
+    let helper = env!("CARGO_BIN_EXE_topal-build-test-action");
+    let units = ["a/b", "a:b", "a_b"]
+        .into_iter()
+        .enumerate()
+        .map(|(index, id)| {
+            let output = format!("{index}.out");
+            json!({
+                "id": id, "kind": "build", "inputs": ["core.t"],
+                "outputs": [output], "dependencies": [],
+                "command": [helper, id, output, "core.t"]
+            })
+        })
+        .collect::<Vec<_>>();
+    fs::write(
+        fixture.source.join("identities.json"),
+        serde_json::to_vec_pretty(&json!({"schema": 1, "units": units})).unwrap(),
+    )
+    .unwrap();
+    let mut options = fixture.options();
+    options.manifest = "identities.json".into();
+    options.jobs = 3;
+    run(&options).unwrap();
+    for (log, id) in [("a%2Fb", "a/b"), ("a%3Ab", "a:b"), ("a%5Fb", "a_b")] {
+        let written = fs::read_to_string(fixture.build.join(format!("logs/{log}.log"))).unwrap();
+        assert_eq!(written.trim(), format!("building {id}"));
+    }
+}
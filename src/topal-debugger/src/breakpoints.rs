@@ -0,0 +1,389 @@
+//! Source-line and function-entry breakpoints.
+//!
+//! A line breakpoint is hit where execution enters its line: at a source state
+//! whose line differs from the preceding source state's. A function breakpoint
+//! is hit at the first source state after an entry into the function.
+//! Conditions and log messages are Topal expressions evaluated against the
+//! bindings of the hit state in the debuggee's selected language context, and
+//! hit counts number hits in execution order, so forward and reverse continue
+//! stop at the same states. Running hit counts are cached per breakpoint, so
+//! each source state's condition is evaluated once however often it is
+//! counted.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::io;
+
+use topal_language::{
+    Diagnostic, Execution, ExecutionHistory, ExecutionTransition, Session, SourceRange, Value,
+};
+
+use super::{line_column, live_source_step};
+
+#[derive(Clone, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub(super) struct BreakpointOptions {
+    /// A Boolean expression that must hold for a hit.
+    pub(super) condition: Option<String>,
+    /// The first hit that stops or logs.
+    pub(super) hit_count: Option<usize>,
+    /// A message whose `{expression}` parts are replaced by their values. A
+    /// breakpoint with a message logs instead of stopping.
+    pub(super) log: Option<String>,
+}
+
+impl BreakpointOptions {
+    /// The options in the form `breakpoints` lists them.
+    pub(super) fn describe(&self) -> String {
+        let mut description = String::new();
+        if let Some(condition) = &self.condition {
+            let _ = write!(description, " if {condition}");
+        }
+        if let Some(hit_count) = self.hit_count {
+            let _ = write!(description, " from hit {hit_count}");
+        }
+        if let Some(log) = &self.log {
+            let _ = write!(description, " log {log}");
+        }
+        description
+    }
+}
+
+#[derive(Debug, Default)]
+pub(super) struct Breakpoints {
+    /// Breakpoints by one-based source line.
+    pub(super) lines: BTreeMap<usize, BreakpointOptions>,
+    /// Breakpoints by function name, optionally qualified by the namespace
+    /// path through which the debuggee calls it, such as `std trim`.
+    pub(super) functions: BTreeMap<String, BreakpointOptions>,
+    /// Binding names whose value changes stop execution.
+    pub(super) watchpoints: BTreeSet<String>,
+    /// Running hit counts by breakpoint site and options.
+    hit_counts: RefCell<BTreeMap<(SiteKey, BreakpointOptions), HitCounts>>,
+}
+
+/// The cursor of each source state counted so far, in order, with the hits
+/// up to and including it.
+type HitCounts = Vec<(usize, usize)>;
+
+/// What continuing to, or arriving at, one source state produced.
+#[derive(Debug, Default)]
+pub(super) struct Arrival {
+    /// Why execution stops at the selected state, if it does.
+    pub(super) stop: Option<&'static str>,
+    /// Log-point messages and breakpoint evaluation failures, in order.
+    pub(super) messages: Vec<String>,
+    /// The diagnostic that stopped live execution.
+    pub(super) failure: Option<Diagnostic>,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Site<'a> {
+    Line(usize),
+    Function(&'a str),
+}
+
+#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum SiteKey {
+    Line(usize),
+    Function(String),
+}
+
+impl Site<'_> {
+    fn key(self) -> SiteKey {
+        match self {
+            Self::Line(line) => SiteKey::Line(line),
+            Self::Function(name) => SiteKey::Function(name.to_owned()),
+        }
+    }
+}
+
+impl Breakpoints {
+    /// Continue through recorded and then live execution until a breakpoint
+    /// or watchpoint stops it, or it completes or fails.
+    pub(super) fn continue_forward(
+        &self,
+        history: &mut ExecutionHistory,
+        session: &mut Session,
+        execution: &mut Execution,
+        complete: &mut bool,
+        source: &str,
+        source_name: &str,
+    ) -> Arrival {
+        let start = bindings(history);
+        let mut arrival = Arrival::default();
+        loop {
+            match live_source_step(history, session, execution, complete) {
+                Ok(true) => {}
+                Ok(false) => return arrival,
+                Err(error) => {
+                    arrival.failure = Some(error);
+                    return arrival;
+                }
+            }
+            if self.reach(history, session, source, source_name, &start, &mut arrival) {
+                return arrival;
+            }
+        }
+    }
+
+    /// Move back through recorded source states until a breakpoint or
+    /// watchpoint stops, or the first source state is reached.
+    pub(super) fn continue_backward(
+        &self,
+        history: &mut ExecutionHistory,
+        session: &Session,
+        source: &str,
+        source_name: &str,
+    ) -> Arrival {
+        let start = bindings(history);
+        let mut arrival = Arrival::default();
+        while history.step_source_backward().is_some() {
+            if self.reach(history, session, source, source_name, &start, &mut arrival) {
+                break;
+            }
+        }
+        arrival
+    }
+
+    /// Record in `arrival` what the selected state produces and whether
+    /// execution stops there. A watchpoint stops where its binding differs
+    /// from the `start` bindings.
+    fn reach(
+        &self,
+        history: &mut ExecutionHistory,
+        session: &Session,
+        source: &str,
+        source_name: &str,
+        start: &BTreeMap<String, Value>,
+        arrival: &mut Arrival,
+    ) -> bool {
+        let mut reached = self.arrive(history, session, source, source_name);
+        arrival.messages.append(&mut reached.messages);
+        let current = bindings(history);
+        arrival.stop = reached.stop.or_else(|| {
+            self.watchpoints
+                .iter()
+                .any(|name| current.get(name) != start.get(name))
+                .then_some("data breakpoint")
+        });
+        arrival.stop.is_some()
+    }
+
+    /// Evaluate the breakpoints hit at the selected source state.
+    pub(super) fn arrive(
+        &self,
+        history: &mut ExecutionHistory,
+        session: &Session,
+        source: &str,
+        source_name: &str,
+    ) -> Arrival {
+        let mut arrival = Arrival::default();
+        if self.lines.is_empty() && self.functions.is_empty() {
+            return arrival;
+        }
+        let cursor = history.cursor();
+        let states = history.source_states();
+        let Some(index) = states.iter().position(|(state, _)| *state == cursor) else {
+            return arrival;
+        };
+        for (site, options) in self.sites(history.transitions(), source, &states, index) {
+            let (label, reason) = match site {
+                Site::Line(line) => (format!("{source_name}:{line}"), "breakpoint"),
+                Site::Function(name) => (name.to_owned(), "function breakpoint"),
+            };
+            let hit = match hit(history, session, options, cursor) {
+                Ok(hit) => hit,
+                Err(message) => {
+                    arrival.messages.push(message);
+                    arrival.stop.get_or_insert(reason);
+                    continue;
+                }
+            };
+            if !hit
+                || options.hit_count.is_some_and(|count| {
+                    self.hits(history, session, source, &states, index, (site, options)) < count
+                })
+            {
+                continue;
+            }
+            if let Some(log) = &options.log {
+                history.seek(cursor);
+                let selected = bindings(history);
+                arrival
+                    .messages
+                    .push(format!("{label}: {}", interpolate(session, &selected, log)));
+            } else {
+                arrival.stop.get_or_insert(reason);
+            }
+        }
+        history.seek(cursor);
+        arrival
+    }
+
+    /// The breakpoints whose site the source state at `index` reaches.
+    fn sites(
+        &self,
+        transitions: &[ExecutionTransition],
+        source: &str,
+        states: &[(usize, SourceRange)],
+        index: usize,
+    ) -> Vec<(Site<'_>, &BreakpointOptions)> {
+        let (cursor, range) = states[index];
+        let line = line_column(source, range.start).0;
+        let previous = index.checked_sub(1).map(|previous| states[previous]);
+        let mut sites = Vec::new();
+        if previous.is_none_or(|(_, range)| line_column(source, range.start).0 != line)
+            && let Some((line, options)) = self.lines.get_key_value(&line)
+        {
+            sites.push((Site::Line(*line), options));
+        }
+        let entered = previous.map_or(0, |(cursor, _)| cursor)..cursor;
+        for (name, options) in &self.functions {
+            if transitions
+                .get(entered.clone())
+                .unwrap_or_default()
+                .iter()
+                .any(|transition| enters(transitions, transition, name))
+            {
+                sites.push((Site::Function(name), options));
+            }
+        }
+        sites
+    }
+
+    /// The number of hits of the breakpoint at `site` up to and including
+    /// the source state at `index`, extending its running count from the
+    /// last state counted. A count whose last state is no longer recorded
+    /// belongs to an earlier execution and starts over.
+    fn hits(
+        &self,
+        history: &mut ExecutionHistory,
+        session: &Session,
+        source: &str,
+        states: &[(usize, SourceRange)],
+        index: usize,
+        (site, options): (Site<'_>, &BreakpointOptions),
+    ) -> usize {
+        let mut cache = self.hit_counts.borrow_mut();
+        let counts = cache.entry((site.key(), options.clone())).or_default();
+        if counts.last().is_some_and(|(cursor, _)| {
+            states
+                .get(counts.len() - 1)
+                .is_none_or(|(state, _)| state != cursor)
+        }) {
+            counts.clear();
+        }
+        while counts.len() <= index {
+            let earlier = counts.len();
+            let previous = counts.last().map_or(0, |(_, hits)| *hits);
+            let reached = self
+                .sites(history.transitions(), source, states, earlier)
+                .iter()
+                .any(|(other, _)| *other == site);
+            let hit = reached && hit(history, session, options, states[earlier].0) == Ok(true);
+            counts.push((states[earlier].0, previous + usize::from(hit)));
+        }
+        counts[index].1
+    }
+}
+
+/// Whether `transition` enters the function `name` denotes. A qualified name
+/// requires that the call selected the function as a namespace member.
+fn enters(
+    transitions: &[ExecutionTransition],
+    transition: &ExecutionTransition,
+    name: &str,
+) -> bool {
+    let (qualifier, member) = name
+        .rsplit_once(' ')
+        .map_or((None, name), |(qualifier, member)| {
+            (Some(qualifier), member)
+        });
+    if transition.event != "function.entry" || transition.detail != member {
+        return false;
+    }
+    let Some(qualifier) = qualifier else {
+        return true;
+    };
+    let selection = if qualifier == "root" {
+        "namespace.member.resolved"
+    } else {
+        "namespace.alias.member.resolved"
+    };
+    transitions[..transition.sequence]
+        .iter()
+        .rev()
+        .find(|earlier| {
+            earlier.detail == member
+                && matches!(
+                    earlier.event,
+                    "function.entry"
+                        | "namespace.member.resolved"
+                        | "namespace.alias.member.resolved"
+                )
+        })
+        .is_some_and(|earlier| earlier.event == selection)
+}
+
+/// Whether the condition of `options` holds at `cursor`.
+fn hit(
+    history: &mut ExecutionHistory,
+    session: &Session,
+    options: &BreakpointOptions,
+    cursor: usize,
+) -> Result<bool, String> {
+    let Some(condition) = &options.condition else {
+        return Ok(true);
+    };
+    history.seek(cursor);
+    match session.inspect_in_context(&bindings(history), condition, &mut io::sink()) {
+        Ok(Value::Boolean(hit)) => Ok(hit),
+        Ok(value) => Err(format!(
+            "breakpoint condition `{condition}` is {}, not Boolean",
+            value.classifier()
+        )),
+        Err(error) => Err(error.render("<breakpoint-condition>")),
+    }
+}
+
+fn bindings(history: &ExecutionHistory) -> BTreeMap<String, Value> {
+    history
+        .state()
+        .map(|state| state.bindings)
+        .unwrap_or_default()
+}
+
+/// Replace each brace-delimited expression in `template` by its value.
+fn interpolate(session: &Session, bindings: &BTreeMap<String, Value>, template: &str) -> String {
+    let mut message = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        message.push_str(&rest[..start]);
+        let mut depth = 0_usize;
+        let end = rest[start..]
+            .char_indices()
+            .find_map(|(offset, character)| {
+                match character {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+                (depth == 0).then_some(start + offset)
+            });
+        let Some(end) = end else {
+            break;
+        };
+        match session.inspect_in_context(bindings, &rest[start + 1..end], &mut io::sink()) {
+            Ok(value) => {
+                let _ = write!(message, "{value}");
+            }
+            Err(error) => {
+                let _ = write!(message, "<error[{}]: {}>", error.code, error.message);
+            }
+        }
+        rest = &rest[end + 1..];
+    }
+    message.push_str(rest);
+    message
+}
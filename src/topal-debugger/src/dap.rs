@@ -0,0 +1,455 @@
+//! Debug Adapter Protocol frontend over standard input and output.
+//!
+//! The adapter drives the same live execution and recorded history as the
+//! command interface. The debuggee is one thread with one `<script>` frame
+//! whose single scope lists the bindings of the selected execution state.
+//! Forward motion executes the debuggee only when the recorded history holds
+//! no later source state; backward motion never executes it.
+
+use std::io::{self, BufRead, Write};
+
+use serde_json::{Value, json};
+use topal_language::{Diagnostic, Session};
+
+use super::{BreakpointOptions, Breakpoints, Debuggee, line_column, live_source_step};
+
+const THREAD: u64 = 1;
+const FRAME: u64 = 1;
+const BINDINGS: u64 = 1;
+
+/// Serve Debug Adapter Protocol requests for `debuggee` until the client
+/// disconnects or closes standard input.
+pub(super) fn serve(
+    debuggee: &mut Debuggee,
+    source: &str,
+    source_name: &str,
+    source_path: &str,
+) -> Result<(), String> {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    let mut adapter = Adapter {
+        debuggee,
+        source,
+        source_name,
+        source_path,
+        breakpoints: Breakpoints::default(),
+        line_base: 1,
+        column_base: 1,
+        stop_on_entry: false,
+        sequence: 0,
+        messages: Vec::new(),
+        exit: false,
+    };
+    while let Some(message) = read_message(&mut input).map_err(|error| error.to_string())? {
+        if message["type"] == "request" {
+            adapter.handle(&message);
+        }
+        for reply in adapter.messages.drain(..) {
+            write_message(&mut output, &reply).map_err(|error| error.to_string())?;
+        }
+        if adapter.exit {
+            break;
+        }
+    }
+    Ok(())
+}
+
+struct Adapter<'a> {
+    debuggee: &'a mut Debuggee,
+    source: &'a str,
+    source_name: &'a str,
+    source_path: &'a str,
+    breakpoints: Breakpoints,
+    /// The client's first line and column numbers, zero or one.
+    line_base: usize,
+    column_base: usize,
+    stop_on_entry: bool,
+    sequence: u64,
+    /// Responses and events not yet written to the client.
+    messages: Vec<Value>,
+    exit: bool,
+}
+
+impl Adapter<'_> {
+    fn handle(&mut self, request: &Value) {
+        let command = request["command"].as_str().unwrap_or_default();
+        let arguments = &request["arguments"];
+        let result = match command {
+            "initialize" => Ok(self.initialize(arguments)),
+            "launch" | "attach" => {
+                self.stop_on_entry = arguments["stopOnEntry"].as_bool().unwrap_or(false);
+                Ok(Value::Null)
+            }
+            "setBreakpoints" => Ok(self.set_breakpoints(arguments)),
+            "setFunctionBreakpoints" => Ok(self.set_function_breakpoints(arguments)),
+            "setExceptionBreakpoints" => Ok(json!({ "breakpoints": [] })),
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD, "name": "<script>" }] })),
+            "stackTrace" => Ok(self.stack_trace()),
+            "scopes" => Ok(json!({
+                "scopes": [{
+                    "name": "Bindings",
+                    "presentationHint": "locals",
+                    "variablesReference": BINDINGS,
+                    "expensive": false
+                }]
+            })),
+            "variables" => Ok(self.variables(arguments)),
+            "evaluate" => self.evaluate(arguments),
+            "continue" | "reverseContinue" => Ok(json!({ "allThreadsContinued": true })),
+            "configurationDone" | "next" | "stepIn" | "stepOut" | "stepBack" => Ok(Value::Null),
+            "disconnect" | "terminate" => {
+                self.exit = true;
+                Ok(Value::Null)
+            }
+            _ => Err(format!("unsupported request `{command}`")),
+        };
+        let success = result.is_ok();
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "success": success,
+            "command": command
+        });
+        match result {
+            Ok(Value::Null) => {}
+            Ok(body) => response["body"] = body,
+            Err(message) => response["message"] = json!(message),
+        }
+        self.send(response);
+        match command {
+            "initialize" => self.event("initialized", Value::Null),
+            "configurationDone" if self.stop_on_entry => self.resume("entry"),
+            "configurationDone" => self.resume("continue"),
+            "next" | "stepIn" | "stepOut" | "stepBack" | "continue" | "reverseContinue" => {
+                self.resume(command);
+            }
+            _ => {}
+        }
+    }
+
+    fn initialize(&mut self, arguments: &Value) -> Value {
+        self.line_base = usize::from(arguments["linesStartAt1"].as_bool().unwrap_or(true));
+        self.column_base = usize::from(arguments["columnsStartAt1"].as_bool().unwrap_or(true));
+        json!({
+            "supportsConfigurationDoneRequest": true,
+            "supportsConditionalBreakpoints": true,
+            "supportsHitConditionalBreakpoints": true,
+            "supportsLogPoints": true,
+            "supportsFunctionBreakpoints": true,
+            "supportsStepBack": true,
+            "supportsTerminateRequest": true
+        })
+    }
+
+    fn set_breakpoints(&mut self, arguments: &Value) -> Value {
+        let requested = arguments["breakpoints"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let ours = arguments["source"]["path"].as_str() == Some(self.source_path);
+        if ours {
+            self.breakpoints.lines.clear();
+        }
+        let breakpoints = requested
+            .iter()
+            .map(|breakpoint| {
+                let line = breakpoint["line"]
+                    .as_u64()
+                    .and_then(|line| usize::try_from(line).ok())
+                    .and_then(|line| (line + 1).checked_sub(self.line_base))
+                    .filter(|line| *line > 0);
+                let verified = match (line, breakpoint_options(breakpoint)) {
+                    (Some(line), Ok(options)) if ours => {
+                        self.breakpoints.lines.insert(line, options);
+                        Ok(())
+                    }
+                    (_, Err(message)) => Err(message),
+                    _ => Err(format!(
+                        "breakpoints apply to lines of {}",
+                        self.source_name
+                    )),
+                };
+                let mut response =
+                    json!({ "verified": verified.is_ok(), "line": breakpoint["line"] });
+                if let Err(message) = verified {
+                    response["message"] = json!(message);
+                }
+                response
+            })
+            .collect::<Vec<_>>();
+        json!({ "breakpoints": breakpoints })
+    }
+
+    fn set_function_breakpoints(&mut self, arguments: &Value) -> Value {
+        self.breakpoints.functions.clear();
+        let breakpoints = arguments["breakpoints"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .map(|breakpoint| {
+                let name = breakpoint["name"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let options = if name.is_empty() {
+                    Err("function breakpoint requires a function name".to_owned())
+                } else {
+                    breakpoint_options(breakpoint)
+                };
+                match options {
+                    Ok(options) => {
+                        self.breakpoints.functions.insert(name, options);
+                        json!({ "verified": true })
+                    }
+                    Err(message) => json!({ "verified": false, "message": message }),
+                }
+            })
+            .collect::<Vec<_>>();
+        json!({ "breakpoints": breakpoints })
+    }
+
+    fn stack_trace(&self) -> Value {
+        let frame = match self
+            .debuggee
+            .history
+            .state()
+            .and_then(|state| state.source_range)
+        {
+            Some(range) => {
+                let (line, column) = line_column(self.source, range.start);
+                let (end_line, end_column) = line_column(self.source, range.end);
+                json!({
+                    "id": FRAME,
+                    "name": "<script>",
+                    "source": self.source_reference(),
+                    "line": line - 1 + self.line_base,
+                    "column": column - 1 + self.column_base,
+                    "endLine": end_line - 1 + self.line_base,
+                    "endColumn": end_column - 1 + self.column_base
+                })
+            }
+            None => json!({
+                "id": FRAME,
+                "name": "<script>",
+                "source": self.source_reference(),
+                "line": 0,
+                "column": 0,
+                "presentationHint": "subtle"
+            }),
+        };
+        json!({ "stackFrames": [frame], "totalFrames": 1 })
+    }
+
+    fn source_reference(&self) -> Value {
+        json!({ "name": self.source_name, "path": self.source_path })
+    }
+
+    fn variables(&self, arguments: &Value) -> Value {
+        if arguments["variablesReference"].as_u64() != Some(BINDINGS) {
+            return json!({ "variables": [] });
+        }
+        let variables = self
+            .debuggee
+            .history
+            .state()
+            .map(|state| {
+                state
+                    .bindings
+                    .iter()
+                    .map(|(name, value)| {
+                        json!({
+                            "name": name,
+                            "value": value.to_string(),
+                            "variablesReference": 0
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        json!({ "variables": variables })
+    }
+
+    fn evaluate(&self, arguments: &Value) -> Result<Value, String> {
+        let expression = arguments["expression"].as_str().unwrap_or_default();
+        let state = self
+            .debuggee
+            .history
+            .state()
+            .ok_or_else(|| "no execution state selected".to_owned())?;
+        Session::inspect(&state.bindings, expression, &mut io::sink())
+            .map(|value| json!({ "result": value.to_string(), "variablesReference": 0 }))
+            .map_err(|error| error.render("<debugger-expression>"))
+    }
+
+    /// Move the selected execution state for `command` and report where it
+    /// stopped, or that the debuggee ran to completion.
+    fn resume(&mut self, command: &str) {
+        let stop = match command {
+            "entry" => self.step().map(|stop| stop.map(|_| "entry")),
+            "next" | "stepIn" => self.step(),
+            "stepOut" | "continue" => self.continue_forward(),
+            "stepBack" => {
+                self.debuggee.history.step_source_backward();
+                Ok(Some("step"))
+            }
+            _ => {
+                let arrival = self.breakpoints.continue_backward(
+                    &mut self.debuggee.history,
+                    &self.debuggee.session,
+                    self.source,
+                    self.source_name,
+                );
+                self.log(arrival.messages);
+                Ok(Some(arrival.stop.unwrap_or("entry")))
+            }
+        };
+        match stop {
+            Ok(Some(reason)) => self.stopped(reason, None),
+            Ok(None) => {
+                self.event("exited", json!({ "exitCode": 0 }));
+                self.event("terminated", Value::Null);
+            }
+            Err(error) => {
+                let rendered = error.render(self.source_name);
+                self.event(
+                    "output",
+                    json!({ "category": "stderr", "output": format!("{rendered}\n") }),
+                );
+                self.stopped("exception", Some(rendered));
+            }
+        }
+    }
+
+    /// Advance one source state. At the end of execution the debuggee stays
+    /// stopped so reverse motion remains available.
+    fn step(&mut self) -> Result<Option<&'static str>, Diagnostic> {
+        let Debuggee {
+            session,
+            execution,
+            history,
+            complete,
+        } = &mut *self.debuggee;
+        live_source_step(history, session, execution, complete)?;
+        Ok(Some("step"))
+    }
+
+    /// Continue until a breakpoint stops execution, or return `None` once
+    /// execution completes without stopping.
+    fn continue_forward(&mut self) -> Result<Option<&'static str>, Diagnostic> {
+        let Debuggee {
+            session,
+            execution,
+            history,
+            complete,
+        } = &mut *self.debuggee;
+        let arrival = self.breakpoints.continue_forward(
+            history,
+            session,
+            execution,
+            complete,
+            self.source,
+            self.source_name,
+        );
+        self.log(arrival.messages);
+        arrival.failure.map_or(Ok(arrival.stop), Err)
+    }
+
+    fn log(&mut self, messages: Vec<String>) {
+        for message in messages {
+            self.event(
+                "output",
+                json!({ "category": "console", "output": format!("{message}\n") }),
+            );
+        }
+    }
+
+    fn stopped(&mut self, reason: &str, text: Option<String>) {
+        let mut body = json!({
+            "reason": reason,
+            "threadId": THREAD,
+            "allThreadsStopped": true
+        });
+        if let Some(text) = text {
+            body["text"] = json!(text);
+        }
+        self.event("stopped", body);
+    }
+
+    fn event(&mut self, event: &str, body: Value) {
+        let mut message = json!({ "type": "event", "event": event });
+        if !body.is_null() {
+            message["body"] = body;
+        }
+        self.send(message);
+    }
+
+    fn send(&mut self, mut message: Value) {
+        self.sequence += 1;
+        message["seq"] = json!(self.sequence);
+        self.messages.push(message);
+    }
+}
+
+/// The condition, hit count, and log message a protocol breakpoint carries.
+fn breakpoint_options(breakpoint: &Value) -> Result<BreakpointOptions, String> {
+    let text = |field: &str| {
+        breakpoint[field]
+            .as_str()
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(str::to_owned)
+    };
+    let hit_count = text("hitCondition")
+        .map(|count| match count.parse::<usize>() {
+            Ok(0) | Err(_) => Err(format!(
+                "hit condition `{count}` must be a positive hit count"
+            )),
+            Ok(count) => Ok(count),
+        })
+        .transpose()?;
+    Ok(BreakpointOptions {
+        condition: text("condition"),
+        hit_count,
+        log: text("logMessage"),
+    })
+}
+
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        if header == "\r\n" || header == "\n" {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("Content-Length")
+        {
+            content_length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let length = content_length
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length"))?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body).map(Some).map_err(|error| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid debug adapter message: {error}"),
+        )
+    })
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = serde_json::to_vec(message).map_err(io::Error::other)?;
+    write!(output, "Content-Length: {}\r\n\r\n", body.len())?;
+    output.write_all(&body)?;
+    output.flush()
+}
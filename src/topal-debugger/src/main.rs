@@ -12,6 +12,14 @@ use topal_language::{
 use topal_source::SourceText;
 use topal_syntax::{DocumentedDeclaration, Statement, extract_documentation, lex, parse};
 
+mod breakpoints;
+mod dap;
+mod queries;
+mod recording;
+
+use breakpoints::{BreakpointOptions, Breakpoints};
+use queries::handle_query_command;
+
 fn main() -> ExitCode {
     match run() {
         Ok(()) => ExitCode::SUCCESS,
@@ -24,26 +32,22 @@ fn main() -> ExitCode {
 
 fn run() -> Result<(), String> {
     let arguments = parse_arguments(env::args().skip(1))?;
-    let source_path = source_entry(&arguments.source)?;
-    let source_name = source_path.display().to_string();
-    let source = fs::read_to_string(&source_path)
-        .map_err(|error| format!("cannot read {source_name}: {error}"))?;
-    let mut session = Session::new();
-    let mut history = ExecutionHistory::new();
-    if Path::new(&arguments.source).is_dir() {
-        load_module_tree(&mut session, Path::new(&arguments.source), &mut history)?;
-    }
-    let execution = session
-        .prepare_source_file(&source, &mut history)
-        .map_err(|error| error.render(&source_name))?;
-    history.rewind();
-    let mut debuggee = Debuggee {
-        session,
-        execution,
-        history,
-        complete: false,
+    let (mut debuggee, source, source_name) = match &arguments.target {
+        Target::Source(source) => load(source)?,
+        Target::Record { recording, source } => return recording::record(recording, source),
+        Target::Replay(recording) => recording::replay(recording)?,
     };
 
+    if arguments.dap {
+        let source_path =
+            fs::canonicalize(&source_name).unwrap_or_else(|_| PathBuf::from(&source_name));
+        return dap::serve(
+            &mut debuggee,
+            &source,
+            &source_name,
+            &source_path.display().to_string(),
+        );
+    }
     println!(
         "loaded {} transitions",
         debuggee.history.transitions().len()
@@ -92,6 +96,40 @@ fn run() -> Result<(), String> {
     }
 }
 
+/// Load the debuggee `source` names, a source file or a module tree
+/// directory, prepared at its first transition.
+fn load(source: &str) -> Result<(Debuggee, String, String), String> {
+    load_with(source, load_module_tree)
+}
+
+/// Load `source` as [`load`] does, loading the module tree of a package
+/// directory with `load_tree`.
+fn load_with(
+    source: &str,
+    load_tree: impl FnOnce(&mut Session, &Path, &mut ExecutionHistory) -> Result<(), String>,
+) -> Result<(Debuggee, String, String), String> {
+    let source_path = source_entry(source)?;
+    let source_name = source_path.display().to_string();
+    let text = fs::read_to_string(&source_path)
+        .map_err(|error| format!("cannot read {source_name}: {error}"))?;
+    let mut session = Session::new();
+    let mut history = ExecutionHistory::new();
+    if Path::new(source).is_dir() {
+        load_tree(&mut session, Path::new(source), &mut history)?;
+    }
+    let execution = session
+        .prepare_source_file(&text, &mut history)
+        .map_err(|error| error.render(&source_name))?;
+    history.rewind();
+    let debuggee = Debuggee {
+        session,
+        execution,
+        history,
+        complete: false,
+    };
+    Ok((debuggee, text, source_name))
+}
+
 fn source_entry(source: &str) -> Result<PathBuf, String> {
     let path = Path::new(source);
     if !path.is_dir() {
@@ -106,8 +144,18 @@ fn source_entry(source: &str) -> Result<PathBuf, String> {
 }
 
 struct Arguments {
-    source: String,
+    target: Target,
     commands: Option<String>,
+    dap: bool,
+}
+
+enum Target {
+    /// A source file or module tree directory to debug.
+    Source(String),
+    /// A source to run to its end and record into a recording file.
+    Record { recording: String, source: String },
+    /// A recording to debug instead of the sources it carries.
+    Replay(String),
 }
 
 fn debug_script_body(script: &str, name: &str) -> Result<(String, usize), String> {
@@ -162,27 +210,59 @@ struct Debuggee {
     complete: bool,
 }
 
+const USAGE: &str = "usage: topal-debug [--script COMMANDS | --dap] (FILE | --replay RECORDING) | topal-debug --record RECORDING FILE";
+
 fn parse_arguments(mut arguments: impl Iterator<Item = String>) -> Result<Arguments, String> {
-    let first = arguments
-        .next()
-        .ok_or_else(|| "usage: topal-debug [--script COMMANDS] FILE".to_owned())?;
-    let (commands, source) = if first == "--script" {
-        let commands = arguments
-            .next()
-            .ok_or_else(|| "--script requires a command file or -".to_owned())?;
-        let source = arguments
-            .next()
-            .ok_or_else(|| "--script requires a Topal source file".to_owned())?;
-        (Some(commands), source)
-    } else if first.starts_with('-') {
-        return Err(format!("unknown option: {first}"));
-    } else {
-        (None, first)
-    };
-    if let Some(extra) = arguments.next() {
-        return Err(format!("unexpected second source file: {extra}"));
+    let (mut source, mut commands, mut record, mut replay, mut dap) =
+        (None, None, None, None, false);
+    while let Some(argument) = arguments.next() {
+        let (slot, missing) = match argument.as_str() {
+            "--script" => (&mut commands, "--script requires a command file or -"),
+            "--record" => (&mut record, "--record requires a recording file"),
+            "--replay" => (&mut replay, "--replay requires a recording file"),
+            "--dap" => {
+                dap = true;
+                continue;
+            }
+            option if option.starts_with('-') => {
+                return Err(format!("unknown option: {option}"));
+            }
+            _ if source.is_some() => {
+                return Err(format!("unexpected second source file: {argument}"));
+            }
+            _ => {
+                source = Some(argument);
+                continue;
+            }
+        };
+        *slot = Some(arguments.next().ok_or_else(|| missing.to_owned())?);
     }
-    Ok(Arguments { source, commands })
+    if commands.is_some() && dap {
+        return Err("--script and --dap select different frontends".into());
+    }
+    let target = match (source, record, replay) {
+        (Some(source), None, None) => Target::Source(source),
+        (Some(source), Some(recording), None) if commands.is_none() && !dap => {
+            Target::Record { recording, source }
+        }
+        (_, Some(_), _) => {
+            return Err("--record runs one source file without a frontend".into());
+        }
+        (None, None, Some(recording)) => Target::Replay(recording),
+        (Some(_), None, Some(_)) => {
+            return Err("--replay debugs the sources its recording carries".into());
+        }
+        (None, None, None) if commands.is_some() => {
+            return Err("--script requires a Topal source file".into());
+        }
+        (None, None, None) if dap => return Err("--dap requires a Topal source file".into()),
+        (None, None, None) => return Err(USAGE.into()),
+    };
+    Ok(Arguments {
+        target,
+        commands,
+        dap,
+    })
 }
 
 #[allow(clippy::too_many_lines)] // Command aliases remain visible in one deterministic dispatcher.
@@ -201,8 +281,7 @@ fn command_loop(
         history,
         complete,
     } = debuggee;
-    let mut breakpoints = BTreeSet::new();
-    let mut watchpoints = BTreeSet::new();
+    let mut breakpoints = Breakpoints::default();
     let mut checkpoints = BTreeMap::new();
     let mut line_number = initial_line_number;
     loop {
@@ -223,6 +302,7 @@ fn command_loop(
             reject_script_shortcut(&command, script_name, line_number)?;
             command
         };
+        let command = continue_print_expression(&mut input, prompt, command, &mut line_number)?;
         match command.as_str() {
             command if command.starts_with("# ") => {}
             command
@@ -249,11 +329,15 @@ fn command_loop(
                 if handle_continue_command(
                     command,
                     history,
+                    session,
+                    execution,
+                    complete,
                     source,
                     source_name,
                     &breakpoints,
-                    &watchpoints,
                 ) => {}
+            command if handle_breakpoint_command(command, &mut breakpoints, source_name) => {}
+            command if handle_query_command(command, history, source, source_name) => {}
             "step" | "s" => match history.step_forward() {
                 Some(transition) => print_transition(transition),
                 None => println!("end of execution"),
@@ -271,26 +355,17 @@ fn command_loop(
             }
             "history" => print_history(history),
             "why" => print_reason(history),
-            "breakpoints" => {
-                print_breakpoints(&breakpoints, source_name);
-            }
             "watchpoints" => {
-                print_watchpoints(&watchpoints);
+                print_watchpoints(&breakpoints.watchpoints);
             }
             "checkpoints" => {
                 print_checkpoints(&checkpoints);
             }
-            command if command.starts_with("break ") => {
-                update_breakpoint(command, "break ", &mut breakpoints, true);
-            }
-            command if command.starts_with("delete ") => {
-                update_breakpoint(command, "delete ", &mut breakpoints, false);
-            }
             command if command.starts_with("watch ") => {
-                update_watchpoint(command, "watch ", &mut watchpoints, true);
+                update_watchpoint(command, "watch ", &mut breakpoints.watchpoints, true);
             }
             command if command.starts_with("unwatch ") => {
-                update_watchpoint(command, "unwatch ", &mut watchpoints, false);
+                update_watchpoint(command, "unwatch ", &mut breakpoints.watchpoints, false);
             }
             command if command.starts_with("checkpoint ") => {
                 save_checkpoint(command, &mut checkpoints, history.cursor());
@@ -315,7 +390,7 @@ fn command_loop(
             }
             "help" | "h" => {
                 println!(
-                    "step | reverse-step | source-step | reverse-source-step | next | reverse-next | finish | reverse-finish | backtrace | break LINE | delete LINE | breakpoints | watch NAME | unwatch NAME | watchpoints | continue | reverse-continue | checkpoint NAME | restore NAME | checkpoints | delete-checkpoint NAME | where | why | history | print | bindings | quit"
+                    "step | reverse-step | source-step | reverse-source-step | next | reverse-next | finish | reverse-finish | backtrace | break LINE | delete LINE | condition LINE EXPRESSION | hits LINE COUNT | log LINE EXPRESSION | break-function NAME | delete-function NAME | breakpoints | watch NAME | unwatch NAME | watchpoints | continue | reverse-continue | checkpoint NAME | restore NAME | checkpoints | delete-checkpoint NAME | where | why | history | last-write NAME | values NAME | first-event EVENT [DETAIL] | seek SEQUENCE | print | bindings | quit"
                 );
                 println!("expression-step (es) advances to the next recorded expression state");
             }
@@ -394,16 +469,23 @@ const DEBUG_COMMANDS: &[&str] = &[
     "backtrace",
     "bindings",
     "break",
+    "break-function",
     "breakpoints",
     "checkpoint",
     "checkpoints",
+    "condition",
     "continue",
     "delete",
     "delete-checkpoint",
+    "delete-function",
     "expression-step",
     "finish",
+    "first-event",
     "help",
     "history",
+    "hits",
+    "last-write",
+    "log",
     "next",
     "print",
     "quit",
@@ -413,9 +495,11 @@ const DEBUG_COMMANDS: &[&str] = &[
     "reverse-next",
     "reverse-source-step",
     "reverse-step",
+    "seek",
     "source-step",
     "step",
     "unwatch",
+    "values",
     "watch",
     "watchpoints",
     "where",
@@ -533,20 +617,33 @@ fn print_reason(history: &ExecutionHistory) {
     }
 }
 
+#[allow(clippy::too_many_arguments)] // Continuing forward resumes the live debuggee.
 fn handle_continue_command(
     command: &str,
     history: &mut ExecutionHistory,
+    session: &mut Session,
+    execution: &mut Execution,
+    complete: &mut bool,
     source: &str,
     source_name: &str,
-    breakpoints: &BTreeSet<usize>,
-    watchpoints: &BTreeSet<String>,
+    breakpoints: &Breakpoints,
 ) -> bool {
     let reverse = match command {
         "continue" | "c" => false,
         "reverse-continue" | "rc" => true,
         _ => return false,
     };
-    if continue_to_stop(history, source, breakpoints, watchpoints, reverse) {
+    let arrival = if reverse {
+        breakpoints.continue_backward(history, session, source, source_name)
+    } else {
+        breakpoints.continue_forward(history, session, execution, complete, source, source_name)
+    };
+    for message in &arrival.messages {
+        println!("{message}");
+    }
+    if let Some(error) = &arrival.failure {
+        println!("{}", error.render(source_name));
+    } else if arrival.stop.is_some() {
         print_source_location(history, source, source_name);
     } else if reverse {
         println!("no earlier breakpoint");
@@ -568,6 +665,37 @@ fn read_command(input: &mut impl BufRead, prompt: bool) -> Result<Option<String>
     Ok((count != 0).then_some(command))
 }
 
+/// Read further lines into a `print` expression while the parser reports
+/// that the expression is still open.
+fn continue_print_expression(
+    input: &mut impl BufRead,
+    prompt: bool,
+    mut command: String,
+    line_number: &mut usize,
+) -> Result<String, String> {
+    while command
+        .strip_prefix("print ")
+        .is_some_and(|expression| Session::continuation(expression).is_some())
+    {
+        if prompt {
+            print!("... ");
+            io::stdout().flush().map_err(|error| error.to_string())?;
+        }
+        let mut line = String::new();
+        if input
+            .read_line(&mut line)
+            .map_err(|error| error.to_string())?
+            == 0
+        {
+            break;
+        }
+        *line_number += 1;
+        command.push('\n');
+        command.push_str(line.trim_end_matches(['\r', '\n']));
+    }
+    Ok(command)
+}
+
 fn print_bindings(history: &ExecutionHistory) {
     if let Some(state) = history.state() {
         for (name, value) in &state.bindings {
@@ -607,17 +735,8 @@ fn handle_frame_command(
             None => println!("start of current frame"),
         },
         "finish" => {
-            while !*complete {
-                match execution.step(session, history) {
-                    Ok(ExecutionStep::Complete(_) | ExecutionStep::Returned { .. }) => {
-                        *complete = true;
-                    }
-                    Ok(ExecutionStep::Advanced { .. }) => {}
-                    Err(error) => {
-                        println!("{}", error.render(source_name));
-                        break;
-                    }
-                }
+            if let Err(error) = run_to_completion(history, session, execution, complete) {
+                println!("{}", error.render(source_name));
             }
             history.finish();
             print_source_location(history, source, source_name);
@@ -632,6 +751,22 @@ fn handle_frame_command(
     true
 }
 
+/// Execute the live debuggee until it completes or a diagnostic stops it.
+fn run_to_completion(
+    history: &mut ExecutionHistory,
+    session: &mut Session,
+    execution: &mut Execution,
+    complete: &mut bool,
+) -> Result<(), topal_language::Diagnostic> {
+    while !*complete {
+        match execution.step(session, history)? {
+            ExecutionStep::Complete(_) | ExecutionStep::Returned { .. } => *complete = true,
+            ExecutionStep::Advanced { .. } => {}
+        }
+    }
+    Ok(())
+}
+
 fn live_source_step(
     history: &mut ExecutionHistory,
     session: &mut Session,
@@ -671,9 +806,12 @@ fn valid_checkpoint_name(name: &str) -> bool {
     !name.is_empty() && !name.chars().any(char::is_whitespace)
 }
 
-fn print_breakpoints(breakpoints: &BTreeSet<usize>, source_name: &str) {
-    for line in breakpoints {
-        println!("{source_name}:{line}");
+fn print_breakpoints(breakpoints: &Breakpoints, source_name: &str) {
+    for (line, options) in &breakpoints.lines {
+        println!("{source_name}:{line}{}", options.describe());
+    }
+    for (name, options) in &breakpoints.functions {
+        println!("function {name}{}", options.describe());
     }
 }
 
@@ -744,15 +882,57 @@ fn update_watchpoint(
     }
 }
 
-fn update_breakpoint(command: &str, prefix: &str, breakpoints: &mut BTreeSet<usize>, insert: bool) {
-    match command[prefix.len()..].trim().parse::<usize>() {
+fn handle_breakpoint_command(
+    command: &str,
+    breakpoints: &mut Breakpoints,
+    source_name: &str,
+) -> bool {
+    let (head, arguments) = command.split_once(' ').unwrap_or((command, ""));
+    let arguments = arguments.trim();
+    match head {
+        "breakpoints" if arguments.is_empty() => print_breakpoints(breakpoints, source_name),
+        "break" | "delete" => update_breakpoint(arguments, &mut breakpoints.lines, head == "break"),
+        "condition" | "hits" | "log" => {
+            let (line, value) = arguments.split_once(' ').unwrap_or((arguments, ""));
+            match line.parse::<usize>() {
+                Ok(0) | Err(_) => println!("breakpoint line must be a positive integer"),
+                Ok(line) => update_breakpoint_options(head, line, value.trim(), breakpoints),
+            }
+        }
+        "break-function" | "delete-function" if arguments.is_empty() => {
+            println!("function breakpoint requires a function name");
+        }
+        "break-function" => {
+            let name = arguments.split_whitespace().collect::<Vec<_>>().join(" ");
+            println!("function breakpoint set for {name}");
+            breakpoints.functions.entry(name).or_default();
+        }
+        "delete-function" => {
+            let name = arguments.split_whitespace().collect::<Vec<_>>().join(" ");
+            if breakpoints.functions.remove(&name).is_some() {
+                println!("function breakpoint removed for {name}");
+            } else {
+                println!("no function breakpoint for {name}");
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+fn update_breakpoint(
+    argument: &str,
+    breakpoints: &mut BTreeMap<usize, BreakpointOptions>,
+    insert: bool,
+) {
+    match argument.parse::<usize>() {
         Ok(0) | Err(_) => println!("breakpoint line must be a positive integer"),
         Ok(line) if insert => {
-            breakpoints.insert(line);
+            breakpoints.entry(line).or_default();
             println!("breakpoint set at line {line}");
         }
         Ok(line) => {
-            if breakpoints.remove(&line) {
+            if breakpoints.remove(&line).is_some() {
                 println!("breakpoint removed from line {line}");
             } else {
                 println!("no breakpoint at line {line}");
@@ -761,31 +941,50 @@ fn update_breakpoint(command: &str, prefix: &str, breakpoints: &mut BTreeSet<usi
     }
 }
 
-fn continue_to_stop(
-    history: &mut ExecutionHistory,
-    source: &str,
-    breakpoints: &BTreeSet<usize>,
-    watchpoints: &BTreeSet<String>,
-    reverse: bool,
-) -> bool {
-    let current_bindings = history
-        .state()
-        .map(|state| state.bindings.clone())
-        .unwrap_or_default();
-    let matches = |state: &topal_language::ExecutionState| {
-        let breakpoint = state.source_range.is_some_and(|range| {
-            let (line, _) = line_column(source, range.start);
-            breakpoints.contains(&line)
-        });
-        let watched_change = watchpoints
-            .iter()
-            .any(|name| state.bindings.get(name) != current_bindings.get(name));
-        breakpoint || watched_change
-    };
-    if reverse {
-        history.continue_source_backward(matches).is_some()
+/// Apply `condition`, `hits`, or `log` to the breakpoint at `line`; an empty
+/// value clears the option. A log-point is created when absent.
+fn update_breakpoint_options(
+    command: &str,
+    line: usize,
+    value: &str,
+    breakpoints: &mut Breakpoints,
+) {
+    let options = if command == "log" {
+        breakpoints.lines.entry(line).or_default()
+    } else if let Some(options) = breakpoints.lines.get_mut(&line) {
+        options
     } else {
-        history.continue_source_forward(matches).is_some()
+        println!("no breakpoint at line {line}");
+        return;
+    };
+    let value = (!value.is_empty()).then(|| value.to_owned());
+    match command {
+        "condition" => {
+            options.condition = value;
+            match &options.condition {
+                Some(condition) => println!("breakpoint at line {line} stops if {condition}"),
+                None => println!("breakpoint at line {line} is unconditional"),
+            }
+        }
+        "hits" => match value.map(|count| count.parse::<usize>()) {
+            Some(Ok(0) | Err(_)) => println!("hit count must be a positive integer"),
+            Some(Ok(count)) => {
+                options.hit_count = Some(count);
+                println!("breakpoint at line {line} stops from hit {count}");
+            }
+            None => {
+                options.hit_count = None;
+                println!("breakpoint at line {line} stops at every hit");
+            }
+        },
+        _ => {
+            options.log = value.map(|expression| format!("{{{expression}}}"));
+            if options.log.is_some() {
+                println!("log-point set at line {line}");
+            } else {
+                println!("breakpoint at line {line} stops instead of logging");
+            }
+        }
     }
 }
 
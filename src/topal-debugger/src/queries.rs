@@ -0,0 +1,100 @@
+//! Time-travel queries over recorded history.
+//!
+//! Queries search every recorded transition and state regardless of the
+//! selected state, and name their results by transition sequence number, the
+//! number `history` lists and `seek` selects. A state change belongs to the
+//! transition after which the changed state is recorded.
+
+use topal_language::{ExecutionHistory, ExecutionTransition};
+
+use super::{print_source_location, print_transition};
+
+/// Handle `last-write`, `values`, `first-event` and `seek`.
+pub(super) fn handle_query_command(
+    command: &str,
+    history: &mut ExecutionHistory,
+    source: &str,
+    source_name: &str,
+) -> bool {
+    let (head, arguments) = command.split_once(' ').unwrap_or((command, ""));
+    let arguments = arguments.trim();
+    match head {
+        "last-write" if !arguments.is_empty() => print_last_write(history, arguments),
+        "values" if !arguments.is_empty() => print_values(history, arguments),
+        "first-event" if !arguments.is_empty() => {
+            let (event, detail) = arguments
+                .split_once(' ')
+                .map_or((arguments, None), |(event, detail)| {
+                    (event, Some(unquote(detail.trim())))
+                });
+            match history
+                .transitions()
+                .iter()
+                .find(|transition| matches_event(transition, event, detail))
+            {
+                Some(transition) => print_transition(transition),
+                None => println!("no transition matches {arguments}"),
+            }
+        }
+        "seek" => match arguments.parse::<usize>() {
+            Ok(sequence) if history.seek(sequence + 1) => {
+                if let Some(transition) = history.current() {
+                    print_transition(transition);
+                }
+                print_source_location(history, source, source_name);
+            }
+            _ => println!("no recorded transition #{arguments}"),
+        },
+        _ => return false,
+    }
+    true
+}
+
+/// Report the last write to binding `name` at or before the selected state.
+fn print_last_write(history: &ExecutionHistory, name: &str) {
+    let cursor = history.cursor();
+    match history
+        .binding_writes(name)
+        .into_iter()
+        .rev()
+        .find(|(written, _)| *written <= cursor)
+    {
+        Some((written, value)) => {
+            println!("{name} last written at #{}: {value}", sequence(written));
+        }
+        None => println!("{name} not written before the selected state"),
+    }
+}
+
+/// List every value binding `name` took, in execution order.
+fn print_values(history: &ExecutionHistory, name: &str) {
+    let writes = history.binding_writes(name);
+    if writes.is_empty() {
+        println!("no recorded writes to {name}");
+    }
+    for (written, value) in writes {
+        println!("#{} {name} = {value}", sequence(written));
+    }
+}
+
+/// The transition after which the state at `cursor` is recorded.
+const fn sequence(cursor: usize) -> usize {
+    cursor.saturating_sub(1)
+}
+
+/// Whether `transition` is the event `filter` names, or one in the event
+/// family it names, such as `binding` for `binding.bind`, with a detail that
+/// contains `detail`.
+fn matches_event(transition: &ExecutionTransition, filter: &str, detail: Option<&str>) -> bool {
+    let event = transition
+        .event
+        .strip_prefix(filter)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'));
+    event && detail.is_none_or(|detail| transition.detail.contains(detail))
+}
+
+fn unquote(text: &str) -> &str {
+    text.strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .unwrap_or(text)
+}
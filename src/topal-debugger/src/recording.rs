@@ -0,0 +1,120 @@
+//! Record and replay modes.
+//!
+//! Record mode runs a debuggee to its end, or to the diagnostic that stops
+//! it, and writes its sources, history, and the filesystem observations its
+//! module tree loader made to a shareable recording. Replay executes the
+//! recorded sources instead of the original inputs, answers each loader query
+//! with the observation recorded at the same cursor instead of repeating it,
+//! and refuses a recording whose history it does not reproduce, so navigation
+//! after replay inspects exactly the recorded execution.
+
+use std::fs;
+use std::path::Path;
+
+use topal_language::{
+    ExecutionHistory, RecordedSource, Recording, Session, record_module_tree, replay_module_tree,
+};
+
+use super::{Debuggee, load_with, run_to_completion};
+
+/// Run `source` to its end and write its recording to `path`.
+pub(super) fn record(path: &str, source: &str) -> Result<(), String> {
+    let mut observations = Vec::new();
+    let (mut debuggee, text, source_name) = load_with(source, |session, root, history| {
+        record_module_tree(session, root, history, &mut observations)
+    })?;
+    let module_root = Path::new(source).is_dir().then(|| source.to_owned());
+    // The module tree is the sources the loader read, as it observed them.
+    let modules = observations
+        .iter()
+        .filter_map(|observation| {
+            Some(RecordedSource {
+                path: observation.kind.strip_prefix("read ")?.to_owned(),
+                text: observation.result.clone(),
+            })
+        })
+        .collect();
+    let failure = finish(&mut debuggee, &source_name);
+    let mut recording = Recording {
+        source_name,
+        source: text,
+        module_root,
+        modules,
+        observations,
+        failure,
+        ..Recording::default()
+    };
+    recording.record_history(&debuggee.history);
+    let bytes = recording
+        .encode()
+        .map_err(|message| format!("cannot record {path}: {message}"))?;
+    fs::write(path, bytes).map_err(|error| format!("cannot write {path}: {error}"))?;
+    println!(
+        "recorded {} transitions to {path}",
+        recording.transitions.len()
+    );
+    if let Some(failure) = &recording.failure {
+        println!("{failure}");
+    }
+    Ok(())
+}
+
+/// Reconstruct the debuggee recorded in `path`, with its history complete
+/// and its cursor before the first transition.
+pub(super) fn replay(path: &str) -> Result<(Debuggee, String, String), String> {
+    let bytes = fs::read(path).map_err(|error| format!("cannot read {path}: {error}"))?;
+    let recording = Recording::decode(&bytes)
+        .map_err(|message| format!("{path}: error[D-RECORDING]: {message}"))?;
+    let mut session = Session::new();
+    let mut history = ExecutionHistory::new();
+    match &recording.module_root {
+        Some(root) => replay_module_tree(
+            &mut session,
+            Path::new(root),
+            &recording.observations,
+            &mut history,
+        ),
+        None => recording
+            .observations
+            .first()
+            .map_or(Ok(()), |observation| {
+                Err(format!(
+                    "replay does not consume the recorded `{}` observation at transition #{}",
+                    observation.kind, observation.cursor
+                ))
+            }),
+    }
+    .map_err(|divergence| format!("{path}: error[D-REPLAY-DIVERGED]: {divergence}"))?;
+    let execution = session
+        .prepare_source_file(&recording.source, &mut history)
+        .map_err(|error| error.render(&recording.source_name))?;
+    let mut debuggee = Debuggee {
+        session,
+        execution,
+        history,
+        complete: false,
+    };
+    let failure = finish(&mut debuggee, &recording.source_name);
+    let divergence = recording.divergence(&debuggee.history).or_else(|| {
+        (failure != recording.failure)
+            .then(|| "replay does not reproduce the recorded outcome".to_owned())
+    });
+    if let Some(divergence) = divergence {
+        return Err(format!("{path}: error[D-REPLAY-DIVERGED]: {divergence}"));
+    }
+    debuggee.history.rewind();
+    Ok((debuggee, recording.source, recording.source_name))
+}
+
+/// Run the debuggee to its end and render the diagnostic that stopped it.
+fn finish(debuggee: &mut Debuggee, source_name: &str) -> Option<String> {
+    let Debuggee {
+        session,
+        execution,
+        history,
+        complete,
+    } = debuggee;
+    run_to_completion(history, session, execution, complete)
+        .err()
+        .map(|error| error.render(source_name))
+}
@@ -2,6 +2,8 @@ use std::io::Write;
 use std::path::Path;
 use std::process::{Command, Stdio};
 
+use serde_json::{Value, json};
+
 fn language_example(name: &str) -> String {
     format!(
         "{}/../../examples/language/{name}",
@@ -18,7 +20,7 @@ fn every_language_example_executes_through_the_debugger() {
         .filter(|path| path.extension().is_some_and(|extension| extension == "t"))
         .collect::<Vec<_>>();
     examples.sort();
-    assert_eq!(examples.len(), 192);
+    assert_eq!(examples.len(), 195);
     let commands = "use language ( version is v0.1, features is ( debug ) )\ncontinue\nquit\n";
     for example in examples {
         let mut child = Command::new(env!("CARGO_BIN_EXE_topal-debug"))
@@ -108,6 +110,238 @@ fn navigates_recorded_execution_in_both_directions() {
     assert!(stdout.contains("#0 <script> at"));
 }
 
+fn framed(message: &Value) -> Vec<u8> {
+    let body = serde_json::to_vec(message).unwrap();
+    let mut framed = format!("Content-Length: {}\r\n\r\n", body.len()).into_bytes();
+    framed.extend(body);
+    framed
+}
+
+fn adapter_messages(output: &[u8]) -> Vec<Value> {
+    let mut remaining = output;
+    let mut messages = Vec::new();
+    while !remaining.is_empty() {
+        let header_end = remaining
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .unwrap();
+        let headers = std::str::from_utf8(&remaining[..header_end]).unwrap();
+        let length = headers
+            .strip_prefix("Content-Length: ")
+            .unwrap()
+            .parse::<usize>()
+            .unwrap();
+        let body_start = header_end + 4;
+        messages.push(serde_json::from_slice(&remaining[body_start..body_start + length]).unwrap());
+        remaining = &remaining[body_start + length..];
+    }
+    messages
+}
+
+/// Send `requests` to `topal-debug --dap` for `source` and return every
+/// response and event it writes.
+fn adapter_transcript(source: &Path, requests: impl IntoIterator<Item = Value>) -> Vec<Value> {
+    let mut input = Vec::new();
+    for (sequence, mut request) in requests.into_iter().enumerate() {
+        request["seq"] = json!(sequence + 1);
+        request["type"] = json!("request");
+        input.extend(framed(&request));
+    }
+    let mut child = Command::new(env!("CARGO_BIN_EXE_topal-debug"))
+        .arg("--dap")
+        .arg(source)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(&input).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    adapter_messages(&output.stdout)
+}
+
+#[test]
+fn debug_adapter_maps_history_control_onto_protocol_requests() {
+    let source = std::fs::canonicalize(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../examples/debugger/basic-history.t"
+    ))
+    .unwrap();
+    let path = source.display().to_string();
+    let requests = [
+        json!({ "command": "initialize", "arguments": { "adapterID": "topal" } }),
+        json!({ "command": "launch", "arguments": { "stopOnEntry": true } }),
+        json!({
+            "command": "setBreakpoints",
+            "arguments": { "source": { "path": path }, "breakpoints": [{ "line": 8 }] }
+        }),
+        json!({ "command": "configurationDone" }),
+        json!({ "command": "stackTrace", "arguments": { "threadId": 1 } }),
+        json!({ "command": "continue", "arguments": { "threadId": 1 } }),
+        json!({ "command": "stackTrace", "arguments": { "threadId": 1 } }),
+        json!({ "command": "variables", "arguments": { "variablesReference": 1 } }),
+        json!({ "command": "evaluate", "arguments": { "expression": "answer + 1" } }),
+        json!({ "command": "stepBack", "arguments": { "threadId": 1 } }),
+        json!({ "command": "stackTrace", "arguments": { "threadId": 1 } }),
+        json!({ "command": "reverseContinue", "arguments": { "threadId": 1 } }),
+        json!({ "command": "continue", "arguments": { "threadId": 1 } }),
+        json!({ "command": "continue", "arguments": { "threadId": 1 } }),
+        json!({ "command": "disconnect" }),
+    ];
+    let messages = adapter_transcript(&source, requests);
+    let response = |request_seq: u64| {
+        messages
+            .iter()
+            .find(|message| message["type"] == "response" && message["request_seq"] == request_seq)
+            .unwrap()
+    };
+    let lines =
+        [5, 7, 11].map(|request| response(request)["body"]["stackFrames"][0]["line"].clone());
+    let stops = messages
+        .iter()
+        .filter(|message| message["event"] == "stopped")
+        .map(|message| message["body"]["reason"].as_str().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(response(1)["body"]["supportsStepBack"], true);
+    assert_eq!(messages[1]["event"], "initialized");
+    assert_eq!(response(3)["body"]["breakpoints"][0]["verified"], true);
+    assert_eq!(lines, [json!(7), json!(8), json!(7)]);
+    assert_eq!(
+        response(8)["body"]["variables"],
+        json!([{ "name": "answer", "value": "40", "variablesReference": 0 }])
+    );
+    assert_eq!(response(9)["body"]["result"], "41");
+    assert_eq!(
+        stops,
+        ["entry", "breakpoint", "step", "entry", "breakpoint"]
+    );
+    assert!(
+        messages
+            .iter()
+            .any(|message| message["event"] == "terminated")
+    );
+    assert!(response(15)["success"].as_bool().unwrap());
+}
+
+#[test]
+fn debug_adapter_honors_conditions_hit_counts_log_points_and_function_breakpoints() {
+    let source = std::fs::canonicalize(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../examples/debugger/breakpoint-conditions.t"
+    ))
+    .unwrap();
+    let path = source.display().to_string();
+    let messages = adapter_transcript(
+        &source,
+        [
+            json!({ "command": "initialize", "arguments": { "adapterID": "topal" } }),
+            json!({ "command": "launch", "arguments": {} }),
+            json!({
+                "command": "setBreakpoints",
+                "arguments": {
+                    "source": { "path": path },
+                    "breakpoints": [{ "line": 11, "logMessage": "partial {a + b}" }]
+                }
+            }),
+            json!({
+                "command": "setFunctionBreakpoints",
+                "arguments": {
+                    "breakpoints": [{ "name": "double", "condition": "x > 3", "hitCondition": "2" }]
+                }
+            }),
+            json!({ "command": "configurationDone" }),
+            json!({ "command": "evaluate", "arguments": { "expression": "x" } }),
+            json!({ "command": "reverseContinue", "arguments": { "threadId": 1 } }),
+            json!({ "command": "continue", "arguments": { "threadId": 1 } }),
+            json!({ "command": "evaluate", "arguments": { "expression": "x" } }),
+            json!({ "command": "continue", "arguments": { "threadId": 1 } }),
+            json!({ "command": "disconnect" }),
+        ],
+    );
+    let response = |request_seq: u64| {
+        messages
+            .iter()
+            .find(|message| message["type"] == "response" && message["request_seq"] == request_seq)
+            .unwrap()
+    };
+    let stops = messages
+        .iter()
+        .filter(|message| message["event"] == "stopped")
+        .map(|message| message["body"]["reason"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    let logs = messages
+        .iter()
+        .filter(|message| message["event"] == "output")
+        .map(|message| message["body"]["output"].as_str().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(response(1)["body"]["supportsConditionalBreakpoints"], true);
+    assert_eq!(response(1)["body"]["supportsLogPoints"], true);
+    assert_eq!(response(4)["body"]["breakpoints"][0]["verified"], true);
+    assert_eq!(response(6)["body"]["result"], "5");
+    assert_eq!(response(9)["body"]["result"], "5");
+    assert_eq!(
+        stops,
+        ["function breakpoint", "entry", "function breakpoint"]
+    );
+    assert_eq!(logs.len(), 4);
+    assert!(logs.iter().all(|log| log.ends_with(":11: partial 14\n")));
+    assert!(
+        messages
+            .iter()
+            .any(|message| message["event"] == "terminated")
+    );
+}
+
+#[test]
+fn stops_at_conditional_counted_and_function_breakpoints_in_both_directions() {
+    let root = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/debugger/");
+    let output = Command::new(env!("CARGO_BIN_EXE_topal-debug"))
+        .args([
+            "--script",
+            &format!("{root}breakpoint-conditions.debug"),
+            &format!("{root}breakpoint-conditions.t"),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let printed = stdout
+        .lines()
+        .filter(|line| line.parse::<i64>().is_ok())
+        .collect::<Vec<_>>();
+    assert_eq!(printed, ["4", "5", "4", "5", "4", "4", "5", "4"]);
+    assert_eq!(stdout.matches("breakpoint-conditions.t:11: 14").count(), 6);
+    assert!(stdout.contains("breakpoint-conditions.t:8 if x > 3"));
+    assert!(stdout.contains("breakpoint-conditions.t:11 log {a + b}"));
+    assert!(stdout.contains("function breakpoint set for double"));
+    assert!(stdout.contains("breakpoint at line 8 stops from hit 2"));
+}
+
+#[test]
+fn answers_history_queries_with_seekable_transitions() {
+    let root = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/debugger/");
+    let output = Command::new(env!("CARGO_BIN_EXE_topal-debug"))
+        .args([
+            "--script",
+            &format!("{root}history-queries.debug"),
+            &format!("{root}breakpoint-conditions.t"),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("#8 x = 3\n#19 x = 4\n#30 x = 5\n"));
+    assert!(stdout.contains("a last written at #14: 6"));
+    assert!(stdout.contains("#7 function.entry [TOPAL-FUNCTION-ORDINARY-001] double"));
+    assert!(stdout.contains("#36 binding.bind [TOPAL-SYN-BIND-001] c"));
+    assert!(stdout.contains("#12 evaluation.result [TOPAL-SYN-GRAMMAR-001] Int\n"));
+    assert!(stdout.contains("breakpoint-conditions.t:8:3\n  x * 2\n"));
+    assert!(stdout.contains("x last written at #8: 3\n3\n"));
+    assert!(stdout.ends_with("x not written before the selected state\n"));
+}
+
 #[test]
 fn executes_the_debuggee_only_when_commands_advance_it() {
     let root = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/debugger/");
@@ -146,6 +380,105 @@ fn retains_inspectable_history_when_live_execution_fails() {
     assert!(stdout.contains("no value at current execution state"));
 }
 
+#[test]
+fn replays_a_recorded_failure_in_both_directions_without_its_source() {
+    let root = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/debugger/");
+    let directory = Path::new(env!("CARGO_TARGET_TMPDIR")).join("recorded-failure");
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    let source = directory.join("failing-history.t");
+    std::fs::copy(format!("{root}failing-history.t"), &source).unwrap();
+    let recording = directory.join("failing-history.recording");
+    let output = Command::new(env!("CARGO_BIN_EXE_topal-debug"))
+        .arg("--record")
+        .arg(&recording)
+        .arg(&source)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("recorded 5 transitions to "));
+    assert!(stdout.contains("error[E-UNBOUND-NAME]: name is not bound"));
+    std::fs::remove_file(&source).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_topal-debug"))
+        .args([
+            "--script",
+            &format!("{root}recorded-failure.debug"),
+            "--replay",
+        ])
+        .arg(&recording)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("loaded 5 transitions\n"));
+    assert_eq!(stdout.matches("error[E-UNBOUND-NAME]").count(), 2);
+    assert!(stdout.contains("failing-history.t:7:11\nanswer is 40"));
+    assert!(stdout.contains("answer = 40"));
+    assert!(
+        stdout.ends_with("before first source statement\nno value at current execution state\n")
+    );
+}
+
+fn copy_tree(from: &Path, to: &Path) {
+    std::fs::create_dir_all(to).unwrap();
+    for entry in std::fs::read_dir(from).unwrap() {
+        let path = entry.unwrap().path();
+        let target = to.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_tree(&path, &target);
+        } else {
+            std::fs::copy(&path, &target).unwrap();
+        }
+    }
+}
+
+#[test]
+fn replays_a_recorded_module_tree_and_rejects_other_files() {
+    let root = concat!(env!("CARGO_MANIFEST_DIR"), "/../../library");
+    let package = Path::new(env!("CARGO_TARGET_TMPDIR")).join("recorded-library");
+    let _ = std::fs::remove_dir_all(&package);
+    copy_tree(Path::new(root), &package);
+    let recording = Path::new(env!("CARGO_TARGET_TMPDIR")).join("library.recording");
+    let output = Command::new(env!("CARGO_BIN_EXE_topal-debug"))
+        .arg("--record")
+        .arg(&recording)
+        .arg(&package)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    // Replay answers the module loader from the recorded observations.
+    std::fs::remove_dir_all(&package).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_topal-debug"))
+        .args(["--script", &format!("{root}/minimum.debug"), "--replay"])
+        .arg(&recording)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("evaluation.result because TOPAL-SYN-GRAMMAR-001 ((Int, Rational,"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_topal-debug"))
+        .args(["--replay", &format!("{root}/application.t")])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("application.t: error[D-RECORDING]: header at byte 8: invalid stream magic")
+    );
+}
+
 #[test]
 fn exposes_intermediate_expression_values_as_reversible_states() {
     let root = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/debugger/");
@@ -754,6 +1087,56 @@ fn records_reversible_exact_numeric_absolute() {
     assert!(stdout.contains("(42, 42, Rational ( 5, 4 ), Rational ( 5, 4 ))"));
 }
 
+#[test]
+fn records_reversible_approximate_numbers() {
+    let root = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/debugger/");
+    let output = Command::new(env!("CARGO_BIN_EXE_topal-debug"))
+        .args([
+            "--script",
+            &format!("{root}scripts/finish-and-reverse.debug"),
+            &language_example("approximate-numbers.t"),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("root./(Binary64,Binary64)"));
+    assert!(stdout.contains("root.reproducible-sum(List Binary64)"));
+    assert!(stdout.contains("Binary64 -Infinity, 2, -4, false"));
+}
+
+#[test]
+fn records_reversible_quantities() {
+    let root = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/debugger/");
+    let output = Command::new(env!("CARGO_BIN_EXE_topal-debug"))
+        .args([
+            "--script",
+            &format!("{root}scripts/finish-and-reverse.debug"),
+            &language_example("quantities.t"),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("(true, 2.5[kg], 100[delta Kelvin], 373.15[Kelvin], true)"));
+}
+
+#[test]
+fn records_reversible_sensitive_values() {
+    let root = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/debugger/");
+    let output = Command::new(env!("CARGO_BIN_EXE_topal-debug"))
+        .args([
+            "--script",
+            &format!("{root}scripts/finish-and-reverse.debug"),
+            &language_example("sensitive-values.t"),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("(true, false)"));
+}
+
 #[test]
 fn records_reversible_named_exact_numeric_negation() {
     let root = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/debugger/");
@@ -2562,6 +2945,41 @@ fn script_mode_rejects_unknown_commands_with_a_line_diagnostic() {
     );
 }
 
+#[test]
+fn print_reads_continuation_lines_until_the_expression_closes() {
+    let source = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../examples/debugger/basic-history.t"
+    );
+    let mut child = Command::new(env!("CARGO_BIN_EXE_topal-debug"))
+        .args(["--script", "-", source])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(
+            b"use language ( version is v0.1, features is ( debug ) )\nsource-step\nsource-step\nprint (answer,\n  \"two\nlines\")\nnot-a-command\n",
+        )
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .contains("(40, \"two\nlines\")\n")
+    );
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("<stdin>:7: error[D-UNKNOWN-COMMAND]")
+    );
+}
+
 #[test]
 fn script_mode_requires_the_debug_language_variant() {
     let source = concat!(
@@ -0,0 +1,626 @@
+//! Canonical formatting of Topal source.
+//!
+//! The formatter reprints every token verbatim, comments included, and changes
+//! only trivia: indentation, spacing between tokens on a line, runs of blank
+//! lines, and the layout of the `use language (...)` header. Indentation
+//! follows the layout rule, so an indented body keeps its structure while each
+//! level becomes two spaces wide, and a line inside brackets indents by its
+//! bracket depth. A result is returned only after it re-parses to the same
+//! syntax as its input.
+
+use std::fmt::{self, Write as _};
+use std::ops::Range;
+
+use topal_source::{SourceError, SourceText, Span};
+use topal_syntax::{Lexed, ParsedSource, Statement, Token, TokenKind, lex, parse};
+
+/// Width of one layout level or open bracket.
+const INDENT: usize = 2;
+
+/// Reason a source cannot be formatted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FormatError {
+    /// The text is not valid Topal source.
+    Source(SourceError),
+    /// The source has a lexical or syntax diagnostic.
+    Syntax {
+        line: usize,
+        column: usize,
+        code: &'static str,
+        message: String,
+    },
+    /// The canonical text would not re-parse to the same syntax.
+    NotEquivalent,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Source(error) => error.fmt(formatter),
+            Self::Syntax {
+                line,
+                column,
+                code,
+                message,
+            } => write!(formatter, "{line}:{column}: {code}: {message}"),
+            Self::NotEquivalent => {
+                formatter.write_str("canonical layout would change the parsed syntax")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// Replacement of one byte range of the normalized source.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Edit {
+    pub span: Span,
+    pub text: String,
+}
+
+/// Format a complete source with LF line endings and one final newline.
+///
+/// # Errors
+///
+/// Returns an error for invalid source, for any lexical or syntax diagnostic,
+/// and when the canonical text would not re-parse to the same syntax.
+pub fn format_source(text: &str) -> Result<String, FormatError> {
+    let document = Document::new(text)?;
+    let formatted = document.print(0..document.lines.len());
+    document.check(&formatted)?;
+    Ok(formatted)
+}
+
+/// Format the top-level statements that `span` of the normalized source
+/// touches, together with the comments and blank lines between them.
+///
+/// # Errors
+///
+/// Returns the errors of [`format_source`].
+pub fn format_range(text: &str, span: Span) -> Result<Edit, FormatError> {
+    let document = Document::new(text)?;
+    let lines = document.statement_lines(span);
+    let start = document.lines[lines.start].start;
+    let end = document
+        .lines
+        .get(lines.end)
+        .map_or(document.source.as_str().len(), |line| line.start);
+    let replacement = document.print(lines);
+    let mut edited = document.source.as_str().to_owned();
+    edited.replace_range(start..end, &replacement);
+    document.check(&edited)?;
+    Ok(Edit {
+        span: Span::new(start, end),
+        text: replacement,
+    })
+}
+
+/// One input line, ended by a newline token or the end of the source.
+struct Line {
+    start: usize,
+    /// Indexes of the line's tokens other than whitespace and newlines.
+    tokens: Vec<usize>,
+    /// Width of the line's leading whitespace.
+    width: usize,
+    /// Whether the line begins a statement at the outermost layout level.
+    top_level: bool,
+    /// Canonical text, or `None` when the line is removed.
+    printed: Option<String>,
+}
+
+struct Document {
+    source: SourceText,
+    lexed: Lexed,
+    parsed: ParsedSource,
+    lines: Vec<Line>,
+}
+
+impl Document {
+    fn new(text: &str) -> Result<Self, FormatError> {
+        let source = SourceText::new(text).map_err(FormatError::Source)?;
+        let lexed = lex(&source);
+        let parsed = parse(&source, &lexed);
+        if let Some(diagnostic) = lexed.diagnostics.iter().chain(&parsed.diagnostics).next() {
+            let position = source.position(diagnostic.span.start);
+            return Err(FormatError::Syntax {
+                line: position.line,
+                column: position.column,
+                code: diagnostic.code,
+                message: diagnostic.message.clone(),
+            });
+        }
+        let mut document = Self {
+            lines: split_lines(&lexed.tokens),
+            source,
+            lexed,
+            parsed,
+        };
+        document.layout();
+        document.collapse_blank_lines();
+        Ok(document)
+    }
+
+    fn token(&self, index: usize) -> Token {
+        self.lexed.tokens[index]
+    }
+
+    fn text(&self, index: usize) -> &str {
+        self.source.slice(self.lexed.tokens[index].span)
+    }
+
+    /// Assign every line its canonical indentation and spacing.
+    fn layout(&mut self) {
+        let header = self.header_lines();
+        // Input widths of the open layout levels, innermost last.
+        let mut levels = vec![0];
+        let mut brackets = Vec::new();
+        let mut base = 0;
+        let mut index = 0;
+        while index < self.lines.len() {
+            if let Some((lines, text)) = header.as_ref().filter(|(lines, _)| lines.start == index) {
+                self.lines[index].printed = Some(text.clone());
+                self.lines[index].top_level = true;
+                for line in &mut self.lines[index + 1..lines.end] {
+                    line.printed = None;
+                }
+                index = lines.end;
+                continue;
+            }
+            let line = &self.lines[index];
+            let Some(&first) = line.tokens.first() else {
+                self.lines[index].printed = Some(String::new());
+                index += 1;
+                continue;
+            };
+            let indent = if !brackets.is_empty() {
+                let closing = line
+                    .tokens
+                    .iter()
+                    .take_while(|token| is_closing(self.token(**token).kind))
+                    .count();
+                base + INDENT * brackets.len().saturating_sub(closing)
+            } else if is_comment(self.token(first).kind) {
+                INDENT * comment_level(&levels, line.width)
+            } else {
+                while levels.last().is_some_and(|width| *width > line.width) {
+                    levels.pop();
+                }
+                if levels.last().is_none_or(|width| *width < line.width) {
+                    levels.push(line.width);
+                }
+                base = INDENT * (levels.len() - 1);
+                self.lines[index].top_level = levels.len() == 1;
+                base
+            };
+            let mut text = " ".repeat(indent);
+            text.push_str(&self.join(&self.lines[index].tokens, &mut brackets, false));
+            self.lines[index].printed = Some(text);
+            index += 1;
+        }
+    }
+
+    /// Print `tokens` of one line with canonical spacing, tracking the open
+    /// brackets. `padded` keeps one space inside parentheses, as the header
+    /// prints `features is ( debug )`.
+    fn join(&self, tokens: &[usize], brackets: &mut Vec<TokenKind>, padded: bool) -> String {
+        let mut text = String::new();
+        let mut previous: Option<usize> = None;
+        for &index in tokens {
+            let token = self.token(index);
+            if let Some(previous) = previous {
+                let gap = self.token(previous).span.end < token.span.start;
+                let in_unit = brackets.contains(&TokenKind::LeftBracket);
+                text.push_str(separator(
+                    self.token(previous).kind,
+                    token.kind,
+                    gap,
+                    in_unit,
+                    padded,
+                ));
+            }
+            match token.kind {
+                TokenKind::LeftParen | TokenKind::LeftBrace | TokenKind::LeftBracket => {
+                    brackets.push(token.kind);
+                }
+                TokenKind::RightParen | TokenKind::RightBrace | TokenKind::RightBracket => {
+                    brackets.pop();
+                }
+                _ => {}
+            }
+            text.push_str(self.text(index));
+            previous = Some(index);
+        }
+        text
+    }
+
+    /// The line range of a leading `use language (...)` header and its
+    /// canonical text: one field per line inside the parentheses.
+    fn header_lines(&self) -> Option<(Range<usize>, String)> {
+        let Some(Statement::LanguageSelection { span, .. }) = self.parsed.statements.first() else {
+            return None;
+        };
+        let first = self.lines.iter().position(|line| {
+            line.tokens
+                .first()
+                .is_some_and(|token| self.token(*token).span.start == span.start)
+        })?;
+        let mut tokens = Vec::new();
+        let mut end = first;
+        while tokens
+            .last()
+            .is_none_or(|token| self.token(*token).span.end < span.end)
+        {
+            let line = self.lines.get(end)?;
+            tokens.extend(&line.tokens);
+            end += 1;
+        }
+        if self.lines[first].width != 0
+            || self.token(*tokens.last()?).span.end != span.end
+            || tokens
+                .iter()
+                .any(|token| is_comment(self.token(*token).kind))
+        {
+            return None;
+        }
+        let opening = tokens
+            .iter()
+            .position(|token| self.token(*token).kind == TokenKind::LeftParen)?;
+        let inner = &tokens[opening + 1..tokens.len() - 1];
+        let mut text = self.join(&tokens[..=opening], &mut Vec::new(), false);
+        if inner.is_empty() {
+            text.push(')');
+            return Some((first..end, text));
+        }
+        let mut depth = 0_usize;
+        let mut fields = vec![Vec::new()];
+        for &token in inner {
+            match self.token(token).kind {
+                TokenKind::Comma if depth == 0 => {
+                    fields.push(Vec::new());
+                    continue;
+                }
+                kind if is_closing(kind) => depth = depth.saturating_sub(1),
+                TokenKind::LeftParen | TokenKind::LeftBrace | TokenKind::LeftBracket => depth += 1,
+                _ => {}
+            }
+            fields
+                .last_mut()
+                .expect("fields start nonempty")
+                .push(token);
+        }
+        let trailing_comma = fields.last().is_some_and(Vec::is_empty);
+        if trailing_comma {
+            fields.pop();
+        }
+        for (position, field) in fields.iter().enumerate() {
+            let _ = write!(
+                text,
+                "\n{}{}",
+                " ".repeat(INDENT),
+                self.join(field, &mut Vec::new(), true)
+            );
+            if position + 1 < fields.len() || trailing_comma {
+                text.push(',');
+            }
+        }
+        text.push_str("\n)");
+        Some((first..end, text))
+    }
+
+    /// Remove leading and trailing blank lines and shorten every run of blank
+    /// lines to one.
+    fn collapse_blank_lines(&mut self) {
+        let mut after_blank = true;
+        for line in &mut self.lines {
+            match line.printed.as_deref() {
+                Some("") if after_blank => line.printed = None,
+                Some("") => after_blank = true,
+                Some(_) => after_blank = false,
+                None => {}
+            }
+        }
+        for line in self.lines.iter_mut().rev() {
+            match line.printed.as_deref() {
+                Some("") => line.printed = None,
+                Some(_) => break,
+                None => {}
+            }
+        }
+    }
+
+    fn print(&self, lines: Range<usize>) -> String {
+        let mut text = String::new();
+        for line in &self.lines[lines] {
+            if let Some(printed) = &line.printed {
+                text.push_str(printed);
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    /// The lines from the top-level statement containing `span.start` up to
+    /// the top-level statement after `span.end`.
+    fn statement_lines(&self, span: Span) -> Range<usize> {
+        let line_of = |offset: usize| {
+            self.lines
+                .partition_point(|line| line.start <= offset)
+                .saturating_sub(1)
+        };
+        let last = line_of(span.end.saturating_sub(1).max(span.start));
+        let start = (0..=line_of(span.start))
+            .rev()
+            .find(|index| self.lines[*index].top_level)
+            .unwrap_or(0);
+        let end = (last + 1..self.lines.len())
+            .find(|index| self.lines[*index].top_level)
+            .unwrap_or(self.lines.len());
+        start..end
+    }
+
+    /// Accept `formatted` only when it has the same tokens, comments included,
+    /// and the same syntax tree as the input.
+    fn check(&self, formatted: &str) -> Result<(), FormatError> {
+        let source = SourceText::new(formatted).map_err(|_| FormatError::NotEquivalent)?;
+        let lexed = lex(&source);
+        let parsed = parse(&source, &lexed);
+        let equivalent = lexed.diagnostics.is_empty()
+            && parsed.diagnostics.is_empty()
+            && significant(&self.source, &self.lexed).eq(significant(&source, &lexed))
+            && shape(&self.lexed, &self.parsed) == shape(&lexed, &parsed);
+        if equivalent {
+            Ok(())
+        } else {
+            Err(FormatError::NotEquivalent)
+        }
+    }
+}
+
+fn split_lines(tokens: &[Token]) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut line = Line {
+        start: 0,
+        tokens: Vec::new(),
+        width: 0,
+        top_level: false,
+        printed: None,
+    };
+    for (index, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::Newline => {
+                let next = Line {
+                    start: token.span.end,
+                    tokens: Vec::new(),
+                    width: 0,
+                    top_level: false,
+                    printed: None,
+                };
+                lines.push(std::mem::replace(&mut line, next));
+            }
+            TokenKind::Whitespace => {
+                if token.span.start == line.start {
+                    line.width = token.span.end - token.span.start;
+                }
+            }
+            _ => line.tokens.push(index),
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+/// The level of a comment line: the open level with its width, one level
+/// deeper than the innermost when it is indented further, or the deepest
+/// enclosing level when it sits between two.
+fn comment_level(levels: &[usize], width: usize) -> usize {
+    let enclosing = levels.partition_point(|level| *level <= width);
+    if levels[enclosing - 1] == width {
+        enclosing - 1
+    } else if enclosing == levels.len() {
+        enclosing
+    } else {
+        enclosing - 1
+    }
+}
+
+/// Spacing between two tokens on one line. `gap` reports whether the input
+/// separated them; inside unit brackets the input spacing is kept.
+fn separator(
+    previous: TokenKind,
+    next: TokenKind,
+    gap: bool,
+    in_unit: bool,
+    padded: bool,
+) -> &'static str {
+    let space = if gap { " " } else { "" };
+    if in_unit {
+        return space;
+    }
+    match (previous, next) {
+        (_, TokenKind::Comment | TokenKind::Documentation) => " ",
+        (
+            TokenKind::LeftParen | TokenKind::LeftBracket,
+            TokenKind::RightParen | TokenKind::RightBracket,
+        ) => "",
+        (TokenKind::LeftBrace, TokenKind::RightBrace)
+        | (TokenKind::Integer | TokenKind::Rational, TokenKind::LeftBracket) => space,
+        (TokenKind::LeftParen, _) | (_, TokenKind::RightParen) if padded => " ",
+        (TokenKind::LeftParen | TokenKind::LeftBracket, _)
+        | (_, TokenKind::RightParen | TokenKind::RightBracket | TokenKind::Comma) => "",
+        (TokenKind::LeftBrace | TokenKind::Comma, _) | (_, TokenKind::RightBrace) => " ",
+        _ if is_operator(previous) || is_operator(next) => " ",
+        _ => space,
+    }
+}
+
+const fn is_operator(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Colon
+            | TokenKind::Arrow
+            | TokenKind::Equals
+            | TokenKind::NotEquals
+            | TokenKind::Less
+            | TokenKind::Greater
+            | TokenKind::LessEqual
+            | TokenKind::GreaterEqual
+            | TokenKind::Compare
+            | TokenKind::Range
+            | TokenKind::Plus
+            | TokenKind::Minus
+            | TokenKind::Star
+            | TokenKind::Slash
+            | TokenKind::SlashPercent
+            | TokenKind::Percent
+            | TokenKind::Caret
+    )
+}
+
+const fn is_closing(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::RightParen | TokenKind::RightBrace | TokenKind::RightBracket
+    )
+}
+
+const fn is_comment(kind: TokenKind) -> bool {
+    matches!(kind, TokenKind::Comment | TokenKind::Documentation)
+}
+
+fn significant<'a>(
+    source: &'a SourceText,
+    lexed: &'a Lexed,
+) -> impl Iterator<Item = (TokenKind, &'a str)> {
+    lexed
+        .tokens
+        .iter()
+        .filter(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::Newline))
+        .map(|token| (token.kind, source.slice(token.span)))
+}
+
+/// The syntax tree with every byte span replaced by its position among the
+/// significant tokens, so trees of differently spaced sources compare equal
+/// exactly when they have the same structure.
+fn shape(lexed: &Lexed, parsed: &ParsedSource) -> String {
+    const SPAN: &str = "Span { start: ";
+    let tokens = lexed
+        .tokens
+        .iter()
+        .filter(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::Newline))
+        .map(|token| token.span)
+        .collect::<Vec<_>>();
+    let position = |offset: &str| {
+        let offset = offset
+            .parse::<usize>()
+            .expect("derived span offsets are decimal");
+        let index = tokens.partition_point(|span| span.end <= offset);
+        match tokens.get(index) {
+            Some(span) if span.start < offset => format!("{index}+{}", offset - span.start),
+            _ => index.to_string(),
+        }
+    };
+    let debug = format!("{:?}", parsed.statements);
+    let mut shape = String::with_capacity(debug.len());
+    let mut rest = debug.as_str();
+    while let Some(found) = rest.find(SPAN) {
+        shape.push_str(&rest[..found]);
+        let (start, after) = rest[found + SPAN.len()..]
+            .split_once(", end: ")
+            .expect("derived span debug names its end");
+        let (end, after) = after
+            .split_once(" }")
+            .expect("derived span debug is closed");
+        let _ = write!(shape, "Span({}, {})", position(start), position(end));
+        rest = after;
+    }
+    shape.push_str(rest);
+    shape
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn reprints_layout_spacing_and_header_canonically() {
+        let source = "use language ( version is v0.1, features is (lint) )\n\n\n# Lead.\nf  is fn (  value : Int )  -> Int\n      value  *  2\n      # Trailing.\n\n\n\n#  Between.\ng is 1[kg * m / (s ^ 2)]\nh is ( \n    first is 1,\n  second is 2 )\nf  2\n      \n";
+        assert_eq!(
+            format_source(source).unwrap(),
+            "use language (\n  version is v0.1,\n  features is ( lint )\n)\n\n# Lead.\nf is fn (value : Int) -> Int\n  value * 2\n  # Trailing.\n\n#  Between.\ng is 1[kg * m / (s ^ 2)]\nh is (\n  first is 1,\n  second is 2)\nf 2\n"
+        );
+    }
+
+    #[test]
+    fn preserves_nested_bodies_and_crlf_input_as_lf() {
+        let source = "use language (\r\n    version is v0.1\r\n)\r\nouter is fn (value : Int) -> Int\r\n    inner is fn (other : Int) -> Int\r\n            other = 0\r\n                true then 1\r\n                otherwise other\r\n    inner value\r\nouter 3\r\n";
+        assert_eq!(
+            format_source(source).unwrap(),
+            "use language (\n  version is v0.1\n)\nouter is fn (value : Int) -> Int\n  inner is fn (other : Int) -> Int\n    other = 0\n      true then 1\n      otherwise other\n  inner value\nouter 3\n"
+        );
+    }
+
+    #[test]
+    fn refuses_diagnostics_and_changed_syntax() {
+        assert!(matches!(
+            format_source("use language ( version is v0.1 )\nf is fn (value) -> Int\n  value\n"),
+            Err(FormatError::Syntax { line: 2, .. })
+        ));
+        let document =
+            Document::new("use language ( version is v0.1 )\nvalue is (1, 2)\n").unwrap();
+        assert_eq!(
+            document.check("use language ( version is v0.1 )\nvalue is (1,\n    2)\n"),
+            Ok(())
+        );
+        assert_eq!(
+            document.check("use language ( version is v0.1 )\nvalue is (1, 2, 3)\n"),
+            Err(FormatError::NotEquivalent)
+        );
+    }
+
+    #[test]
+    fn formats_only_the_touched_top_level_statements() {
+        let source = "use language ( version is v0.1 )\nf is fn ( value : Int ) -> Int\n    value  +  1\ng is ( 1, 2 )\n";
+        let body = source.find("value  +").unwrap();
+        let edit = format_range(source, Span::new(body, body + 5)).unwrap();
+        assert_eq!(
+            &source[edit.span.start..edit.span.end],
+            "f is fn ( value : Int ) -> Int\n    value  +  1\n"
+        );
+        assert_eq!(edit.text, "f is fn (value : Int) -> Int\n  value + 1\n");
+    }
+
+    #[test]
+    fn formats_the_example_corpus_idempotently() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let mut pending = vec![root.join("examples"), root.join("library")];
+        let mut formatted = 0;
+        while let Some(directory) = pending.pop() {
+            for entry in fs::read_dir(directory).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else if path
+                    .extension()
+                    .is_some_and(|extension| extension == "t" || extension == "debug")
+                {
+                    let text = fs::read_to_string(&path).unwrap();
+                    let Ok(once) = format_source(&text) else {
+                        assert!(
+                            !matches!(format_source(&text), Err(FormatError::NotEquivalent)),
+                            "{}",
+                            path.display()
+                        );
+                        continue;
+                    };
+                    assert_eq!(format_source(&once).unwrap(), once, "{}", path.display());
+                    formatted += 1;
+                }
+            }
+        }
+        assert!(formatted > 200);
+    }
+}
@@ -0,0 +1,86 @@
+use std::env;
+use std::fs;
+use std::io::{self, Read as _, Write as _};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use topal_fmt::format_source;
+
+const USAGE: &str = "usage: topal-fmt [--check] [SOURCE...]\n\
+  --check    report sources that are not canonically formatted, without rewriting them\n\
+\n\
+Without SOURCE, format standard input to standard output.";
+
+struct Options {
+    check: bool,
+    sources: Vec<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    match run(env::args().skip(1)) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(message) => {
+            eprintln!("topal-fmt: {message}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Format every requested source and report whether all of them were
+/// already canonical or have been rewritten.
+fn run(arguments: impl Iterator<Item = String>) -> Result<bool, String> {
+    let Some(options) = options(arguments)? else {
+        println!("{USAGE}");
+        return Ok(true);
+    };
+    if options.sources.is_empty() {
+        let mut text = String::new();
+        io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|error| format!("<stdin>: {error}"))?;
+        let formatted = format_source(&text).map_err(|error| format!("<stdin>: {error}"))?;
+        if options.check {
+            return Ok(formatted == text);
+        }
+        io::stdout()
+            .write_all(formatted.as_bytes())
+            .map_err(|error| error.to_string())?;
+        return Ok(true);
+    }
+    let mut canonical = true;
+    for path in &options.sources {
+        let text =
+            fs::read_to_string(path).map_err(|error| format!("{}: {error}", path.display()))?;
+        let formatted =
+            format_source(&text).map_err(|error| format!("{}: {error}", path.display()))?;
+        if formatted == text {
+            continue;
+        }
+        if options.check {
+            println!("{}", path.display());
+            canonical = false;
+        } else {
+            fs::write(path, formatted).map_err(|error| format!("{}: {error}", path.display()))?;
+        }
+    }
+    Ok(canonical)
+}
+
+fn options(arguments: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        check: false,
+        sources: Vec::new(),
+    };
+    for argument in arguments {
+        match argument.as_str() {
+            "--help" | "-h" => return Ok(None),
+            "--check" => options.check = true,
+            option if option.starts_with('-') => {
+                return Err(format!("unknown option `{option}`\n\n{USAGE}"));
+            }
+            _ => options.sources.push(PathBuf::from(argument)),
+        }
+    }
+    Ok(Some(options))
+}
@@ -0,0 +1,83 @@
+use std::fs;
+use std::io::Write as _;
+use std::process::{Command, Stdio};
+
+fn scratch(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("topal-fmt-{}-{name}", std::process::id()))
+}
+
+#[test]
+fn checks_then_rewrites_explicit_sources() {
+    let directory = scratch("sources");
+    fs::create_dir_all(&directory).unwrap();
+    let canonical = directory.join("canonical.t");
+    let messy = directory.join("messy.t");
+    fs::write(
+        &canonical,
+        "use language (\n  version is v0.1\n)\nvalue is 1\n",
+    )
+    .unwrap();
+    fs::write(
+        &messy,
+        "use language ( version is v0.1 )\nvalue  is ( 1,2 )\n\n\n",
+    )
+    .unwrap();
+
+    let check = Command::new(env!("CARGO_BIN_EXE_topal-fmt"))
+        .args(["--check"])
+        .args([&canonical, &messy])
+        .output()
+        .unwrap();
+    assert_eq!(check.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&check.stdout),
+        format!("{}\n", messy.display())
+    );
+    assert!(fs::read_to_string(&messy).unwrap().contains("( 1,2 )"));
+
+    let rewrite = Command::new(env!("CARGO_BIN_EXE_topal-fmt"))
+        .args([&canonical, &messy])
+        .output()
+        .unwrap();
+    assert!(
+        rewrite.status.success(),
+        "{}",
+        String::from_utf8_lossy(&rewrite.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(&messy).unwrap(),
+        "use language (\n  version is v0.1\n)\nvalue is (1, 2)\n"
+    );
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn formats_standard_input_and_reports_syntax_errors() {
+    let run = |input: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_topal-fmt"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .unwrap();
+        child.wait_with_output().unwrap()
+    };
+    let formatted =
+        run("use language ( version is v0.1 )\nf is fn ( value : Int ) -> Int\n    value\n");
+    assert!(formatted.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&formatted.stdout),
+        "use language (\n  version is v0.1\n)\nf is fn (value : Int) -> Int\n  value\n"
+    );
+
+    let invalid = run("use language ( version is v0.1 )\nf is fn (value) -> Int\n  value\n");
+    assert_eq!(invalid.status.code(), Some(2));
+    assert!(invalid.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&invalid.stderr).starts_with("topal-fmt: <stdin>: 2:"));
+}
@@ -7,6 +7,16 @@ use sha2::{Digest, Sha256};
 use topal_semantics::LanguageVersion;
 use topal_source::is_nfc;
 
+mod link;
+mod optimize;
+mod text;
+
+pub use link::{LinkError, link_modules};
+pub use optimize::{
+    INLINE_BLOCK_LIMIT, OptimizationError, OptimizationPass, PassManager, PassReport,
+};
+pub use text::{TextError, parse_text, print_text};
+
 pub const ARTIFACT_REVISION: u64 = 1;
 
 pub const COMPILER_ONLY_ERROR_CODE: &str = "E-COMPILER-ONLY";
@@ -213,7 +223,9 @@ pub enum Instruction {
 }
 
 impl Instruction {
-    fn result_type(&self) -> Option<usize> {
+    /// Type of the SSA value this instruction defines, if any.
+    #[must_use]
+    pub const fn result_type(&self) -> Option<usize> {
         match self {
             Self::Constant { result_type, .. }
             | Self::Product { result_type, .. }
@@ -230,7 +242,9 @@ impl Instruction {
         }
     }
 
-    fn values(&self) -> Vec<usize> {
+    /// Block-local SSA values this instruction uses, in operand order.
+    #[must_use]
+    pub fn values(&self) -> Vec<usize> {
         match self {
             Self::Product { values, .. } | Self::Construct { values, .. } => values.clone(),
             Self::Project { value, .. }
@@ -854,7 +868,13 @@ impl Module {
 #[derive(Clone, Copy, Debug)]
 pub struct ValidatedModule<'a>(&'a Module);
 
-impl ValidatedModule<'_> {
+impl<'a> ValidatedModule<'a> {
+    /// The complete module whose validation produced this witness.
+    #[must_use]
+    pub const fn module(self) -> &'a Module {
+        self.0
+    }
+
     #[must_use]
     pub fn canonical_bytes(self) -> Vec<u8> {
         let mut output = b"TOPALGEIR".to_vec();
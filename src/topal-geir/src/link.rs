@@ -0,0 +1,504 @@
+//! Linking validated GEIR modules into one closed bundle.
+//!
+//! Every table is rebased into the bundle: identities are merged into one
+//! canonical sorted table, structurally equal types and evidence are shared,
+//! and every import must name an identity exported by a linked module at the
+//! exact language revision (`TOPAL-GIR-COMPAT-001`). Two definitions of one
+//! identity are a collision unless their rebased canonical definitions have
+//! the same `structural_identity` (`TOPAL-GIR-ID-001`).
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use topal_semantics::LanguageVersion;
+
+use crate::{
+    ARTIFACT_REVISION, ArtifactError, Evidence, Function, Identity, Instruction, Module,
+    Terminator, Type, ValidatedModule, encode_function, structural_identity,
+};
+
+/// Rejection of a set of modules that cannot form one bundle. `module` is the
+/// position of the offending module in the linker input.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LinkError {
+    NoModules,
+    IncompatibleRevision {
+        module: usize,
+        revision: u64,
+        language: LanguageVersion,
+    },
+    UnresolvedImport {
+        module: usize,
+        import: Box<Identity>,
+    },
+    IncompatibleImport {
+        module: usize,
+        import: Box<Identity>,
+        exported: LanguageVersion,
+    },
+    Collision {
+        module: usize,
+        identity: Box<Identity>,
+    },
+    Invalid(ArtifactError),
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoModules => formatter.write_str("no modules to link"),
+            Self::IncompatibleRevision {
+                module,
+                revision,
+                language,
+            } => write!(
+                formatter,
+                "module {module} has artifact revision {revision} and language {language}, \
+                 which differ from the bundle"
+            ),
+            Self::UnresolvedImport { module, import } => write!(
+                formatter,
+                "module {module} imports {} which no linked module exports",
+                display_identity(import)
+            ),
+            Self::IncompatibleImport {
+                module,
+                import,
+                exported,
+            } => write!(
+                formatter,
+                "module {module} imports {} but it is exported for language {exported}",
+                display_identity(import)
+            ),
+            Self::Collision { module, identity } => write!(
+                formatter,
+                "module {module} redefines {} with a different structural identity",
+                display_identity(identity)
+            ),
+            Self::Invalid(error) => error.fmt(formatter),
+        }
+    }
+}
+
+impl std::error::Error for LinkError {}
+
+fn display_identity(identity: &Identity) -> String {
+    let mut path = vec![identity.package.as_str()];
+    path.extend(identity.module_path.iter().map(String::as_str));
+    path.extend(identity.declaration_path.iter().map(String::as_str));
+    format!("`{}`", path.join("."))
+}
+
+/// Index maps from one input module's tables into the bundle's tables.
+struct Rebase {
+    identities: Vec<usize>,
+    types: Vec<usize>,
+    evidence: Vec<usize>,
+    functions: Vec<usize>,
+}
+
+/// Link validated modules into one validated bundle with no imports.
+///
+/// # Errors
+///
+/// Rejects mismatched artifact or language revisions, imports no module
+/// exports at the exact revision, colliding definitions of one identity, and
+/// a bundle that fails `Module::validate`.
+pub fn link_modules(modules: &[ValidatedModule<'_>]) -> Result<Module, LinkError> {
+    let first = modules.first().ok_or(LinkError::NoModules)?.module();
+    for (index, module) in modules.iter().map(|module| module.module()).enumerate() {
+        if module.revision != ARTIFACT_REVISION || module.language != first.language {
+            return Err(LinkError::IncompatibleRevision {
+                module: index,
+                revision: module.revision,
+                language: module.language,
+            });
+        }
+    }
+    resolve_imports(modules)?;
+    let mut identities = modules
+        .iter()
+        .flat_map(|module| module.module().identities.iter().cloned())
+        .collect::<Vec<_>>();
+    identities.sort();
+    identities.dedup();
+    let mut bundle = Module {
+        revision: ARTIFACT_REVISION,
+        language: first.language,
+        imports: Vec::new(),
+        identities,
+        types: Vec::new(),
+        capabilities: Vec::new(),
+        evidence: Vec::new(),
+        functions: Vec::new(),
+        exports: Vec::new(),
+    };
+    let mut rebases = modules
+        .iter()
+        .map(|module| rebase_tables(module.module(), &mut bundle))
+        .collect::<Vec<_>>();
+    // Assign every distinct function identity its bundle index before any body
+    // is rebased, so applications across modules resolve to the first definition.
+    let mut defined = BTreeMap::new();
+    let mut sources = Vec::new();
+    for (index, module) in modules.iter().map(|module| module.module()).enumerate() {
+        for (function_index, function) in module.functions.iter().enumerate() {
+            let identity = rebases[index].identities[function.identity];
+            let next = sources.len();
+            let slot = *defined.entry(identity).or_insert(next);
+            if slot == next {
+                sources.push((index, function_index));
+            }
+            rebases[index].functions.push(slot);
+        }
+    }
+    for (index, function_index) in &sources {
+        let function = &modules[*index].module().functions[*function_index];
+        bundle
+            .functions
+            .push(rebase_function(function, &rebases[*index]));
+    }
+    for (index, module) in modules.iter().map(|module| module.module()).enumerate() {
+        for (function_index, function) in module.functions.iter().enumerate() {
+            let slot = rebases[index].functions[function_index];
+            if sources[slot] != (index, function_index)
+                && digest(&rebase_function(function, &rebases[index]))
+                    != digest(&bundle.functions[slot])
+            {
+                return Err(LinkError::Collision {
+                    module: index,
+                    identity: Box::new(bundle.identities[bundle.functions[slot].identity].clone()),
+                });
+            }
+        }
+        bundle.exports.extend(
+            module
+                .exports
+                .iter()
+                .map(|export| rebases[index].functions[*export]),
+        );
+        bundle.capabilities.extend(
+            module
+                .capabilities
+                .iter()
+                .map(|identity| rebases[index].identities[*identity]),
+        );
+    }
+    bundle.exports.sort_unstable();
+    bundle.exports.dedup();
+    bundle.capabilities.sort_unstable();
+    bundle.capabilities.dedup();
+    bundle.validate().map_err(LinkError::Invalid)?;
+    Ok(bundle)
+}
+
+fn resolve_imports(modules: &[ValidatedModule<'_>]) -> Result<(), LinkError> {
+    let exported = modules
+        .iter()
+        .flat_map(|module| {
+            let module = module.module();
+            module
+                .exports
+                .iter()
+                .map(|export| &module.identities[module.functions[*export].identity])
+        })
+        .collect::<Vec<_>>();
+    for (index, module) in modules.iter().map(|module| module.module()).enumerate() {
+        for import in &module.imports {
+            if exported.contains(&import) {
+                continue;
+            }
+            if let Some(identity) = exported.iter().find(|identity| {
+                identity.package == import.package
+                    && identity.module_path == import.module_path
+                    && identity.declaration_path == import.declaration_path
+            }) {
+                return Err(LinkError::IncompatibleImport {
+                    module: index,
+                    import: Box::new(import.clone()),
+                    exported: identity.language_revision,
+                });
+            }
+            return Err(LinkError::UnresolvedImport {
+                module: index,
+                import: Box::new(import.clone()),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Merge one module's types and evidence into the bundle, sharing entries that
+/// are equal after rebasing. Types only reference earlier types, so rebasing
+/// in table order always finds each reference already mapped.
+fn rebase_tables(module: &Module, bundle: &mut Module) -> Rebase {
+    let identities = module
+        .identities
+        .iter()
+        .map(|identity| {
+            bundle
+                .identities
+                .binary_search(identity)
+                .unwrap_or_else(|_| unreachable!("bundle identities include every module"))
+        })
+        .collect::<Vec<_>>();
+    let mut types = Vec::with_capacity(module.types.len());
+    for value in &module.types {
+        let value = rebase_type(value, &types, &identities);
+        types.push(intern(&mut bundle.types, value));
+    }
+    let evidence = module
+        .evidence
+        .iter()
+        .map(|proof| {
+            let proof = Evidence {
+                identity: identities[proof.identity],
+                ..proof.clone()
+            };
+            intern(&mut bundle.evidence, proof)
+        })
+        .collect();
+    Rebase {
+        identities,
+        types,
+        evidence,
+        functions: Vec::with_capacity(module.functions.len()),
+    }
+}
+
+fn intern<T: PartialEq>(table: &mut Vec<T>, value: T) -> usize {
+    table
+        .iter()
+        .position(|entry| *entry == value)
+        .unwrap_or_else(|| {
+            table.push(value);
+            table.len() - 1
+        })
+}
+
+fn rebase_type(value: &Type, types: &[usize], identities: &[usize]) -> Type {
+    let ids = |ids: &[usize]| ids.iter().map(|id| types[*id]).collect();
+    let fields = |fields: &[(String, usize)]| {
+        fields
+            .iter()
+            .map(|(label, id)| (label.clone(), types[*id]))
+            .collect()
+    };
+    match value {
+        Type::Primitive(name) => Type::Primitive(name.clone()),
+        Type::Tuple(items) => Type::Tuple(ids(items)),
+        Type::Record(items) => Type::Record(fields(items)),
+        Type::Variant(items) => Type::Variant(fields(items)),
+        Type::Union(items) => Type::Union(ids(items)),
+        // Constraint predicates are not table references (`validate_types`).
+        Type::Constraint { base, predicate } => Type::Constraint {
+            base: types[*base],
+            predicate: *predicate,
+        },
+        Type::Function { inputs, result } => Type::Function {
+            inputs: ids(inputs),
+            result: types[*result],
+        },
+        Type::Existential(id) => Type::Existential(types[*id]),
+        Type::Nominal(id) => Type::Nominal(types[*id]),
+        Type::Application {
+            constructor,
+            arguments,
+        } => Type::Application {
+            constructor: types[*constructor],
+            arguments: ids(arguments),
+        },
+        Type::RecursiveRef(identity) => Type::RecursiveRef(identities[*identity]),
+    }
+}
+
+fn rebase_function(function: &Function, rebase: &Rebase) -> Function {
+    let types = |ids: &[usize]| ids.iter().map(|id| rebase.types[*id]).collect::<Vec<_>>();
+    let identities = |ids: &[usize]| {
+        ids.iter()
+            .map(|id| rebase.identities[*id])
+            .collect::<Vec<_>>()
+    };
+    let mut rebased = function.clone();
+    rebased.identity = rebase.identities[function.identity];
+    rebased.static_parameters = types(&function.static_parameters);
+    rebased.inputs = types(&function.inputs);
+    rebased.result = rebase.types[function.result];
+    rebased.effects = identities(&function.effects);
+    rebased.guarantees = function
+        .guarantees
+        .iter()
+        .map(|id| rebase.evidence[*id])
+        .collect();
+    for block in &mut rebased.blocks {
+        block.parameters = types(&block.parameters);
+        for instruction in &mut block.instructions {
+            rebase_instruction(instruction, rebase);
+        }
+        if let Terminator::TailApply { function, .. } = &mut block.terminator {
+            *function = rebase.functions[*function];
+        }
+    }
+    rebased
+}
+
+fn rebase_instruction(instruction: &mut Instruction, rebase: &Rebase) {
+    match instruction {
+        Instruction::Constant { result_type, .. }
+        | Instruction::Product { result_type, .. }
+        | Instruction::Construct { result_type, .. }
+        | Instruction::Project { result_type, .. }
+        | Instruction::UnpackExists { result_type, .. } => {
+            *result_type = rebase.types[*result_type];
+        }
+        Instruction::Apply {
+            result_type,
+            function,
+            effects,
+            ..
+        } => {
+            *result_type = rebase.types[*result_type];
+            *function = rebase.functions[*function];
+            for effect in effects {
+                *effect = rebase.identities[*effect];
+            }
+        }
+        Instruction::Validate {
+            result_type,
+            evidence,
+            ..
+        }
+        | Instruction::Convert {
+            result_type,
+            evidence,
+            ..
+        }
+        | Instruction::Capability {
+            result_type,
+            evidence,
+        }
+        | Instruction::PackExists {
+            result_type,
+            evidence,
+            ..
+        } => {
+            *result_type = rebase.types[*result_type];
+            *evidence = rebase.evidence[*evidence];
+        }
+        Instruction::Effect {
+            result_type,
+            effect,
+            ..
+        } => {
+            *result_type = rebase.types[*result_type];
+            *effect = rebase.identities[*effect];
+        }
+        Instruction::BeginRegion | Instruction::EndRegion => {}
+    }
+}
+
+fn digest(function: &Function) -> [u8; 32] {
+    let mut bytes = Vec::new();
+    encode_function(function, &mut bytes);
+    structural_identity(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArtifactLimits, parse_text, print_text};
+
+    const LIBRARY: &str = r#"geir 1 language 0.1.0.0
+identity i0 "base" ("core") ("identity") 0.1.0.0
+type t0 primitive "Boolean"
+type t1 primitive "Int"
+function f0 i0 public static () inputs (t1) result t1 effects () guarantees () entry b0
+  block b0 (v0 t1)
+    return v0
+export f0
+"#;
+
+    const APPLICATION: &str = r#"geir 1 language 0.1.0.0
+import "base" ("core") ("identity") 0.1.0.0
+identity i0 "app" ("main") () 0.1.0.0
+type t0 primitive "Int"
+function f0 i0 private static () inputs () result t0 effects () guarantees () entry b0
+  block b0 ()
+    v0 = constant t0 0x2a
+    return v0
+"#;
+
+    fn link(texts: &[&str]) -> Result<Module, LinkError> {
+        let modules = texts
+            .iter()
+            .map(|text| parse_text(text, ArtifactLimits::default()).unwrap())
+            .collect::<Vec<_>>();
+        let validated = modules
+            .iter()
+            .map(|module| module.validate().unwrap())
+            .collect::<Vec<_>>();
+        link_modules(&validated)
+    }
+
+    #[test]
+    fn imports_resolve_into_one_rebased_bundle() {
+        let bundle = link(&[APPLICATION, LIBRARY]).unwrap();
+        assert_eq!(
+            print_text(bundle.validate().unwrap()),
+            r#"geir 1 language 0.1.0.0
+identity i0 "app" ("main") () 0.1.0.0
+identity i1 "base" ("core") ("identity") 0.1.0.0
+type t0 primitive "Int"
+type t1 primitive "Boolean"
+function f0 i0 private static () inputs () result t0 effects () guarantees () entry b0
+  block b0 ()
+    v0 = constant t0 0x2a
+    return v0
+function f1 i1 public static () inputs (t0) result t0 effects () guarantees () entry b0
+  block b0 (v0 t0)
+    return v0
+export f1
+"#
+        );
+        assert_eq!(
+            link(&[LIBRARY, LIBRARY]).unwrap(),
+            link(&[LIBRARY]).unwrap()
+        );
+    }
+
+    #[test]
+    fn imports_must_be_exported_at_the_exact_revision() {
+        assert_eq!(
+            link(&[APPLICATION]),
+            Err(LinkError::UnresolvedImport {
+                module: 0,
+                import: Box::new(Identity {
+                    package: "base".into(),
+                    module_path: vec!["core".into()],
+                    declaration_path: vec!["identity".into()],
+                    language_revision: LanguageVersion::DESIGN_0,
+                }),
+            })
+        );
+        let later = APPLICATION.replace("(\"identity\") 0.1.0.0", "(\"identity\") 0.2.0.0");
+        assert!(matches!(
+            link(&[&later, LIBRARY]),
+            Err(LinkError::IncompatibleImport { module: 0, .. })
+        ));
+        assert_eq!(link(&[]), Err(LinkError::NoModules));
+    }
+
+    #[test]
+    fn differing_definitions_of_one_identity_collide() {
+        let changed = LIBRARY.replace(
+            "  block b0 (v0 t1)\n    return v0",
+            "  block b0 (v0 t1)\n    v1 = constant t1 0x00\n    return v1",
+        );
+        let error = link(&[LIBRARY, &changed]).unwrap_err();
+        assert!(matches!(error, LinkError::Collision { module: 1, .. }));
+        assert_eq!(
+            error.to_string(),
+            "module 1 redefines `base.core.identity` with a different structural identity"
+        );
+    }
+}
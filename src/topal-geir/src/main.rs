@@ -0,0 +1,136 @@
+use std::env;
+use std::fs;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use topal_geir::{ArtifactLimits, Module, decode_canonical, link_modules, parse_text, print_text};
+
+#[derive(Clone, Copy)]
+enum Command {
+    Disassemble,
+    Assemble,
+    Link,
+}
+
+struct Options {
+    command: Command,
+    inputs: Vec<PathBuf>,
+    output: Option<PathBuf>,
+}
+
+fn main() -> std::process::ExitCode {
+    match run(env::args().skip(1)) {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("topal-geir: {message}");
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(arguments: impl Iterator<Item = String>) -> Result<(), String> {
+    let options = options(arguments)?;
+    match options.command {
+        Command::Disassemble => disassemble(single(&options.inputs)?, options.output),
+        Command::Assemble => assemble(
+            single(&options.inputs)?,
+            &options.output.ok_or("assemble requires --output FILE")?,
+        ),
+        Command::Link => link(
+            &options.inputs,
+            &options.output.ok_or("link requires --output FILE")?,
+        ),
+    }
+}
+
+fn single(inputs: &[PathBuf]) -> Result<&PathBuf, String> {
+    match inputs {
+        [input] => Ok(input),
+        _ => Err("provide exactly one input file".into()),
+    }
+}
+
+fn disassemble(input: &PathBuf, output: Option<PathBuf>) -> Result<(), String> {
+    let module = read_artifact(input)?;
+    let validated = module
+        .validate()
+        .map_err(|error| format!("{}: {error}", input.display()))?;
+    let text = print_text(validated);
+    match output {
+        Some(output) => write(&output, text.as_bytes()),
+        None => std::io::stdout()
+            .write_all(text.as_bytes())
+            .map_err(|error| error.to_string()),
+    }
+}
+
+fn assemble(input: &PathBuf, output: &PathBuf) -> Result<(), String> {
+    let text =
+        fs::read_to_string(input).map_err(|error| format!("{}: {error}", input.display()))?;
+    let module = parse_text(&text, ArtifactLimits::default())
+        .map_err(|error| format!("{}: {error}", input.display()))?;
+    let validated = module
+        .validate()
+        .map_err(|error| format!("{}: {error}", input.display()))?;
+    write(output, &validated.canonical_bytes())
+}
+
+fn link(inputs: &[PathBuf], output: &PathBuf) -> Result<(), String> {
+    let modules = inputs
+        .iter()
+        .map(read_artifact)
+        .collect::<Result<Vec<_>, _>>()?;
+    let validated = modules
+        .iter()
+        .zip(inputs)
+        .map(|(module, input)| {
+            module
+                .validate()
+                .map_err(|error| format!("{}: {error}", input.display()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let bundle = link_modules(&validated).map_err(|error| error.to_string())?;
+    let validated = bundle.validate().map_err(|error| error.to_string())?;
+    write(output, &validated.canonical_bytes())
+}
+
+fn read_artifact(path: &PathBuf) -> Result<Module, String> {
+    let bytes = fs::read(path).map_err(|error| format!("{}: {error}", path.display()))?;
+    decode_canonical(&bytes, ArtifactLimits::default())
+        .map_err(|error| format!("{}: {error}", path.display()))
+}
+
+fn write(path: &PathBuf, bytes: &[u8]) -> Result<(), String> {
+    fs::write(path, bytes).map_err(|error| format!("{}: {error}", path.display()))
+}
+
+fn options(mut arguments: impl Iterator<Item = String>) -> Result<Options, String> {
+    const USAGE: &str =
+        "usage: topal-geir (disassemble INPUT [--output FILE] | assemble INPUT --output FILE)";
+    let command = match arguments.next().as_deref() {
+        Some("disassemble") => Command::Disassemble,
+        Some("assemble") => Command::Assemble,
+        Some("link") => Command::Link,
+        Some("--help" | "-h") | None => return Err(USAGE.into()),
+        Some(other) => return Err(format!("unknown command `{other}`")),
+    };
+    let mut inputs = Vec::new();
+    let mut output = None;
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--output" => {
+                output = Some(PathBuf::from(
+                    arguments.next().ok_or("--output requires a file")?,
+                ));
+            }
+            "--help" | "-h" => return Err(USAGE.into()),
+            _ if argument.starts_with('-') => return Err(format!("unknown option `{argument}`")),
+            _ => inputs.push(PathBuf::from(argument)),
+        }
+    }
+    Ok(Options {
+        command,
+        inputs,
+        output,
+    })
+}
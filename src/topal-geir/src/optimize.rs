@@ -0,0 +1,503 @@
+//! Validated GEIR-to-GEIR optimization passes.
+//!
+//! Every pass consumes a validated module and must produce one: the pass
+//! manager reruns `Module::validate` after each pass and rejects the whole
+//! pipeline at the first pass whose output does not validate. Rewrites that
+//! depend on a proved law (`TOPAL-GIR-EVIDENCE-001`) fire only for
+//! `EvidenceStatus::Verified` evidence; `trusted-unverified` evidence and the
+//! checks it guards are retained unchanged.
+
+use crate::{
+    ArtifactError, Block, EvidenceStatus, Function, Instruction, Module, Terminator, Type,
+    ValidatedModule, terminator_targets,
+};
+
+/// Largest callee, in blocks, that `TailApplyInlining` copies into a caller.
+pub const INLINE_BLOCK_LIMIT: usize = 16;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OptimizationPass {
+    /// Replace a `Match` on a block-local constant with a `Branch` to the
+    /// selected target, then drop the targets that became unreachable.
+    ConstantFolding,
+    /// Remove blocks that no path from the function entry reaches.
+    DeadBlockElimination,
+    /// Replace uses of SSA copies with their source value: projections of a
+    /// block-local product, unpacks of a block-local pack, and validations or
+    /// same-type conversions discharged by verified evidence.
+    CopyPropagation,
+    /// Copy a small callee's blocks into the caller in place of `TailApply`.
+    TailApplyInlining,
+}
+
+/// Number of rewrites one pass made.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PassReport {
+    pub pass: OptimizationPass,
+    pub rewrites: usize,
+}
+
+/// A pass produced a module that no longer validates.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OptimizationError {
+    pub pass: OptimizationPass,
+    pub error: ArtifactError,
+}
+
+impl std::fmt::Display for OptimizationError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            formatter,
+            "{:?} produced an invalid module: {}",
+            self.pass, self.error
+        )
+    }
+}
+
+impl std::error::Error for OptimizationError {}
+
+/// Ordered optimization pipeline over validated modules.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PassManager {
+    passes: Vec<OptimizationPass>,
+}
+
+impl Default for PassManager {
+    /// Inline first so propagation and folding see across former calls, then
+    /// fold, then sweep blocks that folding or inlining left unreachable.
+    fn default() -> Self {
+        Self::new([
+            OptimizationPass::TailApplyInlining,
+            OptimizationPass::CopyPropagation,
+            OptimizationPass::ConstantFolding,
+            OptimizationPass::DeadBlockElimination,
+        ])
+    }
+}
+
+impl PassManager {
+    #[must_use]
+    pub fn new(passes: impl IntoIterator<Item = OptimizationPass>) -> Self {
+        Self {
+            passes: passes.into_iter().collect(),
+        }
+    }
+
+    #[must_use]
+    pub fn passes(&self) -> &[OptimizationPass] {
+        &self.passes
+    }
+
+    /// Run every pass in order, validating the output of each.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first pass whose output fails `Module::validate`.
+    pub fn run(
+        &self,
+        module: ValidatedModule<'_>,
+    ) -> Result<(Module, Vec<PassReport>), OptimizationError> {
+        let mut module = module.module().clone();
+        let mut reports = Vec::with_capacity(self.passes.len());
+        for pass in &self.passes {
+            let rewrites = match pass {
+                OptimizationPass::ConstantFolding => fold_constants(&mut module),
+                OptimizationPass::DeadBlockElimination => eliminate_dead_blocks(&mut module),
+                OptimizationPass::CopyPropagation => propagate_copies(&mut module),
+                OptimizationPass::TailApplyInlining => inline_tail_applications(&mut module),
+            };
+            module
+                .validate()
+                .map_err(|error| OptimizationError { pass: *pass, error })?;
+            reports.push(PassReport {
+                pass: *pass,
+                rewrites,
+            });
+        }
+        Ok((module, reports))
+    }
+}
+
+fn fold_constants(module: &mut Module) -> usize {
+    let mut rewrites = 0;
+    for function in &mut module.functions {
+        let mut folded = false;
+        for block in &mut function.blocks {
+            let Terminator::Match { value, targets } = &block.terminator else {
+                continue;
+            };
+            let Some(Instruction::Constant { result_type, bytes }) = defining(block, *value) else {
+                continue;
+            };
+            let selected = match &module.types[*result_type] {
+                Type::Primitive(name) if name == "Boolean" => match bytes.as_slice() {
+                    [flag @ (0 | 1)] => Some(usize::from(*flag)),
+                    _ => None,
+                },
+                Type::Variant(_) => alternative_index(bytes),
+                _ => None,
+            };
+            if let Some(target) = selected.and_then(|index| targets.get(index).copied()) {
+                block.terminator = Terminator::Branch {
+                    target,
+                    arguments: Vec::new(),
+                };
+                rewrites += 1;
+                folded = true;
+            }
+        }
+        if folded {
+            remove_unreachable(function);
+        }
+    }
+    rewrites
+}
+
+fn eliminate_dead_blocks(module: &mut Module) -> usize {
+    module.functions.iter_mut().map(remove_unreachable).sum()
+}
+
+fn propagate_copies(module: &mut Module) -> usize {
+    let mut rewrites = 0;
+    for function in &mut module.functions {
+        for block in &mut function.blocks {
+            rewrites += propagate_block(module.evidence.as_slice(), block);
+        }
+    }
+    rewrites
+}
+
+fn propagate_block(evidence: &[crate::Evidence], block: &mut Block) -> usize {
+    let mut replacement = (0..block.parameters.len()).collect::<Vec<_>>();
+    let mut types = block.parameters.clone();
+    let mut definitions: Vec<Option<usize>> = vec![None; block.parameters.len()];
+    let mut kept = Vec::with_capacity(block.instructions.len());
+    let mut rewrites = 0;
+    for mut instruction in std::mem::take(&mut block.instructions) {
+        map_values(&mut instruction, |value| replacement[value]);
+        let definition = |value: usize| definitions[value].map(|index: usize| &kept[index]);
+        let copy = match &instruction {
+            Instruction::Project { value, field, .. } => match definition(*value) {
+                Some(Instruction::Product { values, .. }) => values.get(*field).copied(),
+                _ => None,
+            },
+            Instruction::UnpackExists { result_type, value } => match definition(*value) {
+                Some(Instruction::PackExists { value: packed, .. })
+                    if types[*packed] == *result_type =>
+                {
+                    Some(*packed)
+                }
+                _ => None,
+            },
+            Instruction::Validate {
+                result_type,
+                value,
+                evidence: proof,
+            }
+            | Instruction::Convert {
+                result_type,
+                value,
+                evidence: proof,
+            } if types[*value] == *result_type
+                && evidence[*proof].status == EvidenceStatus::Verified =>
+            {
+                Some(*value)
+            }
+            _ => None,
+        };
+        if let Some(source) = copy {
+            replacement.push(source);
+            rewrites += 1;
+            continue;
+        }
+        if let Some(result_type) = instruction.result_type() {
+            replacement.push(types.len());
+            types.push(result_type);
+            definitions.push(Some(kept.len()));
+        }
+        kept.push(instruction);
+    }
+    block.instructions = kept;
+    map_terminator_values(&mut block.terminator, |value| replacement[value]);
+    rewrites
+}
+
+fn inline_tail_applications(module: &mut Module) -> usize {
+    let callees = module.functions.clone();
+    let mut rewrites = 0;
+    for (caller_index, caller) in module.functions.iter_mut().enumerate() {
+        for block_index in 0..caller.blocks.len() {
+            let Terminator::TailApply {
+                function,
+                arguments,
+            } = &caller.blocks[block_index].terminator
+            else {
+                continue;
+            };
+            let callee = &callees[*function];
+            if *function == caller_index || !inlinable(caller, callee) {
+                continue;
+            }
+            let offset = caller.blocks.len();
+            caller.blocks[block_index].terminator = Terminator::Branch {
+                target: offset + callee.entry,
+                arguments: arguments.clone(),
+            };
+            for block in &callee.blocks {
+                let mut block = block.clone();
+                map_targets(&mut block.terminator, |target| offset + target);
+                caller.blocks.push(block);
+            }
+            rewrites += 1;
+        }
+    }
+    rewrites
+}
+
+fn inlinable(site: &Function, callee: &Function) -> bool {
+    callee.blocks.len() <= INLINE_BLOCK_LIMIT
+        && callee.blocks[callee.entry].parameters == callee.inputs
+        && callee
+            .effects
+            .iter()
+            .all(|effect| site.effects.contains(effect))
+}
+
+/// Drop blocks unreachable from the entry and renumber the survivors.
+fn remove_unreachable(function: &mut Function) -> usize {
+    let mut reachable = vec![false; function.blocks.len()];
+    let mut pending = vec![function.entry];
+    while let Some(block) = pending.pop() {
+        if !std::mem::replace(&mut reachable[block], true) {
+            pending.extend(terminator_targets(&function.blocks[block].terminator));
+        }
+    }
+    let removed = reachable.iter().filter(|live| !**live).count();
+    if removed == 0 {
+        return 0;
+    }
+    let mut renumbered = vec![usize::MAX; function.blocks.len()];
+    let mut next = 0;
+    for (old, live) in reachable.iter().enumerate() {
+        if *live {
+            renumbered[old] = next;
+            next += 1;
+        }
+    }
+    let blocks = std::mem::take(&mut function.blocks);
+    function.blocks = blocks
+        .into_iter()
+        .zip(reachable)
+        .filter_map(|(block, live)| live.then_some(block))
+        .collect();
+    for block in &mut function.blocks {
+        map_targets(&mut block.terminator, |target| renumbered[target]);
+    }
+    function.entry = renumbered[function.entry];
+    removed
+}
+
+fn defining(block: &Block, value: usize) -> Option<&Instruction> {
+    let index = value.checked_sub(block.parameters.len())?;
+    block
+        .instructions
+        .iter()
+        .filter(|instruction| instruction.result_type().is_some())
+        .nth(index)
+}
+
+fn alternative_index(bytes: &[u8]) -> Option<usize> {
+    let mut value = 0_usize;
+    for (position, byte) in bytes.iter().enumerate() {
+        let shift = u32::try_from(position * 7).ok()?;
+        value |= usize::from(byte & 0x7f).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            return (position + 1 == bytes.len()).then_some(value);
+        }
+    }
+    None
+}
+
+fn map_values(instruction: &mut Instruction, map: impl Fn(usize) -> usize) {
+    match instruction {
+        Instruction::Product { values, .. }
+        | Instruction::Construct { values, .. }
+        | Instruction::Apply {
+            arguments: values, ..
+        }
+        | Instruction::Effect {
+            arguments: values, ..
+        } => values.iter_mut().for_each(|value| *value = map(*value)),
+        Instruction::Project { value, .. }
+        | Instruction::Validate { value, .. }
+        | Instruction::Convert { value, .. }
+        | Instruction::PackExists { value, .. }
+        | Instruction::UnpackExists { value, .. } => *value = map(*value),
+        Instruction::Constant { .. }
+        | Instruction::Capability { .. }
+        | Instruction::BeginRegion
+        | Instruction::EndRegion => {}
+    }
+}
+
+fn map_terminator_values(terminator: &mut Terminator, map: impl Fn(usize) -> usize) {
+    match terminator {
+        Terminator::Return(value)
+        | Terminator::Match { value, .. }
+        | Terminator::Yield { value, .. } => *value = map(*value),
+        Terminator::Branch { arguments, .. } | Terminator::TailApply { arguments, .. } => {
+            for value in arguments {
+                *value = map(*value);
+            }
+        }
+        Terminator::Suspend { .. } => {}
+    }
+}
+
+fn map_targets(terminator: &mut Terminator, map: impl Fn(usize) -> usize) {
+    match terminator {
+        Terminator::Branch { target, .. } => *target = map(*target),
+        Terminator::Match { targets, .. } => {
+            for target in targets {
+                *target = map(*target);
+            }
+        }
+        Terminator::Yield { resume, .. } | Terminator::Suspend { resume } => {
+            *resume = map(*resume);
+        }
+        Terminator::Return(_) | Terminator::TailApply { .. } => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArtifactLimits, parse_text, print_text};
+
+    const HEADER: &str = r#"geir 1 language 0.1.0.0
+identity i0 "example" ("main") ("checked") 0.1.0.0
+identity i1 "example" ("main") ("entry") 0.1.0.0
+identity i2 "example" ("main") ("helper") 0.1.0.0
+type t0 primitive "Int"
+type t1 primitive "Boolean"
+type t2 tuple (t0, t1)
+evidence e0 i0 "smt" 0x01 verified
+evidence e1 i0 "manual" 0x trusted-unverified
+"#;
+
+    fn optimized(body: &str, passes: &[OptimizationPass]) -> (String, Vec<PassReport>) {
+        let module = parse_text(&format!("{HEADER}{body}"), ArtifactLimits::default()).unwrap();
+        let (optimized, reports) = PassManager::new(passes.iter().copied())
+            .run(module.validate().unwrap())
+            .unwrap();
+        let text = print_text(optimized.validate().unwrap());
+        (text[HEADER.len()..].to_owned(), reports)
+    }
+
+    #[test]
+    fn constant_matches_fold_to_branches_and_drop_unselected_rules() {
+        let (text, reports) = optimized(
+            "function f0 i1 public static () inputs () result t0 effects () guarantees () entry b0
+  block b0 ()
+    v0 = constant t1 0x01
+    match v0 (b1, b2)
+  block b1 ()
+    v0 = constant t0 0x00
+    return v0
+  block b2 ()
+    v0 = constant t0 0x01
+    return v0
+export f0
+",
+            &[OptimizationPass::ConstantFolding],
+        );
+        assert_eq!(
+            text,
+            "function f0 i1 public static () inputs () result t0 effects () guarantees () entry b0
+  block b0 ()
+    v0 = constant t1 0x01
+    branch b1 ()
+  block b1 ()
+    v0 = constant t0 0x01
+    return v0
+export f0
+"
+        );
+        assert_eq!(reports[0].rewrites, 1);
+    }
+
+    #[test]
+    fn copy_propagation_discharges_only_verified_evidence() {
+        let (text, reports) = optimized(
+            "function f0 i1 public static () inputs (t0, t1) result t0 effects () guarantees () entry b0
+  block b0 (v0 t0, v1 t1)
+    v2 = product t2 (v0, v1)
+    v3 = project t0 v2 0
+    v4 = validate t0 v3 e0
+    v5 = validate t0 v4 e1
+    return v5
+export f0
+",
+            &[OptimizationPass::CopyPropagation],
+        );
+        assert_eq!(
+            text,
+            "function f0 i1 public static () inputs (t0, t1) result t0 effects () guarantees () entry b0
+  block b0 (v0 t0, v1 t1)
+    v2 = product t2 (v0, v1)
+    v3 = validate t0 v0 e1
+    return v3
+export f0
+"
+        );
+        assert_eq!(reports[0].rewrites, 2);
+    }
+
+    #[test]
+    fn tail_applications_inline_and_dead_cycles_are_removed() {
+        let (text, reports) = optimized(
+            "function f0 i1 public static () inputs (t0) result t0 effects () guarantees () entry b0
+  block b0 (v0 t0)
+    tail-apply f1 (v0)
+function f1 i2 private static () inputs (t0) result t0 effects () guarantees () entry b0
+  block b0 (v0 t0)
+    return v0
+  block b1 ()
+    branch b2 ()
+  block b2 ()
+    branch b1 ()
+export f0
+",
+            &[
+                OptimizationPass::TailApplyInlining,
+                OptimizationPass::DeadBlockElimination,
+            ],
+        );
+        assert_eq!(
+            text,
+            "function f0 i1 public static () inputs (t0) result t0 effects () guarantees () entry b0
+  block b0 (v0 t0)
+    branch b1 (v0)
+  block b1 (v0 t0)
+    return v0
+function f1 i2 private static () inputs (t0) result t0 effects () guarantees () entry b0
+  block b0 (v0 t0)
+    return v0
+export f0
+"
+        );
+        assert_eq!(
+            reports,
+            [
+                PassReport {
+                    pass: OptimizationPass::TailApplyInlining,
+                    rewrites: 1,
+                },
+                PassReport {
+                    pass: OptimizationPass::DeadBlockElimination,
+                    rewrites: 4,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,1129 @@
+//! Stable textual assembly for reviewing and reporting GEIR modules.
+//!
+//! The text form is line oriented. Table entries carry their canonical index
+//! (`i0`, `t3`, `e1`, `f2`, `b0`, `v4`) so a reader can follow references
+//! without counting, and the parser rejects any index that is out of order.
+//! Parsing validates the complete module, so assembling printed text yields
+//! the same canonical bytes as the module it was printed from.
+
+use std::fmt::{self, Write as _};
+
+use topal_semantics::LanguageVersion;
+
+use crate::{
+    ArtifactError, ArtifactLimits, Block, Evidence, EvidenceStatus, Function, Identity,
+    Instruction, Module, Terminator, Type, ValidatedModule, Visibility,
+};
+
+/// Rejection of GEIR assembly text.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TextError {
+    Syntax { line: usize, message: String },
+    Limit { line: usize, message: &'static str },
+    Invalid(ArtifactError),
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { line, message } => write!(formatter, "line {line}: {message}"),
+            Self::Limit { line, message } => write!(formatter, "line {line}: {message}"),
+            Self::Invalid(error) => error.fmt(formatter),
+        }
+    }
+}
+
+impl std::error::Error for TextError {}
+
+/// Print one validated module as assembly text.
+#[must_use]
+pub fn print_text(module: ValidatedModule<'_>) -> String {
+    let module = module.module();
+    let mut output = String::new();
+    let _ = writeln!(
+        output,
+        "geir {} language {}",
+        module.revision,
+        version(module.language)
+    );
+    for identity in &module.imports {
+        let _ = writeln!(output, "import {}", print_identity(identity));
+    }
+    for (index, identity) in module.identities.iter().enumerate() {
+        let _ = writeln!(output, "identity i{index} {}", print_identity(identity));
+    }
+    for (index, value) in module.types.iter().enumerate() {
+        let _ = writeln!(output, "type t{index} {}", print_type(value));
+    }
+    for (index, proof) in module.evidence.iter().enumerate() {
+        let _ = writeln!(
+            output,
+            "evidence e{index} i{} {} {} {}",
+            proof.identity,
+            quote(&proof.calculus),
+            hex(&proof.certificate),
+            match proof.status {
+                EvidenceStatus::Verified => "verified",
+                EvidenceStatus::TrustedUnverified => "trusted-unverified",
+            }
+        );
+    }
+    for identity in &module.capabilities {
+        let _ = writeln!(output, "capability i{identity}");
+    }
+    for (index, function) in module.functions.iter().enumerate() {
+        print_function(index, function, &mut output);
+    }
+    for export in &module.exports {
+        let _ = writeln!(output, "export f{export}");
+    }
+    output
+}
+
+fn print_function(index: usize, function: &Function, output: &mut String) {
+    let _ = writeln!(
+        output,
+        "function f{index} i{} {} static {} inputs {} result t{} effects {} guarantees {} entry b{}",
+        function.identity,
+        match function.visibility {
+            Visibility::Public => "public",
+            Visibility::Private => "private",
+        },
+        references('t', &function.static_parameters),
+        references('t', &function.inputs),
+        function.result,
+        references('i', &function.effects),
+        references('e', &function.guarantees),
+        function.entry
+    );
+    for (block_index, block) in function.blocks.iter().enumerate() {
+        let parameters = block
+            .parameters
+            .iter()
+            .enumerate()
+            .map(|(value, id)| format!("v{value} t{id}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = writeln!(output, "  block b{block_index} ({parameters})");
+        let mut next = block.parameters.len();
+        for instruction in &block.instructions {
+            output.push_str("    ");
+            if instruction.result_type().is_some() {
+                let _ = write!(output, "v{next} = ");
+                next += 1;
+            }
+            output.push_str(&print_instruction(instruction));
+            output.push('\n');
+        }
+        let _ = writeln!(output, "    {}", print_terminator(&block.terminator));
+    }
+}
+
+fn print_instruction(instruction: &Instruction) -> String {
+    match instruction {
+        Instruction::Constant { result_type, bytes } => {
+            format!("constant t{result_type} {}", hex(bytes))
+        }
+        Instruction::Product {
+            result_type,
+            values,
+        } => format!("product t{result_type} {}", references('v', values)),
+        Instruction::Construct {
+            result_type,
+            values,
+        } => format!("construct t{result_type} {}", references('v', values)),
+        Instruction::Project {
+            result_type,
+            value,
+            field,
+        } => format!("project t{result_type} v{value} {field}"),
+        Instruction::Apply {
+            result_type,
+            function,
+            arguments,
+            effects,
+        } => format!(
+            "apply t{result_type} f{function} {} effects {}",
+            references('v', arguments),
+            references('i', effects)
+        ),
+        Instruction::Validate {
+            result_type,
+            value,
+            evidence,
+        } => format!("validate t{result_type} v{value} e{evidence}"),
+        Instruction::Convert {
+            result_type,
+            value,
+            evidence,
+        } => format!("convert t{result_type} v{value} e{evidence}"),
+        Instruction::Capability {
+            result_type,
+            evidence,
+        } => format!("capability t{result_type} e{evidence}"),
+        Instruction::Effect {
+            result_type,
+            effect,
+            arguments,
+        } => format!(
+            "effect t{result_type} i{effect} {}",
+            references('v', arguments)
+        ),
+        Instruction::PackExists {
+            result_type,
+            value,
+            evidence,
+        } => format!("pack t{result_type} v{value} e{evidence}"),
+        Instruction::UnpackExists { result_type, value } => {
+            format!("unpack t{result_type} v{value}")
+        }
+        Instruction::BeginRegion => "begin-region".to_owned(),
+        Instruction::EndRegion => "end-region".to_owned(),
+    }
+}
+
+fn print_terminator(terminator: &Terminator) -> String {
+    match terminator {
+        Terminator::Return(value) => format!("return v{value}"),
+        Terminator::Branch { target, arguments } => {
+            format!("branch b{target} {}", references('v', arguments))
+        }
+        Terminator::Match { value, targets } => {
+            format!("match v{value} {}", references('b', targets))
+        }
+        Terminator::Yield { value, resume } => format!("yield v{value} resume b{resume}"),
+        Terminator::Suspend { resume } => format!("suspend resume b{resume}"),
+        Terminator::TailApply {
+            function,
+            arguments,
+        } => format!("tail-apply f{function} {}", references('v', arguments)),
+    }
+}
+
+fn print_type(value: &Type) -> String {
+    let labelled = |fields: &[(String, usize)]| {
+        let fields = fields
+            .iter()
+            .map(|(label, id)| format!("{} t{id}", quote(label)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("({fields})")
+    };
+    match value {
+        Type::Primitive(name) => format!("primitive {}", quote(name)),
+        Type::Tuple(ids) => format!("tuple {}", references('t', ids)),
+        Type::Record(fields) => format!("record {}", labelled(fields)),
+        Type::Variant(fields) => format!("variant {}", labelled(fields)),
+        Type::Union(ids) => format!("union {}", references('t', ids)),
+        Type::Constraint { base, predicate } => format!("constraint t{base} t{predicate}"),
+        Type::Function { inputs, result } => {
+            format!("function {} -> t{result}", references('t', inputs))
+        }
+        Type::Existential(id) => format!("existential t{id}"),
+        Type::Nominal(id) => format!("nominal t{id}"),
+        Type::Application {
+            constructor,
+            arguments,
+        } => format!("application t{constructor} {}", references('t', arguments)),
+        Type::RecursiveRef(identity) => format!("recursive i{identity}"),
+    }
+}
+
+fn print_identity(identity: &Identity) -> String {
+    let path = |parts: &[String]| {
+        let parts = parts
+            .iter()
+            .map(|part| quote(part))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("({parts})")
+    };
+    format!(
+        "{} {} {} {}",
+        quote(&identity.package),
+        path(&identity.module_path),
+        path(&identity.declaration_path),
+        version(identity.language_revision)
+    )
+}
+
+fn version(version: LanguageVersion) -> String {
+    format!(
+        "{}.{}.{}.{}",
+        version.major, version.minor, version.patch, version.build
+    )
+}
+
+fn references(prefix: char, ids: &[usize]) -> String {
+    let ids = ids
+        .iter()
+        .map(|id| format!("{prefix}{id}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("({ids})")
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut output = "0x".to_owned();
+    for byte in bytes {
+        let _ = write!(output, "{byte:02x}");
+    }
+    output
+}
+
+fn quote(text: &str) -> String {
+    let mut output = "\"".to_owned();
+    for character in text.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            character if character.is_control() => {
+                let _ = write!(output, "\\u{{{:x}}}", u32::from(character));
+            }
+            character => output.push(character),
+        }
+    }
+    output.push('"');
+    output
+}
+
+/// Parse assembly text, then validate the complete module.
+///
+/// # Errors
+///
+/// Returns the first syntax error with its line, a resource-limit violation,
+/// or the validation error of the assembled module.
+pub fn parse_text(text: &str, limits: ArtifactLimits) -> Result<Module, TextError> {
+    if text.len() > limits.bytes {
+        return Err(TextError::Limit {
+            line: 0,
+            message: "assembly text exceeds the byte limit",
+        });
+    }
+    let mut parser = Parser {
+        module: None,
+        limits,
+        instructions: 0,
+        line: 0,
+    };
+    for (index, line) in text.lines().enumerate() {
+        parser.line = index + 1;
+        let line = strip_comment(line);
+        let mut tokens = Tokens::new(tokenize(line).map_err(|message| parser.error(message))?);
+        if tokens.is_empty() {
+            continue;
+        }
+        parser.statement(&mut tokens)?;
+        if let Some(token) = tokens.peek() {
+            return Err(parser.error(format!("unexpected `{token}`")));
+        }
+    }
+    let line = parser.line;
+    let module = parser.module.ok_or(TextError::Syntax {
+        line,
+        message: "missing `geir` header".to_owned(),
+    })?;
+    if let Some(function) = module.functions.last()
+        && function.blocks.is_empty()
+    {
+        return Err(TextError::Syntax {
+            line,
+            message: "function has no blocks".to_owned(),
+        });
+    }
+    module.validate().map_err(TextError::Invalid)?;
+    Ok(module)
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+    Word(String),
+    Text(String),
+    Open,
+    Close,
+    Comma,
+    Equals,
+    Arrow,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Word(word) => formatter.write_str(word),
+            Self::Text(text) => formatter.write_str(&quote(text)),
+            Self::Open => formatter.write_str("("),
+            Self::Close => formatter.write_str(")"),
+            Self::Comma => formatter.write_str(","),
+            Self::Equals => formatter.write_str("="),
+            Self::Arrow => formatter.write_str("->"),
+        }
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (offset, character) in line.char_indices() {
+        match character {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..offset],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut characters = line.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            character if character.is_whitespace() => {}
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            ',' => tokens.push(Token::Comma),
+            '=' => tokens.push(Token::Equals),
+            '-' if characters.peek() == Some(&'>') => {
+                characters.next();
+                tokens.push(Token::Arrow);
+            }
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match characters.next() {
+                        None => return Err("unterminated string".to_owned()),
+                        Some('"') => break,
+                        Some('\\') => match characters.next() {
+                            Some('"') => text.push('"'),
+                            Some('\\') => text.push('\\'),
+                            Some('u') if characters.next() == Some('{') => {
+                                let mut digits = String::new();
+                                loop {
+                                    match characters.next() {
+                                        Some('}') => break,
+                                        Some(digit) => digits.push(digit),
+                                        None => return Err("unterminated escape".to_owned()),
+                                    }
+                                }
+                                let scalar = u32::from_str_radix(&digits, 16)
+                                    .ok()
+                                    .and_then(char::from_u32)
+                                    .ok_or("invalid Unicode escape")?;
+                                text.push(scalar);
+                            }
+                            _ => return Err("invalid string escape".to_owned()),
+                        },
+                        Some(character) => text.push(character),
+                    }
+                }
+                tokens.push(Token::Text(text));
+            }
+            character => {
+                let mut word = character.to_string();
+                while let Some(next) = characters.peek() {
+                    if next.is_whitespace() || matches!(next, '(' | ')' | ',' | '=' | '"') {
+                        break;
+                    }
+                    word.push(*next);
+                    characters.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Tokens {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Tokens {
+    const fn new(tokens: Vec<Token>) -> Self {
+        Self {
+            tokens,
+            position: 0,
+        }
+    }
+
+    const fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+}
+
+struct Parser {
+    module: Option<Module>,
+    limits: ArtifactLimits,
+    instructions: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn error(&self, message: impl Into<String>) -> TextError {
+        TextError::Syntax {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn statement(&mut self, tokens: &mut Tokens) -> Result<(), TextError> {
+        let keyword = self.word(tokens)?;
+        if keyword == "geir" {
+            return self.header(tokens);
+        }
+        let Some(mut module) = self.module.take() else {
+            return Err(self.error("expected the `geir` header first"));
+        };
+        let table_entries = module.imports.len()
+            + module.identities.len()
+            + module.types.len()
+            + module.evidence.len()
+            + module.capabilities.len()
+            + module.functions.len()
+            + module.exports.len();
+        let result = if table_entries >= self.limits.table_entries {
+            Err(TextError::Limit {
+                line: self.line,
+                message: "module tables exceed the entry limit",
+            })
+        } else {
+            self.entry(&keyword, tokens, &mut module)
+        };
+        self.module = Some(module);
+        result
+    }
+
+    fn header(&mut self, tokens: &mut Tokens) -> Result<(), TextError> {
+        if self.module.is_some() {
+            return Err(self.error("duplicate `geir` header"));
+        }
+        let revision = u64::try_from(self.number(tokens)?)
+            .map_err(|_| self.error("artifact revision is out of range"))?;
+        self.keyword(tokens, "language")?;
+        let language = self.version(tokens)?;
+        self.module = Some(Module {
+            revision,
+            language,
+            imports: Vec::new(),
+            identities: Vec::new(),
+            types: Vec::new(),
+            capabilities: Vec::new(),
+            evidence: Vec::new(),
+            functions: Vec::new(),
+            exports: Vec::new(),
+        });
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        keyword: &str,
+        tokens: &mut Tokens,
+        module: &mut Module,
+    ) -> Result<(), TextError> {
+        match keyword {
+            "import" => module.imports.push(self.identity(tokens)?),
+            "identity" => {
+                self.index(tokens, 'i', module.identities.len())?;
+                module.identities.push(self.identity(tokens)?);
+            }
+            "type" => {
+                self.index(tokens, 't', module.types.len())?;
+                module.types.push(self.type_definition(tokens)?);
+            }
+            "evidence" => {
+                self.index(tokens, 'e', module.evidence.len())?;
+                let identity = self.reference(tokens, 'i')?;
+                let calculus = self.text(tokens)?;
+                let certificate = self.bytes(tokens)?;
+                let status = match self.word(tokens)?.as_str() {
+                    "verified" => EvidenceStatus::Verified,
+                    "trusted-unverified" => EvidenceStatus::TrustedUnverified,
+                    other => return Err(self.error(format!("unknown evidence status `{other}`"))),
+                };
+                module.evidence.push(Evidence {
+                    identity,
+                    calculus,
+                    certificate,
+                    status,
+                });
+            }
+            "capability" => module.capabilities.push(self.reference(tokens, 'i')?),
+            "function" => {
+                self.close_function(module)?;
+                self.index(tokens, 'f', module.functions.len())?;
+                module.functions.push(self.function(tokens)?);
+            }
+            "block" => {
+                let Some(function) = module.functions.last_mut() else {
+                    return Err(self.error("block outside a function"));
+                };
+                if function.blocks.len() >= self.limits.blocks {
+                    return Err(TextError::Limit {
+                        line: self.line,
+                        message: "function blocks exceed the block limit",
+                    });
+                }
+                if let Some(previous) = function.blocks.last()
+                    && previous.terminator == OPEN
+                {
+                    return Err(self.error("previous block has no terminator"));
+                }
+                self.index(tokens, 'b', function.blocks.len())?;
+                let parameters = self.block_parameters(tokens)?;
+                function.blocks.push(Block {
+                    parameters,
+                    instructions: Vec::new(),
+                    terminator: OPEN,
+                });
+            }
+            "export" => {
+                self.close_function(module)?;
+                module.exports.push(self.reference(tokens, 'f')?);
+            }
+            word => {
+                let Some(block) = module
+                    .functions
+                    .last_mut()
+                    .and_then(|function| function.blocks.last_mut())
+                else {
+                    return Err(self.error(format!("unexpected `{word}` outside a block")));
+                };
+                if block.terminator != OPEN {
+                    return Err(self.error("instruction after the block terminator"));
+                }
+                if let Some(terminator) = self.terminator(word, tokens)? {
+                    block.terminator = terminator;
+                    return Ok(());
+                }
+                self.instructions += 1;
+                if self.instructions > self.limits.instructions {
+                    return Err(TextError::Limit {
+                        line: self.line,
+                        message: "module instructions exceed the instruction limit",
+                    });
+                }
+                let defined = block.parameters.len()
+                    + block
+                        .instructions
+                        .iter()
+                        .filter(|instruction| instruction.result_type().is_some())
+                        .count();
+                let instruction = self.instruction(word, defined, tokens)?;
+                block.instructions.push(instruction);
+            }
+        }
+        Ok(())
+    }
+
+    fn close_function(&self, module: &Module) -> Result<(), TextError> {
+        let Some(function) = module.functions.last() else {
+            return Ok(());
+        };
+        if function.blocks.is_empty() {
+            return Err(self.error("previous function has no blocks"));
+        }
+        if function
+            .blocks
+            .last()
+            .is_some_and(|block| block.terminator == OPEN)
+        {
+            return Err(self.error("previous block has no terminator"));
+        }
+        Ok(())
+    }
+
+    fn function(&self, tokens: &mut Tokens) -> Result<Function, TextError> {
+        let identity = self.reference(tokens, 'i')?;
+        let visibility = match self.word(tokens)?.as_str() {
+            "public" => Visibility::Public,
+            "private" => Visibility::Private,
+            other => return Err(self.error(format!("unknown visibility `{other}`"))),
+        };
+        self.keyword(tokens, "static")?;
+        let static_parameters = self.references(tokens, 't')?;
+        self.keyword(tokens, "inputs")?;
+        let inputs = self.references(tokens, 't')?;
+        self.keyword(tokens, "result")?;
+        let result = self.reference(tokens, 't')?;
+        self.keyword(tokens, "effects")?;
+        let effects = self.references(tokens, 'i')?;
+        self.keyword(tokens, "guarantees")?;
+        let guarantees = self.references(tokens, 'e')?;
+        self.keyword(tokens, "entry")?;
+        let entry = self.reference(tokens, 'b')?;
+        Ok(Function {
+            identity,
+            visibility,
+            static_parameters,
+            inputs,
+            result,
+            effects,
+            guarantees,
+            blocks: Vec::new(),
+            entry,
+        })
+    }
+
+    fn block_parameters(&self, tokens: &mut Tokens) -> Result<Vec<usize>, TextError> {
+        let mut parameters = Vec::new();
+        self.list(tokens, |parser, tokens| {
+            parser.index(tokens, 'v', parameters.len())?;
+            parameters.push(parser.reference(tokens, 't')?);
+            Ok(())
+        })?;
+        Ok(parameters)
+    }
+
+    fn instruction(
+        &self,
+        word: &str,
+        defined: usize,
+        tokens: &mut Tokens,
+    ) -> Result<Instruction, TextError> {
+        match word {
+            "begin-region" => return Ok(Instruction::BeginRegion),
+            "end-region" => return Ok(Instruction::EndRegion),
+            _ => {}
+        }
+        let Some(result) = word
+            .strip_prefix('v')
+            .and_then(|digits| digits.parse::<usize>().ok())
+        else {
+            return Err(self.error(format!("unknown instruction `{word}`")));
+        };
+        if result != defined {
+            return Err(self.error(format!("expected value `v{defined}`")));
+        }
+        if tokens.next() != Some(Token::Equals) {
+            return Err(self.error("expected `=` after the defined value"));
+        }
+        let operation = self.word(tokens)?;
+        let result_type = self.reference(tokens, 't')?;
+        let instruction = match operation.as_str() {
+            "constant" => Instruction::Constant {
+                result_type,
+                bytes: self.bytes(tokens)?,
+            },
+            "product" => Instruction::Product {
+                result_type,
+                values: self.references(tokens, 'v')?,
+            },
+            "construct" => Instruction::Construct {
+                result_type,
+                values: self.references(tokens, 'v')?,
+            },
+            "project" => Instruction::Project {
+                result_type,
+                value: self.reference(tokens, 'v')?,
+                field: self.number(tokens)?,
+            },
+            "apply" => {
+                let function = self.reference(tokens, 'f')?;
+                let arguments = self.references(tokens, 'v')?;
+                self.keyword(tokens, "effects")?;
+                Instruction::Apply {
+                    result_type,
+                    function,
+                    arguments,
+                    effects: self.references(tokens, 'i')?,
+                }
+            }
+            "validate" => Instruction::Validate {
+                result_type,
+                value: self.reference(tokens, 'v')?,
+                evidence: self.reference(tokens, 'e')?,
+            },
+            "convert" => Instruction::Convert {
+                result_type,
+                value: self.reference(tokens, 'v')?,
+                evidence: self.reference(tokens, 'e')?,
+            },
+            "capability" => Instruction::Capability {
+                result_type,
+                evidence: self.reference(tokens, 'e')?,
+            },
+            "effect" => Instruction::Effect {
+                result_type,
+                effect: self.reference(tokens, 'i')?,
+                arguments: self.references(tokens, 'v')?,
+            },
+            "pack" => Instruction::PackExists {
+                result_type,
+                value: self.reference(tokens, 'v')?,
+                evidence: self.reference(tokens, 'e')?,
+            },
+            "unpack" => Instruction::UnpackExists {
+                result_type,
+                value: self.reference(tokens, 'v')?,
+            },
+            other => return Err(self.error(format!("unknown instruction `{other}`"))),
+        };
+        Ok(instruction)
+    }
+
+    fn terminator(&self, word: &str, tokens: &mut Tokens) -> Result<Option<Terminator>, TextError> {
+        let terminator = match word {
+            "return" => Terminator::Return(self.reference(tokens, 'v')?),
+            "branch" => Terminator::Branch {
+                target: self.reference(tokens, 'b')?,
+                arguments: self.references(tokens, 'v')?,
+            },
+            "match" => Terminator::Match {
+                value: self.reference(tokens, 'v')?,
+                targets: self.references(tokens, 'b')?,
+            },
+            "yield" => {
+                let value = self.reference(tokens, 'v')?;
+                self.keyword(tokens, "resume")?;
+                Terminator::Yield {
+                    value,
+                    resume: self.reference(tokens, 'b')?,
+                }
+            }
+            "suspend" => {
+                self.keyword(tokens, "resume")?;
+                Terminator::Suspend {
+                    resume: self.reference(tokens, 'b')?,
+                }
+            }
+            "tail-apply" => Terminator::TailApply {
+                function: self.reference(tokens, 'f')?,
+                arguments: self.references(tokens, 'v')?,
+            },
+            _ => return Ok(None),
+        };
+        Ok(Some(terminator))
+    }
+
+    fn type_definition(&self, tokens: &mut Tokens) -> Result<Type, TextError> {
+        let kind = self.word(tokens)?;
+        let value = match kind.as_str() {
+            "primitive" => Type::Primitive(self.text(tokens)?),
+            "tuple" => Type::Tuple(self.references(tokens, 't')?),
+            "record" => Type::Record(self.labelled(tokens)?),
+            "variant" => Type::Variant(self.labelled(tokens)?),
+            "union" => Type::Union(self.references(tokens, 't')?),
+            "constraint" => Type::Constraint {
+                base: self.reference(tokens, 't')?,
+                predicate: self.reference(tokens, 't')?,
+            },
+            "function" => {
+                let inputs = self.references(tokens, 't')?;
+                if tokens.next() != Some(Token::Arrow) {
+                    return Err(self.error("expected `->` before the function result"));
+                }
+                Type::Function {
+                    inputs,
+                    result: self.reference(tokens, 't')?,
+                }
+            }
+            "existential" => Type::Existential(self.reference(tokens, 't')?),
+            "nominal" => Type::Nominal(self.reference(tokens, 't')?),
+            "application" => Type::Application {
+                constructor: self.reference(tokens, 't')?,
+                arguments: self.references(tokens, 't')?,
+            },
+            "recursive" => Type::RecursiveRef(self.reference(tokens, 'i')?),
+            other => return Err(self.error(format!("unknown type form `{other}`"))),
+        };
+        Ok(value)
+    }
+
+    fn labelled(&self, tokens: &mut Tokens) -> Result<Vec<(String, usize)>, TextError> {
+        let mut fields = Vec::new();
+        self.list(tokens, |parser, tokens| {
+            let label = parser.text(tokens)?;
+            fields.push((label, parser.reference(tokens, 't')?));
+            Ok(())
+        })?;
+        Ok(fields)
+    }
+
+    fn identity(&self, tokens: &mut Tokens) -> Result<Identity, TextError> {
+        let package = self.text(tokens)?;
+        let module_path = self.texts(tokens)?;
+        let declaration_path = self.texts(tokens)?;
+        let language_revision = self.version(tokens)?;
+        Ok(Identity {
+            package,
+            module_path,
+            declaration_path,
+            language_revision,
+        })
+    }
+
+    fn texts(&self, tokens: &mut Tokens) -> Result<Vec<String>, TextError> {
+        let mut texts = Vec::new();
+        self.list(tokens, |parser, tokens| {
+            texts.push(parser.text(tokens)?);
+            Ok(())
+        })?;
+        Ok(texts)
+    }
+
+    fn references(&self, tokens: &mut Tokens, prefix: char) -> Result<Vec<usize>, TextError> {
+        let mut ids = Vec::new();
+        self.list(tokens, |parser, tokens| {
+            ids.push(parser.reference(tokens, prefix)?);
+            Ok(())
+        })?;
+        Ok(ids)
+    }
+
+    fn list(
+        &self,
+        tokens: &mut Tokens,
+        mut item: impl FnMut(&Self, &mut Tokens) -> Result<(), TextError>,
+    ) -> Result<(), TextError> {
+        if tokens.next() != Some(Token::Open) {
+            return Err(self.error("expected `(`"));
+        }
+        if tokens.peek() == Some(&Token::Close) {
+            tokens.next();
+            return Ok(());
+        }
+        loop {
+            item(self, tokens)?;
+            match tokens.next() {
+                Some(Token::Comma) => {}
+                Some(Token::Close) => return Ok(()),
+                _ => return Err(self.error("expected `,` or `)`")),
+            }
+        }
+    }
+
+    fn index(&self, tokens: &mut Tokens, prefix: char, expected: usize) -> Result<(), TextError> {
+        let index = self.reference(tokens, prefix)?;
+        if index == expected {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{prefix}{expected}`")))
+        }
+    }
+
+    fn reference(&self, tokens: &mut Tokens, prefix: char) -> Result<usize, TextError> {
+        let word = self.word(tokens)?;
+        word.strip_prefix(prefix)
+            .filter(|digits| canonical_number(digits))
+            .and_then(|digits| digits.parse().ok())
+            .ok_or_else(|| self.error(format!("expected a `{prefix}` reference, found `{word}`")))
+    }
+
+    fn number(&self, tokens: &mut Tokens) -> Result<usize, TextError> {
+        let word = self.word(tokens)?;
+        Some(word.as_str())
+            .filter(|digits| canonical_number(digits))
+            .and_then(|digits| digits.parse().ok())
+            .ok_or_else(|| self.error(format!("expected a number, found `{word}`")))
+    }
+
+    fn version(&self, tokens: &mut Tokens) -> Result<LanguageVersion, TextError> {
+        let word = self.word(tokens)?;
+        let parts = word
+            .split('.')
+            .map(|part| {
+                Some(part)
+                    .filter(|digits| canonical_number(digits))
+                    .and_then(|digits| digits.parse::<u64>().ok())
+            })
+            .collect::<Option<Vec<_>>>();
+        let Some([major, minor, patch, build]) = parts.as_deref() else {
+            return Err(self.error(format!(
+                "expected a four-part language revision, found `{word}`"
+            )));
+        };
+        Ok(LanguageVersion {
+            major: *major,
+            minor: *minor,
+            patch: *patch,
+            build: *build,
+        })
+    }
+
+    fn bytes(&self, tokens: &mut Tokens) -> Result<Vec<u8>, TextError> {
+        let word = self.word(tokens)?;
+        let digits = word
+            .strip_prefix("0x")
+            .filter(|digits| digits.len() % 2 == 0)
+            .ok_or_else(|| self.error(format!("expected `0x` bytes, found `{word}`")))?;
+        (0..digits.len())
+            .step_by(2)
+            .map(|offset| {
+                let pair = &digits[offset..offset + 2];
+                pair.bytes()
+                    .all(|digit| matches!(digit, b'0'..=b'9' | b'a'..=b'f'))
+                    .then(|| u8::from_str_radix(pair, 16).ok())
+                    .flatten()
+                    .ok_or_else(|| self.error("bytes use lowercase hexadecimal pairs"))
+            })
+            .collect()
+    }
+
+    fn text(&self, tokens: &mut Tokens) -> Result<String, TextError> {
+        match tokens.next() {
+            Some(Token::Text(text)) => Ok(text),
+            _ => Err(self.error("expected a quoted string")),
+        }
+    }
+
+    fn word(&self, tokens: &mut Tokens) -> Result<String, TextError> {
+        match tokens.next() {
+            Some(Token::Word(word)) => Ok(word),
+            Some(token) => Err(self.error(format!("unexpected `{token}`"))),
+            None => Err(self.error("unexpected end of line")),
+        }
+    }
+
+    fn keyword(&self, tokens: &mut Tokens, keyword: &str) -> Result<(), TextError> {
+        if self.word(tokens)? == keyword {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{keyword}`")))
+        }
+    }
+}
+
+/// Placeholder terminator for a block whose terminator line is still pending.
+const OPEN: Terminator = Terminator::Suspend { resume: usize::MAX };
+
+fn canonical_number(digits: &str) -> bool {
+    !digits.is_empty()
+        && digits.bytes().all(|digit| digit.is_ascii_digit())
+        && (digits == "0" || !digits.starts_with('0'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVERY_FORM: &str = r#"geir 1 language 0.1.0.0
+import "base" ("core") ("Int") 0.1.0.0
+identity i0 "example" ("main") () 0.1.0.0
+identity i1 "example" ("main") ("answer") 0.1.0.0
+identity i2 "example" ("main") ("log \"quoted\"") 0.1.0.0
+type t0 primitive "Int"
+type t1 primitive "Boolean"
+type t2 tuple ()
+type t3 variant ("no" t2, "yes" t2)
+type t4 record ("count" t0, "flag" t1)
+type t5 union (t0, t1)
+type t6 constraint t0 t1
+type t7 function (t0) -> t1
+type t8 existential t0
+type t9 nominal t4
+type t10 application t9 (t0)
+type t11 recursive i1
+evidence e0 i1 "smt" 0x01ff verified
+evidence e1 i1 "manual" 0x trusted-unverified
+capability i2
+function f0 i1 public static (t0) inputs (t0, t1) result t0 effects (i2) guarantees (e0) entry b0
+  block b0 (v0 t0, v1 t1)
+    begin-region
+    v2 = product t4 (v0, v1)
+    v3 = project t0 v2 0
+    v4 = validate t0 v3 e0
+    v5 = convert t0 v4 e1
+    v6 = capability t2 e1
+    v7 = effect t2 i2 (v5)
+    v8 = pack t0 v5 e0
+    v9 = unpack t0 v8
+    v10 = constant t3 0x01
+    end-region
+    match v10 (b1, b2)
+  block b1 ()
+    v0 = construct t2 ()
+    yield v0 resume b3
+  block b2 ()
+    suspend resume b4
+  block b3 (v0 t0)
+    return v0
+  block b4 ()
+    tail-apply f1 ()
+function f1 i1 private static () inputs () result t0 effects () guarantees () entry b0
+  block b0 ()
+    v0 = constant t0 0x2a
+    v1 = constant t1 0x01
+    v2 = apply t0 f0 (v0, v1) effects (i2)
+    branch b1 (v2)
+  block b1 (v0 t0)
+    return v0
+export f0
+"#;
+
+    #[test]
+    fn printed_text_reassembles_to_identical_canonical_bytes() {
+        let module = parse_text(EVERY_FORM, ArtifactLimits::default()).unwrap();
+        let validated = module.validate().unwrap();
+        assert_eq!(print_text(validated), EVERY_FORM);
+        let bytes = validated.canonical_bytes();
+        let decoded = crate::decode_canonical(&bytes, ArtifactLimits::default()).unwrap();
+        let reassembled = parse_text(
+            &print_text(decoded.validate().unwrap()),
+            ArtifactLimits::default(),
+        )
+        .unwrap();
+        assert_eq!(reassembled.validate().unwrap().canonical_bytes(), bytes);
+    }
+
+    #[test]
+    fn parser_reports_lines_for_malformed_and_out_of_order_entries() {
+        let header = "geir 1 language 0.1.0.0\n";
+        assert_eq!(
+            parse_text(
+                &format!("{header}type t1 primitive \"Int\"\n"),
+                ArtifactLimits::default()
+            ),
+            Err(TextError::Syntax {
+                line: 2,
+                message: "expected `t0`".to_owned(),
+            })
+        );
+        assert!(matches!(
+            parse_text(
+                &format!("{header}# comment\ntype t0 primitive \"Int\n"),
+                ArtifactLimits::default()
+            ),
+            Err(TextError::Syntax { line: 3, .. })
+        ));
+        assert!(matches!(
+            parse_text("type t0 tuple ()\n", ArtifactLimits::default()),
+            Err(TextError::Syntax { line: 1, .. })
+        ));
+        let unterminated = EVERY_FORM.replace("    return v0\n  block b4", "  block b4");
+        assert!(matches!(
+            parse_text(&unterminated, ArtifactLimits::default()),
+            Err(TextError::Syntax { line: 41, .. })
+        ));
+    }
+
+    #[test]
+    fn assembled_text_is_validated_as_a_whole_module() {
+        let forward = EVERY_FORM.replace("type t8 existential t0", "type t8 existential t9");
+        assert!(matches!(
+            parse_text(&forward, ArtifactLimits::default()),
+            Err(TextError::Invalid(_))
+        ));
+        let limited = ArtifactLimits {
+            instructions: 3,
+            ..ArtifactLimits::default()
+        };
+        assert!(matches!(
+            parse_text(EVERY_FORM, limited),
+            Err(TextError::Limit { .. })
+        ));
+    }
+}
@@ -0,0 +1,142 @@
+use std::fs;
+use std::process::Command;
+
+fn scratch(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("topal-geir-{}-{name}", std::process::id()))
+}
+
+const TEXT: &str = r#"geir 1 language 0.1.0.0
+identity i0 "example" ("main") ("answer") 0.1.0.0
+type t0 primitive "Int"
+function f0 i0 public static () inputs (t0) result t0 effects () guarantees () entry b0
+  block b0 (v0 t0)
+    return v0
+export f0
+"#;
+
+#[test]
+fn assembled_artifacts_disassemble_to_the_same_text() {
+    let directory = scratch("round-trip");
+    fs::create_dir_all(&directory).unwrap();
+    let source = directory.join("answer.geir.txt");
+    let artifact = directory.join("answer.geir");
+    fs::write(&source, TEXT).unwrap();
+    let assembled = Command::new(env!("CARGO_BIN_EXE_topal-geir"))
+        .args([
+            "assemble",
+            source.to_str().unwrap(),
+            "--output",
+            artifact.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        assembled.status.success(),
+        "{}",
+        String::from_utf8_lossy(&assembled.stderr)
+    );
+    assert!(fs::read(&artifact).unwrap().starts_with(b"TOPALGEIR"));
+    let disassembled = Command::new(env!("CARGO_BIN_EXE_topal-geir"))
+        .args(["disassemble", artifact.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(disassembled.status.success());
+    assert_eq!(String::from_utf8(disassembled.stdout).unwrap(), TEXT);
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn invalid_inputs_fail_with_the_rejected_line_or_stage() {
+    let directory = scratch("invalid");
+    fs::create_dir_all(&directory).unwrap();
+    let source = directory.join("broken.geir.txt");
+    fs::write(&source, TEXT.replace("return v0", "return v1")).unwrap();
+    let result = Command::new(env!("CARGO_BIN_EXE_topal-geir"))
+        .args([
+            "assemble",
+            source.to_str().unwrap(),
+            "--output",
+            directory.join("broken.geir").to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("Ssa"));
+    fs::write(&source, b"TOPALGEIR").unwrap();
+    let result = Command::new(env!("CARGO_BIN_EXE_topal-geir"))
+        .args(["disassemble", source.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+    assert!(!directory.join("broken.geir").exists());
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn linked_bundles_resolve_imports_across_artifacts() {
+    let directory = scratch("link");
+    fs::create_dir_all(&directory).unwrap();
+    let application = TEXT
+        .replace(
+            "identity i0",
+            "import \"base\" (\"core\") (\"answer\") 0.1.0.0\nidentity i0",
+        )
+        .replace("public", "private")
+        .replace("export f0\n", "");
+    let library = TEXT.replace("\"example\" (\"main\")", "\"base\" (\"core\")");
+    let mut artifacts = Vec::new();
+    for (name, text) in [("application", application), ("library", library)] {
+        let source = directory.join(format!("{name}.geir.txt"));
+        let artifact = directory.join(format!("{name}.geir"));
+        fs::write(&source, text).unwrap();
+        assert!(
+            Command::new(env!("CARGO_BIN_EXE_topal-geir"))
+                .args([
+                    "assemble",
+                    source.to_str().unwrap(),
+                    "--output",
+                    artifact.to_str().unwrap(),
+                ])
+                .status()
+                .unwrap()
+                .success()
+        );
+        artifacts.push(artifact);
+    }
+    let bundle = directory.join("bundle.geir");
+    let unresolved = Command::new(env!("CARGO_BIN_EXE_topal-geir"))
+        .args([
+            "link",
+            artifacts[0].to_str().unwrap(),
+            "--output",
+            bundle.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(!unresolved.status.success());
+    assert!(String::from_utf8_lossy(&unresolved.stderr).contains("`base.core.answer`"));
+    assert!(!bundle.exists());
+    let linked = Command::new(env!("CARGO_BIN_EXE_topal-geir"))
+        .args([
+            "link",
+            artifacts[0].to_str().unwrap(),
+            artifacts[1].to_str().unwrap(),
+            "--output",
+            bundle.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        linked.status.success(),
+        "{}",
+        String::from_utf8_lossy(&linked.stderr)
+    );
+    let text = Command::new(env!("CARGO_BIN_EXE_topal-geir"))
+        .args(["disassemble", bundle.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let text = String::from_utf8(text.stdout).unwrap();
+    assert!(!text.contains("import"));
+    assert!(text.contains("export f1"));
+    fs::remove_dir_all(directory).unwrap();
+}
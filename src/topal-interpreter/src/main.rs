@@ -1,11 +1,14 @@
 use std::env;
 use std::fs;
-use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::io::{self, Read};
 use std::path::Path;
 use std::process::ExitCode;
 
+mod repl;
+
 use topal_language::{
-    JsonLines, LanguageVersion, Session, TraceSink, UNICODE_VERSION, load_module_tree,
+    JsonLines, LanguageVersion, ResourceLimits, Session, TraceSink, UNICODE_VERSION,
+    load_module_tree, with_evaluation_stack,
 };
 
 enum Mode {
@@ -18,6 +21,7 @@ struct Arguments {
     mode: Mode,
     source: Option<String>,
     language_version: Option<LanguageVersion>,
+    limits: ResourceLimits,
 }
 
 fn main() -> ExitCode {
@@ -33,30 +37,38 @@ fn main() -> ExitCode {
 fn run() -> Result<(), String> {
     let arguments = parse_arguments(env::args().skip(1))?;
     match arguments.mode {
-        Mode::Interactive => interactive(arguments.source.as_deref(), arguments.language_version),
+        Mode::Interactive if arguments.limits != ResourceLimits::default() => {
+            Err("--fuel, --memory and --call-depth bound script and test runs only".into())
+        }
+        Mode::Interactive => {
+            repl::interactive(arguments.source.as_deref(), arguments.language_version)
+        }
         Mode::Script | Mode::Test => {
             if arguments.language_version.is_some() {
                 return Err("--language-version supplies interactive context only; source files declare their own version".into());
             }
             let source_name = arguments.source.as_deref().unwrap_or("<stdin>");
-            let mut session = Session::new();
-            if matches!(arguments.mode, Mode::Test) {
-                let stderr = io::stderr();
-                let mut trace = JsonLines::new(stderr.lock());
-                evaluate_input(
-                    &mut session,
-                    arguments.source.as_deref(),
-                    source_name,
-                    &mut trace,
-                )
-            } else {
-                evaluate_input(
-                    &mut session,
-                    arguments.source.as_deref(),
-                    source_name,
-                    &mut io::sink(),
-                )
-            }
+            with_evaluation_stack(arguments.limits, || {
+                let mut session = Session::new();
+                session.set_resource_limits(arguments.limits);
+                if matches!(arguments.mode, Mode::Test) {
+                    let stderr = io::stderr();
+                    let mut trace = JsonLines::new(stderr.lock());
+                    evaluate_input(
+                        &mut session,
+                        arguments.source.as_deref(),
+                        source_name,
+                        &mut trace,
+                    )
+                } else {
+                    evaluate_input(
+                        &mut session,
+                        arguments.source.as_deref(),
+                        source_name,
+                        &mut io::sink(),
+                    )
+                }
+            })
         }
     }
 }
@@ -65,6 +77,7 @@ fn parse_arguments(arguments: impl Iterator<Item = String>) -> Result<Arguments,
     let mut mode = Mode::Script;
     let mut source = None;
     let mut language_version = None;
+    let mut limits = ResourceLimits::default();
     let mut arguments = arguments.peekable();
     while let Some(argument) = arguments.next() {
         match argument.as_str() {
@@ -83,9 +96,12 @@ fn parse_arguments(arguments: impl Iterator<Item = String>) -> Result<Arguments,
                         .map_err(|error| format!("invalid language version `{value}`: {error}"))?,
                 );
             }
+            "--fuel" => limits.fuel = Some(limit(&mut arguments, "--fuel")?),
+            "--memory" => limits.value_memory = Some(limit(&mut arguments, "--memory")?),
+            "--call-depth" => limits.call_depth = Some(limit(&mut arguments, "--call-depth")?),
             "--help" => {
                 println!(
-                    "Usage: topal [--interactive [--language-version VERSION] | --test] [FILE]\n\nWith no FILE, source is read from standard input. Source files declare their language version."
+                    "Usage: topal [--interactive [--language-version VERSION] | --test] [--fuel N] [--memory BYTES] [--call-depth N] [FILE]\n\nWith no FILE, source is read from standard input. Source files declare their language version.\n--fuel, --memory and --call-depth stop evaluation with an E-RESOURCE-* diagnostic when a script exceeds them."
                 );
                 std::process::exit(0);
             }
@@ -109,9 +125,22 @@ fn parse_arguments(arguments: impl Iterator<Item = String>) -> Result<Arguments,
         mode,
         source,
         language_version,
+        limits,
     })
 }
 
+fn limit<T: std::str::FromStr>(
+    arguments: &mut impl Iterator<Item = String>,
+    option: &str,
+) -> Result<T, String> {
+    let value = arguments
+        .next()
+        .ok_or_else(|| format!("{option} requires a count"))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid {option} count `{value}`"))
+}
+
 fn read_source(path: Option<&str>) -> Result<String, String> {
     if let Some(path) = path {
         fs::read_to_string(path).map_err(|error| format!("cannot read {path}: {error}"))
@@ -124,90 +153,6 @@ fn read_source(path: Option<&str>) -> Result<String, String> {
     }
 }
 
-fn interactive(
-    source: Option<&str>,
-    language_version: Option<LanguageVersion>,
-) -> Result<(), String> {
-    if source.is_some() {
-        return Err("interactive mode does not accept a source file".into());
-    }
-    let stdin = io::stdin();
-    let mut input = stdin.lock();
-    let language_version =
-        language_version.unwrap_or_else(Session::highest_supported_language_version);
-    let mut session = Session::for_language_version(language_version)
-        .map_err(|error| format!("unsupported language version `{language_version}`: {error}"))?;
-    let terminal = io::stdin().is_terminal();
-    let mut pending = String::new();
-    loop {
-        if terminal {
-            print!("{}", if pending.is_empty() { "> " } else { "... " });
-            io::stdout().flush().map_err(|error| error.to_string())?;
-        }
-        let mut line = String::new();
-        if input
-            .read_line(&mut line)
-            .map_err(|error| error.to_string())?
-            == 0
-        {
-            if !pending.is_empty() {
-                match session.evaluate(&pending, &mut io::sink()) {
-                    Ok(value) => println!("{value}"),
-                    Err(error) => eprintln!("{}", error.render("<interactive>")),
-                }
-            }
-            return Ok(());
-        }
-        if !pending.is_empty()
-            && (line.trim().is_empty() || !line.chars().next().is_some_and(char::is_whitespace))
-            && Session::awaits_dedent(&pending)
-        {
-            match session.evaluate(&pending, &mut io::sink()) {
-                Ok(value) => println!("{value}"),
-                Err(error) => eprintln!("{}", error.render("<interactive>")),
-            }
-            pending.clear();
-        }
-        if line.trim().is_empty() && pending.is_empty() {
-            continue;
-        }
-        if pending.is_empty() && line.trim_start().starts_with('#') {
-            continue;
-        }
-        pending.push_str(&line);
-        if line.chars().next().is_some_and(char::is_whitespace) && Session::awaits_dedent(&pending)
-        {
-            continue;
-        }
-        match session.evaluate(&pending, &mut io::sink()) {
-            Ok(value) => {
-                println!("{value}");
-                pending.clear();
-            }
-            Err(error)
-                if matches!(
-                    error.code.as_str(),
-                    "E-UNTERMINATED-STRING"
-                        | "E-EXPECTED-RPAREN"
-                        | "E-EXPECTED-FUNCTION-BODY"
-                        | "E-EMPTY-INTERFACE"
-                        | "E-EXPECTED-INTERFACE-OPERATIONS"
-                        | "E-EXPECTED-FOREACH-BODY"
-                        | "E-UNSUPPORTED-GENERATOR-HEADER"
-                        | "E-EXPECTED-INDENTED-GENERATOR-HEADER"
-                        | "E-EXPECTED-GENERATOR-BODY"
-                        | "E-EMPTY-UNION"
-                        | "E-UNSUPPORTED-INCOMPLETE-DECISION"
-                        | "E-INCOMPLETE-ERROR-CODE-DECISION"
-                ) => {}
-            Err(error) => {
-                eprintln!("{}", error.render("<interactive>"));
-                pending.clear();
-            }
-        }
-    }
-}
-
 fn evaluate_and_print(
     session: &mut Session,
     source: &str,
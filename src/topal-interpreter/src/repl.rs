@@ -0,0 +1,349 @@
+//! Interactive session loop and its `:` meta-commands.
+
+use std::collections::BTreeSet;
+use std::env;
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+
+use topal_language::{LanguageVersion, Session, Value, lang_documentation};
+use topal_source::SourceText;
+use topal_syntax::{DocumentedDeclaration, Statement, extract_documentation, lex, parse};
+
+const SOURCE_NAME: &str = "<interactive>";
+
+const COMMANDS: &str = "\
+:type EXPRESSION   show the classifier of an expression without evaluating it
+:help [NAME]       show these commands, or the documentation of NAME
+:load FILE         evaluate a source file into this session
+:reset             discard every binding and start a fresh session
+:bindings          list the session's bindings and callables
+:trace on|off      write semantic trace events to standard error
+:save FILE         write the session's accepted inputs as a source file
+:history           list the persisted input history";
+
+struct Repl {
+    session: Session,
+    language_version: LanguageVersion,
+    /// Accepted source units, in evaluation order, replayed by `:save`.
+    transcript: Vec<String>,
+    trace: bool,
+    history: History,
+}
+
+/// Input lines persisted across sessions. `TOPAL_HISTORY` names the file, an
+/// empty value disables persistence, and a terminal session otherwise uses
+/// `~/.topal_history`.
+struct History {
+    path: Option<PathBuf>,
+    entries: Vec<String>,
+}
+
+impl History {
+    fn open(terminal: bool) -> Self {
+        let path = match env::var_os("TOPAL_HISTORY") {
+            Some(path) if path.is_empty() => None,
+            Some(path) => Some(PathBuf::from(path)),
+            None if terminal => {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".topal_history"))
+            }
+            None => None,
+        };
+        let entries = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|text| text.lines().map(str::to_owned).collect())
+            .unwrap_or_default();
+        Self { path, entries }
+    }
+
+    fn record(&mut self, line: &str) {
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.trim().is_empty() {
+            return;
+        }
+        self.entries.push(line.to_owned());
+        if let Some(path) = &self.path {
+            // History is a convenience; failing to persist it never ends the session.
+            let _ = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{line}"));
+        }
+    }
+}
+
+pub fn interactive(
+    source: Option<&str>,
+    language_version: Option<LanguageVersion>,
+) -> Result<(), String> {
+    if source.is_some() {
+        return Err("interactive mode does not accept a source file".into());
+    }
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let language_version =
+        language_version.unwrap_or_else(Session::highest_supported_language_version);
+    let terminal = io::stdin().is_terminal();
+    let mut repl = Repl {
+        session: fresh_session(language_version)?,
+        language_version,
+        transcript: Vec::new(),
+        trace: false,
+        history: History::open(terminal),
+    };
+    let mut pending = String::new();
+    // Documentation comments awaiting the declaration they describe.
+    let mut documentation = String::new();
+    loop {
+        if terminal {
+            print!("{}", if pending.is_empty() { "> " } else { "... " });
+            io::stdout().flush().map_err(|error| error.to_string())?;
+        }
+        let mut line = String::new();
+        if input
+            .read_line(&mut line)
+            .map_err(|error| error.to_string())?
+            == 0
+        {
+            if !pending.is_empty() {
+                repl.evaluate(&pending);
+            }
+            return Ok(());
+        }
+        repl.history.record(&line);
+        if !pending.is_empty()
+            && (line.trim().is_empty() || !line.chars().next().is_some_and(char::is_whitespace))
+            && Session::awaits_dedent(&pending)
+        {
+            repl.evaluate(&pending);
+            pending.clear();
+        }
+        if pending.is_empty() && line.starts_with(':') {
+            documentation.clear();
+            repl.command(line.trim_end());
+            continue;
+        }
+        if line.trim().is_empty() && pending.is_empty() {
+            documentation.clear();
+            continue;
+        }
+        if pending.is_empty() && line.trim_start().starts_with('#') {
+            if line.trim_start().starts_with("###") {
+                documentation.push_str(&line);
+            }
+            continue;
+        }
+        if pending.is_empty() {
+            pending = std::mem::take(&mut documentation);
+        }
+        pending.push_str(&line);
+        if line.chars().next().is_some_and(char::is_whitespace) && Session::awaits_dedent(&pending)
+        {
+            continue;
+        }
+        if Session::continuation(&pending).is_none() {
+            repl.evaluate(&pending);
+            pending.clear();
+        }
+    }
+}
+
+fn fresh_session(language_version: LanguageVersion) -> Result<Session, String> {
+    Session::for_language_version(language_version)
+        .map_err(|error| format!("unsupported language version `{language_version}`: {error}"))
+}
+
+impl Repl {
+    fn evaluate(&mut self, input: &str) {
+        let mut trace = Vec::new();
+        let result = self.session.evaluate(input, &mut trace);
+        self.flush_trace(trace);
+        match result {
+            Ok(value) => self.accept(input, &value),
+            Err(error) => eprintln!("{}", error.render(SOURCE_NAME)),
+        }
+    }
+
+    fn accept(&mut self, input: &str, value: &Value) {
+        println!("{value}");
+        let mut unit = input.to_owned();
+        if !unit.ends_with('\n') {
+            unit.push('\n');
+        }
+        self.transcript.push(unit);
+    }
+
+    fn flush_trace(&self, trace: Vec<String>) {
+        if self.trace {
+            let stderr = io::stderr();
+            let mut stderr = stderr.lock();
+            for event in trace {
+                let _ = writeln!(stderr, "{event}");
+            }
+        }
+    }
+
+    fn command(&mut self, line: &str) {
+        let (command, argument) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(command, argument)| (command, argument.trim()));
+        let result = match (command, argument) {
+            (":type", expression) if !expression.is_empty() => self.classify(expression),
+            (":help", "") => Ok(COMMANDS.to_owned()),
+            (":help", name) => self.help(name),
+            (":load", path) if !path.is_empty() => self.load(path),
+            (":reset", "") => fresh_session(self.language_version).map(|session| {
+                self.session = session;
+                self.transcript.clear();
+                "session reset".to_owned()
+            }),
+            (":bindings", "") => Ok(self.bindings()),
+            (":trace", "on" | "off") => {
+                self.trace = argument == "on";
+                Ok(format!("trace {argument}"))
+            }
+            (":save", path) if !path.is_empty() => self.save(path),
+            (":history", "") => Ok(self.history.entries.join("\n")),
+            (":type" | ":load" | ":save", _) => Err(format!("{command} requires an argument")),
+            (":trace", _) => Err(":trace takes `on` or `off`".into()),
+            (":reset" | ":bindings" | ":history", _) => Err(format!("{command} takes no argument")),
+            _ => Err(format!("unknown command `{command}`; try :help")),
+        };
+        match result {
+            Ok(output) if output.is_empty() => {}
+            Ok(output) => println!("{output}"),
+            Err(message) => eprintln!("{message}"),
+        }
+    }
+
+    /// Classify an expression statically, from its literals, the session's
+    /// bindings and declared function results, so that `:type` never runs
+    /// effects, binds names, or advances session state.
+    fn classify(&self, expression: &str) -> Result<String, String> {
+        self.session
+            .static_classifier(expression)
+            .map_err(|error| error.render(SOURCE_NAME))?
+            .ok_or_else(|| {
+                format!("the classifier of `{expression}` is known only by evaluating it")
+            })
+    }
+
+    /// Resolve `name` the way the debugger's `help` does: exact or trailing
+    /// unqualified matches over session, `std`, and `lang` declarations.
+    fn help(&self, identifier: &str) -> Result<String, String> {
+        let mut declarations = documented_source(&self.transcript.concat());
+        declarations.extend(documented_source(include_str!(
+            "../../../library/std/module.t"
+        )));
+        declarations.extend(lang_documentation());
+        let qualified = identifier.contains(' ');
+        let matches = declarations
+            .iter()
+            .filter(|declaration| {
+                declaration.name == identifier
+                    || (!qualified
+                        && declaration
+                            .name
+                            .split_whitespace()
+                            .next_back()
+                            .is_some_and(|name| name == identifier))
+            })
+            .collect::<Vec<_>>();
+        if matches.is_empty() {
+            return Err(format!("no documentation for `{identifier}`"));
+        }
+        let distinct_names = matches
+            .iter()
+            .map(|declaration| &declaration.name)
+            .collect::<BTreeSet<_>>();
+        if distinct_names.len() > 1 {
+            let mut message = format!("ambiguous identifier `{identifier}`; candidates:");
+            for name in distinct_names {
+                let _ = write!(message, "\n  {name}");
+            }
+            return Err(message);
+        }
+        let mut output = String::new();
+        for declaration in matches {
+            let _ = writeln!(output, "{}", declaration.syntax);
+            if let Some(documentation) = &declaration.documentation {
+                let _ = writeln!(output, "\n{documentation}");
+            }
+            for parameter in &declaration.parameters {
+                if let Some(documentation) = &parameter.documentation {
+                    let _ = writeln!(output, "\n{}: {documentation}", parameter.name);
+                }
+            }
+        }
+        Ok(output.trim_end().to_owned())
+    }
+
+    fn load(&mut self, path: &str) -> Result<String, String> {
+        let text =
+            fs::read_to_string(path).map_err(|error| format!("cannot read {path}: {error}"))?;
+        let mut trace = Vec::new();
+        let result = self.session.evaluate_source_file(&text, &mut trace);
+        self.flush_trace(trace);
+        let value = result.map_err(|error| error.render(path))?;
+        self.transcript.push(without_language_selection(&text));
+        Ok(value.to_string())
+    }
+
+    fn bindings(&self) -> String {
+        let mut output = String::new();
+        for (name, value) in self.session.bindings() {
+            let _ = writeln!(output, "{name} : {} = {value}", value.classifier());
+        }
+        for name in self.session.callable_names() {
+            let _ = writeln!(output, "{name} : Function");
+        }
+        output.trim_end().to_owned()
+    }
+
+    fn save(&self, path: &str) -> Result<String, String> {
+        let mut output = format!(
+            "use language (\n  version is {}\n)\n",
+            self.language_version
+        );
+        for unit in &self.transcript {
+            output.push_str(unit);
+        }
+        fs::write(path, output).map_err(|error| format!("cannot write {path}: {error}"))?;
+        Ok(format!("saved {} inputs to {path}", self.transcript.len()))
+    }
+}
+
+fn documented_source(text: &str) -> Vec<DocumentedDeclaration> {
+    let Ok(source) = SourceText::new(text) else {
+        return Vec::new();
+    };
+    let lexed = lex(&source);
+    let parsed = parse(&source, &lexed);
+    extract_documentation(&source, &lexed, &parsed)
+}
+
+/// Normalized source text after a file's language selection, so that a saved
+/// session declares its language exactly once. Parsed offsets index the
+/// normalized text, not `text`, which may still hold a byte order mark or
+/// CRLF line endings.
+fn without_language_selection(text: &str) -> String {
+    let mut body = match SourceText::new(text) {
+        Ok(source) => {
+            let lexed = lex(&source);
+            match parse(&source, &lexed).statements.first() {
+                Some(Statement::LanguageSelection { span, .. }) => source.as_str()[span.end..]
+                    .trim_start_matches('\n')
+                    .to_owned(),
+                _ => source.as_str().to_owned(),
+            }
+        }
+        Err(_) => text.to_owned(),
+    };
+    if !body.is_empty() && !body.ends_with('\n') {
+        body.push('\n');
+    }
+    body
+}
@@ -1,7 +1,7 @@
 //! Functional conformance tests for TOPAL-SYN-SOURCE-001,
 //! TOPAL-SYN-NUM-001, TOPAL-SYN-GRAMMAR-001, TOPAL-SYN-BIND-001,
 //! TOPAL-NUM-LITERAL-001, TOPAL-NUM-ADD-001, and TOPAL-INTP-MODE-001 through
-//! TOPAL-INTP-MODE-003.
+//! TOPAL-INTP-MODE-004, and TOPAL-INTP-BUDGET-001.
 
 use std::io::Write;
 use std::path::Path;
@@ -35,6 +35,28 @@ fn run(arguments: &[&str], input: &str) -> std::process::Output {
     child.wait_with_output().unwrap()
 }
 
+fn scratch(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("topal-interpreter-{}-{name}", std::process::id()))
+}
+
+fn interactive_with_history(history: &Path, input: &str) -> std::process::Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_topal"))
+        .arg("--interactive")
+        .env("TOPAL_HISTORY", history)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
 fn run_file(path: &Path) -> std::process::Output {
     Command::new(env!("CARGO_BIN_EXE_topal"))
         .arg(path)
@@ -53,7 +75,7 @@ fn every_interpreter_example_is_an_executable_script() {
         .filter(|path| path.extension().is_some_and(|extension| extension == "t"))
         .collect::<Vec<_>>();
     examples.sort();
-    assert_eq!(examples.len(), 192);
+    assert_eq!(examples.len(), 195);
     for example in examples {
         let output = run_file(&example);
         assert!(
@@ -140,6 +162,37 @@ fn script_mode_requires_an_explicit_source_language_version() {
     assert!(stderr.contains("use language"));
 }
 
+#[test]
+fn resource_limits_stop_untrusted_scripts_with_distinct_codes() {
+    let runaway = "branch-count is fn (value : Int) -> Int\n  value\n    <= 0 then 1\n    otherwise (branch-count (value - 1)) + (branch-count (value - 2))\nbranch-count 18\n";
+    let output = run(&["--fuel", "5000"], runaway);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("E-RESOURCE-FUEL")
+    );
+
+    let output = run(&["--memory", "65536"], "2 ^ 100000000\n");
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("E-RESOURCE-MEMORY")
+    );
+
+    let output = run(&["--fuel", "5000", "--memory", "65536"], "2 ^ 10\n");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "1024\n");
+
+    let output = run(&["--fuel", "many"], "1\n");
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("invalid --fuel count `many`")
+    );
+}
+
 #[test]
 fn interactive_language_version_is_explicit_or_defaults_to_highest_supported() {
     assert_eq!(
@@ -663,6 +716,18 @@ fn interactive_mode_recovers_after_diagnostic() {
     );
 }
 
+#[test]
+fn interactive_mode_reports_errors_that_more_input_cannot_repair() {
+    let output = run(&["--interactive"], "(1 ]\n2\n");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"2\n");
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("E-EXPECTED-EXPRESSION")
+    );
+}
+
 #[test]
 fn interactive_mode_preserves_bindings() {
     let output = run(&["--interactive"], "answer is 42\nanswer\n");
@@ -670,6 +735,158 @@ fn interactive_mode_preserves_bindings() {
     assert_eq!(output.stdout, b"()\n42\n");
 }
 
+#[test]
+fn interactive_type_command_classifies_without_evaluating() {
+    let output = run(
+        &["--interactive"],
+        "answer is 42\nboom is fn (value : Int) -> Int\n  value / 0\n\n:type answer + 1\n:type (answer, \"x\")\n:type boom answer\n:type probe is 1\nprobe\n",
+    );
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"()\n()\nInt\n(Int, String)\nInt\n");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("E-EXPECTED-EXPRESSION"));
+    assert!(stderr.contains("E-UNBOUND-NAME"));
+}
+
+#[test]
+fn interactive_bindings_and_reset_commands_report_session_state() {
+    let output = run(
+        &["--interactive"],
+        "answer is 42\ndouble is fn (value : Int) -> Int\n  value * 2\n:bindings\n:reset\n:bindings\nanswer\n",
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "()\n()\nanswer : Int = 42\ndouble : Function\nsession reset\n"
+    );
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("E-UNBOUND-NAME")
+    );
+}
+
+#[test]
+fn interactive_help_command_reads_session_and_lang_documentation() {
+    let output = run(
+        &["--interactive"],
+        "### Double a number.\ndouble is fn (value : Int) -> Int\n  value * 2\n:help double\n:help Rational\n:help missing\n:help\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("double is fn (value : Int) -> Int\n\nDouble a number.\n"));
+    assert!(stdout.contains("lang Rational\n\nExact rational numbers"));
+    assert!(stdout.contains(":trace on|off"));
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("no documentation for `missing`")
+    );
+}
+
+#[test]
+fn interactive_load_and_save_commands_round_trip_a_session() {
+    let directory = scratch("load-save");
+    std::fs::create_dir_all(&directory).unwrap();
+    let library = directory.join("library.t");
+    let saved = directory.join("session.t");
+    std::fs::write(
+        &library,
+        "use language (\n  version is v0.1\n)\ndouble is fn (value : Int) -> Int\n  value * 2\n",
+    )
+    .unwrap();
+    let output = run(
+        &["--interactive"],
+        &format!(
+            "base is 20\n:load {}\nmissing\ndouble (base + 1)\n:save {}\n",
+            library.display(),
+            saved.display()
+        ),
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!("()\n()\n42\nsaved 3 inputs to {}\n", saved.display())
+    );
+    let replayed = run_file(&saved);
+    assert!(replayed.status.success());
+    assert_eq!(replayed.stdout, b"42\n");
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn interactive_save_normalizes_loaded_crlf_and_bom_sources() {
+    let directory = scratch("load-save-crlf");
+    std::fs::create_dir_all(&directory).unwrap();
+    let library = directory.join("library.t");
+    let saved = directory.join("session.t");
+    std::fs::write(
+        &library,
+        "\u{feff}use language (\r\n  version is v0.1\r\n)\r\ndouble is fn (value : Int) -> Int\r\n  value * 2\r\n",
+    )
+    .unwrap();
+    let output = run(
+        &["--interactive"],
+        &format!(
+            ":load {}\ndouble 21\n:save {}\n",
+            library.display(),
+            saved.display()
+        ),
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        std::fs::read_to_string(&saved).unwrap(),
+        "use language (\n  version is v0.1\n)\ndouble is fn (value : Int) -> Int\n  value * 2\ndouble 21\n"
+    );
+    let replayed = run_file(&saved);
+    assert!(replayed.status.success());
+    assert_eq!(replayed.stdout, b"42\n");
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn interactive_trace_command_toggles_semantic_trace_events() {
+    let output = run(&["--interactive"], "1\n:trace on\n2\n:trace off\n3\n");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"1\ntrace on\n2\ntrace off\n3\n");
+    let trace = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(trace.matches("\"event\":\"token.integer\"").count(), 1);
+    assert!(trace.contains("\"detail\":\"2\""));
+}
+
+#[test]
+fn interactive_history_persists_across_sessions() {
+    let history = scratch("history");
+    let _ = std::fs::remove_file(&history);
+    assert!(
+        interactive_with_history(&history, "answer is 42\n\n")
+            .status
+            .success()
+    );
+    let output = interactive_with_history(&history, "answer\n:history\n");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().lines().next(),
+        Some("error[E-UNBOUND-NAME]: name is not bound")
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "answer is 42\nanswer\n:history\n"
+    );
+    std::fs::remove_file(history).unwrap();
+}
+
+#[test]
+fn interactive_rejects_unknown_commands() {
+    let output = run(&["--interactive"], ":bogus\n:trace maybe\n:load\n1\n");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"1\n");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("unknown command `:bogus`"));
+    assert!(stderr.contains(":trace takes `on` or `off`"));
+    assert!(stderr.contains(":load requires an argument"));
+}
+
 #[test]
 fn test_mode_emits_stable_decisions() {
     let output = run(&["--test"], "42\n");
@@ -2150,6 +2367,64 @@ fn every_mode_executes_exact_numeric_absolute() {
     assert!(trace.contains("root.absolute(Rational)"));
 }
 
+#[test]
+fn every_mode_executes_approximate_numbers() {
+    let source = include_str!("../../../examples/language/approximate-numbers.t");
+    for arguments in [&[][..], &["--interactive"][..], &["--test"][..]] {
+        let output = run(arguments, source);
+        assert!(output.status.success());
+        assert!(output.stdout.ends_with(
+            b"(Binary64 ( Rational ( 6004799503160661, 18014398509481984 ) ), Binary64 -Infinity, 2, -4, false, Binary64 ( Rational ( 4203359652212463, 4503599627370496 ) ))\n"
+        ));
+    }
+    let trace = String::from_utf8(run(&["--test"], source).stderr).unwrap();
+    assert!(trace.contains("root./(Binary64,Binary64)"));
+    assert!(trace.contains("TOPAL-NUM-ZERO-DIRECTION-001"));
+    assert!(trace.contains("root.round-to-even(Rational,Binary64)"));
+    assert!(trace.contains("root.saturate(Binary64,Finite Binary64)"));
+    assert!(trace.contains("TOPAL-NUM-REPRODUCIBLE-SUM-001"));
+}
+
+#[test]
+fn every_mode_executes_quantities() {
+    let source = include_str!("../../../examples/language/quantities.t");
+    for arguments in [&[][..], &["--interactive"][..], &["--test"][..]] {
+        let output = run(arguments, source);
+        assert!(output.status.success());
+        assert!(
+            output
+                .stdout
+                .ends_with(b"(true, 2.5[kg], 100[delta Kelvin], 373.15[Kelvin], true)\n")
+        );
+    }
+    let trace = String::from_utf8(run(&["--test"], source).stderr).unwrap();
+    assert!(trace.contains("TOPAL-NUM-DIMENSION-001"));
+    assert!(trace.contains("TOPAL-NUM-QUANTITY-AFFINE-001"));
+    assert!(trace.contains("TOPAL-NUM-QUANTITY-CONVERT-001"));
+}
+
+#[test]
+fn every_mode_executes_sensitive_values_without_tracing_them() {
+    let source = include_str!("../../../examples/language/sensitive-values.t");
+    for arguments in [&[][..], &["--interactive"][..], &["--test"][..]] {
+        let output = run(arguments, source);
+        assert!(output.status.success());
+        assert!(output.stdout.ends_with(b"(true, false)\n"));
+    }
+    let trace = String::from_utf8(run(&["--test"], source).stderr).unwrap();
+    assert!(trace.contains("TOPAL-SENSITIVE-PROVENANCE-001"));
+    assert!(!trace.contains("correct horse"));
+
+    let leaked = format!(
+        "{}\npassword\n",
+        source.trim_end().rsplit_once('\n').unwrap().0
+    );
+    let output = run(&[], &leaked);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("E-SENSITIVE-OUTPUT"));
+}
+
 #[test]
 fn every_mode_executes_named_exact_numeric_negation() {
     let source = include_str!("../../../examples/language/exact-numeric-negate.t");
@@ -3232,6 +3507,17 @@ fn interactive_mode_accumulates_multiline_string() {
     assert!(output.stderr.is_empty());
 }
 
+#[test]
+fn interactive_mode_accumulates_an_open_brace_block() {
+    let output = run(
+        &["--interactive"],
+        "total is {\n  value is 20\n  value + 1\n}\ntotal\n",
+    );
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"()\n21\n");
+    assert!(output.stderr.is_empty());
+}
+
 #[test]
 fn string_trace_retains_complete_tagged_lexeme() {
     let output = run(&["--test"], "tag\"a \"quote\"\"tag\n");
@@ -0,0 +1,1092 @@
+//! Non-executing static analysis of one source unit.
+//!
+//! Name resolution, scalar classifier checks, effect-bound containment, and
+//! interface-capability evidence report the codes the interpreter reports for
+//! the same faults without evaluating any expression. The analysis is
+//! deliberately conservative: it accepts a name bound anywhere a deferred body
+//! could observe it, and judges a classifier only against a literal or an
+//! expression whose scalar classifier is inferred without evaluation.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use num_bigint::BigInt;
+use topal_semantics::{
+    CapabilityEvidence, CapabilitySet, DeclarationIdentity, EffectSet, QualifiedName,
+    StructuralType, TypeIdentity,
+};
+use topal_source::{Diagnostic, SourceText, Span};
+use topal_syntax::{CallableKind, DecisionMatcher, Expression, FunctionParameter, Statement};
+
+use crate::source::{diagnostic, is_builtin_name, parse_integer};
+
+/// The known effects of an effect-row bound `Effects (a, b)`, or `None` when
+/// the bound is not an effect row.
+pub(crate) fn effect_row(bound: &str) -> Option<EffectSet> {
+    let inner = bound
+        .trim()
+        .strip_prefix("Effects")?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')?
+        .trim();
+    if inner.is_empty() {
+        return Some(EffectSet::default());
+    }
+    inner
+        .split(',')
+        .map(|effect| {
+            let components = effect
+                .split_whitespace()
+                .map(str::to_owned)
+                .collect::<Vec<_>>();
+            (!components.is_empty()).then_some(QualifiedName(components))
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(EffectSet::from_effects)
+}
+
+/// Union the effect rows of every function `body` applies, as reported by
+/// `callee`. Names in `parameters` shadow functions.
+pub(crate) fn called_effects(
+    source: &SourceText,
+    body: &[Statement],
+    parameters: &[&str],
+    callee: &impl Fn(&str) -> Option<EffectSet>,
+) -> EffectSet {
+    let mut effects = EffectSet::default();
+    let mut visit = |expression: &Expression| {
+        if let Expression::Application { items, .. } = expression {
+            for item in items {
+                if let Expression::Identifier(span) = item {
+                    let name = source.slice(*span);
+                    if !parameters.contains(&name)
+                        && let Some(row) = callee(name)
+                    {
+                        effects = effects.union(&row);
+                    }
+                }
+            }
+        }
+    };
+    for statement in body {
+        visit_statement_expressions(statement, &mut visit);
+    }
+    effects
+}
+
+/// The diagnostic for a function whose body may perform effects outside its
+/// declared effect-row bound.
+pub(crate) fn effect_bound_diagnostic(
+    source: &SourceText,
+    name: &str,
+    bound: Span,
+    allowed: &EffectSet,
+    performed: &EffectSet,
+) -> Option<Diagnostic> {
+    let outside = performed
+        .iter()
+        .find(|effect| !allowed.contains_all(&EffectSet::from_effects([(*effect).clone()])))?;
+    Some(diagnostic(
+        source,
+        "E-EFFECT-BOUND",
+        bound,
+        format!(
+            "function `{name}` may perform `{}` outside its effect bound",
+            outside.0.join(" ")
+        ),
+    ))
+}
+
+fn visit_statement_expressions(statement: &Statement, visit: &mut impl FnMut(&Expression)) {
+    match statement {
+        Statement::Published { declaration, .. } => {
+            visit_statement_expressions(declaration, visit);
+        }
+        Statement::Binding { value, .. }
+        | Statement::ContextAssignment { value, .. }
+        | Statement::Discard { value, .. }
+        | Statement::Return { value, .. }
+        | Statement::Expression(value) => visit_expression(value, visit),
+        Statement::Implementation {
+            classifier,
+            declarations,
+            ..
+        } => {
+            visit_expression(classifier, visit);
+            for declaration in declarations {
+                visit_statement_expressions(declaration, visit);
+            }
+        }
+        Statement::Function { body, .. }
+        | Statement::Generator { body, .. }
+        | Statement::InterfaceImplementation {
+            declarations: body, ..
+        } => {
+            for statement in body {
+                visit_statement_expressions(statement, visit);
+            }
+        }
+        Statement::Foreach { source, body, .. } => {
+            visit_expression(source, visit);
+            for statement in body {
+                visit_statement_expressions(statement, visit);
+            }
+        }
+        Statement::LanguageSelection { .. }
+        | Statement::DiagnosticControl { .. }
+        | Statement::StateField { .. }
+        | Statement::Union { .. }
+        | Statement::Interface { .. } => {}
+    }
+}
+
+fn visit_expression(expression: &Expression, visit: &mut impl FnMut(&Expression)) {
+    visit(expression);
+    match expression {
+        Expression::Block { statements, .. } => {
+            for statement in statements {
+                visit_statement_expressions(statement, visit);
+            }
+        }
+        Expression::Product { fields, .. } => {
+            for field in fields {
+                visit_expression(&field.value, visit);
+            }
+        }
+        Expression::DecisionTable { subject, rules, .. } => {
+            visit_expression(subject, visit);
+            for rule in rules {
+                if let DecisionMatcher::Comparison { operand, .. } = &rule.matcher {
+                    visit_expression(operand, visit);
+                }
+                visit_expression(&rule.action, visit);
+            }
+        }
+        Expression::AnonymousFunction { body, .. } => visit_expression(body, visit),
+        Expression::Application { items, .. } => {
+            for item in items {
+                visit_expression(item, visit);
+            }
+        }
+        Expression::Unit(_)
+        | Expression::Boolean(_)
+        | Expression::Integer(_)
+        | Expression::Measured { .. }
+        | Expression::Rational(_)
+        | Expression::String(_)
+        | Expression::Identifier(_)
+        | Expression::ContextIdentifier(_)
+        | Expression::Discard(_)
+        | Expression::Callable { .. } => {}
+    }
+}
+
+/// Check `statements` as one source unit without executing it: names that no
+/// declaration binds, values outside a declared scalar classifier,
+/// functions whose calls exceed their declared effect-row bound, and
+/// interface implementations that are not coherent capability evidence.
+/// `predeclared` names are bound before the unit runs, such as the module
+/// namespaces of its package.
+#[must_use]
+pub fn static_diagnostics(
+    source: &SourceText,
+    statements: &[Statement],
+    predeclared: &BTreeSet<String>,
+) -> Vec<Diagnostic> {
+    let mut bound = BTreeMap::new();
+    for statement in statements {
+        count_bound_names(source, statement, &mut bound);
+    }
+    let mut analyzer = Analyzer {
+        source,
+        predeclared,
+        bound,
+        functions: BTreeMap::new(),
+        classifiers: BTreeMap::new(),
+        effects: BTreeMap::new(),
+        interfaces: BTreeMap::new(),
+        capabilities: CapabilitySet::default(),
+        diagnostics: Vec::new(),
+    };
+    analyzer.statements(statements, &mut BTreeSet::new());
+    analyzer.diagnostics
+}
+
+/// Count every name a declaration, parameter, or pattern binds anywhere in
+/// `statement`.
+fn count_bound_names(
+    source: &SourceText,
+    statement: &Statement,
+    bound: &mut BTreeMap<String, usize>,
+) {
+    let mut bind = |span: Span| *bound.entry(source.slice(span).to_owned()).or_default() += 1;
+    let mut nested = Vec::new();
+    let mut expressions = Vec::new();
+    match statement {
+        Statement::Published { declaration, .. } => nested.push(declaration.as_ref()),
+        Statement::Binding { name, value, .. } => {
+            bind(*name);
+            expressions.push(value);
+        }
+        Statement::StateField { name, .. } => bind(*name),
+        Statement::Implementation {
+            name,
+            classifier,
+            declarations,
+            ..
+        } => {
+            bind(*name);
+            expressions.push(classifier);
+            nested.extend(declarations);
+        }
+        Statement::Function {
+            name,
+            parameters,
+            body,
+            ..
+        }
+        | Statement::Generator {
+            name,
+            parameters,
+            body,
+            ..
+        } => {
+            bind(*name);
+            bind_parameters(parameters, &mut bind);
+            nested.extend(body);
+        }
+        Statement::Union {
+            name, alternatives, ..
+        } => {
+            bind(*name);
+            for alternative in alternatives {
+                bind(alternative.name);
+            }
+        }
+        Statement::Interface {
+            name, functions, ..
+        } => {
+            bind(*name);
+            for function in functions {
+                bind(function.name);
+            }
+        }
+        Statement::InterfaceImplementation { declarations, .. } => nested.extend(declarations),
+        Statement::Foreach {
+            result,
+            source,
+            binding,
+            body,
+            ..
+        } => {
+            if let Some((name, _)) = result {
+                bind(*name);
+            }
+            bind(*binding);
+            expressions.push(source);
+            nested.extend(body);
+        }
+        Statement::ContextAssignment { value, .. }
+        | Statement::Discard { value, .. }
+        | Statement::Return { value, .. }
+        | Statement::Expression(value) => expressions.push(value),
+        Statement::LanguageSelection { .. } | Statement::DiagnosticControl { .. } => {}
+    }
+    bind_expression_names(expressions, &mut bind, &mut nested);
+    for statement in nested {
+        count_bound_names(source, statement, bound);
+    }
+}
+
+/// Bind the names that patterns and anonymous-function parameters introduce
+/// in `expressions`, collecting nested block statements into `nested`.
+fn bind_expression_names<'a>(
+    mut expressions: Vec<&'a Expression>,
+    bind: &mut impl FnMut(Span),
+    nested: &mut Vec<&'a Statement>,
+) {
+    while let Some(expression) = expressions.pop() {
+        match expression {
+            Expression::Block { statements, .. } => nested.extend(statements),
+            Expression::Product { fields, .. } => {
+                expressions.extend(fields.iter().map(|field| &field.value));
+            }
+            Expression::DecisionTable { subject, rules, .. } => {
+                expressions.push(subject);
+                for rule in rules {
+                    for binding in matcher_bindings(&rule.matcher) {
+                        bind(binding);
+                    }
+                    if let DecisionMatcher::Comparison { operand, .. } = &rule.matcher {
+                        expressions.push(operand);
+                    }
+                    expressions.push(&rule.action);
+                }
+            }
+            Expression::AnonymousFunction {
+                parameters, body, ..
+            } => {
+                for parameter in parameters {
+                    bind(*parameter);
+                }
+                expressions.push(body);
+            }
+            Expression::Application { items, .. } => expressions.extend(items),
+            _ => {}
+        }
+    }
+}
+
+fn bind_parameters(parameters: &[FunctionParameter], bind: &mut impl FnMut(Span)) {
+    for parameter in parameters {
+        bind(parameter.name);
+        bind_parameters(&parameter.fields, bind);
+    }
+}
+
+fn matcher_bindings(matcher: &DecisionMatcher) -> Vec<Span> {
+    match matcher {
+        DecisionMatcher::Union { binding, .. }
+        | DecisionMatcher::Variant { binding, .. }
+        | DecisionMatcher::Result { binding, .. } => vec![*binding],
+        DecisionMatcher::Optional { binding, .. } => binding.iter().copied().collect(),
+        DecisionMatcher::ListEntry { first, rest, .. } => vec![*first, *rest],
+        DecisionMatcher::Boolean { .. }
+        | DecisionMatcher::Identifier(_)
+        | DecisionMatcher::ListEmpty(_)
+        | DecisionMatcher::ErrorCode { .. }
+        | DecisionMatcher::Comparison { .. }
+        | DecisionMatcher::Otherwise(_) => Vec::new(),
+    }
+}
+
+/// A function declared once in the unit, with the classifiers its arguments
+/// and result can be judged against.
+struct Signature {
+    parameters: Vec<(String, String)>,
+    result: String,
+}
+
+struct Analyzer<'a> {
+    source: &'a SourceText,
+    predeclared: &'a BTreeSet<String>,
+    /// How many declarations, parameters, and patterns bind each name.
+    bound: BTreeMap<String, usize>,
+    functions: BTreeMap<String, Signature>,
+    /// Declared or inferred classifiers of the names bound once in the unit,
+    /// which no other binding can shadow.
+    classifiers: BTreeMap<String, String>,
+    /// Effect rows of the functions declared so far, in declaration order.
+    effects: BTreeMap<String, EffectSet>,
+    /// Operation shapes of the interfaces declared so far.
+    interfaces: BTreeMap<String, OperationShapes>,
+    /// Evidence of the interface capabilities the unit implements.
+    capabilities: CapabilitySet,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Parameter classifiers and result classifier of each operation by name.
+type OperationShapes = BTreeMap<String, (Vec<String>, String)>;
+
+fn operation_shape(
+    source: &SourceText,
+    parameters: &[FunctionParameter],
+    result: Span,
+) -> (Vec<String>, String) {
+    (
+        parameters
+            .iter()
+            .map(|parameter| source.slice(parameter.classifier).to_owned())
+            .collect(),
+        source.slice(result).to_owned(),
+    )
+}
+
+impl Analyzer<'_> {
+    /// Check statements that run in order, extending `scope` with the names
+    /// they bind.
+    fn statements(&mut self, statements: &[Statement], scope: &mut BTreeSet<String>) {
+        for statement in statements {
+            self.statement(statement, scope);
+        }
+    }
+
+    #[allow(clippy::too_many_lines)] // Statement dispatch remains explicit and exhaustively typed.
+    fn statement(&mut self, statement: &Statement, scope: &mut BTreeSet<String>) {
+        match statement {
+            Statement::Published { declaration, .. } => self.statement(declaration, scope),
+            Statement::Binding {
+                name,
+                classifier,
+                value,
+            } => {
+                self.expression(value, scope);
+                let declared = classifier.map(|classifier| self.source.slice(classifier));
+                if let Some(classifier) = declared
+                    && self.outside(value, classifier)
+                {
+                    self.diagnostics.push(diagnostic(
+                        self.source,
+                        "E-BINDING-CLASSIFIER",
+                        value.span(),
+                        format!("initializer does not satisfy `{classifier}`"),
+                    ));
+                }
+                let name = self.source.slice(*name);
+                if self.bound.get(name) == Some(&1)
+                    && let Some(classifier) = declared
+                        .map(str::to_owned)
+                        .or_else(|| self.classifier(value))
+                {
+                    self.classifiers.insert(name.to_owned(), classifier);
+                }
+                scope.insert(name.to_owned());
+            }
+            Statement::Implementation {
+                name,
+                classifier,
+                declarations,
+                ..
+            } => {
+                self.expression(classifier, scope);
+                scope.insert(self.source.slice(*name).to_owned());
+                self.deferred(declarations);
+            }
+            Statement::Function {
+                name,
+                parameters,
+                result,
+                effect_bound,
+                body,
+                ..
+            } => {
+                let name = self.source.slice(*name);
+                scope.insert(name.to_owned());
+                self.function(
+                    name,
+                    parameters,
+                    self.source.slice(*result),
+                    *effect_bound,
+                    body,
+                );
+            }
+            Statement::Generator { name, body, .. } => {
+                scope.insert(self.source.slice(*name).to_owned());
+                self.deferred(body);
+            }
+            Statement::Union {
+                name, alternatives, ..
+            } => {
+                scope.insert(self.source.slice(*name).to_owned());
+                for alternative in alternatives {
+                    scope.insert(self.source.slice(alternative.name).to_owned());
+                }
+            }
+            Statement::Interface {
+                name, functions, ..
+            } => {
+                scope.insert(self.source.slice(*name).to_owned());
+                for function in functions {
+                    scope.insert(self.source.slice(function.name).to_owned());
+                }
+                let shapes = functions
+                    .iter()
+                    .map(|function| {
+                        (
+                            self.source.slice(function.name).to_owned(),
+                            operation_shape(self.source, &function.parameters, function.result),
+                        )
+                    })
+                    .collect();
+                self.interfaces
+                    .insert(self.source.slice(*name).to_owned(), shapes);
+            }
+            Statement::InterfaceImplementation {
+                interface,
+                declarations,
+                span,
+            } => {
+                self.implementation(*interface, declarations, *span);
+                self.deferred(declarations);
+            }
+            Statement::Foreach {
+                result,
+                source,
+                body,
+                ..
+            } => {
+                self.expression(source, scope);
+                self.deferred(body);
+                if let Some((name, _)) = result {
+                    scope.insert(self.source.slice(*name).to_owned());
+                }
+            }
+            Statement::ContextAssignment { value, .. }
+            | Statement::Discard { value, .. }
+            | Statement::Return { value, .. }
+            | Statement::Expression(value) => self.expression(value, scope),
+            Statement::LanguageSelection { .. }
+            | Statement::DiagnosticControl { .. }
+            | Statement::StateField { .. } => {}
+        }
+    }
+
+    /// Check an interface implementation as capability evidence for the unit:
+    /// its interface is declared earlier, its operations match the interface
+    /// shapes exactly, and it is the only evidence for that capability.
+    fn implementation(&mut self, interface: Span, declarations: &[Statement], span: Span) {
+        let name = self.source.slice(interface);
+        let Some(shapes) = self.interfaces.get(name) else {
+            if !self.predeclared.contains(name) {
+                self.diagnostics.push(diagnostic(
+                    self.source,
+                    "E-UNKNOWN-INTERFACE",
+                    interface,
+                    format!("`{name}` is not a declared interface"),
+                ));
+            }
+            return;
+        };
+        let operations = declarations
+            .iter()
+            .map(|declaration| match declaration {
+                Statement::Function {
+                    name,
+                    parameters,
+                    result,
+                    ..
+                } => Some((*name, parameters, *result)),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        let Some(operations) = operations else {
+            self.diagnostics.push(diagnostic(
+                self.source,
+                "E-INTERFACE-IMPLEMENTATION",
+                span,
+                "an interface implementation contains function declarations only",
+            ));
+            return;
+        };
+        let supplied = operations
+            .iter()
+            .map(|(operation, parameters, result)| {
+                (
+                    self.source.slice(*operation).to_owned(),
+                    operation_shape(self.source, parameters, *result),
+                )
+            })
+            .collect::<OperationShapes>();
+        if &supplied != shapes {
+            self.diagnostics.push(diagnostic(
+                self.source,
+                "E-INTERFACE-IMPLEMENTATION",
+                span,
+                "implementation operations must exactly match the interface shapes",
+            ));
+            return;
+        }
+        // An implementation supplies its interface for the whole unit, so
+        // every implementation is evidence about the same unit subject.
+        let evidence = CapabilityEvidence {
+            capability: QualifiedName(vec![name.to_owned()]),
+            subject: TypeIdentity::Structural(StructuralType::Tuple(Vec::new())),
+            roles: operations
+                .iter()
+                .map(|(operation, ..)| {
+                    let operation_name = self.source.slice(*operation).to_owned();
+                    let identity = DeclarationIdentity {
+                        module: String::new(),
+                        name: operation_name.clone(),
+                        ordinal: operation.start,
+                    };
+                    (operation_name, identity)
+                })
+                .collect(),
+        };
+        if self.capabilities.insert(evidence).is_err()
+            && let Some((operation, ..)) = operations.first()
+        {
+            self.diagnostics.push(diagnostic(
+                self.source,
+                "E-DUPLICATE-FUNCTION-OVERLOAD",
+                *operation,
+                "an overload with the same input classifiers and staticness already exists",
+            ));
+        }
+    }
+
+    /// Check a body that runs after the whole unit has been declared, such as
+    /// a function or handler body, against every name the unit binds.
+    fn deferred(&mut self, body: &[Statement]) {
+        let mut scope = self.bound.keys().cloned().collect();
+        let functions = std::mem::take(&mut self.functions);
+        let effects = self.effects.clone();
+        self.statements(body, &mut scope);
+        self.functions = functions;
+        self.effects = effects;
+    }
+
+    fn function(
+        &mut self,
+        name: &str,
+        parameters: &[FunctionParameter],
+        result: &str,
+        effect_bound: Option<Span>,
+        body: &[Statement],
+    ) {
+        let parameter_names = parameters
+            .iter()
+            .map(|parameter| self.source.slice(parameter.name))
+            .collect::<Vec<_>>();
+        let performed = called_effects(self.source, body, &parameter_names, &|callee| {
+            self.effects.get(callee).cloned()
+        });
+        let allowed = effect_bound.and_then(|bound| effect_row(self.source.slice(bound)));
+        if let (Some(bound), Some(allowed)) = (effect_bound, &allowed)
+            && let Some(error) =
+                effect_bound_diagnostic(self.source, name, bound, allowed, &performed)
+        {
+            self.diagnostics.push(error);
+        }
+        let row = allowed.unwrap_or(performed);
+        self.effects
+            .entry(name.to_owned())
+            .and_modify(|effects| *effects = effects.union(&row))
+            .or_insert(row);
+        if self.bound.get(name) == Some(&1)
+            && parameters
+                .iter()
+                .all(|parameter| parameter.fields.is_empty())
+        {
+            self.functions.insert(
+                name.to_owned(),
+                Signature {
+                    parameters: parameters
+                        .iter()
+                        .map(|parameter| {
+                            (
+                                self.source.slice(parameter.name).to_owned(),
+                                self.source.slice(parameter.classifier).to_owned(),
+                            )
+                        })
+                        .collect(),
+                    result: result.to_owned(),
+                },
+            );
+        }
+        for parameter in parameters.iter().filter(|parameter| {
+            parameter.fields.is_empty()
+                && self.bound.get(self.source.slice(parameter.name)) == Some(&1)
+        }) {
+            self.classifiers.insert(
+                self.source.slice(parameter.name).to_owned(),
+                self.source.slice(parameter.classifier).to_owned(),
+            );
+        }
+        if let Some(Statement::Expression(value)) = body.last()
+            && self.outside(value, result)
+        {
+            self.diagnostics.push(diagnostic(
+                self.source,
+                "E-FUNCTION-RESULT-TYPE",
+                value.span(),
+                format!("function `{name}` returned a value outside `{result}`"),
+            ));
+        }
+        self.deferred(body);
+    }
+
+    fn expression(&mut self, expression: &Expression, scope: &BTreeSet<String>) {
+        match expression {
+            Expression::Identifier(span) => self.reference(*span, scope),
+            Expression::Application { items, .. } => self.application(items, scope),
+            Expression::Block { statements, .. } => {
+                self.statements(statements, &mut scope.clone());
+            }
+            Expression::Product { fields, .. } => {
+                // A labeled field whose value is a bare name may be a symbol,
+                // such as a unit symbol or a task identity.
+                for field in fields.iter().filter(|field| {
+                    field.label.is_none() || !matches!(field.value, Expression::Identifier(_))
+                }) {
+                    self.expression(&field.value, scope);
+                }
+            }
+            Expression::DecisionTable { subject, rules, .. } => {
+                self.expression(subject, scope);
+                for rule in rules {
+                    if let DecisionMatcher::Comparison { operand, .. } = &rule.matcher {
+                        self.expression(operand, scope);
+                    }
+                    let mut local = scope.clone();
+                    local.extend(
+                        matcher_bindings(&rule.matcher)
+                            .into_iter()
+                            .map(|binding| self.source.slice(binding).to_owned()),
+                    );
+                    self.expression(&rule.action, &local);
+                }
+            }
+            Expression::AnonymousFunction {
+                parameters, body, ..
+            } => {
+                let mut local = scope.clone();
+                local.extend(
+                    parameters
+                        .iter()
+                        .map(|parameter| self.source.slice(*parameter).to_owned()),
+                );
+                self.expression(body, &local);
+            }
+            Expression::Unit(_)
+            | Expression::Boolean(_)
+            | Expression::Integer(_)
+            | Expression::Measured { .. }
+            | Expression::Rational(_)
+            | Expression::String(_)
+            | Expression::ContextIdentifier(_)
+            | Expression::Discard(_)
+            | Expression::Callable { .. } => {}
+        }
+    }
+
+    /// Check an application. Its head and any name following an operator are
+    /// references; any other identifier may name a handler, member, or
+    /// operation of the value before it, unless the head is a function of
+    /// this unit.
+    fn application(&mut self, items: &[Expression], scope: &BTreeSet<String>) {
+        let head = match items.first() {
+            Some(Expression::Identifier(span)) => Some(self.source.slice(*span)),
+            _ => None,
+        };
+        let call = head.and_then(|head| self.functions.get(head).map(|_| head));
+        for (index, item) in items.iter().enumerate() {
+            let Expression::Identifier(span) = item else {
+                self.expression(item, scope);
+                continue;
+            };
+            let operand = index
+                .checked_sub(1)
+                .is_some_and(|previous| matches!(items[previous], Expression::Callable { .. }));
+            if index == 0 || operand || call.is_some() {
+                self.reference(*span, scope);
+            }
+        }
+        if let Some(name) = call {
+            self.arguments(name, &items[1..]);
+        }
+    }
+
+    /// Report arguments outside the parameters of a function declared once in
+    /// this unit.
+    fn arguments(&mut self, name: &str, arguments: &[Expression]) {
+        let Some(signature) = self.functions.get(name) else {
+            return;
+        };
+        let values = match (arguments, signature.parameters.as_slice()) {
+            ([argument], [_]) => vec![grouped(argument)],
+            ([Expression::Product { fields, .. }], parameters)
+                if parameters.len() > 1
+                    && fields.len() == parameters.len()
+                    && fields.iter().all(|field| field.label.is_none()) =>
+            {
+                fields.iter().map(|field| &field.value).collect()
+            }
+            _ => return,
+        };
+        let error = values
+            .into_iter()
+            .zip(&signature.parameters)
+            .find(|(argument, (_, classifier))| self.outside(argument, classifier))
+            .map(|(argument, (parameter, classifier))| {
+                diagnostic(
+                    self.source,
+                    "E-FUNCTION-ARGUMENT-TYPE",
+                    argument.span(),
+                    format!("argument for `{parameter}` is outside `{classifier}`"),
+                )
+            });
+        self.diagnostics.extend(error);
+    }
+
+    /// Whether `expression` cannot satisfy the scalar `classifier`: it is a
+    /// literal outside it, or its inferred scalar classifier is disjoint from
+    /// it. Classifiers other than the scalar ones are never judged.
+    fn outside(&self, expression: &Expression, classifier: &str) -> bool {
+        literal_outside(self.source, expression, classifier)
+            || self.classifier(expression).is_some_and(|inferred| {
+                matches!(
+                    (scalar_family(&inferred), scalar_family(classifier)),
+                    (Some(inferred), Some(declared)) if inferred != declared
+                )
+            })
+    }
+
+    /// The inferred classifier of `expression`, from names bound once with a
+    /// declared or inferred classifier and functions declared once in this
+    /// unit.
+    fn classifier(&self, expression: &Expression) -> Option<String> {
+        inferred_classifier(
+            self.source,
+            expression,
+            &|name| self.classifiers.get(name).cloned(),
+            &|function| {
+                self.functions
+                    .get(function)
+                    .map(|signature| signature.result.clone())
+            },
+        )
+    }
+
+    fn reference(&mut self, span: Span, scope: &BTreeSet<String>) {
+        let name = self.source.slice(span);
+        if name.starts_with(|character: char| !character.is_lowercase())
+            || scope.contains(name)
+            || self.predeclared.contains(name)
+            || is_builtin_name(name)
+        {
+            return;
+        }
+        self.diagnostics.push(diagnostic(
+            self.source,
+            "E-UNBOUND-NAME",
+            span,
+            "name is not bound",
+        ));
+    }
+}
+
+/// The classifier of `expression` where no evaluation is needed: literals,
+/// grouped expressions, unlabeled tuples, names whose classifier `name`
+/// reports, whole calls of functions whose result classifier `result`
+/// reports, comparisons, and `Int` sums, differences, and products.
+pub(crate) fn inferred_classifier(
+    source: &SourceText,
+    expression: &Expression,
+    name: &impl Fn(&str) -> Option<String>,
+    result: &impl Fn(&str) -> Option<String>,
+) -> Option<String> {
+    let infer = |expression| inferred_classifier(source, expression, name, result);
+    match grouped(expression) {
+        Expression::Integer(_) => Some("Int".into()),
+        Expression::Rational(_) => Some("Rational".into()),
+        Expression::String(_) => Some("String".into()),
+        Expression::Boolean(_) => Some("Boolean".into()),
+        Expression::Unit(_) => Some("Unit".into()),
+        Expression::Identifier(span) => name(source.slice(*span)),
+        Expression::Product { fields, .. }
+            if fields.len() > 1 && fields.iter().all(|field| field.label.is_none()) =>
+        {
+            let classifiers = fields
+                .iter()
+                .map(|field| infer(&field.value))
+                .collect::<Option<Vec<_>>>()?;
+            Some(format!("({})", classifiers.join(", ")))
+        }
+        Expression::Application { items, .. } => match items.as_slice() {
+            [Expression::Identifier(head), _] => result(source.slice(*head)),
+            [
+                _,
+                Expression::Callable {
+                    kind:
+                        CallableKind::Equal
+                        | CallableKind::NotEqual
+                        | CallableKind::Less
+                        | CallableKind::Greater
+                        | CallableKind::LessEqual
+                        | CallableKind::GreaterEqual,
+                    ..
+                },
+                _,
+            ] => Some("Boolean".into()),
+            [first, rest @ ..] if !rest.is_empty() && rest.len() % 2 == 0 => {
+                let integral = |operand| {
+                    infer(operand)
+                        .is_some_and(|classifier| scalar_family(&classifier) == Some("Int"))
+                };
+                (integral(first)
+                    && rest.chunks(2).all(|pair| {
+                        matches!(
+                            pair[0],
+                            Expression::Callable {
+                                kind: CallableKind::Plus
+                                    | CallableKind::Minus
+                                    | CallableKind::Multiply,
+                                ..
+                            }
+                        ) && integral(&pair[1])
+                    }))
+                .then(|| "Int".into())
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The expression a single unlabeled field groups, or `expression` itself.
+fn grouped(expression: &Expression) -> &Expression {
+    match expression {
+        Expression::Product { fields, .. } if fields.len() == 1 && fields[0].label.is_none() => {
+            grouped(&fields[0].value)
+        }
+        expression => expression,
+    }
+}
+
+/// The scalar classifiers whose values are pairwise disjoint, with `Nat`
+/// judged as an `Int` since only evaluation tells them apart.
+fn scalar_family(classifier: &str) -> Option<&'static str> {
+    match classifier {
+        "Int" | "Nat" => Some("Int"),
+        "String" => Some("String"),
+        "Boolean" => Some("Boolean"),
+        "Unit" => Some("Unit"),
+        _ => None,
+    }
+}
+
+/// Whether `expression` is a literal that cannot satisfy the scalar
+/// `classifier`. Classifiers other than the scalar ones are never judged.
+fn literal_outside(source: &SourceText, expression: &Expression, classifier: &str) -> bool {
+    let satisfies = match expression {
+        Expression::Integer(span) => match classifier {
+            "Int" => true,
+            "Nat" => {
+                parse_integer(source.slice(*span)).is_some_and(|value| value >= BigInt::from(0))
+            }
+            "String" | "Boolean" | "Unit" => false,
+            _ => return false,
+        },
+        Expression::String(_) => !matches!(classifier, "Int" | "Nat" | "Boolean" | "Unit"),
+        Expression::Boolean(_) => !matches!(classifier, "Int" | "Nat" | "String" | "Unit"),
+        Expression::Unit(_) => !matches!(classifier, "Int" | "Nat" | "String" | "Boolean"),
+        _ => true,
+    };
+    !satisfies
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::Session;
+
+    const HEADER: &str = "use language ( version is v0.1 )\n";
+
+    fn analyze(text: &str) -> Vec<Diagnostic> {
+        let source = SourceText::new(text).unwrap();
+        let lexed = topal_syntax::lex(&source);
+        let parsed = topal_syntax::parse(&source, &lexed);
+        assert!(parsed.diagnostics.is_empty(), "{:?}", parsed.diagnostics);
+        static_diagnostics(&source, &parsed.statements, &BTreeSet::new())
+    }
+
+    #[test]
+    fn language_examples_have_no_static_findings() {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples/language");
+        let mut examples = std::fs::read_dir(directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "t"))
+            .collect::<Vec<_>>();
+        examples.sort();
+        assert_eq!(examples.len(), 195);
+        for example in examples {
+            let findings = analyze(&std::fs::read_to_string(&example).unwrap());
+            assert!(findings.is_empty(), "{}: {findings:?}", example.display());
+        }
+    }
+
+    #[test]
+    fn static_findings_use_the_interpreter_codes() {
+        for (program, code) in [
+            ("missing + 2", "E-UNBOUND-NAME"),
+            ("count : Nat is -1", "E-BINDING-CLASSIFIER"),
+            (
+                "twice is fn ( value : Int ) -> Int\n  value * 2\ntwice \"two\"",
+                "E-FUNCTION-ARGUMENT-TYPE",
+            ),
+            (
+                "label is fn () -> String\n  42\nlabel ()",
+                "E-FUNCTION-RESULT-TYPE",
+            ),
+            (
+                "size is fn ( value : String ) -> Int\n  1\n\
+                 greet is fn ( name : String ) -> String\n  name\ngreet (size \"x\")",
+                "E-FUNCTION-ARGUMENT-TYPE",
+            ),
+            (
+                "count : Int is 3\nlabel : String is count",
+                "E-BINDING-CLASSIFIER",
+            ),
+            (
+                "count is 3\nflag : Boolean is count",
+                "E-BINDING-CLASSIFIER",
+            ),
+            (
+                "label is fn ( value : Int ) -> String\n  value\nlabel 1",
+                "E-FUNCTION-RESULT-TYPE",
+            ),
+            (
+                "Missing\n  parse is fn ( source : String ) -> Boolean\n    source = \"ok\"",
+                "E-UNKNOWN-INTERFACE",
+            ),
+            (
+                "Parser is Interface\n  parse is fn ( source : String ) -> Boolean\n\
+                 Parser\n  parse is fn ( source : Int ) -> Boolean\n    source = 1",
+                "E-INTERFACE-IMPLEMENTATION",
+            ),
+            (
+                "Parser is Interface\n  parse is fn ( source : String ) -> Boolean\n\
+                 Parser\n  parse is fn ( source : String ) -> Boolean\n    source = \"ok\"\n\
+                 Parser\n  parse is fn ( source : String ) -> Boolean\n    source = \"no\"",
+                "E-DUPLICATE-FUNCTION-OVERLOAD",
+            ),
+        ] {
+            let text = format!("{HEADER}{program}");
+            let error = Session::new()
+                .evaluate_source_file(&text, &mut Vec::new())
+                .unwrap_err();
+            assert_eq!(error.code, code, "{program}");
+            let findings = analyze(&text);
+            assert_eq!(
+                findings
+                    .iter()
+                    .map(|finding| finding.code.as_str())
+                    .collect::<Vec<_>>(),
+                [code],
+                "{program}"
+            );
+            assert_eq!(
+                (findings[0].line, findings[0].column),
+                (error.line, error.column)
+            );
+        }
+    }
+
+    #[test]
+    fn effect_bounds_contain_the_rows_of_called_functions() {
+        let text = format!(
+            "{HEADER}log is fn ( value : Int ) -> Int\n  : Effects (console write)\n  value\n\
+             pure is fn ( value : Int ) -> Int\n  : Effects ()\n  log value\npure 1"
+        );
+        let findings = analyze(&text);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].code, "E-EFFECT-BOUND");
+        assert_eq!(
+            &*findings[0].message,
+            "function `pure` may perform `console write` outside its effect bound"
+        );
+        let error = Session::new()
+            .evaluate_source_file(&text, &mut Vec::new())
+            .unwrap_err();
+        assert_eq!(
+            (error.code, error.line),
+            ("E-EFFECT-BOUND".to_owned(), findings[0].line)
+        );
+        assert!(analyze(&text.replace(": Effects ()", ": Effects (console write)")).is_empty());
+    }
+}
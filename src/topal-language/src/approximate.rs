@@ -0,0 +1,737 @@
+//! Binary approximate numbers, their explicit rounding functions, and exact
+//! reproducible summation.
+//!
+//! Finite values are stored as the exact dyadic rationals they denote, so every
+//! operation rounds its exact result once under a named policy and the stored
+//! value never depends on host floating-point behavior.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use num_bigint::{BigInt, Sign};
+use num_rational::BigRational;
+
+/// A named radix-2 approximate format with gradual subnormals.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ApproximateFormat {
+    pub name: &'static str,
+    /// Significand precision in binary digits, including the leading digit.
+    pub precision: u32,
+    pub minimum_normal_exponent: i64,
+    pub maximum_exponent: i64,
+}
+
+pub const BINARY32: ApproximateFormat = ApproximateFormat {
+    name: "Binary32",
+    precision: 24,
+    minimum_normal_exponent: -126,
+    maximum_exponent: 127,
+};
+
+pub const BINARY64: ApproximateFormat = ApproximateFormat {
+    name: "Binary64",
+    precision: 53,
+    minimum_normal_exponent: -1022,
+    maximum_exponent: 1023,
+};
+
+impl ApproximateFormat {
+    #[must_use]
+    pub fn named(name: &str) -> Option<Self> {
+        [BINARY32, BINARY64]
+            .into_iter()
+            .find(|format| format.name == name)
+    }
+
+    /// The greatest finite magnitude, `(2^p - 1) * 2^(emax - p + 1)`.
+    #[must_use]
+    pub fn largest_finite(self) -> BigRational {
+        let significand = (BigInt::from(1) << self.precision) - 1;
+        BigRational::from_integer(significand)
+            * power_of_two(self.maximum_exponent - i64::from(self.precision) + 1)
+    }
+
+    /// Round an exact value into this format.
+    #[must_use]
+    pub fn round(self, value: &BigRational, rounding: Rounding) -> (ApproximateNumber, Outcome) {
+        if value.numer().sign() == Sign::NoSign {
+            return (
+                ApproximateNumber::Zero(ZeroDirection::Exact),
+                Outcome::Exact,
+            );
+        }
+        let negative = value.numer().sign() == Sign::Minus;
+        let magnitude = if negative { -value } else { value.clone() };
+        let exponent = floor_log2(&magnitude).max(self.minimum_normal_exponent);
+        let quantum = exponent - i64::from(self.precision) + 1;
+        let scaled = &magnitude * power_of_two(-quantum);
+        let truncated = scaled.floor().to_integer();
+        let remainder = scaled - BigRational::from_integer(truncated.clone());
+        let exact = remainder.numer().sign() == Sign::NoSign;
+        let significand = if rounding.rounds_away(negative, &truncated, &remainder) {
+            truncated + 1
+        } else {
+            truncated
+        };
+        let rounded = BigRational::from_integer(significand) * power_of_two(quantum);
+        if rounded > self.largest_finite() {
+            let number = if rounding.overflows_to_infinity(negative) {
+                ApproximateNumber::infinity(negative)
+            } else {
+                ApproximateNumber::Finite(signed(self.largest_finite(), negative))
+            };
+            return (number, Outcome::Overflow);
+        }
+        if rounded.numer().sign() == Sign::NoSign {
+            return (
+                ApproximateNumber::Zero(ZeroDirection::of(negative)),
+                Outcome::Underflow,
+            );
+        }
+        let outcome = if exact {
+            Outcome::Exact
+        } else {
+            Outcome::Rounded
+        };
+        (
+            ApproximateNumber::Finite(signed(rounded, negative)),
+            outcome,
+        )
+    }
+
+    /// Return the value exactly when this format represents it.
+    #[must_use]
+    pub fn exact(self, value: &BigRational) -> Option<ApproximateNumber> {
+        match self.round(value, Rounding::TiesToEven) {
+            (number, Outcome::Exact) => Some(number),
+            _ => None,
+        }
+    }
+}
+
+/// Evidence of the side from which a calculation reached zero. It never takes
+/// part in equality or ordering.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ZeroDirection {
+    FromBelow,
+    Exact,
+    FromAbove,
+}
+
+impl ZeroDirection {
+    const fn of(negative: bool) -> Self {
+        if negative {
+            Self::FromBelow
+        } else {
+            Self::FromAbove
+        }
+    }
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::FromBelow => "FromBelow",
+            Self::Exact => "Exact",
+            Self::FromAbove => "FromAbove",
+        }
+    }
+
+    const fn negate(self) -> Self {
+        match self {
+            Self::FromBelow => Self::FromAbove,
+            Self::Exact => Self::Exact,
+            Self::FromAbove => Self::FromBelow,
+        }
+    }
+
+    /// Direction of a product or quotient of two directional quantities.
+    const fn combine(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Exact, _) | (_, Self::Exact) => Self::Exact,
+            (left, right) => Self::of(!matches!(
+                (left, right),
+                (Self::FromBelow, Self::FromBelow) | (Self::FromAbove, Self::FromAbove)
+            )),
+        }
+    }
+}
+
+/// One semantic approximate value. There is no NaN: operations that would
+/// produce one report `indeterminate` instead.
+#[derive(Clone, Debug, Eq)]
+pub enum ApproximateNumber {
+    Zero(ZeroDirection),
+    Finite(BigRational),
+    NegativeInfinity,
+    PositiveInfinity,
+}
+
+impl PartialEq for ApproximateNumber {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl PartialOrd for ApproximateNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ApproximateNumber {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::NegativeInfinity, Self::NegativeInfinity)
+            | (Self::PositiveInfinity, Self::PositiveInfinity) => Ordering::Equal,
+            (Self::NegativeInfinity, _) | (_, Self::PositiveInfinity) => Ordering::Less,
+            (_, Self::NegativeInfinity) | (Self::PositiveInfinity, _) => Ordering::Greater,
+            (left, right) => left.finite_value().cmp(&right.finite_value()),
+        }
+    }
+}
+
+impl ApproximateNumber {
+    const fn infinity(negative: bool) -> Self {
+        if negative {
+            Self::NegativeInfinity
+        } else {
+            Self::PositiveInfinity
+        }
+    }
+
+    /// The exact value of a zero or finite number; `None` for an infinity.
+    #[must_use]
+    pub fn exact_value(&self) -> Option<BigRational> {
+        match self {
+            Self::Zero(_) | Self::Finite(_) => Some(self.finite_value()),
+            Self::NegativeInfinity | Self::PositiveInfinity => None,
+        }
+    }
+
+    fn finite_value(&self) -> BigRational {
+        match self {
+            Self::Finite(value) => value.clone(),
+            _ => BigRational::from_integer(BigInt::from(0)),
+        }
+    }
+
+    #[must_use]
+    pub const fn is_infinite(&self) -> bool {
+        matches!(self, Self::NegativeInfinity | Self::PositiveInfinity)
+    }
+
+    /// The sign a zero or infinity contributes to products and quotients.
+    fn direction(&self) -> ZeroDirection {
+        match self {
+            Self::Zero(direction) => *direction,
+            Self::Finite(value) => ZeroDirection::of(value.numer().sign() == Sign::Minus),
+            Self::NegativeInfinity => ZeroDirection::FromBelow,
+            Self::PositiveInfinity => ZeroDirection::FromAbove,
+        }
+    }
+
+    #[must_use]
+    pub fn negate(&self) -> Self {
+        match self {
+            Self::Zero(direction) => Self::Zero(direction.negate()),
+            Self::Finite(value) => Self::Finite(-value),
+            Self::NegativeInfinity => Self::PositiveInfinity,
+            Self::PositiveInfinity => Self::NegativeInfinity,
+        }
+    }
+
+    #[must_use]
+    pub fn absolute(&self) -> Self {
+        if self.direction() == ZeroDirection::FromBelow {
+            self.negate()
+        } else {
+            self.clone()
+        }
+    }
+}
+
+/// A value of one approximate format.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ApproximateValue {
+    pub format: ApproximateFormat,
+    pub number: ApproximateNumber,
+}
+
+impl ApproximateValue {
+    /// Whether this value satisfies `classifier`, which is its format name or
+    /// the `Finite` constraint over that format.
+    #[must_use]
+    pub fn has_classifier(&self, classifier: &str) -> bool {
+        classifier == self.format.name
+            || (classifier.strip_prefix("Finite ") == Some(self.format.name)
+                && !self.number.is_infinite())
+    }
+}
+
+impl fmt::Display for ApproximateValue {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.format.name;
+        match &self.number {
+            ApproximateNumber::Zero(ZeroDirection::FromBelow) => write!(formatter, "{name} -0"),
+            ApproximateNumber::Zero(_) => write!(formatter, "{name} 0"),
+            ApproximateNumber::Finite(value) if value.is_integer() => {
+                write!(formatter, "{name} {}", value.numer())
+            }
+            ApproximateNumber::Finite(value) => write!(
+                formatter,
+                "{name} ( Rational ( {}, {} ) )",
+                value.numer(),
+                value.denom()
+            ),
+            ApproximateNumber::NegativeInfinity => write!(formatter, "{name} -Infinity"),
+            ApproximateNumber::PositiveInfinity => write!(formatter, "{name} +Infinity"),
+        }
+    }
+}
+
+/// How a rounded result relates to its exact input.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Exact,
+    Rounded,
+    Overflow,
+    Underflow,
+}
+
+impl Outcome {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Exact => "exact",
+            Self::Rounded => "rounded",
+            Self::Overflow => "overflow",
+            Self::Underflow => "underflow",
+        }
+    }
+}
+
+/// The named ways to select a representable replacement value. The first six
+/// choose the nearest candidate and differ only at ties; the last three are
+/// directed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Rounding {
+    TiesToZero,
+    TiesFromZero,
+    TiesToEven,
+    TiesToOdd,
+    TiesToPositive,
+    TiesToNegative,
+    TowardZero,
+    TowardNegative,
+    TowardPositive,
+}
+
+impl Rounding {
+    const ALL: [Self; 9] = [
+        Self::TiesToZero,
+        Self::TiesFromZero,
+        Self::TiesToEven,
+        Self::TiesToOdd,
+        Self::TiesToPositive,
+        Self::TiesToNegative,
+        Self::TowardZero,
+        Self::TowardNegative,
+        Self::TowardPositive,
+    ];
+
+    /// Resolve the source name of a lossy function such as `round-to-even`.
+    #[must_use]
+    pub fn named(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|rounding| rounding.as_str() == name)
+    }
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::TiesToZero => "round-to-zero",
+            Self::TiesFromZero => "round-from-zero",
+            Self::TiesToEven => "round-to-even",
+            Self::TiesToOdd => "round-to-odd",
+            Self::TiesToPositive => "round-to-pos",
+            Self::TiesToNegative => "round-to-neg",
+            Self::TowardZero => "truncate",
+            Self::TowardNegative => "floor",
+            Self::TowardPositive => "ceiling",
+        }
+    }
+
+    /// Whether this is `truncate`, `floor`, or `ceiling` rather than a
+    /// nearest-value function.
+    #[must_use]
+    pub const fn is_directed(self) -> bool {
+        matches!(
+            self,
+            Self::TowardZero | Self::TowardNegative | Self::TowardPositive
+        )
+    }
+
+    /// Whether a magnitude `truncated + remainder`, with `0 <= remainder < 1`,
+    /// rounds to `truncated + 1` rather than `truncated`.
+    fn rounds_away(self, negative: bool, truncated: &BigInt, remainder: &BigRational) -> bool {
+        if remainder.numer().sign() == Sign::NoSign {
+            return false;
+        }
+        let half = BigRational::new(BigInt::from(1), BigInt::from(2));
+        let odd = truncated.bit(0);
+        match self {
+            Self::TowardZero => false,
+            Self::TowardNegative => negative,
+            Self::TowardPositive => !negative,
+            _ if *remainder != half => *remainder > half,
+            Self::TiesToZero => false,
+            Self::TiesFromZero => true,
+            Self::TiesToEven => odd,
+            Self::TiesToOdd => !odd,
+            Self::TiesToPositive => !negative,
+            Self::TiesToNegative => negative,
+        }
+    }
+
+    /// IEEE overflow: nearest rounding and rounding away from zero reach the
+    /// infinity; rounding toward zero stops at the largest finite value.
+    const fn overflows_to_infinity(self, negative: bool) -> bool {
+        match self {
+            Self::TowardZero => false,
+            Self::TowardNegative => negative,
+            Self::TowardPositive => !negative,
+            _ => true,
+        }
+    }
+
+    /// Round an exact value to an integer.
+    #[must_use]
+    pub fn to_integer(self, value: &BigRational) -> BigInt {
+        let negative = value.numer().sign() == Sign::Minus;
+        let magnitude = if negative { -value } else { value.clone() };
+        let truncated = magnitude.floor().to_integer();
+        let remainder = magnitude - BigRational::from_integer(truncated.clone());
+        let rounded = if self.rounds_away(negative, &truncated, &remainder) {
+            truncated + 1
+        } else {
+            truncated
+        };
+        if negative { -rounded } else { rounded }
+    }
+}
+
+/// An approximate operation with no unambiguous result.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ArithmeticError {
+    Indeterminate,
+    DivisionByZero,
+}
+
+impl ArithmeticError {
+    #[must_use]
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::Indeterminate => "indeterminate",
+            Self::DivisionByZero => "division-by-zero",
+        }
+    }
+}
+
+/// The arithmetic operations an approximate format defines.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operation {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+/// Apply one operation and round its exact result to even in `format`.
+///
+/// # Errors
+///
+/// Returns the arithmetic error code for indeterminate forms and for division
+/// by a zero without directional evidence.
+pub fn apply(
+    format: ApproximateFormat,
+    operation: Operation,
+    left: &ApproximateNumber,
+    right: &ApproximateNumber,
+) -> Result<(ApproximateNumber, Outcome), ArithmeticError> {
+    use ApproximateNumber::{Finite, Zero};
+    let exact = |number| Ok((number, Outcome::Exact));
+    match operation {
+        Operation::Subtract => apply(format, Operation::Add, left, &right.negate()),
+        Operation::Add => match (left, right) {
+            (Zero(left), Zero(right)) => exact(Zero(if left == right {
+                *left
+            } else {
+                ZeroDirection::Exact
+            })),
+            (Zero(_), other) | (other, Zero(_)) => exact(other.clone()),
+            (left, right) if left.is_infinite() || right.is_infinite() => {
+                if left.is_infinite() && right.is_infinite() && left != right {
+                    Err(ArithmeticError::Indeterminate)
+                } else if left.is_infinite() {
+                    exact(left.clone())
+                } else {
+                    exact(right.clone())
+                }
+            }
+            (Finite(left), Finite(right)) => {
+                Ok(format.round(&(left + right), Rounding::TiesToEven))
+            }
+            _ => unreachable!("every approximate addition form is handled"),
+        },
+        Operation::Multiply => {
+            let direction = left.direction().combine(right.direction());
+            match (left, right) {
+                (Zero(_), other) | (other, Zero(_)) if other.is_infinite() => {
+                    Err(ArithmeticError::Indeterminate)
+                }
+                (Zero(_), _) | (_, Zero(_)) => exact(Zero(direction)),
+                (Finite(left), Finite(right)) => {
+                    Ok(format.round(&(left * right), Rounding::TiesToEven))
+                }
+                _ => exact(ApproximateNumber::infinity(
+                    direction == ZeroDirection::FromBelow,
+                )),
+            }
+        }
+        Operation::Divide => {
+            let direction = left.direction().combine(right.direction());
+            match (left, right) {
+                (Zero(_), Zero(_)) => Err(ArithmeticError::Indeterminate),
+                (left, right) if left.is_infinite() && right.is_infinite() => {
+                    Err(ArithmeticError::Indeterminate)
+                }
+                (_, Zero(ZeroDirection::Exact)) => Err(ArithmeticError::DivisionByZero),
+                (_, Zero(_)) => exact(ApproximateNumber::infinity(
+                    direction == ZeroDirection::FromBelow,
+                )),
+                (Zero(_), _) => exact(Zero(direction)),
+                (Finite(_), _) if right.is_infinite() => exact(Zero(direction)),
+                (Finite(left), Finite(right)) => {
+                    Ok(format.round(&(left / right), Rounding::TiesToEven))
+                }
+                _ => exact(ApproximateNumber::infinity(
+                    direction == ZeroDirection::FromBelow,
+                )),
+            }
+        }
+    }
+}
+
+/// Accumulate every entry exactly and round once, so the result is independent
+/// of entry order.
+///
+/// # Errors
+///
+/// Returns `indeterminate` when the entries contain both infinities.
+pub fn exact_sum(
+    format: ApproximateFormat,
+    entries: &[ApproximateNumber],
+    rounding: Rounding,
+) -> Result<(ApproximateNumber, Outcome), ArithmeticError> {
+    let negative = entries.contains(&ApproximateNumber::NegativeInfinity);
+    let positive = entries.contains(&ApproximateNumber::PositiveInfinity);
+    match (negative, positive) {
+        (true, true) => return Err(ArithmeticError::Indeterminate),
+        (true, false) => return Ok((ApproximateNumber::NegativeInfinity, Outcome::Exact)),
+        (false, true) => return Ok((ApproximateNumber::PositiveInfinity, Outcome::Exact)),
+        (false, false) => {}
+    }
+    let total = entries.iter().map(ApproximateNumber::finite_value).fold(
+        BigRational::from_integer(BigInt::from(0)),
+        |total, entry| total + entry,
+    );
+    if total.numer().sign() != Sign::NoSign {
+        return Ok(format.round(&total, rounding));
+    }
+    // An exact zero sum keeps directional evidence only when every entry is a
+    // zero reached from the same side.
+    let mut directions = entries.iter().map(|entry| match entry {
+        ApproximateNumber::Zero(direction) => Some(*direction),
+        _ => None,
+    });
+    let first = directions.next().flatten().unwrap_or(ZeroDirection::Exact);
+    let direction = if directions.all(|direction| direction == Some(first)) {
+        first
+    } else {
+        ZeroDirection::Exact
+    };
+    Ok((ApproximateNumber::Zero(direction), Outcome::Exact))
+}
+
+fn power_of_two(exponent: i64) -> BigRational {
+    let magnitude = BigInt::from(1) << exponent.unsigned_abs();
+    if exponent < 0 {
+        BigRational::new(BigInt::from(1), magnitude)
+    } else {
+        BigRational::from_integer(magnitude)
+    }
+}
+
+/// `floor(log2(value))` for a positive rational.
+fn floor_log2(value: &BigRational) -> i64 {
+    let estimate = i64::try_from(value.numer().bits()).unwrap_or(i64::MAX)
+        - i64::try_from(value.denom().bits()).unwrap_or(i64::MAX);
+    if *value >= power_of_two(estimate) {
+        estimate
+    } else {
+        estimate - 1
+    }
+}
+
+fn signed(value: BigRational, negative: bool) -> BigRational {
+    if negative { -value } else { value }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rational(numerator: i64, denominator: i64) -> BigRational {
+        BigRational::new(BigInt::from(numerator), BigInt::from(denominator))
+    }
+
+    fn finite(numerator: i64, denominator: i64) -> ApproximateNumber {
+        ApproximateNumber::Finite(rational(numerator, denominator))
+    }
+
+    #[test]
+    fn constructs_only_exactly_representable_values() {
+        assert_eq!(BINARY64.exact(&rational(1, 2)), Some(finite(1, 2)));
+        assert_eq!(BINARY64.exact(&rational(1, 10)), None);
+        assert_eq!(
+            BINARY32.exact(&rational((1 << 24) + 1, 1)),
+            None,
+            "Binary32 has 24 significand digits"
+        );
+        assert!(BINARY64.exact(&rational((1 << 53) - 1, 1)).is_some());
+        assert!(BINARY64.exact(&power_of_two(-1074)).is_some());
+        assert_eq!(BINARY64.exact(&power_of_two(-1075)), None);
+    }
+
+    #[test]
+    fn nearest_rounding_functions_differ_only_at_ties() {
+        let cases = [
+            (Rounding::TiesToZero, 2, -2),
+            (Rounding::TiesFromZero, 3, -3),
+            (Rounding::TiesToEven, 2, -2),
+            (Rounding::TiesToOdd, 3, -3),
+            (Rounding::TiesToPositive, 3, -2),
+            (Rounding::TiesToNegative, 2, -3),
+        ];
+        for (rounding, positive, negative) in cases {
+            assert_eq!(rounding.to_integer(&rational(5, 2)), BigInt::from(positive));
+            assert_eq!(
+                rounding.to_integer(&rational(-5, 2)),
+                BigInt::from(negative)
+            );
+            assert_eq!(rounding.to_integer(&rational(13, 5)), BigInt::from(3));
+        }
+        assert_eq!(
+            Rounding::TowardZero.to_integer(&rational(-39, 10)),
+            BigInt::from(-3)
+        );
+        assert_eq!(
+            Rounding::TowardNegative.to_integer(&rational(-39, 10)),
+            BigInt::from(-4)
+        );
+        assert_eq!(
+            Rounding::TowardPositive.to_integer(&rational(-39, 10)),
+            BigInt::from(-3)
+        );
+    }
+
+    #[test]
+    fn overflow_and_underflow_follow_the_rounding_direction() {
+        let huge = BINARY32.largest_finite() * rational(2, 1);
+        assert_eq!(
+            BINARY32.round(&huge, Rounding::TiesToEven),
+            (ApproximateNumber::PositiveInfinity, Outcome::Overflow)
+        );
+        assert_eq!(
+            BINARY32.round(&huge, Rounding::TowardZero),
+            (
+                ApproximateNumber::Finite(BINARY32.largest_finite()),
+                Outcome::Overflow
+            )
+        );
+        let tiny = -power_of_two(-200);
+        let (zero, outcome) = BINARY32.round(&tiny, Rounding::TiesToEven);
+        assert_eq!(outcome, Outcome::Underflow);
+        assert!(matches!(
+            zero,
+            ApproximateNumber::Zero(ZeroDirection::FromBelow)
+        ));
+    }
+
+    #[test]
+    fn directional_zero_selects_the_infinity_of_a_quotient() {
+        let one = finite(1, 1);
+        let below = ApproximateNumber::Zero(ZeroDirection::FromBelow);
+        let above = ApproximateNumber::Zero(ZeroDirection::FromAbove);
+        let exact = ApproximateNumber::Zero(ZeroDirection::Exact);
+        let divide = |left: &ApproximateNumber, right: &ApproximateNumber| {
+            apply(BINARY64, Operation::Divide, left, right).map(|(number, _)| number)
+        };
+        assert_eq!(
+            divide(&one, &above),
+            Ok(ApproximateNumber::PositiveInfinity)
+        );
+        assert_eq!(
+            divide(&one, &below),
+            Ok(ApproximateNumber::NegativeInfinity)
+        );
+        assert_eq!(
+            divide(&one.negate(), &below),
+            Ok(ApproximateNumber::PositiveInfinity)
+        );
+        assert_eq!(divide(&one, &exact), Err(ArithmeticError::DivisionByZero));
+        assert_eq!(divide(&exact, &exact), Err(ArithmeticError::Indeterminate));
+        assert_eq!(below, above, "direction is evidence, not part of equality");
+    }
+
+    #[test]
+    fn infinities_have_no_indeterminate_results() {
+        let positive = ApproximateNumber::PositiveInfinity;
+        let negative = ApproximateNumber::NegativeInfinity;
+        let zero = ApproximateNumber::Zero(ZeroDirection::Exact);
+        assert_eq!(
+            apply(BINARY64, Operation::Add, &positive, &negative),
+            Err(ArithmeticError::Indeterminate)
+        );
+        assert_eq!(
+            apply(BINARY64, Operation::Multiply, &zero, &positive),
+            Err(ArithmeticError::Indeterminate)
+        );
+        assert_eq!(
+            apply(BINARY64, Operation::Multiply, &finite(-2, 1), &positive),
+            Ok((negative, Outcome::Exact))
+        );
+        assert!(finite(-1, 1) > ApproximateNumber::NegativeInfinity);
+    }
+
+    #[test]
+    fn exact_sum_is_independent_of_entry_order() {
+        let large = ApproximateNumber::Finite(power_of_two(60));
+        let entries = [large.clone(), finite(1, 1), large.negate()];
+        let (ordered, _) = entries[1..]
+            .iter()
+            .fold((entries[0].clone(), Outcome::Exact), |(total, _), entry| {
+                apply(BINARY64, Operation::Add, &total, entry).unwrap()
+            });
+        assert_eq!(ordered, ApproximateNumber::Zero(ZeroDirection::Exact));
+        let mut reversed = entries.clone();
+        reversed.reverse();
+        for entries in [entries, reversed] {
+            assert_eq!(
+                exact_sum(BINARY64, &entries, Rounding::TiesToEven),
+                Ok((finite(1, 1), Outcome::Exact))
+            );
+        }
+    }
+}
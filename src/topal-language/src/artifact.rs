@@ -0,0 +1,621 @@
+//! Reference execution of validated GEIR modules.
+//!
+//! The executor gives exported artifacts the observable meaning of the source
+//! interpreter: it runs SSA blocks directly and records the same function,
+//! decision, and generator-suspension trace events, so a compiled module can be
+//! differential-tested against `Session::evaluate_source_file`.
+
+use std::fmt;
+
+use num_bigint::BigInt;
+use topal_geir::{Instruction, Module, Terminator, Type, ValidatedModule};
+use topal_source::{Diagnostic, character_count};
+
+use crate::source::display_string;
+use crate::{TraceEvent, TraceSink};
+
+/// Stable diagnostic for an artifact that cannot continue executing.
+pub const ARTIFACT_EXECUTION_ERROR_CODE: &str = "E-GEIR-EXECUTION";
+
+const FUNCTION_RULE: &str = "TOPAL-FUNCTION-ORDINARY-001";
+
+/// One runtime value of a GEIR artifact.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ArtifactValue {
+    Boolean(bool),
+    Int(BigInt),
+    String(String),
+    Alternative { index: usize, label: String },
+    Product(Vec<Self>),
+    Record(Vec<(String, Self)>),
+    Capability(usize),
+    Opaque { result_type: usize, bytes: Vec<u8> },
+}
+
+impl fmt::Display for ArtifactValue {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Boolean(value) => value.fmt(formatter),
+            Self::Int(value) => value.fmt(formatter),
+            Self::String(value) => formatter.write_str(&display_string(value)),
+            Self::Alternative { label, .. } => formatter.write_str(label),
+            Self::Product(items) => {
+                formatter.write_str("(")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        formatter.write_str(", ")?;
+                    }
+                    item.fmt(formatter)?;
+                }
+                formatter.write_str(")")
+            }
+            Self::Record(fields) => {
+                formatter.write_str("(")?;
+                for (index, (label, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        formatter.write_str(", ")?;
+                    }
+                    write!(formatter, "{label} is {value}")?;
+                }
+                formatter.write_str(")")
+            }
+            Self::Capability(evidence) => write!(formatter, "<capability {evidence}>"),
+            Self::Opaque { bytes, .. } => write!(formatter, "<{} bytes>", bytes.len()),
+        }
+    }
+}
+
+/// Control returned to the host by a running artifact function.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ArtifactStep {
+    Returned(ArtifactValue),
+    Yielded(ArtifactValue),
+    Suspended,
+}
+
+struct Frame {
+    function: usize,
+    block: usize,
+    next: usize,
+    values: Vec<ArtifactValue>,
+    exits: Vec<usize>,
+}
+
+/// A resumable activation of one artifact function.
+pub struct ArtifactExecution<'m> {
+    module: &'m Module,
+    frames: Vec<Frame>,
+    resume: Option<usize>,
+    started: bool,
+}
+
+impl<'m> ArtifactExecution<'m> {
+    /// Activate `function` with `arguments` without running any block.
+    ///
+    /// # Errors
+    ///
+    /// Returns `E-GEIR-EXECUTION` when the function does not exist or the
+    /// arguments do not conform to its inputs.
+    pub fn new(
+        module: ValidatedModule<'m>,
+        function: usize,
+        arguments: Vec<ArtifactValue>,
+    ) -> Result<Self, Diagnostic> {
+        let module = module.module();
+        let Some(target) = module.functions.get(function) else {
+            return Err(execution_error("function index is out of bounds"));
+        };
+        if arguments.len() != target.inputs.len()
+            || arguments
+                .iter()
+                .zip(&target.inputs)
+                .any(|(value, input)| !conforms(module, value, *input))
+        {
+            return Err(execution_error(
+                "arguments do not conform to the function inputs",
+            ));
+        }
+        Ok(Self {
+            module,
+            frames: vec![Frame {
+                function,
+                block: target.entry,
+                next: 0,
+                values: arguments,
+                exits: vec![function],
+            }],
+            resume: None,
+            started: false,
+        })
+    }
+
+    /// Run until the activation returns, yields, or suspends.
+    ///
+    /// # Errors
+    ///
+    /// Returns `E-GEIR-EXECUTION` for an activation that already returned or
+    /// awaits resumption, and for dynamic failures such as an unhandled effect
+    /// or a match value without a target.
+    pub fn run(&mut self, trace: &mut impl TraceSink) -> Result<ArtifactStep, Diagnostic> {
+        if self.resume.is_some() {
+            return Err(execution_error("a suspended activation must be resumed"));
+        }
+        if !self.started {
+            self.started = true;
+            let function = self.frames[0].function;
+            self.enter_function(function, trace);
+        }
+        self.advance(trace)
+    }
+
+    /// Resume a yielded or suspended activation with the protocol value.
+    ///
+    /// # Errors
+    ///
+    /// Returns `E-GEIR-EXECUTION` when the activation is not suspended or the
+    /// value does not conform to the resumption block, and otherwise the same
+    /// diagnostics as [`Self::run`].
+    pub fn resume(
+        &mut self,
+        value: ArtifactValue,
+        trace: &mut impl TraceSink,
+    ) -> Result<ArtifactStep, Diagnostic> {
+        let Some(block) = self.resume.take() else {
+            return Err(execution_error("the activation is not suspended"));
+        };
+        let Some(frame) = self.frames.last_mut() else {
+            return Err(execution_error("the activation already returned"));
+        };
+        let parameters = &self.module.functions[frame.function].blocks[block].parameters;
+        let arguments = match parameters.as_slice() {
+            [] => Vec::new(),
+            [parameter] if conforms(self.module, &value, *parameter) => vec![value],
+            _ => {
+                self.resume = Some(block);
+                return Err(execution_error(
+                    "resumed value does not conform to the resumption block",
+                ));
+            }
+        };
+        frame.block = block;
+        frame.next = 0;
+        frame.values = arguments;
+        self.advance(trace)
+    }
+
+    fn advance(&mut self, trace: &mut impl TraceSink) -> Result<ArtifactStep, Diagnostic> {
+        let module = self.module;
+        loop {
+            let Some(frame) = self.frames.last_mut() else {
+                return Err(execution_error("the activation already returned"));
+            };
+            let block = &module.functions[frame.function].blocks[frame.block];
+            if let Some(instruction) = block.instructions.get(frame.next) {
+                frame.next += 1;
+                if let Instruction::Apply {
+                    function,
+                    arguments,
+                    ..
+                } = instruction
+                {
+                    let arguments = select(&frame.values, arguments);
+                    let entry = module.functions[*function].entry;
+                    self.frames.push(Frame {
+                        function: *function,
+                        block: entry,
+                        next: 0,
+                        values: arguments,
+                        exits: vec![*function],
+                    });
+                    self.enter_function(*function, trace);
+                    continue;
+                }
+                if let Some(value) = evaluate(module, instruction, &frame.values)? {
+                    frame.values.push(value);
+                }
+                continue;
+            }
+            match &block.terminator {
+                Terminator::Return(value) => {
+                    let value = frame.values[*value].clone();
+                    let frame = self.frames.pop().expect("current frame");
+                    for function in frame.exits.iter().rev() {
+                        self.exit_function(*function, trace);
+                    }
+                    let Some(caller) = self.frames.last_mut() else {
+                        return Ok(ArtifactStep::Returned(value));
+                    };
+                    caller.values.push(value);
+                }
+                Terminator::Branch { target, arguments } => {
+                    frame.values = select(&frame.values, arguments);
+                    frame.block = *target;
+                    frame.next = 0;
+                }
+                Terminator::Match { value, targets } => {
+                    let subject = &frame.values[*value];
+                    let Some(selected) =
+                        match_index(subject).filter(|index| *index < targets.len())
+                    else {
+                        return Err(execution_error("match value has no target"));
+                    };
+                    record_decision(subject, targets, selected, trace);
+                    frame.values = Vec::new();
+                    frame.block = targets[selected];
+                    frame.next = 0;
+                }
+                Terminator::Yield { value, resume } => {
+                    let value = frame.values[*value].clone();
+                    let function = frame.function;
+                    self.resume = Some(*resume);
+                    self.suspend(function, trace);
+                    return Ok(ArtifactStep::Yielded(value));
+                }
+                Terminator::Suspend { resume } => {
+                    let function = frame.function;
+                    self.resume = Some(*resume);
+                    self.suspend(function, trace);
+                    return Ok(ArtifactStep::Suspended);
+                }
+                Terminator::TailApply {
+                    function,
+                    arguments,
+                } => {
+                    frame.values = select(&frame.values, arguments);
+                    frame.function = *function;
+                    frame.block = module.functions[*function].entry;
+                    frame.next = 0;
+                    frame.exits.push(*function);
+                    self.enter_function(*function, trace);
+                }
+            }
+        }
+    }
+
+    fn name(&self, function: usize) -> Option<&'m str> {
+        self.module.identities[self.module.functions[function].identity]
+            .declaration_path
+            .last()
+            .map(String::as_str)
+    }
+
+    fn enter_function(&self, function: usize, trace: &mut impl TraceSink) {
+        if let Some(name) = self.name(function) {
+            trace.record(TraceEvent {
+                event: "function.entry",
+                rule: FUNCTION_RULE,
+                detail: name,
+            });
+        }
+    }
+
+    fn exit_function(&self, function: usize, trace: &mut impl TraceSink) {
+        if let Some(name) = self.name(function) {
+            trace.record(TraceEvent {
+                event: "function.exit",
+                rule: FUNCTION_RULE,
+                detail: name,
+            });
+        }
+    }
+
+    fn suspend(&self, function: usize, trace: &mut impl TraceSink) {
+        trace.record(TraceEvent {
+            event: "generator.suspended",
+            rule: "TOPAL-GENERATOR-SUSPEND-001",
+            detail: self.name(function).unwrap_or_default(),
+        });
+    }
+}
+
+/// Run the private entry function of a lowered module to completion.
+///
+/// # Errors
+///
+/// Returns `E-GEIR-EXECUTION` when the module has no entry function, the entry
+/// yields or suspends, or execution fails dynamically.
+pub fn execute_artifact(
+    module: ValidatedModule<'_>,
+    trace: &mut impl TraceSink,
+) -> Result<ArtifactValue, Diagnostic> {
+    let inner = module.module();
+    let Some(entry) = inner.functions.iter().position(|function| {
+        inner.identities[function.identity]
+            .declaration_path
+            .is_empty()
+    }) else {
+        return Err(execution_error("module has no entry function"));
+    };
+    match ArtifactExecution::new(module, entry, Vec::new())?.run(trace)? {
+        ArtifactStep::Returned(value) => Ok(value),
+        ArtifactStep::Yielded(_) | ArtifactStep::Suspended => Err(execution_error(
+            "entry function suspended without a resuming host",
+        )),
+    }
+}
+
+fn select(values: &[ArtifactValue], indices: &[usize]) -> Vec<ArtifactValue> {
+    indices.iter().map(|index| values[*index].clone()).collect()
+}
+
+fn evaluate(
+    module: &Module,
+    instruction: &Instruction,
+    values: &[ArtifactValue],
+) -> Result<Option<ArtifactValue>, Diagnostic> {
+    let value = match instruction {
+        Instruction::Constant { result_type, bytes } => constant(module, *result_type, bytes)?,
+        Instruction::Product {
+            result_type,
+            values: operands,
+        }
+        | Instruction::Construct {
+            result_type,
+            values: operands,
+        } => {
+            let items = select(values, operands);
+            match &module.types[*result_type] {
+                Type::Record(fields) => ArtifactValue::Record(
+                    fields
+                        .iter()
+                        .map(|(label, _)| label.clone())
+                        .zip(items)
+                        .collect(),
+                ),
+                _ => ArtifactValue::Product(items),
+            }
+        }
+        Instruction::Project { value, field, .. } => match &values[*value] {
+            ArtifactValue::Product(items) => items[*field].clone(),
+            ArtifactValue::Record(fields) => fields[*field].1.clone(),
+            _ => return Err(execution_error("projection operand is not a product")),
+        },
+        Instruction::Validate { value, .. }
+        | Instruction::Convert { value, .. }
+        | Instruction::PackExists { value, .. }
+        | Instruction::UnpackExists { value, .. } => values[*value].clone(),
+        Instruction::Capability { evidence, .. } => ArtifactValue::Capability(*evidence),
+        Instruction::Effect { .. } => {
+            return Err(execution_error(
+                "the reference executor has no handler for this effect",
+            ));
+        }
+        Instruction::Apply { .. } => unreachable!("applications push a frame"),
+        Instruction::BeginRegion | Instruction::EndRegion => return Ok(None),
+    };
+    Ok(Some(value))
+}
+
+fn constant(
+    module: &Module,
+    result_type: usize,
+    bytes: &[u8],
+) -> Result<ArtifactValue, Diagnostic> {
+    let value = match &module.types[result_type] {
+        Type::Primitive(name) if name == "Boolean" => match bytes {
+            [0] => ArtifactValue::Boolean(false),
+            [1] => ArtifactValue::Boolean(true),
+            _ => return Err(execution_error("Boolean constant is not one flag byte")),
+        },
+        Type::Primitive(name) if name == "Int" || name == "Nat" => {
+            ArtifactValue::Int(BigInt::from_signed_bytes_be(bytes))
+        }
+        Type::Primitive(name) if name == "String" || name == "Character" => ArtifactValue::String(
+            String::from_utf8(bytes.to_vec())
+                .map_err(|_| execution_error("text constant is not UTF-8"))?,
+        ),
+        Type::Variant(fields) => {
+            let index = alternative_index(bytes)
+                .filter(|index| *index < fields.len())
+                .ok_or_else(|| execution_error("variant constant names no alternative"))?;
+            ArtifactValue::Alternative {
+                index,
+                label: fields[index].0.clone(),
+            }
+        }
+        Type::Tuple(items) if items.is_empty() && bytes.is_empty() => {
+            ArtifactValue::Product(Vec::new())
+        }
+        _ => ArtifactValue::Opaque {
+            result_type,
+            bytes: bytes.to_vec(),
+        },
+    };
+    Ok(value)
+}
+
+fn alternative_index(bytes: &[u8]) -> Option<usize> {
+    let mut value = 0_usize;
+    for (position, byte) in bytes.iter().enumerate() {
+        let shift = u32::try_from(position * 7).ok()?;
+        value |= usize::from(byte & 0x7f).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            return (position + 1 == bytes.len()).then_some(value);
+        }
+    }
+    None
+}
+
+fn match_index(subject: &ArtifactValue) -> Option<usize> {
+    match subject {
+        ArtifactValue::Boolean(value) => Some(usize::from(*value)),
+        ArtifactValue::Alternative { index, .. } => Some(*index),
+        ArtifactValue::Int(value) => usize::try_from(value).ok(),
+        _ => None,
+    }
+}
+
+/// Record the source decision events for one match. Targets are numbered as
+/// rules in first-block order, which is the order lowering allocates them.
+fn record_decision(
+    subject: &ArtifactValue,
+    targets: &[usize],
+    selected: usize,
+    trace: &mut impl TraceSink,
+) {
+    let mut rules = targets.to_vec();
+    rules.sort_unstable();
+    rules.dedup();
+    let rule_index = rules
+        .binary_search(&targets[selected])
+        .expect("selected target is a rule");
+    let decision_rule = if matches!(subject, ArtifactValue::Boolean(_)) {
+        "TOPAL-DECISION-BOOLEAN-001"
+    } else {
+        "TOPAL-DECISION-ENUM-001"
+    };
+    for index in 0..=rule_index {
+        let detail = format!("rule={index};matched={}", index == rule_index);
+        trace.record(TraceEvent {
+            event: "decision.rule.considered",
+            rule: decision_rule,
+            detail: &detail,
+        });
+    }
+    let detail = format!("rule={rule_index}");
+    trace.record(TraceEvent {
+        event: "decision.rule.selected",
+        rule: decision_rule,
+        detail: &detail,
+    });
+}
+
+fn conforms(module: &Module, value: &ArtifactValue, expected: usize) -> bool {
+    match (&module.types[expected], value) {
+        (Type::Primitive(name), ArtifactValue::Boolean(_)) => name == "Boolean",
+        (Type::Primitive(name), ArtifactValue::Int(value)) => {
+            name == "Int" || (name == "Nat" && value.sign() != num_bigint::Sign::Minus)
+        }
+        (Type::Primitive(name), ArtifactValue::String(value)) => {
+            name == "String" || (name == "Character" && character_count(value) == 1)
+        }
+        (Type::Variant(fields), ArtifactValue::Alternative { index, label }) => {
+            fields.get(*index).is_some_and(|(field, _)| field == label)
+        }
+        (Type::Tuple(types), ArtifactValue::Product(items)) => {
+            types.len() == items.len()
+                && types
+                    .iter()
+                    .zip(items)
+                    .all(|(id, item)| conforms(module, item, *id))
+        }
+        (Type::Record(types), ArtifactValue::Record(fields)) => {
+            types.len() == fields.len()
+                && types
+                    .iter()
+                    .zip(fields)
+                    .all(|((label, id), (field, item))| {
+                        label == field && conforms(module, item, *id)
+                    })
+        }
+        (_, ArtifactValue::Capability(_)) => true,
+        (_, ArtifactValue::Opaque { result_type, .. }) => *result_type == expected,
+        _ => false,
+    }
+}
+
+fn execution_error(message: &str) -> Diagnostic {
+    Diagnostic::error(ARTIFACT_EXECUTION_ERROR_CODE, 1, 1, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lower_source_file;
+    use topal_geir::ToolRole;
+
+    fn lowered(body: &str) -> Module {
+        lower_source_file(
+            &format!("use language (\n  version is v0.1\n)\n{body}"),
+            "example",
+            &["main"],
+            ToolRole::Compiler,
+        )
+        .expect("source lowers")
+    }
+
+    #[test]
+    fn tail_applications_keep_nested_exit_order() {
+        let module = lowered(
+            "answer is fn () -> Int\n  increment 41\nincrement is fn (input : Int) -> Int\n  input\nanswer ()\n",
+        );
+        let mut trace = Vec::new();
+        let value =
+            execute_artifact(module.validate().expect("valid"), &mut trace).expect("artifact runs");
+        assert_eq!(value, ArtifactValue::Int(41.into()));
+        let calls = trace
+            .iter()
+            .filter(|event| event.contains("\"function."))
+            .map(|event| {
+                let name = if event.contains("answer") {
+                    "answer"
+                } else {
+                    "increment"
+                };
+                let kind = if event.contains("entry") {
+                    "entry"
+                } else {
+                    "exit"
+                };
+                format!("{kind} {name}")
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            calls,
+            [
+                "entry answer",
+                "entry increment",
+                "exit increment",
+                "exit answer"
+            ]
+        );
+    }
+
+    #[test]
+    fn generators_yield_and_resume_through_the_host() {
+        let module = lowered(
+            "once is generator ( initial : Character )\n  yields Character\n  resumes Unit\n  -> Unit\n\n  resumed is yield initial\n  resumed\n",
+        );
+        let mut trace = Vec::new();
+        let mut execution = ArtifactExecution::new(
+            module.validate().expect("valid"),
+            0,
+            vec![ArtifactValue::String("T".to_owned())],
+        )
+        .expect("activation");
+        assert_eq!(
+            execution.run(&mut trace).expect("yield"),
+            ArtifactStep::Yielded(ArtifactValue::String("T".to_owned()))
+        );
+        assert!(
+            trace
+                .iter()
+                .any(|event| event.contains("generator.suspended"))
+        );
+        assert!(execution.run(&mut trace).is_err());
+        assert_eq!(
+            execution
+                .resume(ArtifactValue::Product(Vec::new()), &mut trace)
+                .expect("return"),
+            ArtifactStep::Returned(ArtifactValue::Product(Vec::new()))
+        );
+        assert!(
+            execution
+                .resume(ArtifactValue::Product(Vec::new()), &mut trace)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn activations_reject_nonconforming_arguments() {
+        let module = lowered("identity is fn (value : Nat) -> Nat\n  value\n");
+        let error = ArtifactExecution::new(
+            module.validate().expect("valid"),
+            0,
+            vec![ArtifactValue::Int((-1).into())],
+        )
+        .err()
+        .expect("negative Nat");
+        assert_eq!(error.code, ARTIFACT_EXECUTION_ERROR_CODE);
+    }
+}
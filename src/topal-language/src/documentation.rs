@@ -3,10 +3,13 @@ use topal_syntax::{DocumentedDeclaration, DocumentedParameter};
 fn entry(name: &str, kind: &str, syntax: &str, documentation: &str) -> DocumentedDeclaration {
     DocumentedDeclaration {
         name: format!("lang {name}"),
+        name_span: None,
+        published: true,
         kind: kind.into(),
         syntax: syntax.into(),
         documentation: Some(documentation.into()),
         parameters: Vec::<DocumentedParameter>::new(),
+        result: None,
     }
 }
 
@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
 use std::rc::Rc;
 
-use crate::{TraceEvent, TraceSink, Value};
+use crate::{RecordedCheckpoint, TraceEvent, TraceSink, Value};
 use topal_source::Span;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -127,6 +127,62 @@ impl ExecutionHistory {
         latest.map(|checkpoint| (bindings, checkpoint))
     }
 
+    /// The cursor and source range of every source state that source
+    /// stepping visits, in execution order.
+    #[must_use]
+    pub fn source_states(&self) -> Vec<(usize, SourceRange)> {
+        self.checkpoints
+            .iter()
+            .filter_map(|checkpoint| {
+                checkpoint
+                    .source_range
+                    .map(|range| (checkpoint.cursor, range))
+            })
+            .collect()
+    }
+
+    /// Every recorded change to the value of binding `name`, in execution
+    /// order, with the cursor of the state that first shows the new value.
+    /// Leaving scope is not a change: an outer binding hidden during a call
+    /// and visible again with the same value afterwards is written once.
+    #[must_use]
+    pub fn binding_writes(&self, name: &str) -> Vec<(usize, Value)> {
+        let mut writes = Vec::<(usize, Value)>::new();
+        for checkpoint in &self.checkpoints {
+            for (_, value) in checkpoint
+                .binding_changes
+                .iter()
+                .filter(|(changed, _)| changed == name)
+            {
+                if let Some(value) = value
+                    && writes.last().is_none_or(|(_, last)| last != value.as_ref())
+                {
+                    writes.push((checkpoint.cursor, value.as_ref().clone()));
+                }
+            }
+        }
+        writes
+    }
+
+    /// Every state checkpoint with its values as displayed text, for
+    /// recordings that reconstruct values by replaying the source.
+    #[must_use]
+    pub fn recorded_checkpoints(&self) -> Vec<RecordedCheckpoint> {
+        self.checkpoints
+            .iter()
+            .map(|checkpoint| RecordedCheckpoint {
+                cursor: checkpoint.cursor,
+                source_range: checkpoint.source_range,
+                binding_changes: checkpoint
+                    .binding_changes
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.as_ref().map(ToString::to_string)))
+                    .collect(),
+                value: checkpoint.value.as_ref().map(ToString::to_string),
+            })
+            .collect()
+    }
+
     pub fn step_source_forward(&mut self) -> Option<ExecutionState> {
         let cursor = self
             .checkpoints
@@ -362,6 +418,49 @@ mod tests {
         assert!(history.cursor() < result_cursor);
     }
 
+    #[test]
+    fn lists_the_source_states_that_source_stepping_visits() {
+        let mut history = ExecutionHistory::new();
+        Session::new()
+            .evaluate(
+                include_str!("../../../examples/debugger/basic-history.t"),
+                &mut history,
+            )
+            .expect("example should evaluate");
+        let states = history.source_states();
+        history.rewind();
+        let mut visited = Vec::new();
+        while let Some(state) = history.step_source_forward() {
+            visited.push((history.cursor(), state.source_range.unwrap()));
+        }
+        assert!(!states.is_empty());
+        assert_eq!(states, visited);
+    }
+
+    #[test]
+    fn lists_every_value_one_binding_takes_in_execution_order() {
+        let mut history = ExecutionHistory::new();
+        Session::new()
+            .evaluate(
+                include_str!("../../../examples/debugger/breakpoint-conditions.t"),
+                &mut history,
+            )
+            .expect("example should evaluate");
+        let writes = history.binding_writes("x");
+        let values = writes
+            .iter()
+            .map(|(_, value)| value.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(values, ["3", "4", "5"]);
+        assert!(writes.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(history.binding_writes("a").len(), 1);
+        let (cursor, _) = writes[1];
+        history.seek(cursor);
+        assert_eq!(history.state().unwrap().bindings["x"].to_string(), "4");
+        history.seek(cursor - 1);
+        assert!(!history.state().unwrap().bindings.contains_key("x"));
+    }
+
     #[test]
     fn stepping_follows_and_reverses_message_transactions() {
         let mut history = ExecutionHistory::new();
@@ -1,12 +1,24 @@
 //! Shared frontend and evaluator for Topal tools.
 
+mod analysis;
+mod approximate;
+mod artifact;
 mod concurrency;
 mod documentation;
 mod execution;
+mod lowering;
 mod modules;
+mod recording;
+mod resources;
+mod sensitive;
 mod source;
 mod trace;
+mod units;
 
+pub use analysis::static_diagnostics;
+pub use artifact::{
+    ARTIFACT_EXECUTION_ERROR_CODE, ArtifactExecution, ArtifactStep, ArtifactValue, execute_artifact,
+};
 pub use concurrency::{
     Admission, DependencyGraph, DependencyKind, Interaction, InteractionForm, Protocol,
     ProtocolTransition, TaskScope, validate_schedule_equivalence,
@@ -15,11 +27,27 @@ pub use documentation::lang_documentation;
 pub use execution::{
     ExecutionHistory, ExecutionSnapshot, ExecutionState, ExecutionTransition, SourceRange,
 };
-pub use modules::load_module_tree;
+pub use lowering::{
+    INVALID_ARTIFACT_ERROR_CODE, UNREPRESENTABLE_ERROR_CODE, export_source_file, lower_source_file,
+};
+pub use modules::{
+    ModuleSource, load_module_tree, module_sources, record_module_tree, replay_module_tree,
+};
+pub use recording::{
+    RECORDING_FORMAT, RecordedCheckpoint, RecordedObservation, RecordedSource, RecordedTransition,
+    Recording,
+};
+pub use resources::{
+    CALL_DEPTH_EXHAUSTED_ERROR_CODE, FUEL_EXHAUSTED_ERROR_CODE, MEMORY_EXHAUSTED_ERROR_CODE,
+    ResourceLimits, with_evaluation_stack,
+};
+pub use sensitive::sensitivity_diagnostics;
 pub use source::{Execution, ExecutionStep, Session, Value};
 pub use topal_semantics::LanguageVersion;
 pub use topal_source::Diagnostic;
 pub use topal_source::UNICODE_VERSION;
 pub use trace::{
-    DEBUGGING_PROFILE, JsonLines, TEST_TRACE_SCHEMA, TESTING_PROFILE, TraceEvent, TraceSink,
+    DEBUGGING_PROFILE, JsonLines, REDACTED, TEST_TRACE_SCHEMA, TESTING_PROFILE, TraceEvent,
+    TraceSink,
 };
+pub use units::dimension_diagnostics;
@@ -0,0 +1,1217 @@
+//! Compiler lowering from checked source declarations into generic export
+//! artifacts.
+//!
+//! Lowering accepts the subset of design-0 whose meaning GEIR revision 1 can
+//! carry without private interpreter semantics: nominal enums, primitive
+//! constants, products, ordinary and generator functions, direct calls, and
+//! Boolean or enum decisions. Every other construct is rejected with a stable
+//! diagnostic instead of producing an incomplete artifact.
+
+use std::collections::BTreeMap;
+use std::io;
+
+use num_bigint::Sign;
+use topal_geir::{
+    ARTIFACT_REVISION, Block, CompilerOnlyOperation, Function, Identity, Instruction, Module,
+    Terminator, ToolRole, Type, ValidatedModule, Visibility, require_compiler,
+};
+use topal_semantics::LanguageVersion;
+use topal_source::{Diagnostic, SourceText, Span, character_count};
+use topal_syntax::{
+    DecisionMatcher, DecisionRule, Expression, FunctionParameter, Statement, lex, parse,
+};
+
+use crate::source::{accepted_source, diagnostic, parse_integer, parse_string};
+
+/// Stable diagnostic for source whose meaning GEIR revision 1 cannot carry.
+pub const UNREPRESENTABLE_ERROR_CODE: &str = "E-GEIR-UNREPRESENTABLE";
+
+/// Stable diagnostic for a lowered module that artifact validation rejects.
+pub const INVALID_ARTIFACT_ERROR_CODE: &str = "E-GEIR-INVALID";
+
+const PRIMITIVE_CLASSIFIERS: [&str; 5] = ["Boolean", "Character", "Int", "Nat", "String"];
+
+/// Lower one source file into a validated GEIR module whose identities belong
+/// to `package` and `module_path`.
+///
+/// Top-level statements other than declarations lower into a private entry
+/// function whose identity has an empty declaration path. Published functions
+/// are exported.
+///
+/// # Errors
+///
+/// Returns `E-COMPILER-ONLY` for every tool other than the compiler, the first
+/// source or syntax diagnostic, a source type diagnostic, or
+/// `E-GEIR-UNREPRESENTABLE` at the first construct that would need semantics
+/// absent from the artifact.
+pub fn lower_source_file(
+    input: &str,
+    package: &str,
+    module_path: &[&str],
+    tool: ToolRole,
+) -> Result<Module, Diagnostic> {
+    require_compiler(tool, CompilerOnlyOperation::ExportGenericArtifact)
+        .map_err(|error| Diagnostic::error(error.code, 1, 1, error.message))?;
+    let source = accepted_source(input, &mut io::sink())?;
+    let lexed = lex(&source);
+    let parsed = parse(&source, &lexed);
+    if let Some(error) = parsed.diagnostics.first() {
+        return Err(diagnostic(&source, error.code, error.span, &error.message));
+    }
+    let Some((
+        Statement::LanguageSelection {
+            version, features, ..
+        },
+        statements,
+    )) = parsed.statements.split_first()
+    else {
+        return Err(diagnostic(
+            &source,
+            "E-MISSING-LANGUAGE-VERSION",
+            Span::new(0, 0),
+            "source files begin with a language-version selection",
+        ));
+    };
+    let requested = source
+        .slice(*version)
+        .parse::<LanguageVersion>()
+        .map_err(|message| diagnostic(&source, "E-LANGUAGE-VERSION", *version, message))?;
+    if requested != LanguageVersion::DESIGN_0 {
+        return Err(diagnostic(
+            &source,
+            "E-UNSUPPORTED-LANGUAGE-VERSION",
+            *version,
+            format!("language version {requested} is not supported"),
+        ));
+    }
+    if let Some(feature) = features.first() {
+        return Err(unrepresentable(
+            &source,
+            *feature,
+            "optional language features have no GEIR revision 1 lowering",
+        ));
+    }
+    let module = Lowering::new(&source).module(package, module_path, statements)?;
+    validated(&module)?;
+    Ok(module)
+}
+
+/// Lower one source file and return its canonical artifact bytes.
+///
+/// # Errors
+///
+/// Returns the same diagnostics as [`lower_source_file`].
+pub fn export_source_file(
+    input: &str,
+    package: &str,
+    module_path: &[&str],
+    tool: ToolRole,
+) -> Result<Vec<u8>, Diagnostic> {
+    let module = lower_source_file(input, package, module_path, tool)?;
+    Ok(validated(&module)?.canonical_bytes())
+}
+
+fn validated(module: &Module) -> Result<ValidatedModule<'_>, Diagnostic> {
+    module
+        .validate()
+        .map_err(|error| Diagnostic::error(INVALID_ARTIFACT_ERROR_CODE, 1, 1, error.to_string()))
+}
+
+struct Declaration<'a> {
+    name: &'a str,
+    span: Span,
+    public: bool,
+    parameters: &'a [FunctionParameter],
+    result: Span,
+    protocol: Option<(Span, Span)>,
+    body: &'a [Statement],
+}
+
+struct Signature {
+    index: usize,
+    inputs: Vec<usize>,
+    result: usize,
+    protocol: Option<(usize, usize)>,
+}
+
+struct Lowering<'a> {
+    source: &'a SourceText,
+    types: Vec<Type>,
+    enums: BTreeMap<&'a str, usize>,
+    alternatives: BTreeMap<&'a str, (usize, usize)>,
+    signatures: BTreeMap<&'a str, Signature>,
+}
+
+impl<'a> Lowering<'a> {
+    const fn new(source: &'a SourceText) -> Self {
+        Self {
+            source,
+            types: Vec::new(),
+            enums: BTreeMap::new(),
+            alternatives: BTreeMap::new(),
+            signatures: BTreeMap::new(),
+        }
+    }
+
+    fn module(
+        mut self,
+        package: &str,
+        module_path: &[&str],
+        statements: &'a [Statement],
+    ) -> Result<Module, Diagnostic> {
+        let mut declarations = BTreeMap::new();
+        let mut script = Vec::new();
+        for statement in statements {
+            let (statement, public) = match statement {
+                Statement::Published { declaration, .. } => (declaration.as_ref(), true),
+                statement => (statement, false),
+            };
+            if let Some(declaration) = self.declaration(statement, public)? {
+                if declarations.contains_key(declaration.name) {
+                    return Err(unrepresentable(
+                        self.source,
+                        declaration.span,
+                        "overloaded functions share one GEIR declaration identity",
+                    ));
+                }
+                declarations.insert(declaration.name, declaration);
+                continue;
+            }
+            if self.enumeration(statement)? {
+                continue;
+            }
+            if public {
+                return Err(unrepresentable(
+                    self.source,
+                    statement_span(statement),
+                    "only functions and enums can be published into a GEIR module",
+                ));
+            }
+            script.push(statement);
+        }
+        let entry = usize::from(!script.is_empty());
+        let identity = |declaration_path: Vec<String>| Identity {
+            package: package.to_owned(),
+            module_path: module_path.iter().map(|part| (*part).to_owned()).collect(),
+            declaration_path,
+            language_revision: LanguageVersion::DESIGN_0,
+        };
+        let mut identities = Vec::new();
+        if entry == 1 {
+            identities.push(identity(Vec::new()));
+        }
+        for (offset, declaration) in declarations.values().enumerate() {
+            identities.push(identity(vec![declaration.name.to_owned()]));
+            let signature = self.signature(entry + offset, declaration)?;
+            self.signatures.insert(declaration.name, signature);
+        }
+        let mut functions = Vec::new();
+        if entry == 1 {
+            functions.push(self.script(&script)?);
+        }
+        for declaration in declarations.values() {
+            functions.push(self.function(declaration)?);
+        }
+        let exports = declarations
+            .values()
+            .filter(|declaration| declaration.public)
+            .map(|declaration| self.signatures[declaration.name].index)
+            .collect();
+        Ok(Module {
+            revision: ARTIFACT_REVISION,
+            language: LanguageVersion::DESIGN_0,
+            imports: Vec::new(),
+            identities,
+            types: self.types,
+            capabilities: Vec::new(),
+            evidence: Vec::new(),
+            functions,
+            exports,
+        })
+    }
+
+    fn declaration(
+        &self,
+        statement: &'a Statement,
+        public: bool,
+    ) -> Result<Option<Declaration<'a>>, Diagnostic> {
+        let declaration = match statement {
+            Statement::Function {
+                name,
+                is_static,
+                parameters,
+                result,
+                effect_bound,
+                body,
+                span,
+            } => {
+                if *is_static {
+                    return Err(unrepresentable(
+                        self.source,
+                        *span,
+                        "static functions are evaluated before export and have no GEIR body",
+                    ));
+                }
+                if let Some(effect_bound) = effect_bound {
+                    return Err(unrepresentable(
+                        self.source,
+                        *effect_bound,
+                        "effect bounds have no exact GEIR effect identity",
+                    ));
+                }
+                Declaration {
+                    name: self.source.slice(*name),
+                    span: *span,
+                    public,
+                    parameters,
+                    result: *result,
+                    protocol: None,
+                    body,
+                }
+            }
+            Statement::Generator {
+                name,
+                parameters,
+                yielded,
+                resumed,
+                result,
+                body,
+                span,
+            } => Declaration {
+                name: self.source.slice(*name),
+                span: *span,
+                public,
+                parameters,
+                result: *result,
+                protocol: Some((*yielded, *resumed)),
+                body,
+            },
+            _ => return Ok(None),
+        };
+        if let Some(parameter) = declaration
+            .parameters
+            .iter()
+            .find(|parameter| !parameter.fields.is_empty() || parameter.default.is_some())
+        {
+            return Err(unrepresentable(
+                self.source,
+                parameter.name,
+                "parameter packages and defaults have no GEIR revision 1 lowering",
+            ));
+        }
+        Ok(Some(declaration))
+    }
+
+    fn enumeration(&mut self, statement: &'a Statement) -> Result<bool, Diagnostic> {
+        let Statement::Binding {
+            name,
+            value: Expression::Application { items, .. },
+            ..
+        } = statement
+        else {
+            return Ok(false);
+        };
+        let [
+            Expression::Identifier(keyword),
+            Expression::Product { fields, .. },
+        ] = items.as_slice()
+        else {
+            return Ok(false);
+        };
+        if self.source.slice(*keyword) != "Enum" {
+            return Ok(false);
+        }
+        let unit = self.intern(Type::Tuple(Vec::new()));
+        let mut alternatives = Vec::new();
+        for field in fields {
+            let (None, Expression::Identifier(alternative)) = (field.label, &field.value) else {
+                return Err(unrepresentable(
+                    self.source,
+                    field.value.span(),
+                    "enum alternatives lower only from plain names",
+                ));
+            };
+            alternatives.push(self.source.slice(*alternative));
+        }
+        let index = self.intern(Type::Variant(
+            alternatives
+                .iter()
+                .map(|alternative| ((*alternative).to_owned(), unit))
+                .collect(),
+        ));
+        let type_name = self.source.slice(*name);
+        if self.enums.insert(type_name, index).is_some() {
+            return Err(diagnostic(
+                self.source,
+                "E-DUPLICATE-BINDING",
+                *name,
+                format!("`{type_name}` is already declared"),
+            ));
+        }
+        for (position, alternative) in alternatives.into_iter().enumerate() {
+            if self
+                .alternatives
+                .insert(alternative, (index, position))
+                .is_some()
+            {
+                return Err(unrepresentable(
+                    self.source,
+                    *name,
+                    format!("enum alternative `{alternative}` is ambiguous between enums"),
+                ));
+            }
+        }
+        Ok(true)
+    }
+
+    fn signature(
+        &mut self,
+        index: usize,
+        declaration: &Declaration<'a>,
+    ) -> Result<Signature, Diagnostic> {
+        let inputs = declaration
+            .parameters
+            .iter()
+            .map(|parameter| self.classifier(parameter.classifier))
+            .collect::<Result<_, _>>()?;
+        let result = self.classifier(declaration.result)?;
+        let protocol = match declaration.protocol {
+            Some((yielded, resumed)) => {
+                Some((self.classifier(yielded)?, self.classifier(resumed)?))
+            }
+            None => None,
+        };
+        Ok(Signature {
+            index,
+            inputs,
+            result,
+            protocol,
+        })
+    }
+
+    fn classifier(&mut self, span: Span) -> Result<usize, Diagnostic> {
+        let name = self.source.slice(span);
+        if let Some(index) = self.enums.get(name) {
+            return Ok(*index);
+        }
+        if name == "Unit" {
+            return Ok(self.intern(Type::Tuple(Vec::new())));
+        }
+        if PRIMITIVE_CLASSIFIERS.contains(&name) {
+            return Ok(self.intern(Type::Primitive(name.to_owned())));
+        }
+        Err(unrepresentable(
+            self.source,
+            span,
+            format!("classifier `{name}` has no GEIR revision 1 lowering"),
+        ))
+    }
+
+    fn intern(&mut self, value: Type) -> usize {
+        if let Some(index) = self.types.iter().position(|existing| *existing == value) {
+            return index;
+        }
+        self.types.push(value);
+        self.types.len() - 1
+    }
+
+    fn script(&mut self, statements: &[&'a Statement]) -> Result<Function, Diagnostic> {
+        let mut body = Body::new(self, Vec::new(), None);
+        let (last, leading) = statements.split_last().expect("script has statements");
+        for statement in leading {
+            body.statement(statement)?;
+        }
+        let Statement::Expression(value) = last else {
+            return Err(unrepresentable(
+                body.lowering.source,
+                statement_span(last),
+                "a lowered script ends with a result expression",
+            ));
+        };
+        let result = body.tail(value, None)?;
+        Ok(body.finish(0, Visibility::Private, Vec::new(), result))
+    }
+
+    fn function(&mut self, declaration: &Declaration<'a>) -> Result<Function, Diagnostic> {
+        let signature = &self.signatures[declaration.name];
+        let (index, inputs, result) = (signature.index, signature.inputs.clone(), signature.result);
+        let mut body = Body::new(self, inputs.clone(), signature.protocol);
+        for (position, parameter) in declaration.parameters.iter().enumerate() {
+            let name = body.lowering.source.slice(parameter.name);
+            if name != "_" {
+                body.locals.insert(name, (0, position));
+            }
+        }
+        let Some((last, leading)) = declaration.body.split_last() else {
+            return Err(unrepresentable(
+                body.lowering.source,
+                declaration.span,
+                "a lowered function has a body",
+            ));
+        };
+        for statement in leading {
+            body.statement(statement)?;
+        }
+        let value = match last {
+            Statement::Expression(value) | Statement::Return { value, .. } => value,
+            statement => {
+                return Err(unrepresentable(
+                    body.lowering.source,
+                    statement_span(statement),
+                    "a lowered function body ends with a result expression",
+                ));
+            }
+        };
+        body.tail(value, Some(result))?;
+        let visibility = if declaration.public {
+            Visibility::Public
+        } else {
+            Visibility::Private
+        };
+        Ok(body.finish(index, visibility, inputs, result))
+    }
+}
+
+/// One function under construction. SSA values are block-local, so a local
+/// binding is usable only in the block that defined it.
+struct Body<'l, 'a> {
+    lowering: &'l mut Lowering<'a>,
+    blocks: Vec<Block>,
+    current: usize,
+    values: Vec<usize>,
+    locals: BTreeMap<&'a str, (usize, usize)>,
+    protocol: Option<(usize, usize)>,
+}
+
+impl<'l, 'a> Body<'l, 'a> {
+    fn new(
+        lowering: &'l mut Lowering<'a>,
+        inputs: Vec<usize>,
+        protocol: Option<(usize, usize)>,
+    ) -> Self {
+        let mut body = Self {
+            lowering,
+            blocks: Vec::new(),
+            current: 0,
+            values: Vec::new(),
+            locals: BTreeMap::new(),
+            protocol,
+        };
+        let entry = body.block(inputs);
+        body.enter(entry);
+        body
+    }
+
+    fn finish(
+        self,
+        identity: usize,
+        visibility: Visibility,
+        inputs: Vec<usize>,
+        result: usize,
+    ) -> Function {
+        Function {
+            identity,
+            visibility,
+            static_parameters: Vec::new(),
+            inputs,
+            result,
+            effects: Vec::new(),
+            guarantees: Vec::new(),
+            blocks: self.blocks,
+            entry: 0,
+        }
+    }
+
+    fn block(&mut self, parameters: Vec<usize>) -> usize {
+        self.blocks.push(Block {
+            parameters,
+            instructions: Vec::new(),
+            terminator: Terminator::Return(0),
+        });
+        self.blocks.len() - 1
+    }
+
+    fn enter(&mut self, block: usize) {
+        self.current = block;
+        self.values.clone_from(&self.blocks[block].parameters);
+    }
+
+    fn terminate(&mut self, terminator: Terminator) {
+        self.blocks[self.current].terminator = terminator;
+    }
+
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        let result_type = instruction
+            .result_type()
+            .expect("lowered instructions produce values");
+        self.blocks[self.current].instructions.push(instruction);
+        self.values.push(result_type);
+        self.values.len() - 1
+    }
+
+    fn unit(&mut self) -> usize {
+        let result_type = self.lowering.intern(Type::Tuple(Vec::new()));
+        self.emit(Instruction::Product {
+            result_type,
+            values: Vec::new(),
+        })
+    }
+
+    fn statement(&mut self, statement: &'a Statement) -> Result<(), Diagnostic> {
+        if let Some((name, yielded)) = self.yielded(statement) {
+            return self.yield_value(name, yielded);
+        }
+        match statement {
+            Statement::Binding {
+                name,
+                classifier,
+                value,
+            } => {
+                let expected = classifier
+                    .map(|classifier| self.lowering.classifier(classifier))
+                    .transpose()?;
+                let bound = self.expression(value, expected)?;
+                if let Some(expected) = expected
+                    && self.values[bound] != expected
+                {
+                    return Err(diagnostic(
+                        self.lowering.source,
+                        "E-BINDING-CLASSIFIER",
+                        value.span(),
+                        "binding value does not satisfy its classifier",
+                    ));
+                }
+                let name = self.lowering.source.slice(*name);
+                if name != "_" {
+                    self.locals.insert(name, (self.current, bound));
+                }
+            }
+            Statement::Discard { value, .. } | Statement::Expression(value) => {
+                self.expression(value, None)?;
+            }
+            Statement::Return { keyword, .. } => {
+                return Err(unrepresentable(
+                    self.lowering.source,
+                    *keyword,
+                    "only the final statement of a function body can return",
+                ));
+            }
+            statement => {
+                return Err(unrepresentable(
+                    self.lowering.source,
+                    statement_span(statement),
+                    "this statement has no GEIR revision 1 lowering",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn yielded(&self, statement: &'a Statement) -> Option<(Option<Span>, &'a Expression)> {
+        let (name, value) = match statement {
+            Statement::Binding { name, value, .. } => (Some(*name), value),
+            Statement::Discard { value, .. } | Statement::Expression(value) => (None, value),
+            _ => return None,
+        };
+        let Expression::Application { items, .. } = value else {
+            return None;
+        };
+        let [Expression::Identifier(keyword), yielded] = items.as_slice() else {
+            return None;
+        };
+        (self.protocol.is_some() && self.lowering.source.slice(*keyword) == "yield")
+            .then_some((name, yielded))
+    }
+
+    fn yield_value(
+        &mut self,
+        name: Option<Span>,
+        yielded: &'a Expression,
+    ) -> Result<(), Diagnostic> {
+        let (yielded_type, resumed_type) = self.protocol.expect("yield is lowered in generators");
+        let value = self.expression(yielded, Some(yielded_type))?;
+        if self.values[value] != yielded_type {
+            return Err(diagnostic(
+                self.lowering.source,
+                "E-FUNCTION-RESULT-TYPE",
+                yielded.span(),
+                "yielded value does not satisfy the generator yield classifier",
+            ));
+        }
+        let resume = self.block(vec![resumed_type]);
+        self.terminate(Terminator::Yield { value, resume });
+        self.enter(resume);
+        if let Some(name) = name {
+            let name = self.lowering.source.slice(name);
+            if name != "_" {
+                self.locals.insert(name, (resume, 0));
+            }
+        }
+        Ok(())
+    }
+
+    /// Lower the final value of a function body and terminate its block.
+    fn tail(&mut self, value: &'a Expression, result: Option<usize>) -> Result<usize, Diagnostic> {
+        if let Some((function, arguments)) = self.call(value)? {
+            let (target, inputs, target_result) = {
+                let signature = &self.lowering.signatures[function];
+                (signature.index, signature.inputs.clone(), signature.result)
+            };
+            if result.is_none_or(|result| result == target_result) {
+                let arguments = self.arguments(value, &inputs, &arguments)?;
+                self.terminate(Terminator::TailApply {
+                    function: target,
+                    arguments,
+                });
+                return Ok(target_result);
+            }
+        }
+        let lowered = self.expression(value, result)?;
+        let actual = self.values[lowered];
+        if result.is_some_and(|result| result != actual) {
+            return Err(diagnostic(
+                self.lowering.source,
+                "E-FUNCTION-RESULT-TYPE",
+                value.span(),
+                "function body value does not satisfy its result classifier",
+            ));
+        }
+        self.terminate(Terminator::Return(lowered));
+        Ok(actual)
+    }
+
+    fn expression(
+        &mut self,
+        expression: &'a Expression,
+        expected: Option<usize>,
+    ) -> Result<usize, Diagnostic> {
+        let source = self.lowering.source;
+        match expression {
+            Expression::Unit(_) => Ok(self.unit()),
+            Expression::Boolean(span) => {
+                let result_type = self.lowering.intern(Type::Primitive("Boolean".to_owned()));
+                Ok(self.emit(Instruction::Constant {
+                    result_type,
+                    bytes: vec![u8::from(source.slice(*span) == "true")],
+                }))
+            }
+            Expression::Integer(span) => {
+                let value = parse_integer(source.slice(*span)).ok_or_else(|| {
+                    diagnostic(
+                        source,
+                        "E-NUMERIC-LITERAL",
+                        *span,
+                        "invalid integer literal",
+                    )
+                })?;
+                let result_type = if let Some(natural) = self.expected_primitive(expected, "Nat")
+                    && value.sign() != Sign::Minus
+                {
+                    natural
+                } else {
+                    self.lowering.intern(Type::Primitive("Int".to_owned()))
+                };
+                Ok(self.emit(Instruction::Constant {
+                    result_type,
+                    bytes: value.to_signed_bytes_be(),
+                }))
+            }
+            Expression::String(span) => {
+                let value = parse_string(source.slice(*span)).ok_or_else(|| {
+                    diagnostic(
+                        source,
+                        "E-STRING-LITERAL",
+                        *span,
+                        "invalid string literal delimiter",
+                    )
+                })?;
+                let result_type = if let Some(character) =
+                    self.expected_primitive(expected, "Character")
+                    && character_count(value) == 1
+                {
+                    character
+                } else {
+                    self.lowering.intern(Type::Primitive("String".to_owned()))
+                };
+                Ok(self.emit(Instruction::Constant {
+                    result_type,
+                    bytes: value.as_bytes().to_vec(),
+                }))
+            }
+            Expression::Identifier(span) => self.identifier(*span),
+            Expression::Product { fields, .. } => self.product(fields),
+            Expression::Block { statements, span } => self.block_expression(statements, *span),
+            Expression::DecisionTable { subject, rules, .. } => {
+                self.decision(subject, rules, expected)
+            }
+            Expression::Application { items, span } => {
+                let Some((function, arguments)) = self.call(expression)? else {
+                    let operator = items
+                        .iter()
+                        .find(|item| matches!(item, Expression::Callable { .. }))
+                        .map_or(*span, Expression::span);
+                    return Err(unrepresentable(
+                        source,
+                        operator,
+                        "this application has no GEIR revision 1 operation",
+                    ));
+                };
+                let (target, inputs, result_type) = {
+                    let signature = &self.lowering.signatures[function];
+                    (signature.index, signature.inputs.clone(), signature.result)
+                };
+                let arguments = self.arguments(expression, &inputs, &arguments)?;
+                Ok(self.emit(Instruction::Apply {
+                    result_type,
+                    function: target,
+                    arguments,
+                    effects: Vec::new(),
+                }))
+            }
+            expression => Err(unrepresentable(
+                source,
+                expression.span(),
+                "this expression has no GEIR revision 1 lowering",
+            )),
+        }
+    }
+
+    fn expected_primitive(&self, expected: Option<usize>, name: &str) -> Option<usize> {
+        expected.filter(|id| matches!(&self.lowering.types[*id], Type::Primitive(primitive) if primitive == name))
+    }
+
+    fn identifier(&mut self, span: Span) -> Result<usize, Diagnostic> {
+        let source = self.lowering.source;
+        let name = source.slice(span);
+        if let Some((block, value)) = self.locals.get(name) {
+            if *block != self.current {
+                return Err(unrepresentable(
+                    source,
+                    span,
+                    format!(
+                        "`{name}` is not live after a GEIR match or yield; SSA values cannot cross that edge"
+                    ),
+                ));
+            }
+            return Ok(*value);
+        }
+        if let Some((result_type, alternative)) = self.lowering.alternatives.get(name) {
+            let result_type = *result_type;
+            let mut bytes = Vec::new();
+            let mut remaining = *alternative;
+            loop {
+                let byte = u8::try_from(remaining & 0x7f).expect("masked to seven bits");
+                remaining >>= 7;
+                if remaining == 0 {
+                    bytes.push(byte);
+                    break;
+                }
+                bytes.push(byte | 0x80);
+            }
+            return Ok(self.emit(Instruction::Constant { result_type, bytes }));
+        }
+        if self.lowering.signatures.contains_key(name) {
+            return Err(unrepresentable(
+                source,
+                span,
+                "function values have no GEIR revision 1 lowering",
+            ));
+        }
+        Err(unrepresentable(
+            source,
+            span,
+            format!("`{name}` is not a lowered local, enum alternative, or function"),
+        ))
+    }
+
+    fn product(&mut self, fields: &'a [topal_syntax::ProductField]) -> Result<usize, Diagnostic> {
+        let mut values = Vec::new();
+        let mut labels = Vec::new();
+        for field in fields {
+            values.push(self.expression(&field.value, None)?);
+            labels.push(field.label.map(|label| self.lowering.source.slice(label)));
+        }
+        let types = values
+            .iter()
+            .map(|value| self.values[*value])
+            .collect::<Vec<_>>();
+        let result_type = if labels.iter().all(Option::is_none) {
+            self.lowering.intern(Type::Tuple(types))
+        } else if labels.iter().all(Option::is_some) {
+            self.lowering.intern(Type::Record(
+                labels
+                    .into_iter()
+                    .zip(types)
+                    .map(|(label, id)| (label.expect("labelled field").to_owned(), id))
+                    .collect(),
+            ))
+        } else {
+            return Err(unrepresentable(
+                self.lowering.source,
+                fields[0].value.span(),
+                "products mixing labelled and positional fields have no GEIR type",
+            ));
+        };
+        Ok(self.emit(Instruction::Product {
+            result_type,
+            values,
+        }))
+    }
+
+    fn block_expression(
+        &mut self,
+        statements: &'a [Statement],
+        span: Span,
+    ) -> Result<usize, Diagnostic> {
+        let Some((Statement::Expression(last), leading)) = statements.split_last() else {
+            return Err(unrepresentable(
+                self.lowering.source,
+                span,
+                "a lowered block ends with a result expression",
+            ));
+        };
+        let outer = self.locals.clone();
+        for statement in leading {
+            self.statement(statement)?;
+        }
+        let value = self.expression(last, None);
+        self.locals = outer;
+        value
+    }
+
+    fn decision(
+        &mut self,
+        subject: &'a Expression,
+        rules: &'a [DecisionRule],
+        expected: Option<usize>,
+    ) -> Result<usize, Diagnostic> {
+        let source = self.lowering.source;
+        let value = self.expression(subject, None)?;
+        let cases = match &self.lowering.types[self.values[value]] {
+            Type::Primitive(name) if name == "Boolean" => {
+                vec!["false".to_owned(), "true".to_owned()]
+            }
+            Type::Variant(fields)
+                if self
+                    .lowering
+                    .enums
+                    .values()
+                    .any(|id| *id == self.values[value]) =>
+            {
+                fields.iter().map(|(label, _)| label.clone()).collect()
+            }
+            _ => {
+                return Err(unrepresentable(
+                    source,
+                    subject.span(),
+                    "only Boolean and enum decisions lower into a GEIR match",
+                ));
+            }
+        };
+        let selected = self.selected_rules(subject, &cases, rules)?;
+        let targets_by_rule = rules
+            .iter()
+            .map(|_| self.block(Vec::new()))
+            .collect::<Vec<_>>();
+        let targets = selected.iter().map(|rule| targets_by_rule[*rule]).collect();
+        self.terminate(Terminator::Match { value, targets });
+        let join = self.block(Vec::new());
+        let mut result_type = expected;
+        for (rule, block) in rules.iter().zip(targets_by_rule) {
+            self.enter(block);
+            let action = self.expression(&rule.action, result_type)?;
+            let action_type = self.values[action];
+            if result_type.is_some_and(|expected| expected != action_type) {
+                return Err(unrepresentable(
+                    source,
+                    rule.action.span(),
+                    "decision actions derive different GEIR types",
+                ));
+            }
+            result_type = Some(action_type);
+            self.terminate(Terminator::Branch {
+                target: join,
+                arguments: vec![action],
+            });
+        }
+        self.blocks[join].parameters = vec![result_type.expect("decisions have rules")];
+        self.enter(join);
+        Ok(0)
+    }
+
+    /// Select the first rule covering each subject case, in case order.
+    fn selected_rules(
+        &self,
+        subject: &Expression,
+        cases: &[String],
+        rules: &[DecisionRule],
+    ) -> Result<Vec<usize>, Diagnostic> {
+        let source = self.lowering.source;
+        let mut selected = vec![None; cases.len()];
+        for (index, rule) in rules.iter().enumerate() {
+            let covered = match &rule.matcher {
+                DecisionMatcher::Boolean { value, .. } if cases == ["false", "true"] => {
+                    vec![usize::from(*value)]
+                }
+                DecisionMatcher::Identifier(span) => {
+                    let name = source.slice(*span);
+                    let Some(case) = cases.iter().position(|case| case == name) else {
+                        return Err(diagnostic(
+                            source,
+                            "E-UNBOUND-NAME",
+                            *span,
+                            format!("`{name}` is not an alternative of the decision subject"),
+                        ));
+                    };
+                    vec![case]
+                }
+                DecisionMatcher::Otherwise(_) => (0..cases.len()).collect(),
+                _ => {
+                    return Err(unrepresentable(
+                        source,
+                        rule.span,
+                        "this decision matcher has no GEIR revision 1 lowering",
+                    ));
+                }
+            };
+            let mut reachable = false;
+            for case in covered {
+                if selected[case].is_none() {
+                    selected[case] = Some(index);
+                    reachable = true;
+                }
+            }
+            if !reachable {
+                return Err(diagnostic(
+                    source,
+                    "E-UNREACHABLE-DECISION-RULE",
+                    rule.span,
+                    "earlier rules already cover this decision rule",
+                ));
+            }
+        }
+        if selected.iter().any(Option::is_none) {
+            return Err(diagnostic(
+                source,
+                "E-INCOMPLETE-DECISION",
+                subject.span(),
+                "decision rules do not cover every subject value",
+            ));
+        }
+        Ok(selected.into_iter().flatten().collect())
+    }
+
+    /// Recognize a direct prefix or infix call of a lowered function.
+    fn call(
+        &self,
+        expression: &'a Expression,
+    ) -> Result<Option<(&'a str, Vec<&'a Expression>)>, Diagnostic> {
+        let Expression::Application { items, .. } = expression else {
+            return Ok(None);
+        };
+        let source = self.lowering.source;
+        let function = |item: &Expression| match item {
+            Expression::Identifier(span)
+                if !self.locals.contains_key(source.slice(*span))
+                    && self.lowering.signatures.contains_key(source.slice(*span)) =>
+            {
+                Some((source.slice(*span), *span))
+            }
+            _ => None,
+        };
+        let (name, span, arguments) = match items.as_slice() {
+            [callee, argument] => {
+                let Some((name, span)) = function(callee) else {
+                    return Ok(None);
+                };
+                let arguments = match argument {
+                    Expression::Unit(_) => Vec::new(),
+                    Expression::Product { fields, .. }
+                        if self.lowering.signatures[name].inputs.len() > 1
+                            && fields.iter().all(|field| field.label.is_none()) =>
+                    {
+                        fields.iter().map(|field| &field.value).collect()
+                    }
+                    argument => vec![argument],
+                };
+                (name, span, arguments)
+            }
+            [left, callee, right] => {
+                let Some((name, span)) = function(callee) else {
+                    return Ok(None);
+                };
+                (name, span, vec![left, right])
+            }
+            _ => return Ok(None),
+        };
+        if self.lowering.signatures[name].protocol.is_some() {
+            return Err(unrepresentable(
+                source,
+                span,
+                "generator activations have no GEIR revision 1 lowering",
+            ));
+        }
+        Ok(Some((name, arguments)))
+    }
+
+    fn arguments(
+        &mut self,
+        call: &'a Expression,
+        inputs: &[usize],
+        arguments: &[&'a Expression],
+    ) -> Result<Vec<usize>, Diagnostic> {
+        if inputs.len() != arguments.len() {
+            return Err(diagnostic(
+                self.lowering.source,
+                "E-FUNCTION-ARGUMENT-ARITY",
+                call.span(),
+                format!(
+                    "function expects {} arguments but received {}",
+                    inputs.len(),
+                    arguments.len()
+                ),
+            ));
+        }
+        let mut values = Vec::new();
+        for (argument, input) in arguments.iter().zip(inputs) {
+            let value = self.expression(argument, Some(*input))?;
+            if self.values[value] != *input {
+                return Err(diagnostic(
+                    self.lowering.source,
+                    "E-FUNCTION-ARGUMENT-TYPE",
+                    argument.span(),
+                    "argument does not satisfy its parameter classifier",
+                ));
+            }
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+fn statement_span(statement: &Statement) -> Span {
+    match statement {
+        Statement::LanguageSelection { span, .. }
+        | Statement::Published { span, .. }
+        | Statement::DiagnosticControl { span, .. }
+        | Statement::Implementation { span, .. }
+        | Statement::ContextAssignment { span, .. }
+        | Statement::Function { span, .. }
+        | Statement::Generator { span, .. }
+        | Statement::Union { span, .. }
+        | Statement::Interface { span, .. }
+        | Statement::InterfaceImplementation { span, .. }
+        | Statement::Foreach { span, .. }
+        | Statement::Discard { span, .. } => *span,
+        Statement::Binding { name, .. } | Statement::StateField { name, .. } => *name,
+        Statement::Return { keyword, .. } => *keyword,
+        Statement::Expression(expression) => expression.span(),
+    }
+}
+
+fn unrepresentable(source: &SourceText, span: Span, message: impl Into<String>) -> Diagnostic {
+    diagnostic(source, UNREPRESENTABLE_ERROR_CODE, span, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use topal_geir::{ArtifactLimits, decode_canonical};
+
+    fn lower(body: &str) -> Result<Module, Diagnostic> {
+        lower_source_file(
+            &format!("use language (\n  version is v0.1\n)\n{body}"),
+            "example",
+            &["main"],
+            ToolRole::Compiler,
+        )
+    }
+
+    #[test]
+    fn lowered_functions_enums_and_decisions_round_trip_canonically() {
+        let input = "use language (\n  version is v0.1\n)\nColor is Enum (Red, Green)\npub choose is fn (condition : Boolean) -> Color\n  condition\n    true then Red\n    otherwise Green\nidentity is fn (value : Color) -> Color\n  value\n(identity (choose true), 50)\n";
+        let bytes = export_source_file(input, "example", &["main"], ToolRole::Compiler)
+            .expect("source lowers");
+        let module = decode_canonical(&bytes, ArtifactLimits::default()).expect("decodes");
+        assert_eq!(module.validate().expect("valid").canonical_bytes(), bytes);
+        assert_eq!(module.functions.len(), 3);
+        assert_eq!(module.identities[0].declaration_path, Vec::<String>::new());
+        assert_eq!(module.exports, vec![1]);
+        assert_eq!(module.functions[1].visibility, Visibility::Public);
+        assert!(matches!(
+            module.functions[1].blocks[0].terminator,
+            Terminator::Match { .. }
+        ));
+        assert!(matches!(
+            module.functions[0].blocks[0].terminator,
+            Terminator::Return(_)
+        ));
+    }
+
+    #[test]
+    fn final_calls_lower_as_tail_applications() {
+        let module = lower(
+            "answer is fn () -> Int\n  increment 41\nincrement is fn (input : Int) -> Int\n  input\n",
+        )
+        .expect("source lowers");
+        assert_eq!(
+            module.functions[0].blocks[0].terminator,
+            Terminator::TailApply {
+                function: 1,
+                arguments: vec![0],
+            }
+        );
+    }
+
+    #[test]
+    fn generators_lower_yields_into_resumption_blocks() {
+        let module = lower(
+            "once is generator ( initial : Character )\n  yields Character\n  resumes Unit\n  -> Unit\n\n  resumed is yield initial\n  resumed\n",
+        )
+        .expect("generator lowers");
+        let blocks = &module.functions[0].blocks;
+        assert_eq!(
+            blocks[0].terminator,
+            Terminator::Yield {
+                value: 0,
+                resume: 1,
+            }
+        );
+        assert_eq!(blocks[1].terminator, Terminator::Return(0));
+    }
+
+    #[test]
+    fn lowering_rejects_semantics_absent_from_the_artifact() {
+        let arithmetic = lower("add is fn (left : Int, right : Int) -> Int\n  left + right\n")
+            .expect_err("arithmetic has no GEIR operation");
+        assert_eq!(arithmetic.code, UNREPRESENTABLE_ERROR_CODE);
+        let captured = lower(
+            "choose is fn (condition : Boolean, fallback : Int) -> Int\n  condition\n    true then 1\n    otherwise fallback\n",
+        )
+        .expect_err("match targets receive no values");
+        assert_eq!(captured.code, UNREPRESENTABLE_ERROR_CODE);
+        assert!(captured.message.contains("`fallback` is not live"));
+        let callable = lower("identity is fn (value : Int) -> Int\n  value\nidentity\n")
+            .expect_err("function values are not artifact values");
+        assert_eq!(callable.code, UNREPRESENTABLE_ERROR_CODE);
+    }
+
+    #[test]
+    fn lowering_is_available_only_to_the_compiler() {
+        for tool in [
+            ToolRole::Interpreter,
+            ToolRole::Debugger,
+            ToolRole::LanguageServer,
+            ToolRole::Linter,
+        ] {
+            let error = lower_source_file(
+                "use language (\n  version is v0.1\n)\n1\n",
+                "example",
+                &[],
+                tool,
+            )
+            .expect_err("non-compiler tool");
+            assert_eq!(error.code, topal_geir::COMPILER_ONLY_ERROR_CODE);
+        }
+    }
+}
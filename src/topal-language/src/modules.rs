@@ -1,9 +1,12 @@
 //! Shared filesystem module loading for interpreters and compiler frontends.
 
+use std::cell::{Cell, RefCell};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::{Session, TraceSink};
+use crate::{
+    ExecutionHistory, ExecutionSnapshot, RecordedObservation, Session, TraceEvent, TraceSink,
+};
 
 /// Load every ordinary source module and constructed child module below one
 /// package directory into an existing session.
@@ -21,21 +24,297 @@ pub fn load_module_tree(
     directory: &Path,
     trace: &mut impl TraceSink,
 ) -> Result<(), String> {
-    let mut paths = fs::read_dir(directory)
-        .map_err(|error| format!("cannot read {}: {error}", directory.display()))?
-        .map(|entry| entry.map(|entry| entry.path()))
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|error| error.to_string())?;
-    paths.sort();
-    for path in &paths {
-        if path.is_dir() {
-            load_child_module(session, path, trace)?;
+    load_tree(session, directory, &FileSystem, trace)
+}
+
+/// Load a module tree as [`load_module_tree`] does, appending every
+/// filesystem observation the loader makes to `observations` at the history
+/// cursor where it was made.
+///
+/// # Errors
+///
+/// Returns the error [`load_module_tree`] would return. The observations made
+/// before the failure remain recorded.
+pub fn record_module_tree(
+    session: &mut Session,
+    directory: &Path,
+    history: &mut ExecutionHistory,
+    observations: &mut Vec<RecordedObservation>,
+) -> Result<(), String> {
+    let cursor = Cell::new(history.cursor());
+    let tree = Recorder {
+        cursor: &cursor,
+        observations: RefCell::new(observations),
+    };
+    load_tree(
+        session,
+        directory,
+        &tree,
+        &mut Cursor {
+            history,
+            cursor: &cursor,
+        },
+    )
+}
+
+/// Load a module tree from `directory` by answering each filesystem query
+/// with the next of the recorded `observations` instead of repeating it.
+///
+/// # Errors
+///
+/// Returns a description of the first query that differs from the recorded
+/// observation at the same cursor, the error the load returns, or a
+/// description of a recorded observation the load leaves unconsumed.
+pub fn replay_module_tree(
+    session: &mut Session,
+    directory: &Path,
+    observations: &[RecordedObservation],
+    history: &mut ExecutionHistory,
+) -> Result<(), String> {
+    let cursor = Cell::new(history.cursor());
+    let tree = Replayer {
+        cursor: &cursor,
+        observations,
+        next: Cell::new(0),
+        divergence: RefCell::new(None),
+    };
+    let loaded = load_tree(
+        session,
+        directory,
+        &tree,
+        &mut Cursor {
+            history,
+            cursor: &cursor,
+        },
+    );
+    if let Some(divergence) = tree.divergence.into_inner() {
+        return Err(divergence);
+    }
+    loaded?;
+    if let Some(unconsumed) = observations.get(tree.next.get()) {
+        return Err(format!(
+            "replay does not consume the recorded `{}` observation at transition #{}",
+            unconsumed.kind, unconsumed.cursor
+        ));
+    }
+    Ok(())
+}
+
+/// The directories and source files a module tree loads from.
+trait SourceTree {
+    /// The paths directly below `directory`, sorted.
+    fn entries(&self, directory: &Path) -> Result<Vec<PathBuf>, String>;
+    fn is_dir(&self, path: &Path) -> bool;
+    fn is_file(&self, path: &Path) -> bool;
+    fn read(&self, path: &Path) -> Result<String, String>;
+}
+
+struct FileSystem;
+
+impl SourceTree for FileSystem {
+    fn entries(&self, directory: &Path) -> Result<Vec<PathBuf>, String> {
+        let mut paths = fs::read_dir(directory)
+            .map_err(|error| format!("cannot read {}: {error}", directory.display()))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        paths.sort();
+        Ok(paths)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn read(&self, path: &Path) -> Result<String, String> {
+        fs::read_to_string(path).map_err(|error| format!("cannot read {}: {error}", path.display()))
+    }
+}
+
+/// Forwards trace events to the history and publishes its cursor to the
+/// source tree observing the same load.
+struct Cursor<'a> {
+    history: &'a mut ExecutionHistory,
+    cursor: &'a Cell<usize>,
+}
+
+impl TraceSink for Cursor<'_> {
+    fn record(&mut self, event: TraceEvent<'_>) {
+        self.history.record(event);
+        self.cursor.set(self.history.cursor());
+    }
+
+    fn checkpoint(&mut self, snapshot: ExecutionSnapshot<'_>) {
+        self.history.checkpoint(snapshot);
+    }
+
+    fn redact(&mut self, text: &str) {
+        self.history.redact(text);
+    }
+}
+
+/// One filesystem query, named as its observation is recorded: the query
+/// kind followed by the path it asks about.
+fn query(kind: &str, path: &Path) -> String {
+    format!("{kind} {}", path.display())
+}
+
+/// Directory entries are recorded one per line, since recorded text is
+/// source text.
+fn entry_list(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The filesystem, with each answer recorded at the current cursor. A failed
+/// query is recorded under its kind with an `-error` suffix.
+struct Recorder<'a> {
+    cursor: &'a Cell<usize>,
+    observations: RefCell<&'a mut Vec<RecordedObservation>>,
+}
+
+impl Recorder<'_> {
+    fn observe(&self, kind: &str, path: &Path, result: String) {
+        self.observations.borrow_mut().push(RecordedObservation {
+            cursor: self.cursor.get(),
+            kind: query(kind, path),
+            result,
+        });
+    }
+
+    fn observe_result(&self, kind: &str, path: &Path, result: Result<String, String>) {
+        match result {
+            Ok(result) => self.observe(kind, path, result),
+            Err(error) => self.observe(&format!("{kind}-error"), path, error),
+        }
+    }
+}
+
+impl SourceTree for Recorder<'_> {
+    fn entries(&self, directory: &Path) -> Result<Vec<PathBuf>, String> {
+        let entries = FileSystem.entries(directory);
+        self.observe_result(
+            "directory",
+            directory,
+            entries.as_deref().map(entry_list).map_err(Clone::clone),
+        );
+        entries
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        let is_dir = FileSystem.is_dir(path);
+        self.observe("is-directory", path, is_dir.to_string());
+        is_dir
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        let is_file = FileSystem.is_file(path);
+        self.observe("is-file", path, is_file.to_string());
+        is_file
+    }
+
+    fn read(&self, path: &Path) -> Result<String, String> {
+        let text = FileSystem.read(path);
+        self.observe_result("read", path, text.clone());
+        text
+    }
+}
+
+/// Recorded observations in place of the filesystem. A query that differs
+/// from the next observation ends the replay with its divergence.
+struct Replayer<'a> {
+    cursor: &'a Cell<usize>,
+    observations: &'a [RecordedObservation],
+    next: Cell<usize>,
+    divergence: RefCell<Option<String>>,
+}
+
+impl Replayer<'_> {
+    /// The recorded answer to `kind` about `path`, or `None` after noting
+    /// the divergence when the recording observed something else.
+    fn answer(&self, kind: &str, path: &Path) -> Option<Result<&str, &str>> {
+        if self.divergence.borrow().is_some() {
+            return None;
+        }
+        let cursor = self.cursor.get();
+        let asked = query(kind, path);
+        let failed = query(&format!("{kind}-error"), path);
+        let Some(observation) = self.observations.get(self.next.get()) else {
+            *self.divergence.borrow_mut() = Some(format!(
+                "replay asks for `{asked}` at transition #{cursor} after the last recorded observation"
+            ));
+            return None;
+        };
+        if observation.cursor != cursor || (observation.kind != asked && observation.kind != failed)
+        {
+            *self.divergence.borrow_mut() = Some(format!(
+                "replay asks for `{asked}` at transition #{cursor} where the recording observed `{}` at transition #{}",
+                observation.kind, observation.cursor
+            ));
+            return None;
+        }
+        self.next.set(self.next.get() + 1);
+        Some(if observation.kind == asked {
+            Ok(&observation.result)
+        } else {
+            Err(&observation.result)
+        })
+    }
+
+    fn replayed(&self, kind: &str, path: &Path) -> Result<&str, String> {
+        match self.answer(kind, path) {
+            Some(Ok(result)) => Ok(result),
+            Some(Err(error)) => Err(error.to_owned()),
+            None => Err(format!("cannot replay `{}`", query(kind, path))),
+        }
+    }
+
+    fn flag(&self, kind: &str, path: &Path) -> bool {
+        matches!(self.answer(kind, path), Some(Ok("true")))
+    }
+}
+
+impl SourceTree for Replayer<'_> {
+    fn entries(&self, directory: &Path) -> Result<Vec<PathBuf>, String> {
+        let entries = self.replayed("directory", directory)?;
+        Ok(entries.lines().map(PathBuf::from).collect())
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.flag("is-directory", path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.flag("is-file", path)
+    }
+
+    fn read(&self, path: &Path) -> Result<String, String> {
+        self.replayed("read", path).map(str::to_owned)
+    }
+}
+
+fn load_tree<T: SourceTree + ?Sized>(
+    session: &mut Session,
+    directory: &Path,
+    tree: &T,
+    trace: &mut impl TraceSink,
+) -> Result<(), String> {
+    for path in &tree.entries(directory)? {
+        if tree.is_dir(path) {
+            load_child_module(session, path, tree, trace)?;
         } else if is_ordinary_source(path) {
             let name = path
                 .file_stem()
                 .ok_or_else(|| format!("source {} has no module name", path.display()))?
                 .to_string_lossy();
-            let source = read_source(path)?;
+            let source = tree.read(path)?;
             session
                 .load_module(&name, &source, trace)
                 .map_err(|error| error.render(&path.display().to_string()))?;
@@ -44,20 +323,88 @@ pub fn load_module_tree(
     Ok(())
 }
 
-fn load_child_module(
+/// One source file evaluated by [`load_module_tree`], with the module path
+/// under which its published declarations become visible.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ModuleSource {
+    pub module: Vec<String>,
+    pub path: PathBuf,
+}
+
+/// List the sources [`load_module_tree`] evaluates below `directory`, in the
+/// same order and under the same canonical module components.
+///
+/// A child directory contributes its `module.t` descriptor under the child's
+/// own path, followed by the ordinary sources below it. Source tools use this
+/// to resolve qualified names without evaluating the package.
+///
+/// # Errors
+///
+/// Returns a rendered filesystem error when a directory cannot be read.
+pub fn module_sources(directory: &Path) -> Result<Vec<ModuleSource>, String> {
+    let mut sources = Vec::new();
+    collect_module_sources(directory, &[], &mut sources)?;
+    Ok(sources)
+}
+
+fn collect_module_sources(
+    directory: &Path,
+    module: &[String],
+    sources: &mut Vec<ModuleSource>,
+) -> Result<(), String> {
+    let mut paths = fs::read_dir(directory)
+        .map_err(|error| format!("cannot read {}: {error}", directory.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+    paths.sort();
+    for path in paths {
+        let name = if path.is_dir() {
+            path.file_name()
+        } else if is_ordinary_source(&path) {
+            path.file_stem()
+        } else {
+            continue;
+        };
+        let Some(name) = name else {
+            continue;
+        };
+        let mut child = module.to_vec();
+        child.push(name.to_string_lossy().into_owned());
+        if path.is_dir() {
+            let descriptor = path.join("module.t");
+            if descriptor.is_file() {
+                sources.push(ModuleSource {
+                    module: child.clone(),
+                    path: descriptor,
+                });
+            }
+            collect_module_sources(&path, &child, sources)?;
+        } else {
+            sources.push(ModuleSource {
+                module: child,
+                path,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn load_child_module<T: SourceTree + ?Sized>(
     parent: &mut Session,
     path: &Path,
+    tree: &T,
     trace: &mut impl TraceSink,
 ) -> Result<(), String> {
-    let mut child = Session::new();
+    let mut child = parent.module_scope();
     let descriptor = path.join("module.t");
-    if descriptor.is_file() {
-        let source = read_source(&descriptor)?;
+    if tree.is_file(&descriptor) {
+        let source = tree.read(&descriptor)?;
         child
             .evaluate_source_file(&source, trace)
             .map_err(|error| error.render(&descriptor.display().to_string()))?;
     }
-    load_module_tree(&mut child, path, trace)?;
+    load_tree(&mut child, path, tree, trace)?;
     let name = path
         .file_name()
         .ok_or_else(|| format!("module {} has no name", path.display()))?
@@ -68,10 +415,6 @@ fn load_child_module(
         .map_err(|error| error.render(&path.display().to_string()))
 }
 
-fn read_source(path: &Path) -> Result<String, String> {
-    fs::read_to_string(path).map_err(|error| format!("cannot read {}: {error}", path.display()))
-}
-
 fn is_ordinary_source(path: &Path) -> bool {
     path.extension().is_some_and(|extension| extension == "t")
         && path.file_name().is_some_and(|name| {
@@ -104,6 +447,87 @@ mod tests {
         assert!(trace.iter().any(|event| event.contains("module.loaded")));
     }
 
+    #[test]
+    fn replayed_observations_load_the_same_module_tree() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../library");
+        let mut loaded = ExecutionHistory::new();
+        load_module_tree(&mut Session::new(), &root, &mut loaded).unwrap();
+        let mut recorded = ExecutionHistory::new();
+        let mut observations = Vec::new();
+        record_module_tree(&mut Session::new(), &root, &mut recorded, &mut observations).unwrap();
+        assert_eq!(recorded, loaded);
+        let read = observations
+            .iter()
+            .find(|observation| observation.kind == query("read", &root.join("std/module.t")))
+            .unwrap();
+        assert!(read.result.contains("use language ("));
+
+        let mut replayed = ExecutionHistory::new();
+        replay_module_tree(&mut Session::new(), &root, &observations, &mut replayed).unwrap();
+        assert_eq!(replayed, loaded);
+
+        // Replay answers from the recording, not the filesystem.
+        let mut moved = ExecutionHistory::new();
+        let elsewhere = Path::new("/nonexistent/library");
+        let error = replay_module_tree(&mut Session::new(), elsewhere, &observations, &mut moved)
+            .unwrap_err();
+        assert!(error.starts_with("replay asks for `directory /nonexistent/library` at transition #0 where the recording observed `directory "));
+
+        let mut edited = observations.clone();
+        let read = edited
+            .iter_mut()
+            .find(|observation| observation.kind.starts_with("read "))
+            .unwrap();
+        read.result = "use language ( version is v0.1 )\nmissing\n".to_owned();
+        let error = replay_module_tree(
+            &mut Session::new(),
+            &root,
+            &edited,
+            &mut ExecutionHistory::new(),
+        )
+        .unwrap_err();
+        assert!(error.contains("E-"), "{error}");
+
+        let mut extra = observations;
+        extra.push(RecordedObservation {
+            cursor: 0,
+            kind: "clock".to_owned(),
+            result: "0".to_owned(),
+        });
+        let error = replay_module_tree(
+            &mut Session::new(),
+            &root,
+            &extra,
+            &mut ExecutionHistory::new(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error,
+            "replay does not consume the recorded `clock` observation at transition #0"
+        );
+    }
+
+    #[test]
+    fn module_sources_follow_the_loaded_library_tree() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../library");
+        let sources = module_sources(&root).unwrap();
+        let std = sources
+            .iter()
+            .position(|source| source.module == ["std"])
+            .unwrap();
+        assert!(sources[std].path.ends_with("std/module.t"));
+        let addresses = sources
+            .iter()
+            .position(|source| source.module == ["std", "network", "addresses"])
+            .unwrap();
+        assert!(std < addresses);
+        assert!(
+            sources
+                .iter()
+                .all(|source| !source.path.ends_with("application.t"))
+        );
+    }
+
     #[test]
     fn shared_library_ordering_retains_one_exact_generic_type() {
         let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../library");
@@ -0,0 +1,582 @@
+//! Shareable recordings of one debugged execution.
+//!
+//! A recording is one native serialization stream carrying the debuggee's
+//! sources, the transitions and state checkpoints its execution recorded, and
+//! the external observations replay consumes instead of repeating. Each
+//! observation is the answer to one query outside the program, such as a
+//! module tree directory listing or source read, kept at the history cursor
+//! where it was made. Recordings are shared files, so both directions stay
+//! within the default serialization [`Limits`]. Replay reconstructs Topal
+//! state by executing the recorded sources and compares the reconstructed
+//! history with the recorded one, so a recorded value is kept only as the
+//! text the debugger displays for it.
+
+use topal_semantics::LanguageVersion;
+use topal_serialization::{
+    Event, Header, Limits, SerializedValue, Stream, StreamByteOrder, TypeDefinition, deserialize,
+    serialize,
+};
+
+use crate::{ExecutionHistory, ExecutionTransition, SourceRange};
+
+/// The recording format version this toolchain writes and replays.
+pub const RECORDING_FORMAT: u64 = 1;
+
+const RECORDING_IDENTITY: &str = "topal-debug-recording";
+
+const UNIT: usize = 0;
+const COUNT: usize = 1;
+const TEXT: usize = 2;
+const OPTIONAL_COUNT: usize = 3;
+const OPTIONAL_TEXT: usize = 4;
+const RECORDING: usize = 5;
+const MODULE_SOURCE: usize = 6;
+const TRANSITION: usize = 7;
+const BINDING_CHANGE: usize = 8;
+const BINDING_CHANGES: usize = 9;
+const SOURCE_RANGE: usize = 10;
+const OPTIONAL_SOURCE_RANGE: usize = 11;
+const CHECKPOINT: usize = 12;
+const OBSERVATION: usize = 13;
+
+/// One module source evaluated before the entry source.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordedSource {
+    pub path: String,
+    pub text: String,
+}
+
+/// An [`ExecutionTransition`] with its event and rule identities owned.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordedTransition {
+    pub event: String,
+    pub rule: String,
+    pub detail: String,
+    pub transaction: Option<u64>,
+    pub sender: Option<u64>,
+    pub receiver: Option<u64>,
+}
+
+impl From<&ExecutionTransition> for RecordedTransition {
+    fn from(transition: &ExecutionTransition) -> Self {
+        Self {
+            event: transition.event.to_owned(),
+            rule: transition.rule.to_owned(),
+            detail: transition.detail.clone(),
+            transaction: transition.transaction,
+            sender: transition.sender,
+            receiver: transition.receiver,
+        }
+    }
+}
+
+/// One state checkpoint, with the bindings it changes and its value as
+/// displayed text. A removed binding has no value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordedCheckpoint {
+    pub cursor: usize,
+    pub source_range: Option<SourceRange>,
+    pub binding_changes: Vec<(String, Option<String>)>,
+    pub value: Option<String>,
+}
+
+/// The result of one external observation at a history cursor.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordedObservation {
+    pub cursor: usize,
+    pub kind: String,
+    pub result: String,
+}
+
+/// A versioned record of one execution that replays without its original
+/// inputs.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Recording {
+    /// The entry source as the debugger named it.
+    pub source_name: String,
+    pub source: String,
+    /// The module tree directory loaded before the entry source, if any.
+    pub module_root: Option<String>,
+    pub modules: Vec<RecordedSource>,
+    pub transitions: Vec<RecordedTransition>,
+    pub checkpoints: Vec<RecordedCheckpoint>,
+    /// External observations in the order the execution made them.
+    pub observations: Vec<RecordedObservation>,
+    /// The rendered diagnostic that stopped execution, if it failed.
+    pub failure: Option<String>,
+}
+
+impl Recording {
+    /// Record the transitions and checkpoints of `history`.
+    pub fn record_history(&mut self, history: &ExecutionHistory) {
+        self.transitions = history
+            .transitions()
+            .iter()
+            .map(RecordedTransition::from)
+            .collect();
+        self.checkpoints = history.recorded_checkpoints();
+    }
+
+    /// Describe the first difference between the recorded history and
+    /// `history`, reconstructed by executing the recorded sources.
+    #[must_use]
+    pub fn divergence(&self, history: &ExecutionHistory) -> Option<String> {
+        let transitions = history.transitions();
+        if let Some(sequence) = (0..self.transitions.len().max(transitions.len())).find(|index| {
+            transitions.get(*index).map(RecordedTransition::from)
+                != self.transitions.get(*index).cloned()
+        }) {
+            return Some(match self.transitions.get(sequence) {
+                Some(recorded) => format!(
+                    "transition #{sequence} does not reproduce recorded {} [{}] {}",
+                    recorded.event, recorded.rule, recorded.detail
+                ),
+                None => format!(
+                    "replay continues past the {} recorded transitions",
+                    self.transitions.len()
+                ),
+            });
+        }
+        let checkpoints = history.recorded_checkpoints();
+        (0..self.checkpoints.len().max(checkpoints.len()))
+            .find(|index| checkpoints.get(*index) != self.checkpoints.get(*index))
+            .map(|index| {
+                let cursor = self
+                    .checkpoints
+                    .get(index)
+                    .or_else(|| checkpoints.get(index))
+                    .map_or(0, |checkpoint| checkpoint.cursor);
+                format!("state at transition #{cursor} does not reproduce the recorded state")
+            })
+    }
+
+    /// Encode the recording as one native serialization stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns the protocol error for recorded text the stream cannot carry,
+    /// or why the stream exceeds the limits [`Recording::decode`] accepts.
+    pub fn encode(&self) -> Result<Vec<u8>, String> {
+        let mut events = vec![Event {
+            type_id: RECORDING,
+            value: SerializedValue::Product(vec![
+                SerializedValue::Int(i128::from(RECORDING_FORMAT)),
+                text(&self.source_name),
+                text(&self.source),
+                optional(self.module_root.as_deref().map(text)),
+                optional(self.failure.as_deref().map(text)),
+            ]),
+        }];
+        events.extend(self.modules.iter().map(|module| Event {
+            type_id: MODULE_SOURCE,
+            value: SerializedValue::Product(vec![text(&module.path), text(&module.text)]),
+        }));
+        events.extend(self.transitions.iter().map(|transition| {
+            let identity = |identity: Option<u64>| optional(identity.map(number));
+            Event {
+                type_id: TRANSITION,
+                value: SerializedValue::Product(vec![
+                    text(&transition.event),
+                    text(&transition.rule),
+                    text(&transition.detail),
+                    identity(transition.transaction),
+                    identity(transition.sender),
+                    identity(transition.receiver),
+                ]),
+            }
+        }));
+        events.extend(self.checkpoints.iter().map(|checkpoint| Event {
+            type_id: CHECKPOINT,
+            value: SerializedValue::Product(vec![
+                count(checkpoint.cursor),
+                optional(checkpoint.source_range.map(|range| {
+                    SerializedValue::Product(vec![count(range.start), count(range.end)])
+                })),
+                SerializedValue::Sequence(
+                    checkpoint
+                        .binding_changes
+                        .iter()
+                        .map(|(name, value)| {
+                            SerializedValue::Product(vec![
+                                text(name),
+                                optional(value.as_deref().map(text)),
+                            ])
+                        })
+                        .collect(),
+                ),
+                optional(checkpoint.value.as_deref().map(text)),
+            ]),
+        }));
+        events.extend(self.observations.iter().map(|observation| Event {
+            type_id: OBSERVATION,
+            value: SerializedValue::Product(vec![
+                count(observation.cursor),
+                text(&observation.kind),
+                text(&observation.result),
+            ]),
+        }));
+        let bytes = serialize(&Stream {
+            header: Header {
+                language_identity: RECORDING_IDENTITY.into(),
+                language_version: LanguageVersion::DESIGN_0,
+                byte_order: StreamByteOrder::Little,
+                streaming: false,
+            },
+            types: types(),
+            events,
+        })
+        .map_err(|error| error.to_string())?;
+        deserialize(&bytes, Limits::default())
+            .map_err(|error| format!("recording exceeds the limits replay accepts: {error}"))?;
+        Ok(bytes)
+    }
+
+    /// Decode a recording written by [`Recording::encode`].
+    ///
+    /// # Errors
+    ///
+    /// Returns why `bytes` are not a recording in a supported format.
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let stream = deserialize(bytes, Limits::default()).map_err(|error| error.to_string())?;
+        if stream.header.language_identity != RECORDING_IDENTITY {
+            return Err("not a Topal debugger recording".into());
+        }
+        let mut events = stream.events.iter();
+        let Some(Event {
+            type_id: RECORDING,
+            value: SerializedValue::Product(fields),
+        }) = events.next()
+        else {
+            return Err(malformed());
+        };
+        let [format, source_name, source, module_root, failure] = fields.as_slice() else {
+            return Err(malformed());
+        };
+        let format = as_number(format)?;
+        if format != RECORDING_FORMAT {
+            return Err(format!(
+                "unsupported recording format {format}; this debugger replays format {RECORDING_FORMAT}"
+            ));
+        }
+        if stream.types != types() {
+            return Err(malformed());
+        }
+        let mut recording = Self {
+            source_name: as_text(source_name)?,
+            source: as_text(source)?,
+            module_root: as_optional(module_root)?.map(as_text).transpose()?,
+            failure: as_optional(failure)?.map(as_text).transpose()?,
+            ..Self::default()
+        };
+        for event in events {
+            let SerializedValue::Product(fields) = &event.value else {
+                return Err(malformed());
+            };
+            match (event.type_id, fields.as_slice()) {
+                (MODULE_SOURCE, [path, text]) => recording.modules.push(RecordedSource {
+                    path: as_text(path)?,
+                    text: as_text(text)?,
+                }),
+                (TRANSITION, [event, rule, detail, transaction, sender, receiver]) => {
+                    let identity = |value| as_optional(value)?.map(as_number).transpose();
+                    recording.transitions.push(RecordedTransition {
+                        event: as_text(event)?,
+                        rule: as_text(rule)?,
+                        detail: as_text(detail)?,
+                        transaction: identity(transaction)?,
+                        sender: identity(sender)?,
+                        receiver: identity(receiver)?,
+                    });
+                }
+                (CHECKPOINT, [cursor, source_range, binding_changes, value]) => {
+                    recording.checkpoints.push(RecordedCheckpoint {
+                        cursor: as_count(cursor)?,
+                        source_range: as_optional(source_range)?
+                            .map(as_source_range)
+                            .transpose()?,
+                        binding_changes: as_binding_changes(binding_changes)?,
+                        value: as_optional(value)?.map(as_text).transpose()?,
+                    });
+                }
+                (OBSERVATION, [cursor, kind, result]) => {
+                    recording.observations.push(RecordedObservation {
+                        cursor: as_count(cursor)?,
+                        kind: as_text(kind)?,
+                        result: as_text(result)?,
+                    });
+                }
+                _ => return Err(malformed()),
+            }
+        }
+        Ok(recording)
+    }
+}
+
+/// The type table of format [`RECORDING_FORMAT`], indexed by the type ids
+/// above.
+fn types() -> Vec<TypeDefinition> {
+    let record = |identity: &str, fields: &[(&str, usize)]| TypeDefinition::Record {
+        identity: identity.into(),
+        fields: fields
+            .iter()
+            .map(|(label, type_id)| ((*label).to_owned(), *type_id))
+            .collect(),
+    };
+    let optional = |identity: &str, some: usize| TypeDefinition::Variant {
+        identity: identity.into(),
+        alternatives: vec![("none".into(), UNIT), ("some".into(), some)],
+    };
+    vec![
+        TypeDefinition::Unit {
+            identity: "Unit".into(),
+        },
+        TypeDefinition::Int {
+            identity: "Count".into(),
+            signed: false,
+            width_bits: 64,
+        },
+        TypeDefinition::Text {
+            identity: "Text".into(),
+        },
+        optional("Optional-count", COUNT),
+        optional("Optional-text", TEXT),
+        record(
+            "Recording",
+            &[
+                ("format", COUNT),
+                ("source-name", TEXT),
+                ("source", TEXT),
+                ("module-root", OPTIONAL_TEXT),
+                ("failure", OPTIONAL_TEXT),
+            ],
+        ),
+        record("Module-source", &[("path", TEXT), ("text", TEXT)]),
+        record(
+            "Transition",
+            &[
+                ("event", TEXT),
+                ("rule", TEXT),
+                ("detail", TEXT),
+                ("transaction", OPTIONAL_COUNT),
+                ("sender", OPTIONAL_COUNT),
+                ("receiver", OPTIONAL_COUNT),
+            ],
+        ),
+        record(
+            "Binding-change",
+            &[("name", TEXT), ("value", OPTIONAL_TEXT)],
+        ),
+        TypeDefinition::Sequence {
+            identity: "Binding-changes".into(),
+            element: BINDING_CHANGE,
+        },
+        TypeDefinition::Tuple {
+            identity: "Source-range".into(),
+            components: vec![COUNT, COUNT],
+        },
+        optional("Optional-source-range", SOURCE_RANGE),
+        record(
+            "Checkpoint",
+            &[
+                ("cursor", COUNT),
+                ("source-range", OPTIONAL_SOURCE_RANGE),
+                ("binding-changes", BINDING_CHANGES),
+                ("value", OPTIONAL_TEXT),
+            ],
+        ),
+        record(
+            "Observation",
+            &[("cursor", COUNT), ("kind", TEXT), ("result", TEXT)],
+        ),
+    ]
+}
+
+fn text(value: &str) -> SerializedValue {
+    SerializedValue::Text(value.to_owned())
+}
+
+fn number(value: u64) -> SerializedValue {
+    SerializedValue::Int(i128::from(value))
+}
+
+fn count(value: usize) -> SerializedValue {
+    number(value as u64)
+}
+
+fn optional(value: Option<SerializedValue>) -> SerializedValue {
+    match value {
+        None => SerializedValue::Variant {
+            alternative: 0,
+            value: Box::new(SerializedValue::Unit),
+        },
+        Some(value) => SerializedValue::Variant {
+            alternative: 1,
+            value: Box::new(value),
+        },
+    }
+}
+
+fn malformed() -> String {
+    "malformed debugger recording".into()
+}
+
+fn as_text(value: &SerializedValue) -> Result<String, String> {
+    match value {
+        SerializedValue::Text(text) => Ok(text.clone()),
+        _ => Err(malformed()),
+    }
+}
+
+fn as_number(value: &SerializedValue) -> Result<u64, String> {
+    match value {
+        SerializedValue::Int(number) => u64::try_from(*number).map_err(|_| malformed()),
+        _ => Err(malformed()),
+    }
+}
+
+fn as_count(value: &SerializedValue) -> Result<usize, String> {
+    usize::try_from(as_number(value)?).map_err(|_| malformed())
+}
+
+fn as_optional(value: &SerializedValue) -> Result<Option<&SerializedValue>, String> {
+    match value {
+        SerializedValue::Variant { alternative: 0, .. } => Ok(None),
+        SerializedValue::Variant {
+            alternative: 1,
+            value,
+        } => Ok(Some(value)),
+        _ => Err(malformed()),
+    }
+}
+
+fn as_source_range(value: &SerializedValue) -> Result<SourceRange, String> {
+    match value {
+        SerializedValue::Product(bounds) => match bounds.as_slice() {
+            [start, end] => Ok(SourceRange {
+                start: as_count(start)?,
+                end: as_count(end)?,
+            }),
+            _ => Err(malformed()),
+        },
+        _ => Err(malformed()),
+    }
+}
+
+fn as_binding_changes(value: &SerializedValue) -> Result<Vec<(String, Option<String>)>, String> {
+    let SerializedValue::Sequence(changes) = value else {
+        return Err(malformed());
+    };
+    changes
+        .iter()
+        .map(|change| match change {
+            SerializedValue::Product(fields) => match fields.as_slice() {
+                [name, value] => Ok((
+                    as_text(name)?,
+                    as_optional(value)?.map(as_text).transpose()?,
+                )),
+                _ => Err(malformed()),
+            },
+            _ => Err(malformed()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Session;
+
+    #[test]
+    fn round_trips_a_recorded_history_through_a_native_stream() {
+        let source = "use language ( version is v0.1 )\nanswer is 40\nanswer + 2";
+        let mut history = ExecutionHistory::new();
+        Session::new()
+            .evaluate_source_file(source, &mut history)
+            .unwrap();
+        let mut recording = Recording {
+            source_name: "answer.t".into(),
+            source: source.into(),
+            observations: vec![RecordedObservation {
+                cursor: 3,
+                kind: "clock".into(),
+                result: "0".into(),
+            }],
+            ..Recording::default()
+        };
+        recording.record_history(&history);
+        assert!(recording.checkpoints.iter().any(|checkpoint| {
+            checkpoint.binding_changes == [("answer".to_owned(), Some("40".to_owned()))]
+        }));
+        let decoded = Recording::decode(&recording.encode().unwrap()).unwrap();
+        assert_eq!(decoded, recording);
+        assert_eq!(decoded.divergence(&history), None);
+
+        let mut changed = ExecutionHistory::new();
+        Session::new()
+            .evaluate_source_file(&source.replace("40", "41"), &mut changed)
+            .unwrap();
+        assert!(
+            decoded
+                .divergence(&changed)
+                .unwrap()
+                .contains("does not reproduce recorded")
+        );
+    }
+
+    #[test]
+    fn rejects_streams_that_are_not_supported_recordings() {
+        let stream = |identity: &str, format: i128| Stream {
+            header: Header {
+                language_identity: identity.into(),
+                language_version: LanguageVersion::DESIGN_0,
+                byte_order: StreamByteOrder::Little,
+                streaming: false,
+            },
+            types: types(),
+            events: vec![Event {
+                type_id: RECORDING,
+                value: SerializedValue::Product(vec![
+                    SerializedValue::Int(format),
+                    text("answer.t"),
+                    text(""),
+                    optional(None),
+                    optional(None),
+                ]),
+            }],
+        };
+        let decode =
+            |identity, format| Recording::decode(&serialize(&stream(identity, format)).unwrap());
+        assert!(decode(RECORDING_IDENTITY, 1).is_ok());
+        assert_eq!(
+            decode(RECORDING_IDENTITY, 2),
+            Err("unsupported recording format 2; this debugger replays format 1".into())
+        );
+        assert_eq!(
+            decode("topal", 1),
+            Err("not a Topal debugger recording".into())
+        );
+
+        let mut oversized = stream(RECORDING_IDENTITY, 1);
+        oversized.events[0] = Event {
+            type_id: RECORDING,
+            value: SerializedValue::Product(vec![
+                SerializedValue::Int(1),
+                text("answer.t"),
+                text(&"x".repeat(Limits::default().text_bytes + 1)),
+                optional(None),
+                optional(None),
+            ]),
+        };
+        assert!(Recording::decode(&serialize(&oversized).unwrap()).is_err());
+        let recording = Recording {
+            source: "x".repeat(Limits::default().text_bytes + 1),
+            ..Recording::default()
+        };
+        assert!(
+            recording
+                .encode()
+                .unwrap_err()
+                .starts_with("recording exceeds the limits replay accepts")
+        );
+    }
+}
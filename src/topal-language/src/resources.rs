@@ -0,0 +1,419 @@
+//! Resource budgets for sandboxed evaluation.
+//!
+//! Hosts that evaluate code they do not trust to terminate quickly, such as
+//! lint rules, build policies and downloaded scripts, bound a session by fuel,
+//! one unit per evaluated expression, by the approximate size of each value it
+//! produces, and by the depth of active function calls. Exhausting a budget
+//! stops evaluation with a distinct `E-RESOURCE-*` diagnostic instead of a
+//! result. A session without limits is unbounded.
+//!
+//! Every kind of invocation, named and anonymous functions, generator
+//! segments and task handlers, enters the call-depth budget through
+//! [`ResourceMeter::enter_call`]. The value-memory budget bounds each value on
+//! its own, not the total a session retains: it is checked once the value is
+//! built, so one operation may briefly allocate its own result beyond the
+//! budget; only integer and rational powers, whose results grow exponentially
+//! with their operands, are refused before computing. Fuel bounds how many
+//! such values are built.
+//!
+//! Evaluation recurses on the native stack, so a call-depth budget only
+//! produces its diagnostic when the stack can hold that many calls. Hosts run
+//! budgeted sessions through [`with_evaluation_stack`], which reserves a stack
+//! sized from the budget.
+
+use std::cell::Cell;
+use std::mem::size_of;
+use std::panic;
+use std::thread;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use topal_source::{SourceText, Span};
+
+use crate::Diagnostic;
+use crate::source::{Value, diagnostic};
+
+/// Evaluation fuel is exhausted.
+pub const FUEL_EXHAUSTED_ERROR_CODE: &str = "E-RESOURCE-FUEL";
+/// A value exceeds the value-memory budget.
+pub const MEMORY_EXHAUSTED_ERROR_CODE: &str = "E-RESOURCE-MEMORY";
+/// Function calls nest deeper than the call-depth budget.
+pub const CALL_DEPTH_EXHAUSTED_ERROR_CODE: &str = "E-RESOURCE-CALL-DEPTH";
+
+/// Native stack reserved for evaluation outside any call, such as parsing,
+/// module loading and deeply nested top-level expressions.
+const BASE_EVALUATION_STACK: usize = 16 * 1024 * 1024;
+/// Native stack reserved for each call the call-depth budget admits. One call
+/// through a conditional body uses about 300 KiB in an unoptimized build.
+const STACK_PER_CALL: usize = 512 * 1024;
+
+/// Budgets for one session. `None` leaves a resource unbounded.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ResourceLimits {
+    /// Expressions the session may evaluate.
+    pub fuel: Option<u64>,
+    /// Approximate size in bytes of any single value the session produces.
+    /// Values are bounded one at a time, not by the total the session
+    /// retains. Integer and rational powers are checked before computing.
+    pub value_memory: Option<usize>,
+    /// Function calls that may be active at once.
+    pub call_depth: Option<usize>,
+}
+
+impl ResourceLimits {
+    /// The native stack that lets evaluation reach the call-depth budget
+    /// without overflowing, or `None` when call depth is unbounded.
+    #[must_use]
+    pub const fn evaluation_stack_size(&self) -> Option<usize> {
+        match self.call_depth {
+            Some(depth) => Some(
+                STACK_PER_CALL
+                    .saturating_mul(depth)
+                    .saturating_add(BASE_EVALUATION_STACK),
+            ),
+            None => None,
+        }
+    }
+}
+
+/// Run `evaluate` on a thread whose stack holds every call `limits` admits,
+/// so exceeding the call-depth budget reports `E-RESOURCE-CALL-DEPTH` instead
+/// of overflowing the host's stack. Without a call-depth budget `evaluate`
+/// runs on the calling thread. A [`Session`](crate::Session) belongs to one
+/// thread, so `evaluate` creates the session it budgets.
+///
+/// # Panics
+///
+/// Panics when the evaluation thread cannot be created, and resumes any panic
+/// raised by `evaluate`.
+pub fn with_evaluation_stack<T: Send>(
+    limits: ResourceLimits,
+    evaluate: impl FnOnce() -> T + Send,
+) -> T {
+    let Some(stack_size) = limits.evaluation_stack_size() else {
+        return evaluate();
+    };
+    thread::scope(|scope| {
+        thread::Builder::new()
+            .name("topal-evaluation".into())
+            .stack_size(stack_size)
+            .spawn_scoped(scope, evaluate)
+            .expect("the evaluation thread starts")
+            .join()
+            .unwrap_or_else(|payload| panic::resume_unwind(payload))
+    })
+}
+
+/// The limits of one session and the fuel it has consumed, shared by every
+/// scope evaluated on its behalf.
+#[derive(Debug, Default)]
+pub(crate) struct ResourceMeter {
+    limits: ResourceLimits,
+    consumed: Cell<u64>,
+    active_calls: Cell<usize>,
+}
+
+/// One active invocation; leaving it on drop keeps the depth balanced when
+/// the invocation fails.
+#[must_use]
+pub(crate) struct EnteredCall<'a>(&'a Cell<usize>);
+
+impl Drop for EnteredCall<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() - 1);
+    }
+}
+
+impl ResourceMeter {
+    pub(crate) const fn new(limits: ResourceLimits) -> Self {
+        Self {
+            limits,
+            consumed: Cell::new(0),
+            active_calls: Cell::new(0),
+        }
+    }
+
+    pub(crate) const fn limits(&self) -> ResourceLimits {
+        self.limits
+    }
+
+    pub(crate) const fn consumed(&self) -> u64 {
+        self.consumed.get()
+    }
+
+    /// Charge one unit of fuel for evaluating the expression at `span`.
+    pub(crate) fn consume_fuel(&self, source: &SourceText, span: Span) -> Result<(), Diagnostic> {
+        let consumed = self.consumed.get().saturating_add(1);
+        self.consumed.set(consumed);
+        match self.limits.fuel {
+            Some(fuel) if consumed > fuel => Err(diagnostic(
+                source,
+                FUEL_EXHAUSTED_ERROR_CODE,
+                span,
+                format!("evaluation exhausted its fuel of {fuel} expressions"),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Admit a value of approximately `bytes` produced at `span`.
+    pub(crate) fn admit_size(
+        &self,
+        source: &SourceText,
+        span: Span,
+        bytes: u128,
+    ) -> Result<(), Diagnostic> {
+        match self.limits.value_memory {
+            Some(memory) if bytes > memory as u128 => Err(diagnostic(
+                source,
+                MEMORY_EXHAUSTED_ERROR_CODE,
+                span,
+                format!(
+                    "value of about {bytes} bytes exceeds the value-memory limit of {memory} bytes"
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Enter the invocation at `span`, which stays active until the returned
+    /// guard is dropped.
+    pub(crate) fn enter_call(
+        &self,
+        source: &SourceText,
+        span: Span,
+    ) -> Result<EnteredCall<'_>, Diagnostic> {
+        let depth = self.active_calls.get() + 1;
+        match self.limits.call_depth {
+            Some(limit) if depth > limit => Err(diagnostic(
+                source,
+                CALL_DEPTH_EXHAUSTED_ERROR_CODE,
+                span,
+                format!("function calls nest deeper than the limit of {limit}"),
+            )),
+            _ => {
+                self.active_calls.set(depth);
+                Ok(EnteredCall(&self.active_calls))
+            }
+        }
+    }
+}
+
+/// The approximate number of bytes `value` occupies, counting numeric
+/// magnitudes, text and container entries.
+pub(crate) fn value_size(value: &Value) -> u128 {
+    let own = size_of::<Value>() as u128;
+    let contents = match value {
+        Value::Int(value) | Value::SizeBits(value) => int_size(value),
+        Value::Rational(value) => rational_size(value),
+        Value::Quantity(quantity) => rational_size(&quantity.magnitude),
+        Value::IntRange { lower, upper } => int_size(lower) + int_size(upper),
+        Value::RationalRange { lower, upper } => rational_size(lower) + rational_size(upper),
+        Value::Modular {
+            lower,
+            upper,
+            value,
+            ..
+        } => int_size(lower) + int_size(upper) + int_size(value),
+        Value::String(text) => text.len() as u128,
+        Value::SerializationStream(bytes) => bytes.len() as u128,
+        Value::List { entries, .. }
+        | Value::Array { entries, .. }
+        | Value::Set { entries, .. }
+        | Value::Tuple(entries) => entries.iter().map(value_size).sum(),
+        Value::Bag { entries, .. } => entries.iter().map(|(entry, _)| value_size(entry)).sum(),
+        Value::Map { entries, .. } => entries
+            .iter()
+            .map(|(key, value)| value_size(key) + value_size(value))
+            .sum(),
+        Value::Record(fields) => fields
+            .iter()
+            .map(|(name, value)| name.len() as u128 + value_size(value))
+            .sum(),
+        Value::Optional {
+            payload: Some(payload),
+            ..
+        } => value_size(payload),
+        Value::Refined { value, .. } | Value::Continue(value) | Value::Finish(value) => {
+            value_size(value)
+        }
+        _ => 0,
+    };
+    own + contents
+}
+
+/// The approximate number of bytes `base ^ exponent` occupies.
+pub(crate) fn power_size(base: &BigInt, exponent: &BigInt) -> u128 {
+    let exponent = u128::try_from(exponent.magnitude()).unwrap_or(u128::MAX);
+    u128::from(base.bits().saturating_sub(1))
+        .saturating_mul(exponent)
+        .div_ceil(8)
+}
+
+fn int_size(value: &BigInt) -> u128 {
+    u128::from(value.bits().div_ceil(8))
+}
+
+fn rational_size(value: &BigRational) -> u128 {
+    int_size(value.numer()) + int_size(value.denom())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Session;
+
+    const HEADER: &str = "use language (\n  version is v0.1\n)\n";
+
+    /// Run `check` against a fresh session under `limits`, on the stack the
+    /// limits require.
+    fn limited(limits: ResourceLimits, check: impl FnOnce(&mut Session) + Send) {
+        with_evaluation_stack(limits, || {
+            let mut session = Session::new();
+            session.set_resource_limits(limits);
+            check(&mut session);
+        });
+    }
+
+    #[test]
+    fn fuel_stops_runaway_evaluation_with_a_distinct_code() {
+        let source = format!(
+            "{HEADER}branch-count is fn (value : Int) -> Int\n  value\n    <= 0 then 1\n    otherwise (branch-count (value - 1)) + (branch-count (value - 2))\nbranch-count 18\n"
+        );
+        limited(
+            ResourceLimits {
+                fuel: Some(10_000),
+                call_depth: Some(32),
+                ..ResourceLimits::default()
+            },
+            |session| {
+                let error = session
+                    .evaluate_source_file(&source, &mut std::io::sink())
+                    .unwrap_err();
+                assert_eq!(error.code, FUEL_EXHAUSTED_ERROR_CODE);
+                assert_eq!(session.fuel_consumed(), 10_001);
+
+                session.set_resource_limits(ResourceLimits::default());
+                assert_eq!(session.fuel_consumed(), 0);
+                assert_eq!(
+                    session.evaluate("1 + 2", &mut std::io::sink()).unwrap(),
+                    Value::Int(3.into())
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn memory_limit_refuses_large_values_before_computing_powers() {
+        limited(
+            ResourceLimits {
+                value_memory: Some(4096),
+                ..ResourceLimits::default()
+            },
+            |session| {
+                let error = session
+                    .evaluate_source_file(
+                        &format!("{HEADER}2 ^ 1000000000000\n"),
+                        &mut std::io::sink(),
+                    )
+                    .unwrap_err();
+                assert_eq!(error.code, MEMORY_EXHAUSTED_ERROR_CODE);
+                assert_eq!(
+                    session.evaluate("2 ^ 64", &mut std::io::sink()).unwrap(),
+                    Value::Int(BigInt::from(1_u128 << 64))
+                );
+                let error = session
+                    .evaluate(&format!("\"{}\"", "x".repeat(8192)), &mut std::io::sink())
+                    .unwrap_err();
+                assert_eq!(error.code, MEMORY_EXHAUSTED_ERROR_CODE);
+            },
+        );
+    }
+
+    #[test]
+    fn call_depth_limit_applies_to_loaded_modules() {
+        let module = format!(
+            "{HEADER}pub count-down is fn (value : Int) -> Int\n  value\n    <= 0 then 0\n    otherwise count-down (value - 1)\n"
+        );
+        limited(
+            ResourceLimits {
+                call_depth: Some(8),
+                ..ResourceLimits::default()
+            },
+            |session| {
+                session
+                    .load_module("counting", &module, &mut std::io::sink())
+                    .unwrap();
+                assert_eq!(
+                    session
+                        .evaluate("counting count-down 7", &mut std::io::sink())
+                        .unwrap(),
+                    Value::Int(0.into())
+                );
+                let error = session
+                    .evaluate("counting count-down 8", &mut std::io::sink())
+                    .unwrap_err();
+                assert_eq!(error.code, CALL_DEPTH_EXHAUSTED_ERROR_CODE);
+            },
+        );
+    }
+
+    #[test]
+    fn deep_call_depth_limits_report_instead_of_overflowing_the_stack() {
+        let source = format!(
+            "{HEADER}count-down is fn (value : Int) -> Int\n  value\n    <= 0 then 0\n    otherwise count-down (value - 1)\ncount-down 1000\n"
+        );
+        limited(
+            ResourceLimits {
+                call_depth: Some(256),
+                ..ResourceLimits::default()
+            },
+            |session| {
+                let error = session
+                    .evaluate_source_file(&source, &mut std::io::sink())
+                    .unwrap_err();
+                assert_eq!(error.code, CALL_DEPTH_EXHAUSTED_ERROR_CODE);
+            },
+        );
+    }
+
+    #[test]
+    fn call_depth_limit_applies_to_anonymous_self_application() {
+        let source = format!("{HEADER}loop is {{ self, n }} self (self, n + 1)\nloop (loop, 0)\n");
+        limited(
+            ResourceLimits {
+                call_depth: Some(16),
+                ..ResourceLimits::default()
+            },
+            |session| {
+                let error = session
+                    .evaluate_source_file(&source, &mut std::io::sink())
+                    .unwrap_err();
+                assert_eq!(error.code, CALL_DEPTH_EXHAUSTED_ERROR_CODE);
+                assert_eq!(
+                    session.evaluate("1 + 2", &mut std::io::sink()).unwrap(),
+                    Value::Int(3.into())
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn call_depth_limit_applies_to_generators() {
+        let source = format!(
+            "{HEADER}nested is generator ( depth : Int )\n  yields Int\n  resumes Unit\n  -> Unit\n\n  inner is nested (depth + 1)\n  _ is yield depth\n  ()\ngenerated is nested 0\n"
+        );
+        limited(
+            ResourceLimits {
+                call_depth: Some(8),
+                ..ResourceLimits::default()
+            },
+            |session| {
+                let error = session
+                    .evaluate_source_file(&source, &mut std::io::sink())
+                    .unwrap_err();
+                assert_eq!(error.code, CALL_DEPTH_EXHAUSTED_ERROR_CODE);
+            },
+        );
+    }
+}
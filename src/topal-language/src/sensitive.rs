@@ -0,0 +1,500 @@
+//! Direct sensitive-information provenance and its boundary diagnostics.
+//!
+//! A name is sensitive when its classifier is `Sensitive T` or its initializer
+//! carries sensitive information. Operators, containers, closures, and library
+//! operations propagate provenance from their operands; comparisons are
+//! conclusions rather than copies. A declared function states its contract
+//! through `Sensitive` parameter and result classifiers.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use topal_semantics::Sensitivity;
+use topal_source::{Diagnostic, SourceText, Span};
+use topal_syntax::{CallableKind, DecisionMatcher, Expression, FunctionParameter, Statement};
+
+use crate::source::diagnostic;
+
+/// The classifier `T` of `Sensitive T`.
+pub(crate) fn sensitive_payload(classifier: &str) -> Option<&str> {
+    classifier
+        .strip_prefix("Sensitive")
+        .filter(|payload| payload.starts_with(char::is_whitespace))
+        .map(str::trim_start)
+}
+
+fn classified(classifier: &str) -> Sensitivity {
+    if sensitive_payload(classifier).is_some() {
+        Sensitivity::Sensitive
+    } else {
+        Sensitivity::Public
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Contract {
+    parameters: Vec<(String, Sensitivity)>,
+    result: Sensitivity,
+}
+
+/// Provenance of the names a session has bound so far.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct SensitivityScope {
+    values: BTreeSet<String>,
+    functions: BTreeMap<String, Contract>,
+    serializers: BTreeSet<String>,
+}
+
+impl SensitivityScope {
+    /// Check `statements` as one source unit following the names already bound.
+    pub(crate) fn diagnostics(
+        &self,
+        source: &SourceText,
+        statements: &[Statement],
+    ) -> Vec<Diagnostic> {
+        let mut checker = Checker {
+            source,
+            diagnostics: Vec::new(),
+            returns: Vec::new(),
+        };
+        let mut scope = self.clone();
+        if checker.statements(statements, &mut scope).is_sensitive()
+            && let Some(Statement::Expression(output)) = statements.last()
+        {
+            checker.report(
+                "E-SENSITIVE-OUTPUT",
+                output.span(),
+                "the program result carries sensitive information and would be printed".to_owned(),
+            );
+        }
+        checker.diagnostics
+    }
+
+    /// Record the provenance established by one executed statement, returning
+    /// the name it bound to sensitive information.
+    pub(crate) fn observe(&mut self, source: &SourceText, statement: &Statement) -> Option<String> {
+        let mut checker = Checker {
+            source,
+            diagnostics: Vec::new(),
+            returns: Vec::new(),
+        };
+        checker.statement(statement, self);
+        match statement {
+            Statement::Binding { name, .. } => Some(source.slice(*name))
+                .filter(|name| self.values.contains(*name))
+                .map(str::to_owned),
+            _ => None,
+        }
+    }
+}
+
+/// Check sensitive-information boundaries across `statements`: unclassified
+/// bindings, parameters, and results, the printed program result, and native
+/// serialization.
+#[must_use]
+pub fn sensitivity_diagnostics(source: &SourceText, statements: &[Statement]) -> Vec<Diagnostic> {
+    SensitivityScope::default().diagnostics(source, statements)
+}
+
+struct Checker<'a> {
+    source: &'a SourceText,
+    diagnostics: Vec<Diagnostic>,
+    /// Explicit `return` values of each enclosing function body.
+    returns: Vec<Vec<(Span, Sensitivity)>>,
+}
+
+impl Checker<'_> {
+    /// Check `statements` in order and return the provenance of a final
+    /// expression statement.
+    fn statements(
+        &mut self,
+        statements: &[Statement],
+        scope: &mut SensitivityScope,
+    ) -> Sensitivity {
+        let mut last = Sensitivity::Public;
+        for statement in statements {
+            last = self.statement(statement, scope);
+        }
+        last
+    }
+
+    fn statement(&mut self, statement: &Statement, scope: &mut SensitivityScope) -> Sensitivity {
+        match statement {
+            Statement::Binding {
+                name,
+                classifier,
+                value,
+            } => self.binding(*name, *classifier, value, scope),
+            Statement::Published { declaration, .. } => {
+                self.statement(declaration, scope);
+            }
+            Statement::Function {
+                name,
+                parameters,
+                result,
+                body,
+                ..
+            } => {
+                scope.functions.insert(
+                    self.source.slice(*name).to_owned(),
+                    Contract {
+                        parameters: parameters
+                            .iter()
+                            .map(|parameter| {
+                                (
+                                    self.source.slice(parameter.name).to_owned(),
+                                    classified(self.source.slice(parameter.classifier)),
+                                )
+                            })
+                            .collect(),
+                        result: classified(self.source.slice(*result)),
+                    },
+                );
+                self.function(parameters, *result, body, scope);
+            }
+            Statement::Generator {
+                parameters, body, ..
+            } => {
+                let mut inner = self.parameter_scope(parameters, scope);
+                self.returns.push(Vec::new());
+                self.statements(body, &mut inner);
+                self.returns.pop();
+            }
+            Statement::Foreach {
+                result,
+                source,
+                binding,
+                body,
+                ..
+            } => {
+                let traversed = self.expression(source, scope);
+                let mut inner = scope.clone();
+                bind(&mut inner, self.source.slice(*binding), traversed);
+                self.statements(body, &mut inner);
+                if let Some((result, _)) = result {
+                    bind(scope, self.source.slice(*result), traversed);
+                }
+            }
+            Statement::Implementation { declarations, .. } => {
+                self.statements(declarations, &mut scope.clone());
+            }
+            Statement::InterfaceImplementation { declarations, .. } => {
+                self.statements(declarations, scope);
+            }
+            Statement::ContextAssignment { value, .. } | Statement::Discard { value, .. } => {
+                self.expression(value, scope);
+            }
+            Statement::Return { value, .. } => {
+                let sensitivity = self.expression(value, scope);
+                if let Some(returns) = self.returns.last_mut() {
+                    returns.push((value.span(), sensitivity));
+                }
+            }
+            Statement::Expression(value) => return self.expression(value, scope),
+            Statement::LanguageSelection { .. }
+            | Statement::DiagnosticControl { .. }
+            | Statement::StateField { .. }
+            | Statement::Union { .. }
+            | Statement::Interface { .. } => {}
+        }
+        Sensitivity::Public
+    }
+
+    fn binding(
+        &mut self,
+        name: Span,
+        classifier: Option<Span>,
+        value: &Expression,
+        scope: &mut SensitivityScope,
+    ) {
+        let name = self.source.slice(name);
+        if let Expression::Application { items, .. } = value
+            && let [
+                Expression::Identifier(lang),
+                Expression::Identifier(version),
+                operation,
+            ] = items.as_slice()
+            && self.source.slice(*lang) == "lang"
+            && self.source.slice(*version) == "version"
+            && self.is_serialize(operation)
+        {
+            scope.serializers.insert(name.to_owned());
+        }
+        let carried = self.expression(value, scope);
+        let declared = classifier.map(|classifier| self.source.slice(classifier));
+        if carried.is_sensitive()
+            && let Some(declared) = declared
+            && sensitive_payload(declared).is_none()
+        {
+            self.report(
+                "E-SENSITIVE-CLASSIFIER",
+                value.span(),
+                format!(
+                    "`{name}` receives sensitive information, so its classifier must be `Sensitive {declared}`"
+                ),
+            );
+        }
+        bind(
+            scope,
+            name,
+            carried.join(declared.map_or(Sensitivity::Public, classified)),
+        );
+    }
+
+    fn function(
+        &mut self,
+        parameters: &[FunctionParameter],
+        result: Span,
+        body: &[Statement],
+        scope: &SensitivityScope,
+    ) {
+        let mut inner = self.parameter_scope(parameters, scope);
+        self.returns.push(Vec::new());
+        let last = self.statements(body, &mut inner);
+        let mut results = self.returns.pop().unwrap_or_default();
+        if let Some(Statement::Expression(value)) = body.last() {
+            results.push((value.span(), last));
+        }
+        let declared = self.source.slice(result);
+        if sensitive_payload(declared).is_some() {
+            return;
+        }
+        for (span, sensitivity) in results {
+            if sensitivity.is_sensitive() {
+                self.report(
+                    "E-SENSITIVE-RESULT",
+                    span,
+                    format!(
+                        "this result carries sensitive information, so the function result must be `Sensitive {declared}`"
+                    ),
+                );
+            }
+        }
+    }
+
+    fn parameter_scope(
+        &self,
+        parameters: &[FunctionParameter],
+        scope: &SensitivityScope,
+    ) -> SensitivityScope {
+        let mut inner = scope.clone();
+        for parameter in parameters {
+            bind(
+                &mut inner,
+                self.source.slice(parameter.name),
+                classified(self.source.slice(parameter.classifier)),
+            );
+        }
+        inner
+    }
+
+    fn expression(&mut self, expression: &Expression, scope: &SensitivityScope) -> Sensitivity {
+        match expression {
+            Expression::Identifier(name) if scope.values.contains(self.source.slice(*name)) => {
+                Sensitivity::Sensitive
+            }
+            Expression::Product { fields, .. } => {
+                fields.iter().fold(Sensitivity::Public, |carried, field| {
+                    carried.join(self.expression(&field.value, scope))
+                })
+            }
+            Expression::Block { statements, .. } => self.statements(statements, &mut scope.clone()),
+            Expression::DecisionTable { subject, rules, .. } => {
+                let subject = self.expression(subject, scope);
+                let mut carried = Sensitivity::Public;
+                for rule in rules {
+                    let mut inner = scope.clone();
+                    for binding in matcher_bindings(&rule.matcher) {
+                        bind(&mut inner, self.source.slice(binding), subject);
+                    }
+                    if let DecisionMatcher::Comparison { operand, .. } = &rule.matcher {
+                        self.expression(operand, scope);
+                    }
+                    carried = carried.join(self.expression(&rule.action, &inner));
+                }
+                carried
+            }
+            Expression::AnonymousFunction {
+                parameters, body, ..
+            } => {
+                let mut inner = scope.clone();
+                for parameter in parameters {
+                    inner.values.remove(self.source.slice(*parameter));
+                }
+                // A closure capturing sensitive information carries it.
+                self.expression(body, &inner)
+            }
+            Expression::Application { items, .. } => self.application(items, scope),
+            _ => Sensitivity::Public,
+        }
+    }
+
+    fn application(&mut self, items: &[Expression], scope: &SensitivityScope) -> Sensitivity {
+        if let Some(subject) = self.serialized_subject(items, scope) {
+            if self.expression(subject, scope).is_sensitive() {
+                self.report(
+                    "E-SENSITIVE-SERIALIZATION",
+                    subject.span(),
+                    "native serialization does not accept sensitive information".to_owned(),
+                );
+            }
+            return Sensitivity::Public;
+        }
+        if let [Expression::Identifier(callee), argument] = items
+            && let Some(contract) = scope.functions.get(self.source.slice(*callee))
+        {
+            let arguments = match argument {
+                Expression::Product { fields, .. } if fields.len() == contract.parameters.len() => {
+                    fields
+                        .iter()
+                        .map(|field| {
+                            (
+                                field.label.map(|label| self.source.slice(label)),
+                                field.value.span(),
+                                self.expression(&field.value, scope),
+                            )
+                        })
+                        .collect()
+                }
+                argument => vec![(None, argument.span(), self.expression(argument, scope))],
+            };
+            return self.call(self.source.slice(*callee), contract, &arguments);
+        }
+        let Some(first) = items.first() else {
+            return Sensitivity::Public;
+        };
+        let mut carried = self.expression(first, scope);
+        let mut index = 1;
+        while index < items.len() {
+            match (&items[index], items.get(index + 1)) {
+                (Expression::Callable { kind, .. }, Some(operand)) if is_comparison(*kind) => {
+                    self.expression(operand, scope);
+                    carried = Sensitivity::Public;
+                    index += 2;
+                }
+                (Expression::Identifier(callee), Some(operand))
+                    if scope.functions.contains_key(self.source.slice(*callee)) =>
+                {
+                    let left = Span::new(first.span().start, items[index - 1].span().end);
+                    let arguments = [
+                        (None, left, carried),
+                        (None, operand.span(), self.expression(operand, scope)),
+                    ];
+                    carried = self.call(
+                        self.source.slice(*callee),
+                        &scope.functions[self.source.slice(*callee)],
+                        &arguments,
+                    );
+                    index += 2;
+                }
+                (item, _) => {
+                    carried = carried.join(self.expression(item, scope));
+                    index += 1;
+                }
+            }
+        }
+        carried
+    }
+
+    /// Check that every sensitive argument reaches a `Sensitive` parameter and
+    /// return the provenance of the declared result.
+    fn call(
+        &mut self,
+        callee: &str,
+        contract: &Contract,
+        arguments: &[(Option<&str>, Span, Sensitivity)],
+    ) -> Sensitivity {
+        let positional = arguments.len() == contract.parameters.len();
+        for (index, (label, span, sensitivity)) in arguments.iter().enumerate() {
+            if !sensitivity.is_sensitive() {
+                continue;
+            }
+            let parameter = match label {
+                Some(label) => contract.parameters.iter().find(|(name, _)| name == label),
+                None if positional => contract.parameters.get(index),
+                None => None,
+            };
+            let accepted = parameter.map_or_else(
+                || {
+                    contract
+                        .parameters
+                        .iter()
+                        .any(|(_, accepts)| accepts.is_sensitive())
+                },
+                |(_, accepts)| accepts.is_sensitive(),
+            );
+            if !accepted {
+                let target = parameter.map_or_else(
+                    || format!("`{callee}`"),
+                    |(name, _)| format!("parameter `{name}` of `{callee}`"),
+                );
+                self.report(
+                    "E-SENSITIVE-ARGUMENT",
+                    *span,
+                    format!("{target} is not classified `Sensitive` and cannot receive sensitive information"),
+                );
+            }
+        }
+        contract.result
+    }
+
+    fn serialized_subject<'e>(
+        &self,
+        items: &'e [Expression],
+        scope: &SensitivityScope,
+    ) -> Option<&'e Expression> {
+        match items {
+            [_, operation, subject] if self.is_serialize(operation) => Some(subject),
+            [Expression::Identifier(serializer), subject]
+                if scope.serializers.contains(self.source.slice(*serializer)) =>
+            {
+                Some(subject)
+            }
+            _ => None,
+        }
+    }
+
+    fn is_serialize(&self, expression: &Expression) -> bool {
+        matches!(expression, Expression::Application { items, .. }
+            if matches!(items.as_slice(),
+                [Expression::Identifier(lang), Expression::Identifier(operation)]
+                    if self.source.slice(*lang) == "lang"
+                        && self.source.slice(*operation) == "serialize"))
+    }
+
+    fn report(&mut self, code: &'static str, span: Span, message: String) {
+        self.diagnostics
+            .push(diagnostic(self.source, code, span, message).with_source_span(span));
+    }
+}
+
+fn bind(scope: &mut SensitivityScope, name: &str, sensitivity: Sensitivity) {
+    if sensitivity.is_sensitive() {
+        scope.values.insert(name.to_owned());
+    } else {
+        scope.values.remove(name);
+    }
+}
+
+const fn is_comparison(kind: CallableKind) -> bool {
+    matches!(
+        kind,
+        CallableKind::Equal
+            | CallableKind::NotEqual
+            | CallableKind::Less
+            | CallableKind::Greater
+            | CallableKind::LessEqual
+            | CallableKind::GreaterEqual
+            | CallableKind::Compare
+    )
+}
+
+fn matcher_bindings(matcher: &DecisionMatcher) -> Vec<Span> {
+    match matcher {
+        DecisionMatcher::Union { binding, .. }
+        | DecisionMatcher::Variant { binding, .. }
+        | DecisionMatcher::Result { binding, .. } => vec![*binding],
+        DecisionMatcher::Optional { binding, .. } => binding.iter().copied().collect(),
+        DecisionMatcher::ListEntry { first, rest, .. } => vec![*first, *rest],
+        _ => Vec::new(),
+    }
+}
@@ -6,7 +6,7 @@ use std::rc::Rc;
 
 use num_bigint::BigInt;
 use num_rational::BigRational;
-use topal_semantics::{LanguageVersion, ObjectKind};
+use topal_semantics::{EffectSet, LanguageVersion, ObjectKind};
 use topal_serialization::{
     Event as SerializedEvent, Header as SerializationHeader, Limits as SerializationLimits,
     SerializedValue, Stream as SerializationStream, StreamByteOrder, TypeDefinition,
@@ -17,10 +17,18 @@ use topal_source::{
     characters, lowercase, normalize_nfc, normalize_nfd, uppercase,
 };
 use topal_syntax::{
-    CallableKind, DecisionMatcher, Expression, FunctionParameter, Statement, extract_documentation,
-    lex, parse,
+    CallableKind, Continuation, DecisionMatcher, Expression, FunctionParameter, Statement,
+    UnitExpression, extract_documentation, lex, parse,
 };
 
+use crate::analysis::{called_effects, effect_bound_diagnostic, effect_row, inferred_classifier};
+use crate::approximate::{
+    self, ApproximateFormat, ApproximateNumber, ApproximateValue, Operation, Outcome, Rounding,
+    ZeroDirection,
+};
+use crate::resources::{ResourceLimits, ResourceMeter, power_size, value_size};
+use crate::sensitive::{SensitivityScope, sensitive_payload};
+use crate::units::{self, Applied, Declaration, Quantity, UnitRegistry};
 use crate::{ExecutionSnapshot, TraceEvent, TraceSink};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -65,6 +73,8 @@ pub enum Value {
     },
     Int(BigInt),
     Rational(BigRational),
+    Approximate(Box<ApproximateValue>),
+    Quantity(Box<Quantity>),
     IntRange {
         lower: BigInt,
         upper: BigInt,
@@ -235,6 +245,16 @@ impl Value {
             _ => ObjectKind::Value,
         }
     }
+
+    /// Return the classifier an interactive or tooling view reports for this
+    /// value; type objects are classified as `Type` rather than by their name.
+    #[must_use]
+    pub fn classifier(&self) -> String {
+        match self {
+            Self::Type(_) | Self::ModularType(_) => value_classifier(self).to_owned(),
+            _ => structural_value_classifier(self),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
@@ -370,6 +390,8 @@ impl fmt::Display for Value {
                 )
             }
             Self::Int(value) => value.fmt(formatter),
+            Self::Approximate(value) => value.fmt(formatter),
+            Self::Quantity(quantity) => quantity.fmt(formatter),
             Self::Rational(value) => {
                 write!(
                     formatter,
@@ -605,11 +627,14 @@ pub struct Session {
     enum_types: BTreeMap<String, BTreeSet<String>>,
     union_types: Box<BTreeMap<String, BTreeMap<String, Option<String>>>>,
     generic_types: BTreeMap<String, String>,
+    units: Box<UnitRegistry>,
+    sensitivity: Box<SensitivityScope>,
     call_stack: Vec<ActiveCall>,
     static_context: bool,
     task_state: Option<BTreeMap<String, Value>>,
     next_task_identity: Cell<u64>,
     next_transaction_identity: Cell<u64>,
+    resources: Rc<ResourceMeter>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -621,6 +646,8 @@ struct UserFunction {
     result: String,
     generic_names: BTreeSet<String>,
     effect_bound: Option<String>,
+    /// The declared effect row, or the rows of the functions the body calls.
+    effects: EffectSet,
     body: Vec<Statement>,
     bindings: BTreeMap<String, Value>,
     termination_rule: Option<&'static str>,
@@ -1385,11 +1412,14 @@ impl Session {
             enum_types: self.enum_types.clone(),
             union_types: self.union_types.clone(),
             generic_types: self.generic_types.clone(),
+            units: self.units.clone(),
+            sensitivity: self.sensitivity.clone(),
             call_stack: Vec::new(),
             static_context: false,
             task_state: Some(snapshot.state),
             next_task_identity: Cell::new(self.next_task_identity.get()),
             next_transaction_identity: Cell::new(self.next_transaction_identity.get()),
+            resources: Rc::clone(&self.resources),
         };
         bind_generator_arguments(&mut scope, &generator.parameters, argument, trace);
         let mut cursor = 0;
@@ -1446,6 +1476,10 @@ impl Session {
         state: BTreeMap<String, Value>,
         trace: &mut impl TraceSink,
     ) -> Result<(Value, BTreeMap<String, Value>), Diagnostic> {
+        let _call = self.resources.enter_call(
+            &definition.source,
+            statement_span(function.body.first().expect("handler body is nonempty")),
+        )?;
         let mut scope = Self {
             bindings: function.bindings.clone(),
             functions: Box::new(definition.handlers.clone()),
@@ -1460,6 +1494,8 @@ impl Session {
             enum_types: self.enum_types.clone(),
             union_types: self.union_types.clone(),
             generic_types: self.generic_types.clone(),
+            units: self.units.clone(),
+            sensitivity: self.sensitivity.clone(),
             call_stack: vec![ActiveCall {
                 name: definition.name.clone(),
                 signature: function_signature(&definition.name, function),
@@ -1470,6 +1506,7 @@ impl Session {
             task_state: Some(state),
             next_task_identity: Cell::new(self.next_task_identity.get()),
             next_transaction_identity: Cell::new(self.next_transaction_identity.get()),
+            resources: Rc::clone(&self.resources),
         };
         bind_function_arguments(
             &mut scope,
@@ -1894,6 +1931,33 @@ impl Session {
         })
     }
 
+    /// Bound this session's further evaluation by `limits` and restart its
+    /// fuel count. Function scopes and modules evaluated on the session's
+    /// behalf share its budget.
+    pub fn set_resource_limits(&mut self, limits: ResourceLimits) {
+        self.resources = Rc::new(ResourceMeter::new(limits));
+    }
+
+    /// The limits bounding this session's evaluation.
+    #[must_use]
+    pub fn resource_limits(&self) -> ResourceLimits {
+        self.resources.limits()
+    }
+
+    /// The fuel consumed since the resource limits were last set.
+    #[must_use]
+    pub fn fuel_consumed(&self) -> u64 {
+        self.resources.consumed()
+    }
+
+    /// A fresh module scope that shares this session's resource budget.
+    pub(crate) fn module_scope(&self) -> Self {
+        Self {
+            resources: Rc::clone(&self.resources),
+            ..Self::new()
+        }
+    }
+
     /// The highest source language version implemented by this evaluator.
     #[must_use]
     pub const fn highest_supported_language_version() -> LanguageVersion {
@@ -1926,7 +1990,7 @@ impl Session {
                 format!("module `{name}` is already declared"),
             ));
         }
-        let mut module = Self::new();
+        let mut module = self.module_scope();
         module.evaluate_source_file(input, trace)?;
         self.attach_module(name, module, trace)
     }
@@ -1986,6 +2050,37 @@ impl Session {
         }))
     }
 
+    /// Top-level value bindings visible in this session, ordered by name.
+    #[must_use]
+    pub const fn bindings(&self) -> &BTreeMap<String, Value> {
+        &self.bindings
+    }
+
+    /// Names of the top-level functions and generators declared in this
+    /// session, ordered and without duplicate overloads.
+    #[must_use]
+    pub fn callable_names(&self) -> Vec<&str> {
+        let mut names = self
+            .functions
+            .keys()
+            .chain(self.generators.keys())
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Report what an interactive session must still read before `input`
+    /// can be submitted, or `None` when the input is ready to evaluate.
+    #[must_use]
+    pub fn continuation(input: &str) -> Option<Continuation> {
+        let source = SourceText::new(input).ok()?;
+        parse(&source, &lex(&source))
+            .incomplete
+            .map(|incomplete| incomplete.expected)
+    }
+
     /// Report whether a complete block statement should await a dedented line
     /// before an interactive session submits it.
     #[must_use]
@@ -2051,7 +2146,8 @@ impl Session {
     ///
     /// # Errors
     ///
-    /// Returns a source or syntax diagnostic before any statement executes.
+    /// Returns a source, syntax, static dimension, or sensitive-information
+    /// diagnostic before any statement executes.
     pub fn prepare(
         &self,
         input: &str,
@@ -2072,6 +2168,13 @@ impl Session {
         if parsed.statements.is_empty() {
             return Err(expected_statement(input));
         }
+        if let Some(error) = units::dimension_diagnostics(&source, &parsed.statements)
+            .into_iter()
+            .chain(self.sensitivity.diagnostics(&source, &parsed.statements))
+            .next()
+        {
+            return Err(error);
+        }
         Ok(Execution {
             source,
             statements: parsed.statements,
@@ -2171,6 +2274,114 @@ impl Session {
         bindings: &BTreeMap<String, Value>,
         input: &str,
         trace: &mut impl TraceSink,
+    ) -> Result<Value, Diagnostic> {
+        Self::inspect_selected(
+            bindings,
+            input,
+            LanguageVersion::DESIGN_0,
+            BTreeSet::new(),
+            trace,
+        )
+    }
+
+    /// Evaluate one expression against an immutable binding snapshot in this
+    /// session's selected language version and features.
+    ///
+    /// # Errors
+    ///
+    /// Returns a diagnostic when the input is not exactly one expression or
+    /// when that expression cannot be evaluated.
+    pub fn inspect_in_context(
+        &self,
+        bindings: &BTreeMap<String, Value>,
+        input: &str,
+        trace: &mut impl TraceSink,
+    ) -> Result<Value, Diagnostic> {
+        Self::inspect_selected(
+            bindings,
+            input,
+            self.language_version,
+            self.language_features.clone(),
+            trace,
+        )
+    }
+
+    /// The classifier of one expression, inferred from its literals, this
+    /// session's bindings, and the declared results of its functions without
+    /// evaluating anything, or `None` when only evaluation could tell.
+    ///
+    /// # Errors
+    ///
+    /// Returns a source or syntax diagnostic, or a diagnostic when the input
+    /// is not exactly one expression.
+    pub fn static_classifier(&self, input: &str) -> Result<Option<String>, Diagnostic> {
+        let execution = self.prepare(input, &mut std::io::sink())?;
+        let [Statement::Expression(expression)] = execution.statements.as_slice() else {
+            return Err(diagnostic(
+                &execution.source,
+                "E-EXPECTED-EXPRESSION",
+                statement_span(&execution.statements[0]),
+                "static classification requires exactly one expression",
+            ));
+        };
+        Ok(inferred_classifier(
+            &execution.source,
+            expression,
+            &|name| self.bindings.get(name).map(Value::classifier),
+            &|function| {
+                let overloads = self.functions.get(function)?;
+                let result = &overloads.first()?.result;
+                overloads
+                    .iter()
+                    .all(|overload| {
+                        &overload.result == result
+                            && !result
+                                .split(|character: char| {
+                                    !character.is_alphanumeric() && character != '-'
+                                })
+                                .any(|word| overload.generic_names.contains(word))
+                    })
+                    .then(|| result.clone())
+            },
+        ))
+    }
+
+    /// Evaluate one expression against this session's declarations with
+    /// `bindings` added, leaving the session itself unchanged. Hosts use this
+    /// to apply a loaded module's functions to values they supply.
+    ///
+    /// # Errors
+    ///
+    /// Returns a diagnostic when the input is not exactly one expression, when
+    /// a supplied binding name is already declared, or when the expression
+    /// cannot be evaluated.
+    pub fn evaluate_in_context(
+        &self,
+        bindings: &BTreeMap<String, Value>,
+        input: &str,
+        trace: &mut impl TraceSink,
+    ) -> Result<Value, Diagnostic> {
+        let mut session = self.clone();
+        for (name, value) in bindings {
+            if !session.declared_names.insert(name.clone()) {
+                return Err(Diagnostic::error(
+                    "E-DUPLICATE-BINDING",
+                    1,
+                    1,
+                    format!("supplied binding `{name}` is already bound in this scope"),
+                ));
+            }
+            session.bindings.insert(name.clone(), value.clone());
+        }
+        session.evaluate_one_expression(input, trace)
+    }
+
+    fn inspect_selected(
+        bindings: &BTreeMap<String, Value>,
+        input: &str,
+        language_version: LanguageVersion,
+        language_features: BTreeSet<String>,
+        trace: &mut impl TraceSink,
     ) -> Result<Value, Diagnostic> {
         let mut session = Self {
             bindings: bindings.clone(),
@@ -2179,20 +2390,31 @@ impl Session {
             declared_names: bindings.keys().cloned().collect(),
             published_names: BTreeSet::new(),
             documentation: Box::new(BTreeMap::new()),
-            language_version: LanguageVersion::DESIGN_0,
-            language_features: BTreeSet::new(),
+            language_version,
+            language_features,
             consumed_names: BTreeSet::new(),
             local_function_names: BTreeSet::new(),
             enum_types: BTreeMap::new(),
             union_types: Box::new(BTreeMap::new()),
             generic_types: BTreeMap::new(),
+            units: Box::default(),
+            sensitivity: Box::default(),
             call_stack: Vec::new(),
             static_context: false,
             task_state: None,
             next_task_identity: Cell::new(0),
             next_transaction_identity: Cell::new(0),
+            resources: Rc::default(),
         };
-        let mut execution = session.prepare(input, trace)?;
+        session.evaluate_one_expression(input, trace)
+    }
+
+    fn evaluate_one_expression(
+        &mut self,
+        input: &str,
+        trace: &mut impl TraceSink,
+    ) -> Result<Value, Diagnostic> {
+        let mut execution = self.prepare(input, trace)?;
         if !matches!(execution.statements.as_slice(), [Statement::Expression(_)]) {
             let span = execution
                 .statements
@@ -2205,7 +2427,7 @@ impl Session {
                 "debugger inspection requires exactly one expression",
             ));
         }
-        match execution.step(&mut session, trace)? {
+        match execution.step(self, trace)? {
             ExecutionStep::Complete(value) => Ok(value),
             ExecutionStep::Advanced { .. } => unreachable!("one expression completes execution"),
             ExecutionStep::Returned { .. } => unreachable!("inspection rejects return statements"),
@@ -2220,6 +2442,72 @@ impl Session {
         });
     }
 
+    /// Construct a quantity or point from a unit suffix, or a storage size
+    /// from `[b]` or `[B]` when no declared unit claims that spelling.
+    fn evaluate_measured(
+        &self,
+        source: &SourceText,
+        value: Span,
+        unit: &UnitExpression,
+        span: Span,
+        trace: &mut impl TraceSink,
+    ) -> Result<Value, Diagnostic> {
+        let resolved = self.units.resolve(source, unit);
+        if let (Err(_), UnitExpression::Atom(atom)) = (&resolved, unit)
+            && matches!(source.slice(*atom), "b" | "B")
+        {
+            return evaluate_size_literal(source, value, *atom, span, trace);
+        }
+        let unit = resolved.map_err(|(span, error)| unit_diagnostic(source, span, error))?;
+        let magnitude = units::literal_magnitude(source.slice(value)).ok_or_else(|| {
+            diagnostic(
+                source,
+                "E-QUANTITY-LITERAL",
+                value,
+                "a quantity magnitude must be an Int or Rational literal",
+            )
+        })?;
+        let quantity = Quantity { magnitude, unit };
+        trace.record(TraceEvent {
+            event: "numeric.quantity.constructed",
+            rule: "TOPAL-NUM-UNIT-EXPRESSION-001",
+            detail: &format!(
+                "{}:{}",
+                quantity.unit.text(),
+                if quantity.is_point() {
+                    "point"
+                } else {
+                    "linear"
+                }
+            ),
+        });
+        Ok(Value::Quantity(Box::new(quantity)))
+    }
+
+    /// Apply a binary callable, refusing a power whose result would exceed
+    /// the value-memory budget before computing it.
+    fn apply_binary_within_budget(
+        &self,
+        source: &SourceText,
+        kind: CallableKind,
+        left: Value,
+        right: Value,
+        spans: (Span, Span, Span),
+        trace: &mut impl TraceSink,
+    ) -> Result<Value, Diagnostic> {
+        if kind == CallableKind::Power && self.resources.limits().value_memory.is_some() {
+            let projected = match (&left, &right) {
+                (Value::Int(base), Value::Int(exponent)) => power_size(base, exponent),
+                (Value::Rational(base), Value::Int(exponent)) => {
+                    power_size(base.numer(), exponent) + power_size(base.denom(), exponent)
+                }
+                _ => 0,
+            };
+            self.resources.admit_size(source, spans.0, projected)?;
+        }
+        apply_binary(source, kind, left, right, spans, trace)
+    }
+
     #[allow(clippy::too_many_lines)] // Keep recursive expression cases together and auditable.
     fn evaluate_expression(
         &self,
@@ -2227,37 +2515,12 @@ impl Session {
         expression: &Expression,
         trace: &mut impl TraceSink,
     ) -> Result<Value, Diagnostic> {
+        self.resources.consume_fuel(source, expression.span())?;
         let value = match expression {
             Expression::Block { statements, .. } => self.evaluate_block(source, statements, trace),
             Expression::Boolean(span) => Ok(evaluate_boolean_literal(source, *span, trace)),
             Expression::Measured { value, unit, span } => {
-                let amount = parse_integer(source.slice(*value)).ok_or_else(|| {
-                    diagnostic(
-                        source,
-                        "E-SIZE-LITERAL",
-                        *span,
-                        "size must use a Nat literal",
-                    )
-                })?;
-                if amount < BigInt::from(0) || !matches!(source.slice(*unit), "b" | "B") {
-                    return Err(diagnostic(
-                        source,
-                        "E-SIZE-LITERAL",
-                        *span,
-                        "size must be nonnegative and use `[b]` or `[B]`",
-                    ));
-                }
-                let bits = if source.slice(*unit) == "B" {
-                    amount * 8
-                } else {
-                    amount
-                };
-                trace.record(TraceEvent {
-                    event: "layout.size.constructed",
-                    rule: "TOPAL-LAYOUT-SIZE-001",
-                    detail: source.slice(*span),
-                });
-                Ok(Value::SizeBits(bits))
+                self.evaluate_measured(source, *value, unit, *span, trace)
             }
             Expression::Unit(_) => {
                 trace.record(TraceEvent {
@@ -2477,7 +2740,7 @@ impl Session {
                             let right_span = operand.span();
                             let right = self.evaluate_expression(source, operand, trace)?;
                             matches!(
-                                apply_binary(
+                                self.apply_binary_within_budget(
                                     source,
                                     *kind,
                                     subject.clone(),
@@ -2538,11 +2801,14 @@ impl Session {
                         enum_types: self.enum_types.clone(),
                         union_types: self.union_types.clone(),
                         generic_types: self.generic_types.clone(),
+                        units: self.units.clone(),
+                        sensitivity: self.sensitivity.clone(),
                         call_stack: self.call_stack.clone(),
                         static_context: self.static_context,
                         task_state: self.task_state.clone(),
                         next_task_identity: Cell::new(self.next_task_identity.get()),
                         next_transaction_identity: Cell::new(self.next_transaction_identity.get()),
+                        resources: Rc::clone(&self.resources),
                     };
                     branch.bindings.insert(name.to_owned(), subject);
                     trace.record(TraceEvent {
@@ -2820,7 +3086,7 @@ impl Session {
                 if matches!(
                     items.as_slice(),
                     [Expression::Identifier(operation), ..]
-                        if matches!(source.slice(*operation), "unzip" | "collect" | "collect-set" | "collect-bag" | "collect-map")
+                        if MATERIALIZATIONS.contains(&source.slice(*operation))
                 ) || matches!(
                     items.as_slice(),
                     [_, Expression::Identifier(operation), ..]
@@ -2942,6 +3208,12 @@ impl Session {
                     });
                     return Ok(Value::String(text));
                 }
+                if let Some(result) = evaluate_quantity_conversion(self, source, items, trace) {
+                    return result;
+                }
+                if let Some(result) = evaluate_approximate_application(self, source, items, trace) {
+                    return result;
+                }
                 if let [Expression::Identifier(constructor), operand] = items.as_slice()
                     && source.slice(*constructor) == "Int"
                 {
@@ -3313,11 +3585,14 @@ impl Session {
                         enum_types: self.enum_types.clone(),
                         union_types: self.union_types.clone(),
                         generic_types: self.generic_types.clone(),
+                        units: self.units.clone(),
+                        sensitivity: self.sensitivity.clone(),
                         call_stack: self.call_stack.clone(),
                         static_context: false,
                         task_state: None,
                         next_task_identity: Cell::new(self.next_task_identity.get()),
                         next_transaction_identity: Cell::new(self.next_transaction_identity.get()),
+                        resources: Rc::clone(&self.resources),
                     };
                     bind_generator_arguments(
                         &mut generator_scope,
@@ -3489,12 +3764,16 @@ impl Session {
                         enum_types: self.enum_types.clone(),
                         union_types: self.union_types.clone(),
                         generic_types: BTreeMap::new(),
+                        units: self.units.clone(),
+                        sensitivity: self.sensitivity.clone(),
                         call_stack: self.call_stack.clone(),
                         static_context: function.is_static,
                         task_state: None,
                         next_task_identity: Cell::new(self.next_task_identity.get()),
                         next_transaction_identity: Cell::new(self.next_transaction_identity.get()),
+                        resources: Rc::clone(&self.resources),
                     };
+                    let _call = self.resources.enter_call(source, *name_span)?;
                     function_scope.call_stack.push(ActiveCall {
                         name: name.to_owned(),
                         signature: signature.clone(),
@@ -3599,10 +3878,7 @@ impl Session {
                 }
                 if items.len() == 2
                     && let Expression::Identifier(name) = &items[0]
-                    && matches!(
-                        source.slice(*name),
-                        "array-at?" | "map-lookup" | "set-contains?" | "bag-multiplicity"
-                    )
+                    && COLLECTION_QUERIES.contains(&source.slice(*name))
                 {
                     let operation = source.slice(*name);
                     let operand_span = items[1].span();
@@ -4138,7 +4414,7 @@ impl Session {
                     };
                     let right_span = right.span();
                     let right = self.evaluate_expression(source, right, trace)?;
-                    result = apply_binary(
+                    result = self.apply_binary_within_budget(
                         source,
                         *kind,
                         result,
@@ -4156,6 +4432,10 @@ impl Session {
                 Ok(result)
             }
         }?;
+        if self.resources.limits().value_memory.is_some() {
+            self.resources
+                .admit_size(source, expression.span(), value_size(&value))?;
+        }
         self.checkpoint(trace, Some(&value), Some(expression.span()));
         Ok(value)
     }
@@ -4772,7 +5052,7 @@ impl Session {
             Value::Tuple(mut operands) if operands.len() == 2 => {
                 let right = operands.pop().expect("two operands");
                 let left = operands.pop().expect("two operands");
-                apply_binary(
+                self.apply_binary_within_budget(
                     source,
                     kind,
                     left,
@@ -5633,6 +5913,7 @@ impl Session {
                 ),
             ));
         }
+        let _call = self.resources.enter_call(source, call_span)?;
         let mut invocation = self.clone();
         invocation.bindings = bindings.clone();
         for (parameter, argument) in parameters.iter().zip(arguments) {
@@ -6990,6 +7271,26 @@ impl Execution {
                 "an overload with the same input classifiers and staticness already exists",
             ));
         }
+        let parameter_names = parameters
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        let performed = called_effects(&self.source, body, &parameter_names, &|callee| {
+            session.functions.get(callee).map(|overloads| {
+                overloads
+                    .iter()
+                    .fold(EffectSet::default(), |effects, function| {
+                        effects.union(&function.effects)
+                    })
+            })
+        });
+        let allowed = effect_bound_text.as_deref().and_then(effect_row);
+        if let (Some(bound), Some(allowed)) = (effect_bound, &allowed)
+            && let Some(error) =
+                effect_bound_diagnostic(&self.source, name_text, bound, allowed, &performed)
+        {
+            return Err(error);
+        }
         let direct_termination_rule = prove_euclidean_recursion(
             &self.source,
             name_text,
@@ -7014,6 +7315,7 @@ impl Execution {
             result: result_text.to_owned(),
             generic_names,
             effect_bound: effect_bound_text.clone(),
+            effects: allowed.unwrap_or(performed),
             body: body.to_vec(),
             bindings: session.bindings.clone(),
             termination_rule,
@@ -7182,6 +7484,16 @@ impl Execution {
         trace: &mut impl TraceSink,
     ) -> Result<ExecutionStep, Diagnostic> {
         let statement = &self.statements[self.cursor];
+        let sensitive = session.sensitivity.observe(&self.source, statement);
+        if let Statement::Binding {
+            classifier: Some(classifier),
+            value,
+            ..
+        } = statement
+            && sensitive_payload(self.source.slice(*classifier)).is_some()
+        {
+            redact_literals(&self.source, value, trace);
+        }
         let (value, span) = match statement {
             Statement::LanguageSelection {
                 version,
@@ -7574,6 +7886,16 @@ impl Execution {
                 (value, expression.span())
             }
         };
+        if let Some(name) = sensitive {
+            if let Some(bound) = session.bindings.get(&name) {
+                redact_sensitive(trace, bound);
+            }
+            trace.record(TraceEvent {
+                event: "sensitive.classified",
+                rule: "TOPAL-SENSITIVE-PROVENANCE-001",
+                detail: &name,
+            });
+        }
         self.cursor += 1;
         if self.cursor == self.statements.len() {
             record_result(trace, &value);
@@ -7628,6 +7950,11 @@ impl Execution {
         {
             return Ok(BindingOutcome::Bound(value, span));
         }
+        if let Some((value, span)) =
+            declare_measure(&self.source, name, initializer, session, trace)?
+        {
+            return Ok(BindingOutcome::Bound(value, span));
+        }
         let mut evaluated =
             evaluate_binding_initializer(&self.source, session, initializer, classifier, trace)?;
         consume_generator_argument(&self.source, session, initializer);
@@ -7668,6 +7995,15 @@ impl Execution {
                 return Ok(BindingOutcome::Returned(evaluated, initializer.span()));
             }
             if !value_has_classifier(&evaluated, classifier_text) {
+                if let Value::Quantity(quantity) = &evaluated
+                    && session.units.is_dimension_classifier(classifier_text)
+                {
+                    return Err(unit_diagnostic(
+                        &self.source,
+                        initializer.span(),
+                        units::classifier_mismatch(&quantity.classifier(), classifier_text),
+                    ));
+                }
                 if classifier_text == "Character"
                     && let Value::String(text) = &evaluated
                 {
@@ -8113,77 +8449,802 @@ fn construct_rational(
     Ok(Value::Rational(value))
 }
 
-const fn cover(first: Span, second: Span) -> Span {
-    Span {
-        start: first.start,
-        end: second.end,
+/// Evaluate approximate construction, the lossy numeric functions, and
+/// approximate sums, or return `None` when `items` has none of their shapes.
+fn evaluate_approximate_application(
+    session: &Session,
+    source: &SourceText,
+    items: &[Expression],
+    trace: &mut impl TraceSink,
+) -> Option<Result<Value, Diagnostic>> {
+    match items {
+        [Expression::Identifier(constructor), operand] => {
+            let format = ApproximateFormat::named(source.slice(*constructor))?;
+            Some(construct_approximate(
+                session, source, format, operand, trace,
+            ))
+        }
+        [
+            values,
+            Expression::Identifier(function),
+            Expression::Identifier(format),
+        ] if source.slice(*function) == "reproducible-sum" => {
+            let format = ApproximateFormat::named(source.slice(*format))?;
+            Some(
+                session
+                    .evaluate_expression(source, values, trace)
+                    .and_then(|entries| {
+                        sum_approximate(source, values, entries, format, None, trace)
+                    }),
+            )
+        }
+        [
+            values,
+            Expression::Identifier(function),
+            Expression::Identifier(rounding),
+            Expression::Identifier(format),
+        ] if source.slice(*function) == "exact-sum" => {
+            let rounding = Rounding::named(source.slice(*rounding))?;
+            let format = ApproximateFormat::named(source.slice(*format))?;
+            Some(
+                session
+                    .evaluate_expression(source, values, trace)
+                    .and_then(|entries| {
+                        sum_approximate(source, values, entries, format, Some(rounding), trace)
+                    }),
+            )
+        }
+        [operand, Expression::Identifier(function), target] => {
+            let function = source.slice(*function);
+            let rounding = Rounding::named(function);
+            if rounding.is_none() && function != "saturate" {
+                return None;
+            }
+            let target = LossyTarget::of(source, target)?;
+            Some(
+                session
+                    .evaluate_expression(source, operand, trace)
+                    .and_then(|value| {
+                        apply_lossy(source, operand, &value, rounding, target, trace)
+                    }),
+            )
+        }
+        _ => None,
     }
 }
 
-fn statement_span(statement: &Statement) -> Span {
-    match statement {
-        Statement::Binding { name, value, .. } => cover(*name, value.span()),
-        Statement::LanguageSelection { span, .. }
-        | Statement::Published { span, .. }
-        | Statement::DiagnosticControl { span, .. }
-        | Statement::Implementation { span, .. }
-        | Statement::ContextAssignment { span, .. }
-        | Statement::Function { span, .. }
-        | Statement::Generator { span, .. }
-        | Statement::Union { span, .. }
-        | Statement::Interface { span, .. }
-        | Statement::InterfaceImplementation { span, .. }
-        | Statement::Foreach { span, .. } => *span,
-        Statement::StateField { name, classifier } => cover(*name, *classifier),
-        Statement::Discard { span, value } => cover(*span, value.span()),
-        Statement::Return { keyword, value } => cover(*keyword, value.span()),
-        Statement::Expression(expression) => expression.span(),
-    }
+fn approximate_value(format: ApproximateFormat, number: ApproximateNumber) -> Value {
+    Value::Approximate(Box::new(ApproximateValue { format, number }))
 }
 
-fn declaration_name<'a>(source: &'a SourceText, statement: &Statement) -> Option<&'a str> {
-    let name = match statement {
-        Statement::Binding { name, .. }
-        | Statement::Implementation { name, .. }
-        | Statement::Function { name, .. }
-        | Statement::Generator { name, .. }
-        | Statement::Union { name, .. }
-        | Statement::Interface { name, .. } => *name,
-        Statement::Published { declaration, .. } => return declaration_name(source, declaration),
-        _ => return None,
+/// The exact or approximate numeric operand of an approximate operation and
+/// the classifier it was obtained from.
+fn approximate_operand(value: &Value) -> Option<(ApproximateNumber, &'static str)> {
+    let exact = |value: BigRational| {
+        if value.numer() == &BigInt::from(0) {
+            ApproximateNumber::Zero(ZeroDirection::Exact)
+        } else {
+            ApproximateNumber::Finite(value)
+        }
     };
-    Some(source.slice(name))
+    match value {
+        Value::Int(value) => Some((exact(BigRational::from_integer(value.clone())), "Int")),
+        Value::Rational(value) => Some((exact(value.clone()), "Rational")),
+        Value::Approximate(value) => Some((value.number.clone(), value.format.name)),
+        _ => None,
+    }
 }
 
-fn supported_generator_body(source: &SourceText, body: &[Statement]) -> bool {
-    if !matches!(
-        body.last(),
-        Some(Statement::Expression(_) | Statement::Return { .. })
-    ) {
-        return false;
-    }
-    for statement in &body[..body.len().saturating_sub(1)] {
-        if yielded_statement(source, statement).is_none()
-            && !matches!(
-                statement,
-                Statement::Published { .. }
-                    | Statement::DiagnosticControl { .. }
-                    | Statement::Binding { .. }
-                    | Statement::ContextAssignment { .. }
-                    | Statement::Discard { .. }
-                    | Statement::Function { .. }
-                    | Statement::Return { .. }
-            )
-        {
-            return false;
-        }
+fn trace_zero_direction(trace: &mut impl TraceSink, number: &ApproximateNumber) {
+    if let ApproximateNumber::Zero(direction) = number {
+        trace.record(TraceEvent {
+            event: "numeric.zero.direction",
+            rule: "TOPAL-NUM-ZERO-DIRECTION-001",
+            detail: direction.as_str(),
+        });
     }
-    true
 }
 
-fn discarded_yield_expression<'a>(
+/// Construct a dynamic arithmetic failure at `span` and record it.
+fn approximate_error(
     source: &SourceText,
-    statement: &'a Statement,
+    span: Span,
+    domain: String,
+    code: &str,
+    rule: &'static str,
+    trace: &mut impl TraceSink,
+) -> Value {
+    let position = source.position(span.start);
+    trace.record(TraceEvent {
+        event: "result.error.constructed",
+        rule,
+        detail: &format!("{domain};{code}"),
+    });
+    Value::Error {
+        domain,
+        code: code.to_owned(),
+        line: position.line,
+        column: position.column,
+    }
+}
+
+/// Exact checked construction of an approximate value. The spellings
+/// `+Infinity`, `-Infinity`, and `-0` name the format's infinities and its zero
+/// reached from below.
+fn construct_approximate(
+    session: &Session,
+    source: &SourceText,
+    format: ApproximateFormat,
+    operand: &Expression,
+    trace: &mut impl TraceSink,
+) -> Result<Value, Diagnostic> {
+    let spelled = match operand {
+        Expression::Identifier(name) => match source.slice(*name) {
+            "+Infinity" => Some(ApproximateNumber::PositiveInfinity),
+            "-Infinity" => Some(ApproximateNumber::NegativeInfinity),
+            _ => None,
+        },
+        Expression::Integer(literal)
+            if source.slice(*literal).starts_with('-')
+                && parse_integer(source.slice(*literal)) == Some(BigInt::from(0)) =>
+        {
+            Some(ApproximateNumber::Zero(ZeroDirection::FromBelow))
+        }
+        _ => None,
+    };
+    if let Some(number) = spelled {
+        let spelling = source.slice(operand.span());
+        trace.record(TraceEvent {
+            event: "numeric.approximate.constructed",
+            rule: "TOPAL-NUM-APPROX-CONSTRUCT-001",
+            detail: &format!("{spelling}->{}:exact", format.name),
+        });
+        trace_zero_direction(trace, &number);
+        return Ok(approximate_value(format, number));
+    }
+    let value = session.evaluate_expression(source, operand, trace)?;
+    let Some((number, from)) = approximate_operand(&value) else {
+        return Err(diagnostic(
+            source,
+            "E-APPROXIMATE-CONSTRUCTOR-OPERAND",
+            operand.span(),
+            format!("{} construction requires a numeric operand", format.name),
+        ));
+    };
+    // Zeros and infinities exist in every format, so only finite values can
+    // fail exact construction.
+    let constructed = match number {
+        ApproximateNumber::Finite(value) => format.exact(&value),
+        number => Some(number),
+    };
+    let Some(number) = constructed else {
+        if expression_is_closed(operand) {
+            return Err(diagnostic(
+                source,
+                "E-APPROXIMATE-NOT-REPRESENTABLE",
+                operand.span(),
+                format!(
+                    "{} cannot represent this {from} value exactly; select a nearby value with a named rounding function such as round-to-even",
+                    format.name
+                ),
+            ));
+        }
+        return Ok(approximate_error(
+            source,
+            operand.span(),
+            format!("root.{}({from})", format.name),
+            "not-representable",
+            "TOPAL-NUM-APPROX-CONSTRUCT-001",
+            trace,
+        ));
+    };
+    trace.record(TraceEvent {
+        event: "numeric.approximate.constructed",
+        rule: "TOPAL-NUM-APPROX-CONSTRUCT-001",
+        detail: &format!("{from}->{}:exact", format.name),
+    });
+    trace_zero_direction(trace, &number);
+    Ok(approximate_value(format, number))
+}
+
+/// The numeric type a lossy function selects its replacement value from.
+#[derive(Clone, Copy)]
+enum LossyTarget {
+    Int,
+    Approximate {
+        format: ApproximateFormat,
+        finite: bool,
+    },
+}
+
+impl LossyTarget {
+    fn of(source: &SourceText, target: &Expression) -> Option<Self> {
+        match target {
+            Expression::Identifier(name) if source.slice(*name) == "Int" => Some(Self::Int),
+            Expression::Identifier(name) => Some(Self::Approximate {
+                format: ApproximateFormat::named(source.slice(*name))?,
+                finite: false,
+            }),
+            Expression::Application { items, .. } => match items.as_slice() {
+                [Expression::Identifier(finite), Expression::Identifier(name)]
+                    if source.slice(*finite) == "Finite" =>
+                {
+                    Some(Self::Approximate {
+                        format: ApproximateFormat::named(source.slice(*name))?,
+                        finite: true,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn name(self) -> String {
+        match self {
+            Self::Int => "Int".to_owned(),
+            Self::Approximate {
+                format,
+                finite: false,
+            } => format.name.to_owned(),
+            Self::Approximate {
+                format,
+                finite: true,
+            } => format!("Finite {}", format.name),
+        }
+    }
+}
+
+/// Apply a named rounding function, or `saturate` when `rounding` is `None`.
+fn apply_lossy(
+    source: &SourceText,
+    operand: &Expression,
+    value: &Value,
+    rounding: Option<Rounding>,
+    target: LossyTarget,
+    trace: &mut impl TraceSink,
+) -> Result<Value, Diagnostic> {
+    let function = rounding.map_or("saturate", Rounding::as_str);
+    let Some((number, from)) = approximate_operand(value) else {
+        return Err(diagnostic(
+            source,
+            "E-LOSSY-FUNCTION-OPERAND",
+            operand.span(),
+            format!("{function} requires an Int, Rational, or approximate operand"),
+        ));
+    };
+    let selection = format!("root.{function}({from},{})", target.name());
+    trace.record(TraceEvent {
+        event: "operator.selected",
+        rule: "TOPAL-TYPE-CALL-001",
+        detail: &selection,
+    });
+    let result = if let Some(rounding) = rounding {
+        round_lossy(number, rounding, target, trace)
+    } else {
+        let LossyTarget::Approximate {
+            format,
+            finite: true,
+        } = target
+        else {
+            return Err(diagnostic(
+                source,
+                "E-SATURATE-TARGET",
+                operand.span(),
+                format!(
+                    "saturate requires a target with least and greatest finite values, and {} has none",
+                    target.name()
+                ),
+            ));
+        };
+        saturate(number, format, trace)
+    };
+    match result {
+        Ok(value) => Ok(value),
+        Err(code) if expression_is_closed(operand) => Err(diagnostic(
+            source,
+            if code == "out-of-range" {
+                "E-LOSSY-OUT-OF-RANGE"
+            } else {
+                "E-SATURATE-NOT-REPRESENTABLE"
+            },
+            operand.span(),
+            format!("{function} cannot produce {}: {code}", target.name()),
+        )),
+        Err(code) => Ok(approximate_error(
+            source,
+            operand.span(),
+            selection,
+            code,
+            if rounding.is_some() {
+                "TOPAL-NUM-LOSSY-ROUND-001"
+            } else {
+                "TOPAL-NUM-SATURATE-001"
+            },
+            trace,
+        )),
+    }
+}
+
+fn round_lossy(
+    number: ApproximateNumber,
+    rounding: Rounding,
+    target: LossyTarget,
+    trace: &mut impl TraceSink,
+) -> Result<Value, &'static str> {
+    let (value, outcome) = match (target, number) {
+        (LossyTarget::Int, number) => {
+            let exact = number.exact_value().ok_or("out-of-range")?;
+            let rounded = rounding.to_integer(&exact);
+            let outcome = if BigRational::from_integer(rounded.clone()) == exact {
+                Outcome::Exact
+            } else {
+                Outcome::Rounded
+            };
+            (Value::Int(rounded), outcome)
+        }
+        (LossyTarget::Approximate { format, finite }, ApproximateNumber::Finite(value)) => {
+            let (number, outcome) = format.round(&value, rounding);
+            if finite && outcome == Outcome::Overflow {
+                return Err("out-of-range");
+            }
+            trace_zero_direction(trace, &number);
+            (approximate_value(format, number), outcome)
+        }
+        (LossyTarget::Approximate { finite: true, .. }, number) if number.is_infinite() => {
+            return Err("out-of-range");
+        }
+        (LossyTarget::Approximate { format, .. }, number) => {
+            trace_zero_direction(trace, &number);
+            (approximate_value(format, number), Outcome::Exact)
+        }
+    };
+    trace.record(TraceEvent {
+        event: "numeric.lossy.applied",
+        rule: if rounding.is_directed() {
+            "TOPAL-NUM-LOSSY-DIRECTED-001"
+        } else {
+            "TOPAL-NUM-LOSSY-ROUND-001"
+        },
+        detail: outcome.as_str(),
+    });
+    Ok(value)
+}
+
+fn saturate(
+    number: ApproximateNumber,
+    format: ApproximateFormat,
+    trace: &mut impl TraceSink,
+) -> Result<Value, &'static str> {
+    let greatest = ApproximateNumber::Finite(format.largest_finite());
+    let least = greatest.negate();
+    let (number, detail) = if number > greatest {
+        (greatest, "upper")
+    } else if number < least {
+        (least, "lower")
+    } else if let ApproximateNumber::Finite(value) = number {
+        (format.exact(&value).ok_or("not-representable")?, "within")
+    } else {
+        (number, "within")
+    };
+    trace.record(TraceEvent {
+        event: "numeric.saturated",
+        rule: "TOPAL-NUM-SATURATE-001",
+        detail,
+    });
+    trace_zero_direction(trace, &number);
+    Ok(approximate_value(format, number))
+}
+
+/// Sum a List of approximate values exactly and round once, so the result does
+/// not depend on entry order. `reproducible-sum` rounds to even.
+fn sum_approximate(
+    source: &SourceText,
+    operand: &Expression,
+    values: Value,
+    format: ApproximateFormat,
+    rounding: Option<Rounding>,
+    trace: &mut impl TraceSink,
+) -> Result<Value, Diagnostic> {
+    let function = if rounding.is_some() {
+        "exact-sum"
+    } else {
+        "reproducible-sum"
+    };
+    let entries = match values {
+        Value::List { entries, .. } => entries
+            .into_iter()
+            .map(|entry| match entry {
+                Value::Approximate(entry) if entry.format == format => Some(entry.number),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>(),
+        _ => None,
+    };
+    let Some(entries) = entries else {
+        return Err(diagnostic(
+            source,
+            "E-APPROXIMATE-SUM-OPERAND",
+            operand.span(),
+            format!("{function} requires a List of {} values", format.name),
+        ));
+    };
+    let selection = format!("root.{function}(List {})", format.name);
+    trace.record(TraceEvent {
+        event: "operator.selected",
+        rule: "TOPAL-TYPE-CALL-001",
+        detail: &selection,
+    });
+    let rounding = rounding.unwrap_or(Rounding::TiesToEven);
+    match approximate::exact_sum(format, &entries, rounding) {
+        Ok((number, outcome)) => {
+            trace.record(TraceEvent {
+                event: "numeric.sum.reproducible",
+                rule: "TOPAL-NUM-REPRODUCIBLE-SUM-001",
+                detail: &format!("{}:{}", rounding.as_str(), outcome.as_str()),
+            });
+            trace_zero_direction(trace, &number);
+            Ok(approximate_value(format, number))
+        }
+        Err(error) => Ok(approximate_error(
+            source,
+            operand.span(),
+            selection,
+            error.code(),
+            "TOPAL-NUM-REPRODUCIBLE-SUM-001",
+            trace,
+        )),
+    }
+}
+
+fn apply_approximate_binary(
+    source: &SourceText,
+    kind: CallableKind,
+    left: &ApproximateValue,
+    right: &ApproximateValue,
+    span: Span,
+    trace: &mut impl TraceSink,
+) -> Result<Value, Diagnostic> {
+    let operation = match kind {
+        CallableKind::Plus => Operation::Add,
+        CallableKind::Minus => Operation::Subtract,
+        CallableKind::Multiply => Operation::Multiply,
+        CallableKind::Divide => Operation::Divide,
+        _ => {
+            return Err(diagnostic(
+                source,
+                "E-NO-APPLICABLE-OVERLOAD",
+                span,
+                "approximate values support +, -, *, and /",
+            ));
+        }
+    };
+    let format = left.format;
+    let selection = format!(
+        "root.{}({},{})",
+        callable_name(kind),
+        format.name,
+        format.name
+    );
+    trace.record(TraceEvent {
+        event: "operator.selected",
+        rule: "TOPAL-TYPE-CALL-001",
+        detail: &selection,
+    });
+    if operation == Operation::Divide {
+        trace_zero_direction(trace, &right.number);
+    }
+    let infinite = left.number.is_infinite() || right.number.is_infinite();
+    match approximate::apply(format, operation, &left.number, &right.number) {
+        Ok((number, outcome)) => {
+            trace.record(TraceEvent {
+                event: "numeric.approximate.rounded",
+                rule: if infinite {
+                    "TOPAL-NUM-APPROX-INFINITY-001"
+                } else {
+                    "TOPAL-NUM-APPROX-ARITHMETIC-001"
+                },
+                detail: outcome.as_str(),
+            });
+            trace_zero_direction(trace, &number);
+            Ok(approximate_value(format, number))
+        }
+        Err(error) => Ok(approximate_error(
+            source,
+            span,
+            selection,
+            error.code(),
+            if error == approximate::ArithmeticError::DivisionByZero {
+                "TOPAL-NUM-ZERO-DIRECTION-001"
+            } else {
+                "TOPAL-NUM-APPROX-INFINITY-001"
+            },
+            trace,
+        )),
+    }
+}
+
+fn evaluate_size_literal(
+    source: &SourceText,
+    value: Span,
+    unit: Span,
+    span: Span,
+    trace: &mut impl TraceSink,
+) -> Result<Value, Diagnostic> {
+    let amount = parse_integer(source.slice(value)).ok_or_else(|| {
+        diagnostic(
+            source,
+            "E-SIZE-LITERAL",
+            span,
+            "size must use a Nat literal",
+        )
+    })?;
+    if amount < BigInt::from(0) {
+        return Err(diagnostic(
+            source,
+            "E-SIZE-LITERAL",
+            span,
+            "size must be nonnegative and use `[b]` or `[B]`",
+        ));
+    }
+    let bits = if source.slice(unit) == "B" {
+        amount * 8
+    } else {
+        amount
+    };
+    trace.record(TraceEvent {
+        event: "layout.size.constructed",
+        rule: "TOPAL-LAYOUT-SIZE-001",
+        detail: source.slice(span),
+    });
+    Ok(Value::SizeBits(bits))
+}
+
+fn unit_diagnostic(source: &SourceText, span: Span, error: units::UnitError) -> Diagnostic {
+    diagnostic(source, error.code, span, error.message)
+}
+
+/// Evaluate `quantity as Unit` or `quantity as Delta Unit`, or return `None`
+/// when `items` is not a conversion.
+fn evaluate_quantity_conversion(
+    session: &Session,
+    source: &SourceText,
+    items: &[Expression],
+    trace: &mut impl TraceSink,
+) -> Option<Result<Value, Diagnostic>> {
+    let position = items.iter().position(
+        |item| matches!(item, Expression::Identifier(span) if source.slice(*span) == "as"),
+    )?;
+    if position == 0 {
+        return None;
+    }
+    let (operands, target) = (&items[..position], &items[position + 1..]);
+    let unit = units::conversion_target(source, &session.units, target)?;
+    let target_span = cover(target[0].span(), target[target.len() - 1].span());
+    Some(
+        unit.map_err(|error| unit_diagnostic(source, target_span, error))
+            .and_then(|unit| {
+                let span = cover(operands[0].span(), target_span);
+                let operand = match operands {
+                    [operand] => session.evaluate_expression(source, operand, trace)?,
+                    _ => session.evaluate_expression(
+                        source,
+                        &Expression::Application {
+                            items: operands.to_vec(),
+                            span: cover(operands[0].span(), operands[operands.len() - 1].span()),
+                        },
+                        trace,
+                    )?,
+                };
+                let Value::Quantity(quantity) = operand else {
+                    return Err(diagnostic(
+                        source,
+                        "E-NO-APPLICABLE-OVERLOAD",
+                        span,
+                        "`as` converts a quantity or point to another unit",
+                    ));
+                };
+                let converted = units::convert(&quantity, &unit)
+                    .map_err(|error| unit_diagnostic(source, span, error))?;
+                trace.record(TraceEvent {
+                    event: "numeric.quantity.converted",
+                    rule: "TOPAL-NUM-QUANTITY-CONVERT-001",
+                    detail: &format!("{}->{}", quantity.unit.text(), unit.text()),
+                });
+                Ok(Value::Quantity(Box::new(converted)))
+            }),
+    )
+}
+
+/// Apply an operator with at least one quantity operand; exact numbers take
+/// part as dimensionless quantities and dimensionless results become numbers.
+fn apply_quantity_binary(
+    source: &SourceText,
+    kind: CallableKind,
+    left: Value,
+    right: Value,
+    span: Span,
+    trace: &mut impl TraceSink,
+) -> Result<Value, Diagnostic> {
+    let (Some(left), Some(right)) = (quantity_operand(left), quantity_operand(right)) else {
+        return Err(diagnostic(
+            source,
+            "E-NO-APPLICABLE-OVERLOAD",
+            span,
+            "a quantity combines only with another quantity or an exact number",
+        ));
+    };
+    let symbol = callable_name(kind);
+    match units::apply(kind, symbol, &left, &right)
+        .map_err(|error| unit_diagnostic(source, span, error))?
+    {
+        Applied::Quantity(quantity, form) => {
+            trace.record(TraceEvent {
+                event: "numeric.quantity.arithmetic",
+                rule: if form.contains("point") {
+                    "TOPAL-NUM-QUANTITY-AFFINE-001"
+                } else {
+                    "TOPAL-NUM-QUANTITY-LINEAR-001"
+                },
+                detail: &format!("{symbol}:{form}"),
+            });
+            Ok(match quantity.dimensionless() {
+                Some(number) if number.is_integer() => Value::Int(number.to_integer()),
+                Some(number) => Value::Rational(number),
+                None => Value::Quantity(quantity),
+            })
+        }
+        Applied::Ordering(ordering) => {
+            trace.record(TraceEvent {
+                event: "numeric.quantity.compared",
+                rule: "TOPAL-NUM-QUANTITY-LINEAR-001",
+                detail: symbol,
+            });
+            Ok(match kind {
+                CallableKind::Equal => Value::Boolean(ordering == Ordering::Equal),
+                CallableKind::NotEqual => Value::Boolean(ordering != Ordering::Equal),
+                CallableKind::Less => Value::Boolean(ordering == Ordering::Less),
+                CallableKind::Greater => Value::Boolean(ordering == Ordering::Greater),
+                CallableKind::LessEqual => Value::Boolean(ordering != Ordering::Greater),
+                CallableKind::GreaterEqual => Value::Boolean(ordering != Ordering::Less),
+                _ => Value::Enum {
+                    type_name: "Comparison".to_owned(),
+                    alternative: match ordering {
+                        Ordering::Less => "Less",
+                        Ordering::Equal => "Equal",
+                        Ordering::Greater => "Greater",
+                    }
+                    .to_owned(),
+                },
+            })
+        }
+    }
+}
+
+fn quantity_operand(value: Value) -> Option<Quantity> {
+    match value {
+        Value::Quantity(quantity) => Some(*quantity),
+        Value::Int(value) => Some(Quantity::number(BigRational::from_integer(value))),
+        Value::Rational(value) => Some(Quantity::number(value)),
+        _ => None,
+    }
+}
+
+fn declare_measure(
+    source: &SourceText,
+    name: Span,
+    expression: &Expression,
+    session: &mut Session,
+    trace: &mut impl TraceSink,
+) -> Result<Option<(Value, Span)>, Diagnostic> {
+    let Some(declaration) = units::declaration(source, &session.units, expression) else {
+        return Ok(None);
+    };
+    let declaration = declaration.map_err(|(span, error)| unit_diagnostic(source, span, error))?;
+    let name_text = source.slice(name);
+    match declaration {
+        Declaration::Dimension(kind) => {
+            session.units.declare_dimension(name_text, kind);
+            trace.record(TraceEvent {
+                event: "numeric.dimension.declared",
+                rule: "TOPAL-NUM-DIMENSION-001",
+                detail: &format!("{name_text}:{}", kind.as_str()),
+            });
+        }
+        Declaration::Unit(unit) => {
+            session
+                .units
+                .declare_unit(name_text, &unit)
+                .map_err(|error| unit_diagnostic(source, expression.span(), error))?;
+            trace.record(TraceEvent {
+                event: "numeric.unit.declared",
+                rule: if unit.prefixes.is_empty() {
+                    "TOPAL-NUM-UNIT-DECLARE-001"
+                } else {
+                    "TOPAL-NUM-UNIT-PREFIX-001"
+                },
+                detail: &format!("{name_text}:{}", unit.form.as_str()),
+            });
+        }
+    }
+    session
+        .bindings
+        .insert(name_text.to_owned(), Value::Type(name_text.to_owned()));
+    session.declared_names.insert(name_text.to_owned());
+    Ok(Some((Value::Unit, cover(name, expression.span()))))
+}
+
+const fn cover(first: Span, second: Span) -> Span {
+    Span {
+        start: first.start,
+        end: second.end,
+    }
+}
+
+fn statement_span(statement: &Statement) -> Span {
+    match statement {
+        Statement::Binding { name, value, .. } => cover(*name, value.span()),
+        Statement::LanguageSelection { span, .. }
+        | Statement::Published { span, .. }
+        | Statement::DiagnosticControl { span, .. }
+        | Statement::Implementation { span, .. }
+        | Statement::ContextAssignment { span, .. }
+        | Statement::Function { span, .. }
+        | Statement::Generator { span, .. }
+        | Statement::Union { span, .. }
+        | Statement::Interface { span, .. }
+        | Statement::InterfaceImplementation { span, .. }
+        | Statement::Foreach { span, .. } => *span,
+        Statement::StateField { name, classifier } => cover(*name, *classifier),
+        Statement::Discard { span, value } => cover(*span, value.span()),
+        Statement::Return { keyword, value } => cover(*keyword, value.span()),
+        Statement::Expression(expression) => expression.span(),
+    }
+}
+
+fn declaration_name<'a>(source: &'a SourceText, statement: &Statement) -> Option<&'a str> {
+    let name = match statement {
+        Statement::Binding { name, .. }
+        | Statement::Implementation { name, .. }
+        | Statement::Function { name, .. }
+        | Statement::Generator { name, .. }
+        | Statement::Union { name, .. }
+        | Statement::Interface { name, .. } => *name,
+        Statement::Published { declaration, .. } => return declaration_name(source, declaration),
+        _ => return None,
+    };
+    Some(source.slice(name))
+}
+
+fn supported_generator_body(source: &SourceText, body: &[Statement]) -> bool {
+    if !matches!(
+        body.last(),
+        Some(Statement::Expression(_) | Statement::Return { .. })
+    ) {
+        return false;
+    }
+    for statement in &body[..body.len().saturating_sub(1)] {
+        if yielded_statement(source, statement).is_none()
+            && !matches!(
+                statement,
+                Statement::Published { .. }
+                    | Statement::DiagnosticControl { .. }
+                    | Statement::Binding { .. }
+                    | Statement::ContextAssignment { .. }
+                    | Statement::Discard { .. }
+                    | Statement::Function { .. }
+                    | Statement::Return { .. }
+            )
+        {
+            return false;
+        }
+    }
+    true
+}
+
+fn discarded_yield_expression<'a>(
+    source: &SourceText,
+    statement: &'a Statement,
 ) -> Option<&'a Expression> {
     let Statement::Discard {
         value: Expression::Application { items, .. },
@@ -8239,6 +9300,11 @@ fn advance_custom_generator(
     name: &str,
     trace: &mut impl TraceSink,
 ) -> Result<(), Diagnostic> {
+    let Some(first) = body.get(*cursor) else {
+        return Ok(());
+    };
+    let resources = Rc::clone(&scope.resources);
+    let _call = resources.enter_call(source, statement_span(first))?;
     while *cursor < body.len() {
         let statement = &body[*cursor];
         *cursor += 1;
@@ -8757,7 +9823,11 @@ fn declare_variant(
     Some((Value::Unit, expression.span()))
 }
 
+#[allow(clippy::too_many_lines)] // Classifier membership stays exhaustive across every value kind.
 fn value_has_classifier(value: &Value, classifier: &str) -> bool {
+    if let Some(payload) = sensitive_payload(classifier) {
+        return value_has_classifier(value, payload);
+    }
     if classifier == "MessageContext"
         && matches!(value, Value::Record(fields)
             if fields.iter().any(|(name, _)| name == "session-id")
@@ -8854,6 +9924,8 @@ fn value_has_classifier(value: &Value, classifier: &str) -> bool {
             type_name == classifier
         }
         (Value::Union(union), classifier) => union.type_name == classifier,
+        (Value::Approximate(value), classifier) => value.has_classifier(classifier),
+        (Value::Quantity(quantity), classifier) => quantity.classifier() == classifier,
         _ => false,
     }
 }
@@ -8865,6 +9937,8 @@ fn supported_generator_value_classifier(
     matches!(
         classifier,
         "Unit"
+            | "Binary32"
+            | "Binary64"
             | "Boolean"
             | "Character"
             | "Comparison"
@@ -9332,6 +10406,8 @@ fn supported_generic_classifier(
             supported_generic_classifier(input, generic_names, enum_types)
                 && supported_generic_classifier(result, generic_names, enum_types)
         })
+        || sensitive_payload(classifier)
+            .is_some_and(|payload| supported_generic_classifier(payload, generic_names, enum_types))
         || tuple_classifiers(classifier).is_some_and(|items| {
             items
                 .into_iter()
@@ -9390,7 +10466,9 @@ fn collect_generic_names(
         names.insert(name.to_owned());
         return;
     }
-    if let Some(payload) = applied_classifier(classifier, "Optional") {
+    if let Some(payload) =
+        applied_classifier(classifier, "Optional").or_else(|| sensitive_payload(classifier))
+    {
         collect_generic_names(payload, enum_types, names);
         return;
     }
@@ -10264,7 +11342,9 @@ fn supported_value_classifier(
 ) -> bool {
     matches!(
         classifier,
-        "Boolean"
+        "Binary32"
+            | "Binary64"
+            | "Boolean"
             | "Character"
             | "Completed"
             | "Comparison"
@@ -10305,6 +11385,8 @@ fn supported_value_classifier(
         })
         || result_success_classifier(classifier)
             .is_some_and(|success| supported_value_classifier(success, enum_types))
+        || sensitive_payload(classifier)
+            .is_some_and(|payload| supported_value_classifier(payload, enum_types))
 }
 
 fn generator_classifiers(classifier: &str) -> Option<(&str, &str, &str)> {
@@ -10366,7 +11448,10 @@ fn parenthesized_end(text: &str) -> Option<usize> {
     None
 }
 
-fn accepted_source(input: &str, trace: &mut impl TraceSink) -> Result<SourceText, Diagnostic> {
+pub(crate) fn accepted_source(
+    input: &str,
+    trace: &mut impl TraceSink,
+) -> Result<SourceText, Diagnostic> {
     let source = SourceText::new(input).map_err(|error| {
         let (line, column) = raw_position(input, error.span.start);
         let mut diagnostic = Diagnostic::error(error.code, line, column, error.message)
@@ -10396,6 +11481,46 @@ fn expected_statement(input: &str) -> Diagnostic {
         )
 }
 
+/// Register the rendering of a sensitive value, and the contents of a
+/// sensitive string, with the trace sink so later trace details omit them.
+fn redact_sensitive(trace: &mut impl TraceSink, value: &Value) {
+    if let Value::String(text) = value {
+        trace.redact(text);
+    }
+    trace.redact(&value.to_string());
+}
+
+/// Register the literals of a `Sensitive T` initializer, of every literal
+/// kind, before it is evaluated, so that literal trace events already omit
+/// them.
+fn redact_literals(source: &SourceText, expression: &Expression, trace: &mut impl TraceSink) {
+    match expression {
+        Expression::String(span) => {
+            let literal = source.slice(*span);
+            trace.redact(literal);
+            trace.redact(literal.trim_matches('"'));
+        }
+        Expression::Integer(span) | Expression::Rational(span) => {
+            trace.redact(source.slice(*span));
+        }
+        Expression::Measured { value, span, .. } => {
+            trace.redact(source.slice(*span));
+            trace.redact(source.slice(*value));
+        }
+        Expression::Product { fields, .. } => {
+            for field in fields {
+                redact_literals(source, &field.value, trace);
+            }
+        }
+        Expression::Application { items, .. } => {
+            for item in items {
+                redact_literals(source, item, trace);
+            }
+        }
+        _ => {}
+    }
+}
+
 fn record_result(trace: &mut impl TraceSink, value: &Value) {
     let classifier = structural_value_classifier(value);
     trace.record(TraceEvent {
@@ -10669,6 +11794,8 @@ fn value_classifier(value: &Value) -> &'static str {
         Value::Effects(_) => "Effect",
         Value::Int(_) => "Int",
         Value::Rational(_) => "Rational",
+        Value::Approximate(value) => value.format.name,
+        Value::Quantity(_) => "Quantity",
         Value::IntRange { .. } | Value::RationalRange { .. } => "Range",
         Value::Optional { .. } => "Optional",
         Value::List { .. } => "List",
@@ -10740,6 +11867,7 @@ fn value_classifier(value: &Value) -> &'static str {
 
 fn structural_value_classifier(value: &Value) -> String {
     match value {
+        Value::Quantity(quantity) => quantity.classifier(),
         Value::IntRange { .. } => "Range Int".into(),
         Value::RationalRange { .. } => "Range Rational".into(),
         Value::Tuple(values) => format!(
@@ -11481,14 +12609,14 @@ fn evaluate_string_literal(
     Ok(Value::String(value.to_owned()))
 }
 
-fn parse_string(lexeme: &str) -> Option<&str> {
+pub(crate) fn parse_string(lexeme: &str) -> Option<&str> {
     let opening = lexeme.find('"')?;
     let closing_length = opening + 1;
     (lexeme.len() >= opening + 1 + closing_length)
         .then(|| &lexeme[opening + 1..lexeme.len() - closing_length])
 }
 
-fn display_string(value: &str) -> String {
+pub(crate) fn display_string(value: &str) -> String {
     if !value.contains('"') {
         return format!("\"{value}\"");
     }
@@ -11511,6 +12639,9 @@ fn apply_binary(
     let (span, left_span, right_span) = spans;
     let left = forget_refinement(left, trace, "constraint->base:left");
     let right = forget_refinement(right, trace, "constraint->base:right");
+    if matches!(left, Value::Quantity(_)) || matches!(right, Value::Quantity(_)) {
+        return apply_quantity_binary(source, kind, left, right, span, trace);
+    }
     if matches!(kind, CallableKind::Equal | CallableKind::NotEqual) {
         return apply_equality(source, kind, left, right, span, trace);
     }
@@ -11598,6 +12729,9 @@ fn apply_binary(
         (Value::Int(left), Value::Int(right)) => {
             apply_int_binary(source, kind, left, right, right_span, trace)
         }
+        (Value::Approximate(left), Value::Approximate(right)) if left.format == right.format => {
+            apply_approximate_binary(source, kind, &left, &right, span, trace)
+        }
         (Value::Rational(left), Value::Rational(right)) => {
             apply_rational_binary(source, kind, left, right, span, right_span, trace)
         }
@@ -11923,6 +13057,15 @@ fn values_compare(left: Value, right: Value, trace: &mut impl TraceSink) -> Opti
         ) if left_type == right_type => Some(left.cmp(&right)),
         (Value::Int(left), Value::Int(right)) => Some(left.cmp(&right)),
         (Value::Rational(left), Value::Rational(right)) => Some(left.cmp(&right)),
+        (Value::Approximate(left), Value::Approximate(right)) if left.format == right.format => {
+            Some(left.number.cmp(&right.number))
+        }
+        (Value::Quantity(left), Value::Quantity(right)) => {
+            match units::apply(CallableKind::Compare, "<=>", &left, &right) {
+                Ok(Applied::Ordering(ordering)) => Some(ordering),
+                _ => None,
+            }
+        }
         (Value::Int(left), Value::Rational(right)) => {
             trace_conversion(trace, "Int->Rational:left");
             Some(BigRational::from_integer(left).cmp(&right))
@@ -13629,6 +14772,15 @@ fn values_equal(left: Value, right: Value, trace: &mut impl TraceSink) -> Option
         (Value::Boolean(left), Value::Boolean(right)) => Some(left == right),
         (Value::Int(left), Value::Int(right)) => Some(left == right),
         (Value::Rational(left), Value::Rational(right)) => Some(left == right),
+        (Value::Approximate(left), Value::Approximate(right)) if left.format == right.format => {
+            Some(left.number == right.number)
+        }
+        (Value::Quantity(left), Value::Quantity(right)) => {
+            match units::apply(CallableKind::Equal, "=", &left, &right) {
+                Ok(Applied::Ordering(ordering)) => Some(ordering == Ordering::Equal),
+                _ => None,
+            }
+        }
         (Value::Int(left), Value::Rational(right)) => {
             trace_conversion(trace, "Int->Rational:left");
             Some(BigRational::from_integer(left) == right)
@@ -14239,6 +15391,16 @@ fn apply_negate(
     trace: &mut impl TraceSink,
 ) -> Result<Value, Diagnostic> {
     match operand {
+        Value::Quantity(operand) => {
+            let negated =
+                units::negate(&operand).map_err(|error| unit_diagnostic(source, span, error))?;
+            trace.record(TraceEvent {
+                event: "evaluation.negate",
+                rule: "TOPAL-NUM-QUANTITY-LINEAR-001",
+                detail: &operand.classifier(),
+            });
+            Ok(Value::Quantity(Box::new(negated)))
+        }
         Value::Int(operand) => {
             trace.record(TraceEvent {
                 event: "operator.selected",
@@ -14265,6 +15427,21 @@ fn apply_negate(
             });
             Ok(Value::Rational(-operand))
         }
+        Value::Approximate(operand) => {
+            trace.record(TraceEvent {
+                event: "operator.selected",
+                rule: "TOPAL-TYPE-CALL-001",
+                detail: &format!("root.-({})", operand.format.name),
+            });
+            trace.record(TraceEvent {
+                event: "evaluation.negate",
+                rule: "TOPAL-NUM-APPROX-ARITHMETIC-001",
+                detail: operand.format.name,
+            });
+            let number = operand.number.negate();
+            trace_zero_direction(trace, &number);
+            Ok(approximate_value(operand.format, number))
+        }
         Value::SizeBits(_) => Err(diagnostic(
             source,
             "E-NEGATE-OPERAND",
@@ -14351,7 +15528,7 @@ fn apply_negate(
     }
 }
 
-const fn callable_name(kind: CallableKind) -> &'static str {
+pub(crate) const fn callable_name(kind: CallableKind) -> &'static str {
     match kind {
         CallableKind::Equal => "=",
         CallableKind::NotEqual => "/=",
@@ -14371,7 +15548,7 @@ const fn callable_name(kind: CallableKind) -> &'static str {
     }
 }
 
-fn diagnostic(
+pub(crate) fn diagnostic(
     source: &SourceText,
     code: &'static str,
     span: Span,
@@ -14402,6 +15579,36 @@ fn closest_name<'a>(name: &str, candidates: impl Iterator<Item = &'a String>) ->
         .map(|(_, candidate)| candidate)
 }
 
+/// Collection queries applied to one `(collection, key)` product.
+const COLLECTION_QUERIES: [&str; 4] = [
+    "array-at?",
+    "map-lookup",
+    "set-contains?",
+    "bag-multiplicity",
+];
+
+/// Operations that materialize a list or finite generator as a collection.
+const MATERIALIZATIONS: [&str; 5] = [
+    "unzip",
+    "collect",
+    "collect-set",
+    "collect-bag",
+    "collect-map",
+];
+
+/// Lowercase names that evaluation interprets as namespaces, statement
+/// keywords, or forms with their own dispatch rather than as bindings.
+const LANGUAGE_FORMS: [&str; 7] = ["empty?", "lang", "read", "root", "std", "use", "yield"];
+
+/// Whether evaluation resolves the lowercase `name` without a source
+/// declaration. Static analysis treats exactly these names as bound.
+pub(crate) fn is_builtin_name(name: &str) -> bool {
+    ROOT_OPERATIONS.contains(&name)
+        || COLLECTION_QUERIES.contains(&name)
+        || MATERIALIZATIONS.contains(&name)
+        || LANGUAGE_FORMS.contains(&name)
+}
+
 const ROOT_OPERATIONS: [&str; 29] = [
     "absolute",
     "byte-count",
@@ -14539,14 +15746,14 @@ fn raw_position(source: &str, offset: usize) -> (usize, usize) {
     (line, column)
 }
 
-fn parse_integer(token: &str) -> Option<BigInt> {
+pub(crate) fn parse_integer(token: &str) -> Option<BigInt> {
     if let Some(unsigned) = token.strip_prefix('-') {
         return parse_unsigned_integer(unsigned).map(std::ops::Neg::neg);
     }
     parse_unsigned_integer(token)
 }
 
-fn parse_rational(token: &str) -> Option<BigRational> {
+pub(crate) fn parse_rational(token: &str) -> Option<BigRational> {
     if let Some(unsigned) = token.strip_prefix('-') {
         return parse_unsigned_rational(unsigned).map(std::ops::Neg::neg);
     }
@@ -16639,6 +17846,348 @@ fn checked_nat_construction_validates_the_nonnegative_constraint() {
     assert_eq!(error.code, "E-NAT-OUT-OF-RANGE");
 }
 
+#[test]
+fn checked_approximate_construction_is_exact_and_fallible() {
+    let mut trace = Vec::new();
+    let value = Session::new()
+        .evaluate(
+            "as-binary is fn (value : Rational) -> Result (Binary64, lang arithmetic ArithmeticErrorCode)\n  Binary64 value\n(Binary64 0.5, Binary32 3, Binary32 -0, Binary64 -Infinity, as-binary 0.1)\n",
+            &mut trace,
+        )
+        .unwrap();
+    assert_eq!(
+        value.to_string(),
+        "(Binary64 ( Rational ( 1, 2 ) ), Binary32 3, Binary32 -0, Binary64 -Infinity, Error ( domain is root.Binary64(Rational), code is not-representable ))"
+    );
+    assert!(trace.iter().any(|event| {
+        event.contains("TOPAL-NUM-APPROX-CONSTRUCT-001") && event.contains("-0->Binary32:exact")
+    }));
+    assert!(trace.iter().any(|event| {
+        event.contains("TOPAL-NUM-ZERO-DIRECTION-001") && event.contains("FromBelow")
+    }));
+
+    let error = Session::new()
+        .evaluate("Binary64 0.1\n", &mut std::io::sink())
+        .unwrap_err();
+    assert_eq!(error.code, "E-APPROXIMATE-NOT-REPRESENTABLE");
+}
+
+#[test]
+fn approximate_arithmetic_rounds_once_and_keeps_zero_direction() {
+    let mut trace = Vec::new();
+    let value = Session::new()
+        .evaluate(
+            "one is Binary64 1\nthree is Binary64 3\nbelow is Binary64 -0\nabove is - below\n(one / three, one / below, one / above, below = above, below < one, one - one, above - above)\n",
+            &mut trace,
+        )
+        .unwrap();
+    assert_eq!(
+        value.to_string(),
+        "(Binary64 ( Rational ( 6004799503160661, 18014398509481984 ) ), Binary64 -Infinity, Binary64 +Infinity, true, true, Binary64 0, Binary64 0)"
+    );
+    assert!(trace.iter().any(|event| {
+        event.contains("numeric.approximate.rounded")
+            && event.contains("TOPAL-NUM-APPROX-ARITHMETIC-001")
+            && event.contains("\"detail\":\"rounded\"")
+    }));
+
+    let value = Session::new()
+        .evaluate(
+            "big is Binary64 +Infinity\nzero is Binary64 0\n(big - big, zero * big, zero / zero)\n",
+            &mut std::io::sink(),
+        )
+        .map(|value| value.to_string());
+    assert!(value.is_ok_and(|value| value.matches("code is indeterminate").count() == 3));
+}
+
+#[test]
+fn lossy_numeric_functions_name_their_replacement_value() {
+    let mut trace = Vec::new();
+    let value = Session::new()
+        .evaluate(
+            "big is Binary64 +Infinity\n(2.6 round-to-zero Int, 2.5 round-to-zero Int, 2.5 round-from-zero Int, -3.9 truncate Int, -3.9 floor Int, -3.9 ceiling Int, 16777217 round-to-even Binary32, 16777217 round-to-odd Binary32, big saturate ( Finite Binary32 ))\n",
+            &mut trace,
+        )
+        .unwrap();
+    assert_eq!(
+        value.to_string(),
+        "(3, 2, 3, -3, -4, -3, Binary32 16777216, Binary32 16777218, Binary32 340282346638528859811704183484516925440)"
+    );
+    assert!(trace.iter().any(|event| {
+        event.contains("TOPAL-NUM-LOSSY-DIRECTED-001") && event.contains("rounded")
+    }));
+    assert!(
+        trace
+            .iter()
+            .any(|event| { event.contains("TOPAL-NUM-SATURATE-001") && event.contains("upper") })
+    );
+
+    for (source, code) in [
+        (
+            "1e400 round-to-even ( Finite Binary64 )\n",
+            "E-LOSSY-OUT-OF-RANGE",
+        ),
+        (
+            "0.1 saturate ( Finite Binary64 )\n",
+            "E-SATURATE-NOT-REPRESENTABLE",
+        ),
+        ("1 saturate Int\n", "E-SATURATE-TARGET"),
+    ] {
+        let error = Session::new()
+            .evaluate(source, &mut std::io::sink())
+            .unwrap_err();
+        assert_eq!(error.code, code, "{source}");
+    }
+}
+
+#[test]
+fn reproducible_approximate_sums_ignore_entry_order() {
+    let mut trace = Vec::new();
+    let value = Session::new()
+        .evaluate(
+            "large is Binary64 ( 2 ^ 100 )\none is Binary64 1\nsmall is Binary64 ( Rational ( 1, 4 ) )\nforward : List Binary64 is Entry ( large, Entry ( one, Entry ( small, Entry ( - large, Empty ) ) ) )\nbackward : List Binary64 is Entry ( - large, Entry ( small, Entry ( one, Entry ( large, Empty ) ) ) )\n(forward reproducible-sum Binary64, backward reproducible-sum Binary64, forward exact-sum floor Binary64)\n",
+            &mut trace,
+        )
+        .unwrap();
+    assert_eq!(
+        value.to_string(),
+        "(Binary64 ( Rational ( 5, 4 ) ), Binary64 ( Rational ( 5, 4 ) ), Binary64 ( Rational ( 5, 4 ) ))"
+    );
+    assert!(trace.iter().any(|event| {
+        event.contains("TOPAL-NUM-REPRODUCIBLE-SUM-001") && event.contains("round-to-even:exact")
+    }));
+}
+
+#[cfg(test)]
+const UNITS_PRELUDE: &str = "Mass is Dimension\nDistance is Dimension\nDuration is Dimension\nTemperature is AffineDimension\nGram is MeasurementUnit ( symbol is g, prefixes is SI, dimension is Mass )\nMetre is MeasurementUnit ( symbol is m, prefixes is SI, dimension is Distance )\nSecond is MeasurementUnit ( symbol is s, prefixes is SI, dimension is Duration )\nNewton is MeasurementUnit ( symbol is N, prefixes is SI, scale is 1[kg * m / (s ^ 2)] )\nKelvin is MeasurementUnit ( symbol is K, prefixes is SI, dimension is Temperature )\nCelsius is MeasurementUnit ( symbol is °C, prefixes is none, reference is Kelvin, scale is 1, offset is 273.15[ΔK] )\n";
+
+#[test]
+fn linear_quantities_normalize_dimensions_and_scales() {
+    let mut trace = Vec::new();
+    let value = Session::new()
+        .evaluate(
+            &format!(
+                "{UNITS_PRELUDE}(2[kg] * 4[m] / (1[s] ^ 2) = 8[N], 2[kg] + 500[g], 8[N] + 2[kg * m / (s ^ 2)], 1[Kilogram * Metre] < 2[kg * m], 3[m] / 1[m], 1[km] as Metre)\n"
+            ),
+            &mut trace,
+        )
+        .unwrap();
+    assert_eq!(
+        value.to_string(),
+        "(true, 2.5[kg], 10[N], true, 3, 1000[Metre])"
+    );
+    assert!(trace.iter().any(|event| {
+        event.contains("numeric.unit.declared")
+            && event.contains("TOPAL-NUM-UNIT-PREFIX-001")
+            && event.contains("Newton:derived")
+    }));
+    assert!(trace.iter().any(|event| {
+        event.contains("TOPAL-NUM-QUANTITY-LINEAR-001") && event.contains("*:product")
+    }));
+
+    for (source, code) in [
+        ("1[m] + 1[s]\n", "E-DIMENSION-MISMATCH"),
+        ("length : Distance is 1[s]\n", "E-DIMENSION-MISMATCH"),
+        ("1[kg * Metre]\n", "E-UNIT-VOCABULARY"),
+        ("5[delta m]\n", "E-UNIT-VOCABULARY"),
+        ("1[Centi Metre]\n", "E-MEASUREMENT-SUFFIX"),
+        ("1[furlong]\n", "E-UNKNOWN-UNIT"),
+        ("1[m] as Second\n", "E-DIMENSION-MISMATCH"),
+        (
+            "Centimetre is MeasurementUnit ( symbol is cmx, prefixes is none, scale is 1[cm] )\n",
+            "E-UNIT-COLLISION",
+        ),
+        (
+            "Foot is MeasurementUnit ( symbol is ft, prefixes is none, scale is 0[m] )\n",
+            "E-UNIT-DECLARATION",
+        ),
+    ] {
+        let error = Session::new()
+            .evaluate(&format!("{UNITS_PRELUDE}{source}"), &mut std::io::sink())
+            .unwrap_err();
+        assert_eq!(error.code, code, "{source}");
+    }
+}
+
+#[test]
+fn affine_points_follow_point_and_delta_rules() {
+    let mut trace = Vec::new();
+    let value = Session::new()
+        .evaluate(
+            &format!(
+                "{UNITS_PRELUDE}freezing : Temperature is 0[°C]\nboiling : Temperature is freezing + 100[Δ°C]\ndifference : Delta Temperature is boiling - freezing\n(difference, boiling as Kelvin, difference as Delta Kelvin, 5[delta Celsius] + freezing, boiling - 1[ΔK] < boiling)\n"
+            ),
+            &mut trace,
+        )
+        .unwrap();
+    assert_eq!(
+        value.to_string(),
+        "(100[Δ°C], 373.15[Kelvin], 100[delta Kelvin], 5[°C], true)"
+    );
+    assert!(trace.iter().any(|event| {
+        event.contains("TOPAL-NUM-QUANTITY-AFFINE-001") && event.contains("-:point-point")
+    }));
+    assert!(trace.iter().any(|event| {
+        event.contains("TOPAL-NUM-QUANTITY-CONVERT-001") && event.contains("°C->Kelvin")
+    }));
+
+    for source in [
+        "20[°C] + 20[°C]\n",
+        "2[°C] * 2\n",
+        "1[°C] ^ 2\n",
+        "- 1[°C]\n",
+    ] {
+        let error = Session::new()
+            .evaluate(&format!("{UNITS_PRELUDE}{source}"), &mut std::io::sink())
+            .unwrap_err();
+        assert_eq!(error.code, "E-AFFINE-OPERATION", "{source}");
+    }
+    let error = Session::new()
+        .evaluate(
+            &format!("{UNITS_PRELUDE}change : Delta Temperature is 5[°C]\n"),
+            &mut std::io::sink(),
+        )
+        .unwrap_err();
+    assert_eq!(error.code, "E-DIMENSION-MISMATCH");
+}
+
+#[test]
+fn prefixed_data_units_keep_size_literals_available() {
+    let value = Session::new()
+        .evaluate(
+            "sizes is (4[B], 3[b])\nDataAmount is Dimension\nBit is MeasurementUnit ( symbol is b, prefixes is ( SI, Binary ), dimension is DataAmount )\nByte is MeasurementUnit ( symbol is B, prefixes is ( SI, Binary ), scale is 8[b] )\n(sizes, 1[B] = 8[b], 1[KiB] = 1_024[B], 2[Kibibyte], 1[MiB] as kB)\n",
+            &mut std::io::sink(),
+        )
+        .unwrap();
+    assert_eq!(
+        value.to_string(),
+        "((32[b], 3[b]), true, true, 2[Kibibyte], 1048.576[kB])"
+    );
+}
+
+#[test]
+fn static_dimension_errors_stop_execution_before_any_statement() {
+    let mut trace = Vec::new();
+    let error = Session::new()
+        .evaluate(
+            &format!("{UNITS_PRELUDE}length is 3[m]\nlater is length + 2[s]\n"),
+            &mut trace,
+        )
+        .unwrap_err();
+    assert_eq!(error.code, "E-DIMENSION-MISMATCH");
+    assert!(
+        !trace
+            .iter()
+            .any(|event| event.contains("numeric.dimension.declared"))
+    );
+
+    let value = Session::new()
+        .evaluate(
+            &format!("{UNITS_PRELUDE}length is 3[m]\ntwice is fn (length : Int) -> Int\n  length + length\ntwice 2\n"),
+            &mut std::io::sink(),
+        )
+        .unwrap();
+    assert_eq!(value.to_string(), "4");
+}
+
+#[test]
+fn sensitive_provenance_follows_copies_containers_and_contracts() {
+    let mut trace = Vec::new();
+    let value = Session::new()
+        .evaluate(
+            "password : Sensitive String is \"hunter2\"\ncopy is password\nlogin is ( credential is copy, user is \"ada\" )\nkeep is fn (value : Sensitive String) -> Sensitive String\n  value\nsame is fn (left : Sensitive String, right : Sensitive String) -> Boolean\n  left = right\nkept is keep password\n(same (kept, password), copy = \"guess\")\n",
+            &mut trace,
+        )
+        .unwrap();
+    assert_eq!(value.to_string(), "(true, false)");
+    for name in ["password", "copy", "login", "kept"] {
+        assert!(trace.iter().any(|event| {
+            event.contains("TOPAL-SENSITIVE-PROVENANCE-001")
+                && event.contains(&format!("\"detail\":\"{name}\""))
+        }));
+    }
+
+    let prelude = "password : Sensitive String is \"hunter2\"\n";
+    for (source, code) in [
+        ("password\n", "E-SENSITIVE-OUTPUT"),
+        ("( 1, password )\n", "E-SENSITIVE-OUTPUT"),
+        ("copy : String is password\n", "E-SENSITIVE-CLASSIFIER"),
+        (
+            "show is fn (value : String) -> String\n  value\nshown is show password\n",
+            "E-SENSITIVE-ARGUMENT",
+        ),
+        (
+            "leak is fn (value : Sensitive String) -> String\n  value\n",
+            "E-SENSITIVE-RESULT",
+        ),
+        (
+            "stream is v0.1 (lang serialize) password\n",
+            "E-SENSITIVE-SERIALIZATION",
+        ),
+    ] {
+        let mut trace = Vec::new();
+        let error = Session::new()
+            .evaluate(&format!("{prelude}{source}"), &mut trace)
+            .unwrap_err();
+        assert_eq!(error.code, code, "{source}");
+        assert!(!error.message.contains("hunter2"));
+        assert!(
+            !trace
+                .iter()
+                .any(|event| event.contains("TOPAL-SENSITIVE-PROVENANCE-001"))
+        );
+    }
+}
+
+#[test]
+fn sensitive_bindings_persist_across_session_inputs() {
+    let mut session = Session::new();
+    session
+        .evaluate(
+            "password : Sensitive String is \"hunter2\"\n",
+            &mut std::io::sink(),
+        )
+        .unwrap();
+    let error = session
+        .evaluate("password\n", &mut std::io::sink())
+        .unwrap_err();
+    assert_eq!(error.code, "E-SENSITIVE-OUTPUT");
+}
+
+#[test]
+fn json_lines_traces_omit_sensitive_literals() {
+    let mut written = Vec::new();
+    let mut trace = crate::JsonLines::new(&mut written);
+    Session::new()
+        .evaluate(
+            "password : Sensitive String is \"hunter2\"\ncopy is password\n(copy = \"hunter2\", \"public\")\n",
+            &mut trace,
+        )
+        .unwrap();
+    let written = String::from_utf8(written).unwrap();
+    assert!(!written.contains("hunter2"));
+    assert!(written.contains(crate::REDACTED));
+    assert!(written.contains("public"));
+}
+
+#[test]
+fn json_lines_traces_omit_sensitive_integer_literals() {
+    let mut written = Vec::new();
+    let mut trace = crate::JsonLines::new(&mut written);
+    Session::new()
+        .evaluate(
+            "pin : Sensitive Int is 4242
+public is 42420 + 1
+",
+            &mut trace,
+        )
+        .unwrap();
+    let written = String::from_utf8(written).unwrap();
+    assert!(!written.contains("\"4242\""));
+    assert!(written.contains(crate::REDACTED));
+    assert!(written.contains("\"42420\""));
+}
+
 #[test]
 fn closed_rational_construction_canonicalizes_components() {
     let mut trace = Vec::new();
@@ -18298,3 +19847,37 @@ fn interface_implementations_require_exact_shapes() {
         .unwrap_err();
     assert_eq!(error.code, "E-INTERFACE-IMPLEMENTATION");
 }
+
+#[test]
+fn applies_loaded_functions_to_supplied_bindings_without_changing_the_session() {
+    let mut session = Session::new();
+    session
+        .evaluate_source_file(
+            "use language (\n  version is v0.1\n)\naccept is fn static (kind : String, name : String) -> Boolean\n  name != \"tmp\"\n",
+            &mut std::io::sink(),
+        )
+        .unwrap();
+    let bindings = |name: &str| {
+        BTreeMap::from([
+            ("kind".to_owned(), Value::String("binding".into())),
+            ("name".to_owned(), Value::String(name.into())),
+        ])
+    };
+    for (name, accepted) in [("total", true), ("tmp", false)] {
+        assert_eq!(
+            session
+                .evaluate_in_context(&bindings(name), "kind accept name", &mut std::io::sink())
+                .unwrap(),
+            Value::Boolean(accepted)
+        );
+    }
+    assert!(!session.bindings.contains_key("kind"));
+    let error = session
+        .evaluate_in_context(
+            &BTreeMap::from([("accept".to_owned(), Value::Unit)]),
+            "accept",
+            &mut std::io::sink(),
+        )
+        .unwrap_err();
+    assert_eq!(error.code, "E-DUPLICATE-BINDING");
+}
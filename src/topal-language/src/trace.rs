@@ -20,6 +20,10 @@ pub trait TraceSink {
     fn record(&mut self, event: TraceEvent<'_>);
 
     fn checkpoint(&mut self, _snapshot: ExecutionSnapshot<'_>) {}
+
+    /// Register the text of a sensitive value; a sink that writes details
+    /// outside the program omits it from every later event.
+    fn redact(&mut self, _text: &str) {}
 }
 
 impl TraceSink for Vec<String> {
@@ -54,10 +58,15 @@ impl TraceEvent<'_> {
     }
 }
 
-/// A trace sink backed by a writer.
+/// Replacement for sensitive text in written trace details.
+pub const REDACTED: &str = "<redacted>";
+
+/// A trace sink backed by a writer. Details never contain text registered
+/// through [`TraceSink::redact`].
 pub struct JsonLines<W> {
     writer: W,
     profiles: Vec<&'static str>,
+    redactions: Vec<String>,
 }
 
 impl<W> JsonLines<W> {
@@ -66,6 +75,7 @@ impl<W> JsonLines<W> {
         Self {
             writer,
             profiles: Vec::new(),
+            redactions: Vec::new(),
         }
     }
 
@@ -74,7 +84,11 @@ impl<W> JsonLines<W> {
         let mut profiles = profiles.into_iter().collect::<Vec<_>>();
         profiles.sort_unstable();
         profiles.dedup();
-        Self { writer, profiles }
+        Self {
+            writer,
+            profiles,
+            redactions: Vec::new(),
+        }
     }
 }
 
@@ -86,18 +100,67 @@ impl<W: Write> TraceSink for JsonLines<W> {
         } else {
             &self.profiles
         };
+        let mut detail = event.detail.to_owned();
+        for redaction in &self.redactions {
+            detail = redact_whole_tokens(&detail, redaction);
+        }
+        let event = TraceEvent {
+            detail: &detail,
+            ..event
+        };
         let _ = writeln!(
             self.writer,
             "{}",
             event.to_json_line_with_profiles(profiles)
         );
     }
+
+    fn redact(&mut self, text: &str) {
+        if !text.is_empty() && !self.redactions.iter().any(|known| known == text) {
+            self.redactions.push(text.to_owned());
+            // Replace longer text first so that a contained secret cannot
+            // leave the remainder of a longer one visible.
+            self.redactions
+                .sort_by_key(|redaction| std::cmp::Reverse(redaction.len()));
+        }
+    }
 }
 
 impl TraceSink for io::Sink {
     fn record(&mut self, _event: TraceEvent<'_>) {}
 }
 
+/// Replace each occurrence of `secret` in `detail` that is not part of a
+/// longer identifier or number, so public text that merely contains the
+/// secret's characters stays readable.
+fn redact_whole_tokens(detail: &str, secret: &str) -> String {
+    let mut redacted = String::with_capacity(detail.len());
+    let mut rest = detail;
+    while let Some(start) = rest.find(secret) {
+        let end = start + secret.len();
+        let before = rest[..start].chars().last().or(redacted.chars().last());
+        let joins_before =
+            secret.starts_with(is_token_character) && before.is_some_and(is_token_character);
+        let joins_after =
+            secret.ends_with(is_token_character) && rest[end..].starts_with(is_token_character);
+        if joins_before || joins_after {
+            let next = start + rest[start..].chars().next().map_or(1, char::len_utf8);
+            redacted.push_str(&rest[..next]);
+            rest = &rest[next..];
+        } else {
+            redacted.push_str(&rest[..start]);
+            redacted.push_str(REDACTED);
+            rest = &rest[end..];
+        }
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+fn is_token_character(character: char) -> bool {
+    character.is_alphanumeric() || matches!(character, '_' | '-' | '.')
+}
+
 fn escape(value: &str) -> String {
     let mut escaped = String::with_capacity(value.len());
     for character in value.chars() {
@@ -156,6 +219,35 @@ mod tests {
         assert!(line.contains("line\\nnext"));
     }
 
+    #[test]
+    fn json_lines_redact_registered_sensitive_text() {
+        let mut sink = JsonLines::new(Vec::new());
+        sink.redact("hunter2");
+        sink.record(TraceEvent {
+            event: "binding",
+            rule: "RULE",
+            detail: "password=hunter2;copy=hunter2",
+        });
+        let written = String::from_utf8(sink.writer).unwrap();
+        assert!(!written.contains("hunter2"));
+        assert!(written.contains("password=<redacted>;copy=<redacted>"));
+    }
+
+    #[test]
+    fn json_lines_redact_only_whole_tokens() {
+        let mut sink = JsonLines::new(Vec::new());
+        sink.redact("4242");
+        sink.record(TraceEvent {
+            event: "binding",
+            rule: "RULE",
+            detail: "pin=4242;public=42421;code=x4242;sum=4242.5;copy=(4242)",
+        });
+        let written = String::from_utf8(sink.writer).unwrap();
+        assert!(
+            written.contains("pin=<redacted>;public=42421;code=x4242;sum=4242.5;copy=(<redacted>)")
+        );
+    }
+
     #[test]
     fn one_event_always_occupies_one_json_line() {
         let line = TraceEvent {
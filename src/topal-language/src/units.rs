@@ -0,0 +1,1501 @@
+//! Dimensions, measurement units, and dimensioned quantities.
+//!
+//! Every unit normalizes to integer dimension exponents and an exact scale
+//! relative to its dimension's fundamental unit. An affine unit additionally
+//! records the coordinate of its origin in the fundamental unit. The linear
+//! difference of an affine dimension `T` is the dimension `Delta T`, so deltas
+//! combine like any other linear quantity.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use num_bigint::{BigInt, Sign};
+use num_rational::BigRational;
+use topal_source::{Diagnostic, SourceText, Span};
+use topal_syntax::{CallableKind, Expression, ProductField, Statement, UnitExpression};
+
+use crate::source::{diagnostic, parse_integer, parse_rational};
+
+/// Dimension exponents keyed by linear dimension classifier.
+pub type Dimensions = BTreeMap<String, i32>;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DimensionKind {
+    Linear,
+    Affine,
+}
+
+impl DimensionKind {
+    #[must_use]
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "Dimension" => Some(Self::Linear),
+            "AffineDimension" => Some(Self::Affine),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::Affine => "affine",
+        }
+    }
+}
+
+/// A language-defined prefix family; programs cannot redefine its factors.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum PrefixFamily {
+    Si,
+    Binary,
+}
+
+struct Prefix {
+    name: &'static str,
+    symbol: &'static str,
+    base: u32,
+    exponent: i32,
+}
+
+const fn prefix(name: &'static str, symbol: &'static str, base: u32, exponent: i32) -> Prefix {
+    Prefix {
+        name,
+        symbol,
+        base,
+        exponent,
+    }
+}
+
+const SI_PREFIXES: &[Prefix] = &[
+    prefix("Quetta", "Q", 10, 30),
+    prefix("Ronna", "R", 10, 27),
+    prefix("Yotta", "Y", 10, 24),
+    prefix("Zetta", "Z", 10, 21),
+    prefix("Exa", "E", 10, 18),
+    prefix("Peta", "P", 10, 15),
+    prefix("Tera", "T", 10, 12),
+    prefix("Giga", "G", 10, 9),
+    prefix("Mega", "M", 10, 6),
+    prefix("Kilo", "k", 10, 3),
+    prefix("Hecto", "h", 10, 2),
+    prefix("Deca", "da", 10, 1),
+    prefix("Deci", "d", 10, -1),
+    prefix("Centi", "c", 10, -2),
+    prefix("Milli", "m", 10, -3),
+    prefix("Micro", "µ", 10, -6),
+    prefix("Nano", "n", 10, -9),
+    prefix("Pico", "p", 10, -12),
+    prefix("Femto", "f", 10, -15),
+    prefix("Atto", "a", 10, -18),
+    prefix("Zepto", "z", 10, -21),
+    prefix("Yocto", "y", 10, -24),
+    prefix("Ronto", "r", 10, -27),
+    prefix("Quecto", "q", 10, -30),
+];
+
+const BINARY_PREFIXES: &[Prefix] = &[
+    prefix("Kibi", "Ki", 2, 10),
+    prefix("Mebi", "Mi", 2, 20),
+    prefix("Gibi", "Gi", 2, 30),
+    prefix("Tebi", "Ti", 2, 40),
+    prefix("Pebi", "Pi", 2, 50),
+    prefix("Exbi", "Ei", 2, 60),
+    prefix("Zebi", "Zi", 2, 70),
+    prefix("Yobi", "Yi", 2, 80),
+];
+
+impl PrefixFamily {
+    #[must_use]
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "SI" => Some(Self::Si),
+            "Binary" => Some(Self::Binary),
+            _ => None,
+        }
+    }
+
+    const fn prefixes(self) -> &'static [Prefix] {
+        match self {
+            Self::Si => SI_PREFIXES,
+            Self::Binary => BINARY_PREFIXES,
+        }
+    }
+}
+
+impl Prefix {
+    fn factor(&self) -> BigRational {
+        let base = BigRational::from_integer(BigInt::from(self.base));
+        base.pow(self.exponent)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Vocabulary {
+    Symbolic,
+    Named,
+}
+
+/// One reserved spelling usable inside `[]`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Atom {
+    vocabulary: Vocabulary,
+    unit: String,
+    factor: BigRational,
+    /// A compiler-derived `Δ` symbol naming the delta of an affine unit.
+    delta: bool,
+}
+
+/// The affine origin of a unit: its point dimension and the coordinate of its
+/// zero in the dimension's fundamental unit.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Origin {
+    dimension: String,
+    offset: BigRational,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct UnitDefinition {
+    dimensions: Dimensions,
+    scale: BigRational,
+    origin: Option<Origin>,
+}
+
+/// How a `MeasurementUnit` declaration defines its unit.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UnitForm {
+    Fundamental {
+        dimension: String,
+    },
+    DerivedLinear {
+        scale: Quantity,
+    },
+    DerivedAffine {
+        reference: String,
+        scale: BigRational,
+        offset: Quantity,
+    },
+}
+
+impl UnitForm {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fundamental { .. } => "fundamental",
+            Self::DerivedLinear { .. } => "derived",
+            Self::DerivedAffine { .. } => "affine",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnitDeclaration {
+    pub symbol: String,
+    pub prefixes: Vec<PrefixFamily>,
+    pub form: UnitForm,
+}
+
+/// A binding initializer that declares a dimension or unit.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Declaration {
+    Dimension(DimensionKind),
+    Unit(Box<UnitDeclaration>),
+}
+
+/// A rejected unit declaration, unit expression, or quantity operation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnitError {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl UnitError {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Whether this error is a dimension or affine-operation mismatch rather
+    /// than an unresolved spelling or a numeric failure.
+    #[must_use]
+    pub fn is_dimension_error(&self) -> bool {
+        matches!(self.code, "E-DIMENSION-MISMATCH" | "E-AFFINE-OPERATION")
+    }
+}
+
+/// Dimensions, units, and every reserved unit spelling in scope.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct UnitRegistry {
+    dimensions: BTreeMap<String, DimensionKind>,
+    units: BTreeMap<String, UnitDefinition>,
+    fundamentals: BTreeMap<String, String>,
+    atoms: BTreeMap<String, Atom>,
+}
+
+impl UnitRegistry {
+    #[must_use]
+    pub fn dimension(&self, name: &str) -> Option<DimensionKind> {
+        self.dimensions.get(name).copied()
+    }
+
+    pub fn declare_dimension(&mut self, name: &str, kind: DimensionKind) {
+        self.dimensions.insert(name.to_owned(), kind);
+    }
+
+    /// Whether `classifier` names a declared dimension or the `Delta` of a
+    /// declared affine dimension.
+    #[must_use]
+    pub fn is_dimension_classifier(&self, classifier: &str) -> bool {
+        classifier
+            .strip_prefix("Delta ")
+            .map_or(self.dimensions.contains_key(classifier), |dimension| {
+                self.dimension(dimension) == Some(DimensionKind::Affine)
+            })
+    }
+
+    /// Declare `name` and reserve its symbolic, named, prefixed, and derived
+    /// delta spellings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the declaration references an unknown or
+    /// incompatible object, repeats a fundamental unit, or collides with a
+    /// reserved spelling.
+    pub fn declare_unit(
+        &mut self,
+        name: &str,
+        declaration: &UnitDeclaration,
+    ) -> Result<(), UnitError> {
+        let definition = self.definition(declaration)?;
+        let mut spellings = vec![
+            (declaration.symbol.clone(), Vocabulary::Symbolic, one()),
+            (name.to_owned(), Vocabulary::Named, one()),
+        ];
+        let stem = name.to_lowercase();
+        for family in &declaration.prefixes {
+            for prefix in family.prefixes() {
+                spellings.push((
+                    format!("{}{}", prefix.symbol, declaration.symbol),
+                    Vocabulary::Symbolic,
+                    prefix.factor(),
+                ));
+                spellings.push((
+                    format!("{}{stem}", prefix.name),
+                    Vocabulary::Named,
+                    prefix.factor(),
+                ));
+            }
+        }
+        let mut atoms = spellings
+            .into_iter()
+            .map(|(spelling, vocabulary, factor)| {
+                (
+                    spelling,
+                    Atom {
+                        vocabulary,
+                        unit: name.to_owned(),
+                        factor,
+                        delta: false,
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        if definition.origin.is_some() {
+            let deltas = atoms
+                .iter()
+                .filter(|(_, atom)| atom.vocabulary == Vocabulary::Symbolic)
+                .map(|(spelling, atom)| {
+                    (
+                        format!("Δ{spelling}"),
+                        Atom {
+                            delta: true,
+                            ..atom.clone()
+                        },
+                    )
+                })
+                .collect::<Vec<_>>();
+            atoms.extend(deltas);
+        }
+        let mut seen = BTreeSet::new();
+        for (spelling, _) in &atoms {
+            if let Some(existing) = self.atoms.get(spelling) {
+                return Err(UnitError::new(
+                    "E-UNIT-COLLISION",
+                    format!(
+                        "unit spelling `{spelling}` is already reserved by `{}`",
+                        existing.unit
+                    ),
+                ));
+            }
+            if !seen.insert(spelling.as_str()) {
+                return Err(UnitError::new(
+                    "E-UNIT-COLLISION",
+                    format!("`{name}` reserves the spelling `{spelling}` twice"),
+                ));
+            }
+        }
+        if let UnitForm::Fundamental { dimension } = &declaration.form {
+            self.fundamentals.insert(dimension.clone(), name.to_owned());
+        }
+        self.atoms.extend(atoms);
+        self.units.insert(name.to_owned(), definition);
+        Ok(())
+    }
+
+    fn definition(&self, declaration: &UnitDeclaration) -> Result<UnitDefinition, UnitError> {
+        match &declaration.form {
+            UnitForm::Fundamental { dimension } => {
+                let Some(kind) = self.dimension(dimension) else {
+                    return Err(UnitError::new(
+                        "E-UNIT-DECLARATION",
+                        format!("`{dimension}` is not a declared Dimension or AffineDimension"),
+                    ));
+                };
+                if let Some(existing) = self.fundamentals.get(dimension) {
+                    return Err(UnitError::new(
+                        "E-UNIT-DECLARATION",
+                        format!("`{dimension}` already has the fundamental unit `{existing}`"),
+                    ));
+                }
+                Ok(match kind {
+                    DimensionKind::Linear => UnitDefinition {
+                        dimensions: Dimensions::from([(dimension.clone(), 1)]),
+                        scale: one(),
+                        origin: None,
+                    },
+                    DimensionKind::Affine => UnitDefinition {
+                        dimensions: Dimensions::from([(format!("Delta {dimension}"), 1)]),
+                        scale: one(),
+                        origin: Some(Origin {
+                            dimension: dimension.clone(),
+                            offset: zero(),
+                        }),
+                    },
+                })
+            }
+            UnitForm::DerivedLinear { scale } => {
+                if scale.unit.point.is_some() {
+                    return Err(UnitError::new(
+                        "E-UNIT-DECLARATION",
+                        "a linear unit's scale must be a linear quantity, not a point",
+                    ));
+                }
+                if scale.unit.dimensions.is_empty() {
+                    return Err(UnitError::new(
+                        "E-UNIT-DECLARATION",
+                        "a derived linear unit requires a dimensioned scale",
+                    ));
+                }
+                nonzero_scale(&scale.magnitude)?;
+                Ok(UnitDefinition {
+                    dimensions: scale.unit.dimensions.clone(),
+                    scale: &scale.magnitude * &scale.unit.scale,
+                    origin: None,
+                })
+            }
+            UnitForm::DerivedAffine {
+                reference,
+                scale,
+                offset,
+            } => {
+                let Some(UnitDefinition {
+                    dimensions,
+                    scale: reference_scale,
+                    origin: Some(origin),
+                }) = self.units.get(reference)
+                else {
+                    return Err(UnitError::new(
+                        "E-UNIT-DECLARATION",
+                        format!("`{reference}` is not a declared affine MeasurementUnit"),
+                    ));
+                };
+                nonzero_scale(scale)?;
+                if offset.unit.point.is_some() || &offset.unit.dimensions != dimensions {
+                    return Err(UnitError::new(
+                        "E-UNIT-DECLARATION",
+                        format!(
+                            "the offset must be a `Delta {}` quantity, but found `{}`",
+                            origin.dimension,
+                            offset.classifier()
+                        ),
+                    ));
+                }
+                Ok(UnitDefinition {
+                    dimensions: dimensions.clone(),
+                    scale: scale * reference_scale,
+                    origin: Some(Origin {
+                        dimension: origin.dimension.clone(),
+                        offset: &offset.magnitude * &offset.unit.scale + &origin.offset,
+                    }),
+                })
+            }
+        }
+    }
+
+    /// Resolve a bracketed unit expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns the offending span and error for an undeclared atom, mixed
+    /// vocabularies, a misapplied `delta`, or a point unit inside a compound.
+    pub fn resolve(
+        &self,
+        source: &SourceText,
+        expression: &UnitExpression,
+    ) -> Result<QuantityUnit, (Span, UnitError)> {
+        self.resolve_part(source, expression, &mut None)
+    }
+
+    fn resolve_part(
+        &self,
+        source: &SourceText,
+        expression: &UnitExpression,
+        vocabulary: &mut Option<Vocabulary>,
+    ) -> Result<QuantityUnit, (Span, UnitError)> {
+        match expression {
+            UnitExpression::Atom(span) => self.atom(source.slice(*span), *span, vocabulary, false),
+            UnitExpression::Delta { unit, .. } => {
+                self.atom(source.slice(*unit), *unit, vocabulary, true)
+            }
+            UnitExpression::Group { inner, .. } => {
+                let mut unit = self.resolve_part(source, inner, vocabulary)?;
+                unit.text = format!("({})", unit.text);
+                Ok(unit)
+            }
+            UnitExpression::Product { left, right, span }
+            | UnitExpression::Quotient { left, right, span } => {
+                let left = self.resolve_part(source, left, vocabulary)?;
+                let right = self.resolve_part(source, right, vocabulary)?;
+                let divide = matches!(expression, UnitExpression::Quotient { .. });
+                left.combine(&right, divide).map_err(|error| (*span, error))
+            }
+            UnitExpression::Power {
+                base,
+                exponent,
+                span,
+            } => {
+                let base = self.resolve_part(source, base, vocabulary)?;
+                let exponent = parse_integer(source.slice(*exponent))
+                    .and_then(|exponent| i32::try_from(exponent).ok())
+                    .ok_or_else(|| {
+                        (
+                            *exponent,
+                            UnitError::new(
+                                "E-UNIT-EXPONENT",
+                                "a unit exponent must be a 32-bit Int literal",
+                            ),
+                        )
+                    })?;
+                base.power(exponent).map_err(|error| (*span, error))
+            }
+        }
+    }
+
+    fn atom(
+        &self,
+        spelling: &str,
+        span: Span,
+        vocabulary: &mut Option<Vocabulary>,
+        delta_operator: bool,
+    ) -> Result<QuantityUnit, (Span, UnitError)> {
+        let Some(atom) = self.atoms.get(spelling) else {
+            return Err((
+                span,
+                UnitError::new(
+                    "E-UNKNOWN-UNIT",
+                    format!("unit `{spelling}` is not declared in this scope"),
+                ),
+            ));
+        };
+        let used = if delta_operator {
+            Vocabulary::Named
+        } else {
+            atom.vocabulary
+        };
+        if atom.vocabulary != used || vocabulary.is_some_and(|selected| selected != used) {
+            return Err((
+                span,
+                UnitError::new(
+                    "E-UNIT-VOCABULARY",
+                    "a unit expression uses either symbols or complete unit names, not both",
+                ),
+            ));
+        }
+        *vocabulary = Some(used);
+        let definition = &self.units[&atom.unit];
+        let scale = &definition.scale * &atom.factor;
+        let delta_text = if delta_operator {
+            format!("delta {spelling}")
+        } else {
+            format!("Δ{spelling}")
+        };
+        match &definition.origin {
+            Some(origin) if !delta_operator && !atom.delta => Ok(QuantityUnit {
+                text: spelling.to_owned(),
+                dimensions: definition.dimensions.clone(),
+                scale,
+                point: Some(PointUnit {
+                    dimension: origin.dimension.clone(),
+                    offset: origin.offset.clone(),
+                    delta_text,
+                }),
+            }),
+            None if delta_operator => Err((
+                span,
+                UnitError::new(
+                    "E-UNIT-DELTA",
+                    format!("`{spelling}` is already a linear unit and has no `delta`"),
+                ),
+            )),
+            _ => Ok(QuantityUnit {
+                text: if delta_operator {
+                    delta_text
+                } else {
+                    spelling.to_owned()
+                },
+                dimensions: definition.dimensions.clone(),
+                scale,
+                point: None,
+            }),
+        }
+    }
+
+    /// Resolve the target of `quantity as Unit` or `quantity as Delta Unit`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the target is not a declared unit spelling or a
+    /// `Delta` target is not affine.
+    pub fn conversion_target(&self, unit: &str, delta: bool) -> Result<QuantityUnit, UnitError> {
+        self.atom(unit, Span::default(), &mut None, delta)
+            .map_err(|(_, error)| error)
+    }
+}
+
+fn zero() -> BigRational {
+    BigRational::from_integer(BigInt::from(0))
+}
+
+fn one() -> BigRational {
+    BigRational::from_integer(BigInt::from(1))
+}
+
+fn is_zero(value: &BigRational) -> bool {
+    value.numer().sign() == Sign::NoSign
+}
+
+fn nonzero_scale(scale: &BigRational) -> Result<(), UnitError> {
+    if is_zero(scale) {
+        return Err(UnitError::new(
+            "E-UNIT-DECLARATION",
+            "a unit scale must be nonzero",
+        ));
+    }
+    Ok(())
+}
+
+/// The point side of an affine unit.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PointUnit {
+    dimension: String,
+    offset: BigRational,
+    delta_text: String,
+}
+
+/// A resolved unit: its spelling, dimensions, scale to the fundamental units,
+/// and, for points, the affine origin.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuantityUnit {
+    text: String,
+    dimensions: Dimensions,
+    scale: BigRational,
+    point: Option<PointUnit>,
+}
+
+impl QuantityUnit {
+    /// The unit of a plain number.
+    fn one() -> Self {
+        Self {
+            text: String::new(),
+            dimensions: Dimensions::new(),
+            scale: one(),
+            point: None,
+        }
+    }
+
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    fn classifier(&self) -> String {
+        self.point.as_ref().map_or_else(
+            || dimension_classifier(&self.dimensions),
+            |point| point.dimension.clone(),
+        )
+    }
+
+    fn linear(&self, operation: &str) -> Result<(), UnitError> {
+        match &self.point {
+            Some(point) => Err(UnitError::new(
+                "E-AFFINE-OPERATION",
+                format!(
+                    "a `{}` point cannot be {operation}; subtract another point to obtain a `Delta {}`",
+                    point.dimension, point.dimension
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn combine(&self, right: &Self, divide: bool) -> Result<Self, UnitError> {
+        self.linear(if divide { "divided" } else { "multiplied" })?;
+        right.linear(if divide { "divided" } else { "multiplied" })?;
+        let mut dimensions = self.dimensions.clone();
+        for (dimension, exponent) in &right.dimensions {
+            let entry = dimensions.entry(dimension.clone()).or_insert(0);
+            *entry += if divide { -exponent } else { *exponent };
+            if *entry == 0 {
+                dimensions.remove(dimension);
+            }
+        }
+        let text = match (self.text.is_empty(), right.text.is_empty()) {
+            (_, true) => self.text.clone(),
+            (true, false) if divide => format!("{} ^ -1", grouped(&right.text)),
+            (true, false) => right.text.clone(),
+            (false, false) => format!(
+                "{} {} {}",
+                self.text,
+                if divide { "/" } else { "*" },
+                grouped(&right.text)
+            ),
+        };
+        Ok(Self {
+            text,
+            dimensions,
+            scale: if divide {
+                &self.scale / &right.scale
+            } else {
+                &self.scale * &right.scale
+            },
+            point: None,
+        })
+    }
+
+    fn power(&self, exponent: i32) -> Result<Self, UnitError> {
+        self.linear("raised to a power")?;
+        Ok(Self {
+            text: if self.text.is_empty() {
+                String::new()
+            } else {
+                format!("{} ^ {exponent}", grouped(&self.text))
+            },
+            dimensions: self
+                .dimensions
+                .iter()
+                .filter(|_| exponent != 0)
+                .map(|(dimension, power)| (dimension.clone(), power * exponent))
+                .collect(),
+            scale: self.scale.pow(exponent),
+            point: None,
+        })
+    }
+
+    fn delta(&self) -> Self {
+        Self {
+            text: self
+                .point
+                .as_ref()
+                .map_or_else(|| self.text.clone(), |point| point.delta_text.clone()),
+            dimensions: self.dimensions.clone(),
+            scale: self.scale.clone(),
+            point: None,
+        }
+    }
+}
+
+fn grouped(text: &str) -> String {
+    if text.contains(' ') && !(text.starts_with('(') && text.ends_with(')')) {
+        format!("({text})")
+    } else {
+        text.to_owned()
+    }
+}
+
+/// Render dimension exponents as a classifier such as `Distance / Duration`,
+/// grouped so that Topal's left-to-right operators read it unambiguously.
+fn dimension_classifier(dimensions: &Dimensions) -> String {
+    if dimensions.is_empty() {
+        return "Rational".to_owned();
+    }
+    let compound = dimensions.len() > 1 || dimensions.values().any(|exponent| *exponent < 0);
+    let term = |name: &str, exponent: i32| {
+        let name = if name.contains(' ') && (compound || exponent.abs() != 1) {
+            format!("({name})")
+        } else {
+            name.to_owned()
+        };
+        match exponent.abs() {
+            1 => name,
+            power if compound => format!("({name} ^ {power})"),
+            power => format!("{name} ^ {power}"),
+        }
+    };
+    let join = |terms: &[(&String, &i32)]| {
+        terms
+            .iter()
+            .map(|(name, exponent)| term(name, **exponent))
+            .collect::<Vec<_>>()
+            .join(" * ")
+    };
+    let (numerator, denominator): (Vec<_>, Vec<_>) =
+        dimensions.iter().partition(|(_, exponent)| **exponent > 0);
+    let numerator = if numerator.is_empty() {
+        "1".to_owned()
+    } else {
+        join(&numerator)
+    };
+    match denominator.len() {
+        0 => numerator,
+        1 => format!("{numerator} / {}", join(&denominator)),
+        _ => format!("{numerator} / ({})", join(&denominator)),
+    }
+}
+
+/// An exact magnitude in a resolved unit. Plain numbers taking part in
+/// quantity arithmetic are dimensionless quantities with an empty unit.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Quantity {
+    pub magnitude: BigRational,
+    pub unit: QuantityUnit,
+}
+
+impl Quantity {
+    #[must_use]
+    pub fn number(value: BigRational) -> Self {
+        Self {
+            magnitude: value,
+            unit: QuantityUnit::one(),
+        }
+    }
+
+    /// The dimension classifier, or the affine dimension of a point.
+    #[must_use]
+    pub fn classifier(&self) -> String {
+        self.unit.classifier()
+    }
+
+    #[must_use]
+    pub const fn is_point(&self) -> bool {
+        self.unit.point.is_some()
+    }
+
+    /// The exact number denoted by a linear quantity without dimensions.
+    #[must_use]
+    pub fn dimensionless(&self) -> Option<BigRational> {
+        (self.unit.point.is_none() && self.unit.dimensions.is_empty())
+            .then(|| &self.magnitude * &self.unit.scale)
+    }
+
+    /// The coordinate in the fundamental units, including any affine origin.
+    fn canonical(&self) -> BigRational {
+        let offset = self
+            .unit
+            .point
+            .as_ref()
+            .map_or_else(zero, |point| point.offset.clone());
+        &self.magnitude * &self.unit.scale + offset
+    }
+
+    fn compatible(&self, other: &Self) -> bool {
+        self.unit.dimensions == other.unit.dimensions
+            && self.unit.point.as_ref().map(|point| &point.dimension)
+                == other.unit.point.as_ref().map(|point| &point.dimension)
+    }
+
+    fn shifted(&self, by: &Self, subtract: bool) -> Self {
+        let amount = &by.magnitude * &by.unit.scale / &self.unit.scale;
+        Self {
+            magnitude: if subtract {
+                &self.magnitude - amount
+            } else {
+                &self.magnitude + amount
+            },
+            unit: self.unit.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "{}[{}]",
+            magnitude_text(&self.magnitude),
+            self.unit.text
+        )
+    }
+}
+
+/// Write an integer or terminating decimal as a literal, and any other
+/// magnitude in the canonical `Rational` form.
+fn magnitude_text(value: &BigRational) -> String {
+    if value.is_integer() {
+        return value.numer().to_string();
+    }
+    let (two, five) = (BigInt::from(2), BigInt::from(5));
+    let mut rest = value.denom().clone();
+    let (mut twos, mut fives) = (0_u32, 0_u32);
+    while (&rest % &two).sign() == Sign::NoSign {
+        rest /= &two;
+        twos += 1;
+    }
+    while (&rest % &five).sign() == Sign::NoSign {
+        rest /= &five;
+        fives += 1;
+    }
+    if rest != BigInt::from(1) {
+        return format!("Rational ( {}, {} )", value.numer(), value.denom());
+    }
+    let digits = twos.max(fives);
+    let negative = value.numer().sign() == Sign::Minus;
+    let scaled = (value * BigRational::from_integer(BigInt::from(10).pow(digits)))
+        .to_integer()
+        .magnitude()
+        .to_string();
+    let width = digits as usize + 1;
+    let padded = format!("{scaled:0>width$}");
+    let (whole, fraction) = padded.split_at(padded.len() - digits as usize);
+    let sign = if negative { "-" } else { "" };
+    format!("{sign}{whole}.{fraction}")
+}
+
+/// The result of applying an operator to two quantities.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Applied {
+    /// A quantity, with the trace detail naming the arithmetic form used.
+    Quantity(Box<Quantity>, &'static str),
+    Ordering(Ordering),
+}
+
+/// Apply a binary operator under the linear and affine quantity rules.
+///
+/// # Errors
+///
+/// Returns an error for incompatible dimensions, an invalid point operation,
+/// a non-Int power, division by zero, or an operator quantities lack.
+pub fn apply(
+    kind: CallableKind,
+    symbol: &str,
+    left: &Quantity,
+    right: &Quantity,
+) -> Result<Applied, UnitError> {
+    match kind {
+        CallableKind::Plus | CallableKind::Minus => {
+            add(symbol, left, right, kind == CallableKind::Minus)
+                .map(|(quantity, form)| Applied::Quantity(Box::new(quantity), form))
+        }
+        CallableKind::Multiply | CallableKind::Divide => {
+            let divide = kind == CallableKind::Divide;
+            let unit = left.unit.combine(&right.unit, divide)?;
+            if divide && is_zero(&right.magnitude) {
+                return Err(division_by_zero());
+            }
+            let magnitude = if divide {
+                &left.magnitude / &right.magnitude
+            } else {
+                &left.magnitude * &right.magnitude
+            };
+            Ok(Applied::Quantity(
+                Box::new(Quantity { magnitude, unit }),
+                "product",
+            ))
+        }
+        CallableKind::Power => {
+            let exponent = right
+                .dimensionless()
+                .filter(BigRational::is_integer)
+                .and_then(|exponent| i32::try_from(exponent.to_integer()).ok())
+                .ok_or_else(|| {
+                    UnitError::new(
+                        "E-UNIT-EXPONENT",
+                        "a quantity power requires a 32-bit Int exponent",
+                    )
+                })?;
+            let unit = left.unit.power(exponent)?;
+            if exponent < 0 && is_zero(&left.magnitude) {
+                return Err(division_by_zero());
+            }
+            let magnitude = left.magnitude.pow(exponent);
+            Ok(Applied::Quantity(
+                Box::new(Quantity { magnitude, unit }),
+                "power",
+            ))
+        }
+        CallableKind::Equal
+        | CallableKind::NotEqual
+        | CallableKind::Less
+        | CallableKind::Greater
+        | CallableKind::LessEqual
+        | CallableKind::GreaterEqual
+        | CallableKind::Compare => {
+            if !left.compatible(right) {
+                return Err(mismatch(symbol, left, right));
+            }
+            Ok(Applied::Ordering(left.canonical().cmp(&right.canonical())))
+        }
+        CallableKind::Range | CallableKind::QuotientModulo | CallableKind::Modulo => {
+            Err(UnitError::new(
+                "E-NO-APPLICABLE-OVERLOAD",
+                format!("quantities have no `{symbol}`; use +, -, *, /, ^, or a comparison"),
+            ))
+        }
+    }
+}
+
+fn add(
+    symbol: &str,
+    left: &Quantity,
+    right: &Quantity,
+    subtract: bool,
+) -> Result<(Quantity, &'static str), UnitError> {
+    let same_dimensions = left.unit.dimensions == right.unit.dimensions;
+    match (&left.unit.point, &right.unit.point) {
+        (None, None) if same_dimensions => Ok((left.shifted(right, subtract), "linear")),
+        (Some(_), None) if same_dimensions => Ok((
+            left.shifted(right, subtract),
+            if subtract {
+                "point-delta"
+            } else {
+                "point+delta"
+            },
+        )),
+        (None, Some(_)) if same_dimensions && !subtract => {
+            Ok((right.shifted(left, false), "delta+point"))
+        }
+        (Some(point), Some(other)) if point.dimension == other.dimension && subtract => {
+            let magnitude = (left.canonical() - right.canonical()) / &left.unit.scale;
+            Ok((
+                Quantity {
+                    magnitude,
+                    unit: left.unit.delta(),
+                },
+                "point-point",
+            ))
+        }
+        (Some(point), Some(other)) if point.dimension == other.dimension => Err(UnitError::new(
+            "E-AFFINE-OPERATION",
+            format!(
+                "two `{0}` points cannot be added; add a `Delta {0}` to one point instead",
+                point.dimension
+            ),
+        )),
+        (None, Some(point)) if same_dimensions => Err(UnitError::new(
+            "E-AFFINE-OPERATION",
+            format!(
+                "a `{0}` point cannot be subtracted from a `Delta {0}`",
+                point.dimension
+            ),
+        )),
+        _ => Err(mismatch(symbol, left, right)),
+    }
+}
+
+/// Negate a linear quantity; points have no negation.
+///
+/// # Errors
+///
+/// Returns an error when `quantity` is a point.
+pub fn negate(quantity: &Quantity) -> Result<Quantity, UnitError> {
+    quantity.unit.linear("negated")?;
+    Ok(Quantity {
+        magnitude: -&quantity.magnitude,
+        unit: quantity.unit.clone(),
+    })
+}
+
+/// Express `quantity` in `target`, preserving one point or one difference.
+///
+/// # Errors
+///
+/// Returns an error when the dimensions differ or a point and a delta would
+/// be exchanged.
+pub fn convert(quantity: &Quantity, target: &QuantityUnit) -> Result<Quantity, UnitError> {
+    if quantity.unit.dimensions != target.dimensions {
+        return Err(classifier_mismatch(
+            &quantity.classifier(),
+            &target.classifier(),
+        ));
+    }
+    if quantity.unit.point.is_some() != target.point.is_some() {
+        return Err(UnitError::new(
+            "E-AFFINE-OPERATION",
+            "convert a point to a point unit such as `as Kelvin`, and a delta to a delta unit such as `as Delta Kelvin`",
+        ));
+    }
+    let offset = target
+        .point
+        .as_ref()
+        .map_or_else(zero, |point| point.offset.clone());
+    Ok(Quantity {
+        magnitude: (quantity.canonical() - offset) / &target.scale,
+        unit: target.clone(),
+    })
+}
+
+fn mismatch(symbol: &str, left: &Quantity, right: &Quantity) -> UnitError {
+    UnitError::new(
+        "E-DIMENSION-MISMATCH",
+        format!(
+            "`{symbol}` requires compatible dimensions, but found `{}` and `{}`",
+            left.classifier(),
+            right.classifier()
+        ),
+    )
+}
+
+/// The error for a quantity of dimension `found` where `required` is expected.
+#[must_use]
+pub fn classifier_mismatch(found: &str, required: &str) -> UnitError {
+    UnitError::new(
+        "E-DIMENSION-MISMATCH",
+        format!("a `{found}` quantity cannot be used as `{required}`"),
+    )
+}
+
+fn division_by_zero() -> UnitError {
+    UnitError::new("E-DIVISION-BY-ZERO", "a quantity cannot be divided by zero")
+}
+
+/// Recognize a `Dimension`, `AffineDimension`, or `MeasurementUnit ( ... )`
+/// initializer; `None` means `expression` declares neither.
+#[must_use]
+pub fn declaration(
+    source: &SourceText,
+    registry: &UnitRegistry,
+    expression: &Expression,
+) -> Option<Result<Declaration, (Span, UnitError)>> {
+    match expression {
+        Expression::Identifier(span) => {
+            DimensionKind::named(source.slice(*span)).map(|kind| Ok(Declaration::Dimension(kind)))
+        }
+        Expression::Application { items, span } => match items.as_slice() {
+            [
+                Expression::Identifier(constructor),
+                Expression::Product { fields, .. },
+            ] if source.slice(*constructor) == "MeasurementUnit" => Some(
+                unit_declaration(source, registry, fields, *span)
+                    .map(|unit| Declaration::Unit(Box::new(unit))),
+            ),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn unit_declaration(
+    source: &SourceText,
+    registry: &UnitRegistry,
+    fields: &[ProductField],
+    span: Span,
+) -> Result<UnitDeclaration, (Span, UnitError)> {
+    let invalid =
+        |span: Span, message: String| Err((span, UnitError::new("E-UNIT-DECLARATION", message)));
+    let mut labeled = BTreeMap::new();
+    for field in fields {
+        let Some(label) = field.label else {
+            return invalid(
+                field.value.span(),
+                "`MeasurementUnit` fields are labeled".to_owned(),
+            );
+        };
+        let name = source.slice(label);
+        if !matches!(
+            name,
+            "symbol" | "prefixes" | "dimension" | "scale" | "reference" | "offset"
+        ) {
+            return invalid(label, format!("`MeasurementUnit` has no field `{name}`"));
+        }
+        if labeled.insert(name, &field.value).is_some() {
+            return invalid(label, format!("`{name}` is supplied more than once"));
+        }
+    }
+    let symbol = match labeled.remove("symbol") {
+        Some(Expression::Identifier(symbol)) => source.slice(*symbol).to_owned(),
+        Some(other) => return invalid(other.span(), "a unit symbol is one unit atom".to_owned()),
+        None => return invalid(span, "every `MeasurementUnit` supplies `symbol`".to_owned()),
+    };
+    let prefixes = match labeled.remove("prefixes") {
+        Some(value) => prefix_families(source, value)?,
+        None => {
+            return invalid(
+                span,
+                "every `MeasurementUnit` supplies `prefixes`".to_owned(),
+            );
+        }
+    };
+    let form = match labeled.keys().copied().collect::<Vec<_>>().as_slice() {
+        ["dimension"] => UnitForm::Fundamental {
+            dimension: identifier(source, labeled["dimension"])?,
+        },
+        ["scale"] => UnitForm::DerivedLinear {
+            scale: literal_quantity(source, registry, labeled["scale"])?,
+        },
+        ["offset", "reference", "scale"] => {
+            let scale = labeled["scale"];
+            let Some(factor) = literal_quantity(source, registry, scale)?.dimensionless() else {
+                return invalid(
+                    scale.span(),
+                    "an affine unit's scale is a dimensionless number".to_owned(),
+                );
+            };
+            UnitForm::DerivedAffine {
+                reference: identifier(source, labeled["reference"])?,
+                scale: factor,
+                offset: literal_quantity(source, registry, labeled["offset"])?,
+            }
+        }
+        _ => {
+            return invalid(
+                span,
+                "`MeasurementUnit` takes `dimension`; a dimensioned `scale`; or `reference`, `scale`, and `offset`".to_owned(),
+            );
+        }
+    };
+    Ok(UnitDeclaration {
+        symbol,
+        prefixes,
+        form,
+    })
+}
+
+fn prefix_families(
+    source: &SourceText,
+    value: &Expression,
+) -> Result<Vec<PrefixFamily>, (Span, UnitError)> {
+    let invalid = || {
+        (
+            value.span(),
+            UnitError::new(
+                "E-UNIT-DECLARATION",
+                "`prefixes` is `none`, `SI`, `Binary`, or a product such as `( SI, Binary )`",
+            ),
+        )
+    };
+    match value {
+        Expression::Identifier(span) if source.slice(*span) == "none" => Ok(Vec::new()),
+        Expression::Identifier(span) => PrefixFamily::named(source.slice(*span))
+            .map(|family| vec![family])
+            .ok_or_else(invalid),
+        Expression::Product { fields, .. } => {
+            let mut families = Vec::new();
+            for field in fields {
+                let family = match (&field.label, &field.value) {
+                    (None, Expression::Identifier(span)) => {
+                        PrefixFamily::named(source.slice(*span))
+                    }
+                    _ => None,
+                }
+                .ok_or_else(invalid)?;
+                if families.contains(&family) {
+                    return Err(invalid());
+                }
+                families.push(family);
+            }
+            Ok(families)
+        }
+        _ => Err(invalid()),
+    }
+}
+
+fn identifier(source: &SourceText, value: &Expression) -> Result<String, (Span, UnitError)> {
+    match value {
+        Expression::Identifier(span) => Ok(source.slice(*span).to_owned()),
+        other => Err((
+            other.span(),
+            UnitError::new("E-UNIT-DECLARATION", "expected a declared name"),
+        )),
+    }
+}
+
+/// Evaluate a number or quantity literal, as declarations require.
+///
+/// # Errors
+///
+/// Returns an error for any other expression or an unresolved unit.
+pub fn literal_quantity(
+    source: &SourceText,
+    registry: &UnitRegistry,
+    expression: &Expression,
+) -> Result<Quantity, (Span, UnitError)> {
+    let (value, unit) = match expression {
+        Expression::Integer(value) | Expression::Rational(value) => (*value, None),
+        Expression::Measured { value, unit, .. } => (*value, Some(unit)),
+        other => {
+            return Err((
+                other.span(),
+                UnitError::new(
+                    "E-UNIT-DECLARATION",
+                    "a unit scale or offset is a number or quantity literal",
+                ),
+            ));
+        }
+    };
+    let magnitude = literal_magnitude(source.slice(value)).ok_or_else(|| {
+        (
+            value,
+            UnitError::new("E-UNIT-DECLARATION", "malformed numeric literal"),
+        )
+    })?;
+    match unit {
+        Some(unit) => Ok(Quantity {
+            magnitude,
+            unit: registry.resolve(source, unit)?,
+        }),
+        None => Ok(Quantity::number(magnitude)),
+    }
+}
+
+/// The exact value of an `Int` or `Rational` literal.
+#[must_use]
+pub fn literal_magnitude(text: &str) -> Option<BigRational> {
+    parse_integer(text)
+        .map(BigRational::from_integer)
+        .or_else(|| parse_rational(text))
+}
+
+/// Check dimensions statically across `statements`: every operator and
+/// dimension-classified binding whose operands are quantity literals or names
+/// bound to them, without executing the program.
+#[must_use]
+pub fn dimension_diagnostics(source: &SourceText, statements: &[Statement]) -> Vec<Diagnostic> {
+    let mut checker = DimensionChecker {
+        source,
+        registry: UnitRegistry::default(),
+        diagnostics: Vec::new(),
+    };
+    checker.statements(statements, &mut BTreeMap::new());
+    checker.diagnostics
+}
+
+struct DimensionChecker<'a> {
+    source: &'a SourceText,
+    registry: UnitRegistry,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl DimensionChecker<'_> {
+    fn statements(&mut self, statements: &[Statement], scope: &mut BTreeMap<String, Quantity>) {
+        for statement in statements {
+            match statement {
+                Statement::Binding {
+                    name,
+                    classifier,
+                    value,
+                } => self.binding(*name, *classifier, value, scope),
+                Statement::Published { declaration, .. } => {
+                    self.statements(std::slice::from_ref(declaration), scope);
+                }
+                Statement::Function {
+                    parameters, body, ..
+                }
+                | Statement::Generator {
+                    parameters, body, ..
+                } => {
+                    let mut inner = scope.clone();
+                    for parameter in parameters {
+                        inner.remove(self.source.slice(parameter.name));
+                    }
+                    self.statements(body, &mut inner);
+                }
+                Statement::Foreach { binding, body, .. } => {
+                    let mut inner = scope.clone();
+                    inner.remove(self.source.slice(*binding));
+                    self.statements(body, &mut inner);
+                }
+                Statement::Implementation {
+                    declarations: body, ..
+                }
+                | Statement::InterfaceImplementation {
+                    declarations: body, ..
+                } => self.statements(body, &mut scope.clone()),
+                Statement::ContextAssignment { value, .. }
+                | Statement::Discard { value, .. }
+                | Statement::Return { value, .. }
+                | Statement::Expression(value) => {
+                    self.infer(value, scope);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn binding(
+        &mut self,
+        name: Span,
+        classifier: Option<Span>,
+        value: &Expression,
+        scope: &mut BTreeMap<String, Quantity>,
+    ) {
+        let name = self.source.slice(name);
+        match declaration(self.source, &self.registry, value) {
+            // Invalid declarations are reported when the program executes.
+            Some(Ok(Declaration::Dimension(kind))) => self.registry.declare_dimension(name, kind),
+            Some(Ok(Declaration::Unit(unit))) => {
+                let _ = self.registry.declare_unit(name, &unit);
+            }
+            Some(Err(_)) => {}
+            None => {
+                scope.remove(name);
+                let Some(quantity) = self.infer(value, scope) else {
+                    return;
+                };
+                if let Some(classifier) = classifier.map(|classifier| self.source.slice(classifier))
+                    && self.registry.is_dimension_classifier(classifier)
+                    && quantity.classifier() != classifier
+                {
+                    self.report(
+                        value.span(),
+                        &classifier_mismatch(&quantity.classifier(), classifier),
+                    );
+                    return;
+                }
+                scope.insert(name.to_owned(), quantity);
+            }
+        }
+    }
+
+    fn infer(
+        &mut self,
+        expression: &Expression,
+        scope: &BTreeMap<String, Quantity>,
+    ) -> Option<Quantity> {
+        match expression {
+            Expression::Integer(_) | Expression::Rational(_) | Expression::Measured { .. } => {
+                literal_quantity(self.source, &self.registry, expression).ok()
+            }
+            Expression::Identifier(span) => scope.get(self.source.slice(*span)).cloned(),
+            Expression::Application { items, .. } => self.application(items, scope),
+            Expression::Block { statements, .. } => {
+                self.statements(statements, &mut scope.clone());
+                None
+            }
+            Expression::Product { fields, .. } => {
+                for field in fields {
+                    self.infer(&field.value, scope);
+                }
+                None
+            }
+            Expression::AnonymousFunction {
+                parameters, body, ..
+            } => {
+                let mut inner = scope.clone();
+                for parameter in parameters {
+                    inner.remove(self.source.slice(*parameter));
+                }
+                self.infer(body, &inner);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn application(
+        &mut self,
+        items: &[Expression],
+        scope: &BTreeMap<String, Quantity>,
+    ) -> Option<Quantity> {
+        let (mut accumulator, mut index) = match items {
+            [
+                Expression::Callable {
+                    kind: CallableKind::Minus,
+                    ..
+                },
+                operand,
+                ..,
+            ] => (
+                self.infer(operand, scope)
+                    .and_then(|operand| negate(&operand).ok()),
+                2,
+            ),
+            [first, ..] => (self.infer(first, scope), 1),
+            [] => return None,
+        };
+        while index < items.len() {
+            match &items[index..] {
+                [Expression::Identifier(keyword), target @ ..]
+                    if self.source.slice(*keyword) == "as" =>
+                {
+                    let target = conversion_target(self.source, &self.registry, target)?.ok()?;
+                    return match convert(&accumulator?, &target) {
+                        Ok(converted) => Some(converted),
+                        Err(error) => {
+                            self.report(cover(&items[0], &items[items.len() - 1]), &error);
+                            None
+                        }
+                    };
+                }
+                [Expression::Callable { kind, .. }, operand, ..] => {
+                    let right = self.infer(operand, scope);
+                    accumulator = match (accumulator, right) {
+                        (Some(left), Some(right)) => {
+                            match apply(*kind, crate::source::callable_name(*kind), &left, &right) {
+                                Ok(Applied::Quantity(quantity, _)) => Some(*quantity),
+                                Ok(Applied::Ordering(_)) => None,
+                                Err(error) => {
+                                    self.report(cover(&items[0], operand), &error);
+                                    None
+                                }
+                            }
+                        }
+                        _ => None,
+                    };
+                    index += 2;
+                }
+                rest => {
+                    for item in rest {
+                        self.infer(item, scope);
+                    }
+                    return None;
+                }
+            }
+        }
+        accumulator
+    }
+
+    fn report(&mut self, span: Span, error: &UnitError) {
+        if error.is_dimension_error() {
+            self.diagnostics.push(
+                diagnostic(self.source, error.code, span, error.message.clone())
+                    .with_source_span(span),
+            );
+        }
+    }
+}
+
+/// Resolve `Unit` or `Delta Unit` after `as`; `None` for any other shape.
+#[must_use]
+pub fn conversion_target(
+    source: &SourceText,
+    registry: &UnitRegistry,
+    target: &[Expression],
+) -> Option<Result<QuantityUnit, UnitError>> {
+    match target {
+        [Expression::Identifier(unit)] => {
+            Some(registry.conversion_target(source.slice(*unit), false))
+        }
+        [Expression::Identifier(delta), Expression::Identifier(unit)]
+            if source.slice(*delta) == "Delta" =>
+        {
+            Some(registry.conversion_target(source.slice(*unit), true))
+        }
+        _ => None,
+    }
+}
+
+const fn cover(first: &Expression, last: &Expression) -> Span {
+    Span::new(first.span().start, last.span().end)
+}
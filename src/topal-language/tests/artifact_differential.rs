@@ -0,0 +1,112 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use topal_geir::{PassManager, ToolRole};
+use topal_language::{Session, execute_artifact, lower_source_file};
+use topal_semantics::{ObservableMemoryOutcome, validate_optimization};
+
+const COMPARED_EVENTS: [&str; 5] = [
+    "function.entry",
+    "function.exit",
+    "decision.rule.considered",
+    "decision.rule.selected",
+    "generator.suspended",
+];
+
+fn examples() -> Vec<PathBuf> {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples/language");
+    let mut paths = fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "t"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+fn compared(trace: Vec<String>) -> Vec<String> {
+    trace
+        .into_iter()
+        .filter(|event| {
+            COMPARED_EVENTS
+                .iter()
+                .any(|name| event.contains(&format!("\"event\":\"{name}\"")))
+        })
+        .collect()
+}
+
+#[test]
+fn lowered_examples_match_the_source_interpreter() {
+    let mut compared_examples = 0;
+    let mut compared_events = 0;
+    for path in examples() {
+        let source = fs::read_to_string(&path).unwrap();
+        let Ok(module) = lower_source_file(&source, "examples", &["language"], ToolRole::Compiler)
+        else {
+            continue;
+        };
+        let mut interpreted_trace = Vec::new();
+        let interpreted = Session::new()
+            .evaluate_source_file(&source, &mut interpreted_trace)
+            .unwrap_or_else(|error| panic!("{} failed: {}", path.display(), error.message));
+        let mut artifact_trace = Vec::new();
+        let executed = execute_artifact(module.validate().unwrap(), &mut artifact_trace)
+            .unwrap_or_else(|error| {
+                panic!("{} artifact failed: {}", path.display(), error.message)
+            });
+        assert_eq!(
+            executed.to_string(),
+            interpreted.to_string(),
+            "{} result diverged",
+            path.display()
+        );
+        let artifact_events = compared(artifact_trace);
+        assert_eq!(
+            artifact_events,
+            compared(interpreted_trace),
+            "{} trace diverged",
+            path.display()
+        );
+        compared_examples += 1;
+        compared_events += artifact_events.len();
+    }
+    assert!(
+        compared_examples >= 5,
+        "too few examples lower into GEIR for differential testing"
+    );
+    assert!(
+        compared_events > 0,
+        "differential traces recorded no events"
+    );
+}
+
+#[test]
+fn optimized_examples_preserve_observable_results() {
+    let mut rewritten = 0;
+    for path in examples() {
+        let source = fs::read_to_string(&path).unwrap();
+        let Ok(module) = lower_source_file(&source, "examples", &["language"], ToolRole::Compiler)
+        else {
+            continue;
+        };
+        let (optimized, reports) = PassManager::default()
+            .run(module.validate().unwrap())
+            .unwrap_or_else(|error| panic!("{}: {error}", path.display()));
+        rewritten += reports.iter().map(|report| report.rewrites).sum::<usize>();
+        let observe = |module: &topal_geir::Module| {
+            vec![
+                match execute_artifact(module.validate().unwrap(), &mut Vec::new()) {
+                    Ok(value) => ObservableMemoryOutcome::Value(value.to_string()),
+                    Err(error) => ObservableMemoryOutcome::Error(error.message.into()),
+                },
+            ]
+        };
+        assert_eq!(
+            validate_optimization(&observe(&module), &observe(&optimized)),
+            Ok(()),
+            "{} optimization changed its result",
+            path.display()
+        );
+    }
+    assert!(rewritten > 0, "no example exercised an optimization pass");
+}
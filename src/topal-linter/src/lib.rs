@@ -1,4 +1,4 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
 use std::fs;
 use std::io::Write;
@@ -7,16 +7,27 @@ use std::process::ExitCode;
 
 use serde::Serialize;
 use topal_best_practices::{Catalog, CatalogEntry};
-use topal_language::{Session, Value as LanguageValue};
+use topal_language::{
+    ResourceLimits, Session, Value as LanguageValue, dimension_diagnostics,
+    sensitivity_diagnostics, with_evaluation_stack,
+};
 use topal_source::{Diagnostic, Severity, SourceText, Span};
 use topal_syntax::{
-    CallableKind, DecisionMatcher, DiagnosticControlKind, Expression, Statement, SyntaxDiagnostic,
-    lex, parse,
+    CallableKind, DecisionMatcher, DiagnosticControlKind, Expression, Lexed, ParsedSource,
+    Statement, SyntaxDiagnostic, Token, TokenKind, lex, parse,
 };
 
 const MAX_RULE_SOURCE_BYTES: usize = 16 * 1024;
 const MAX_RULE_EXPRESSION_NODES: usize = 128;
 const MAX_RULE_INTEGER_BYTES: usize = 64;
+const MAX_RULE_STRING_BYTES: usize = 256;
+/// Budgets for one rule application, independent of the static admission
+/// bounds above.
+const RULE_LIMITS: ResourceLimits = ResourceLimits {
+    fuel: Some(4 * MAX_RULE_EXPRESSION_NODES as u64),
+    value_memory: Some(64 * 1024),
+    call_depth: Some(16),
+};
 
 const USAGE: &str = "usage: topal-lint [OPTIONS] SOURCE...\n\
   --check-rule PATH              validate a Topal lint-variant rule module\n\
@@ -501,40 +512,57 @@ fn validate_rule_text(
     }
 }
 
+/// A rule module holds its language selection, its entry function, and any
+/// static helper functions the entry calls. Every body shares one bounded
+/// pure-expression budget.
 fn validate_contained_rule(
     source: &SourceText,
     statements: &[Statement],
     entry_point: &str,
 ) -> Result<(), String> {
-    let [
-        Statement::LanguageSelection { .. },
-        Statement::Function { name, body, .. },
-    ] = statements
-    else {
-        return Err(
-            "L-RULE-CONTAINMENT: a lint rule module contains only its language selection and entry function"
-                .into(),
-        );
+    let containment = || {
+        "L-RULE-CONTAINMENT: a lint rule module contains only its language selection and static functions"
+            .to_owned()
     };
-    if source.slice(*name) != entry_point {
+    let [Statement::LanguageSelection { .. }, functions @ ..] = statements else {
+        return Err(containment());
+    };
+    let mut declares_entry = false;
+    let mut remaining = MAX_RULE_EXPRESSION_NODES;
+    for function in functions {
+        let Statement::Function {
+            name,
+            is_static,
+            body,
+            ..
+        } = function
+        else {
+            return Err(containment());
+        };
+        if source.slice(*name) == entry_point {
+            declares_entry = true;
+        } else if !is_static {
+            return Err(containment());
+        }
+        for statement in body {
+            let expression = match statement {
+                Statement::Expression(expression) => expression,
+                Statement::Return { value, .. } => value,
+                _ => {
+                    return Err(
+                        "L-RULE-CONTAINMENT: lint rule bodies contain only pure result expressions"
+                            .into(),
+                    );
+                }
+            };
+            validate_rule_expression(source, expression, &mut remaining)?;
+        }
+    }
+    if !declares_entry {
         return Err(format!(
             "L-RULE-ENTRY: lint rule module does not declare entry point `{entry_point}`"
         ));
     }
-    let mut remaining = MAX_RULE_EXPRESSION_NODES;
-    for statement in body {
-        let expression = match statement {
-            Statement::Expression(expression) => expression,
-            Statement::Return { value, .. } => value,
-            _ => {
-                return Err(
-                    "L-RULE-CONTAINMENT: lint rule bodies contain only pure result expressions"
-                        .into(),
-                );
-            }
-        };
-        validate_rule_expression(source, expression, &mut remaining)?;
-    }
     Ok(())
 }
 
@@ -565,6 +593,10 @@ fn validate_rule_expression(
         Expression::Integer(_) => Err(format!(
             "L-RULE-RESOURCE: lint rule integer literal exceeds {MAX_RULE_INTEGER_BYTES} bytes"
         )),
+        Expression::String(span) if source.slice(*span).len() <= MAX_RULE_STRING_BYTES => Ok(()),
+        Expression::String(_) => Err(format!(
+            "L-RULE-RESOURCE: lint rule string literal exceeds {MAX_RULE_STRING_BYTES} bytes"
+        )),
         Expression::Application { items, .. } => {
             for item in items {
                 validate_rule_expression(source, item, remaining)?;
@@ -898,14 +930,52 @@ pub struct SourceEdit {
     pub span: Span,
     pub replacement: String,
     pub safety: RectificationSafety,
+    /// Index of the reported diagnostic this edit rectifies, if any.
+    pub diagnostic: Option<usize>,
 }
 
 impl SourceEdit {
-    const fn is_eligible(&self) -> bool {
+    /// Whether `--fix` and other automatic adapters may apply the edit
+    /// without review.
+    #[must_use]
+    pub const fn is_eligible(&self) -> bool {
         !matches!(self.safety, RectificationSafety::ReviewRequired)
     }
 }
 
+/// Insert a structured `lang disable-diagnostic` control before the innermost
+/// statement containing `finding`, at that statement's indentation.
+///
+/// Returns `None` when the text is not valid source or no statement contains
+/// the finding.
+#[must_use]
+pub fn suppression_edit(text: &str, identity: &str, finding: Span) -> Option<SourceEdit> {
+    let source = SourceText::new(text).ok()?;
+    let lexed = lex(&source);
+    let parsed = parse(&source, &lexed);
+    let mut statements = parsed.statements.as_slice();
+    let mut enclosing = None;
+    while let Some(statement) = statements.iter().find(|statement| {
+        !matches!(statement, Statement::DiagnosticControl { .. }) && {
+            let span = lint_statement_span(statement);
+            span.start <= finding.start && finding.end <= span.end
+        }
+    }) {
+        enclosing = Some(lint_statement_span(statement));
+        statements = nested_statements(statement).unwrap_or_default();
+    }
+    let line_start = source.as_str()[..enclosing?.start]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1);
+    let indentation = &source.as_str()[line_start..enclosing?.start];
+    Some(SourceEdit {
+        span: Span::new(line_start, line_start),
+        replacement: format!("{indentation}lang disable-diagnostic ( {identity} )\n"),
+        safety: RectificationSafety::PresentationOnly,
+        diagnostic: None,
+    })
+}
+
 /// Evidence level controlling whether `--fix` may apply an edit.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum RectificationSafety {
@@ -972,6 +1042,24 @@ impl LintEngine {
         validate_overrides(&self.catalog, &overrides)?;
         analyze_text(text, &self.catalog, &overrides)
     }
+
+    /// Lint normalized source that the host has already lexed and parsed, as
+    /// an editor does for a document whose tokens it maintains incrementally.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as [`Self::lint_text`].
+    pub fn lint_parsed(
+        &self,
+        source: &SourceText,
+        lexed: &Lexed,
+        parsed: &ParsedSource,
+        controls: &[LintControl],
+    ) -> Result<LintReport, String> {
+        let overrides = policy_overrides(controls)?;
+        validate_overrides(&self.catalog, &overrides)?;
+        analyze_source(source, lexed, parsed, &self.catalog, &overrides)
+    }
 }
 
 /// Lint normalized in-memory Topal source with explicitly enabled built-in
@@ -1037,13 +1125,23 @@ fn analyze_text(
     catalog: &Catalog,
     overrides: &[Override],
 ) -> Result<LintReport, String> {
-    let mut findings = Vec::new();
     let source = match normalized_lint_source(text) {
         Ok(source) => source,
         Err(report) => return Ok(report),
     };
     let lexed = lex(&source);
     let parsed = parse(&source, &lexed);
+    analyze_source(&source, &lexed, &parsed, catalog, overrides)
+}
+
+fn analyze_source(
+    source: &SourceText,
+    lexed: &Lexed,
+    parsed: &ParsedSource,
+    catalog: &Catalog,
+    overrides: &[Override],
+) -> Result<LintReport, String> {
+    let mut findings = Vec::new();
     let mut seen = BTreeSet::new();
     let mut has_error = false;
     let mut rectifications = Vec::new();
@@ -1052,11 +1150,17 @@ fn analyze_text(
     diagnostics.extend(parsed.diagnostics.iter());
     for diagnostic in diagnostics {
         if seen.insert((diagnostic.code, diagnostic.span.start, diagnostic.span.end)) {
-            findings.push(shared_syntax_diagnostic(&source, diagnostic));
+            findings.push(shared_syntax_diagnostic(source, diagnostic));
             has_error = true;
         }
     }
     if seen.is_empty() {
+        let semantic_errors = dimension_diagnostics(source, &parsed.statements)
+            .into_iter()
+            .chain(sensitivity_diagnostics(source, &parsed.statements))
+            .collect::<Vec<_>>();
+        has_error |= !semantic_errors.is_empty();
+        findings.extend(semantic_errors);
         for entry in &catalog.entries {
             let entry_policy = policy(entry, overrides)?;
             if !entry_policy.enabled {
@@ -1064,7 +1168,7 @@ fn analyze_text(
             }
             if let Some(rule) = &entry.lint_rule {
                 let rule_findings = match rule.engine.as_str() {
-                    "topal" => topal_rule(entry, &source, &parsed.statements)?,
+                    "topal" => topal_rule(entry, source, &lexed.tokens, &parsed.statements)?,
                     other => {
                         return Err(format!(
                             "lint rule {} uses unsupported engine `{other}`",
@@ -1074,14 +1178,15 @@ fn analyze_text(
                 };
                 for rule_finding in rule_findings {
                     if diagnostic_suppresses(
-                        &source,
+                        source,
                         &parsed.statements,
                         &entry.identity,
                         rule_finding.span,
                     ) {
                         continue;
                     }
-                    if let Some(rectification) = rule_finding.rectification.clone() {
+                    if let Some(mut rectification) = rule_finding.rectification.clone() {
+                        rectification.diagnostic = Some(findings.len());
                         rectifications.push(rectification);
                     }
                     let position = source.position(rule_finding.span.start);
@@ -1240,13 +1345,14 @@ const fn lint_statement_span(statement: &Statement) -> Span {
 fn topal_rule(
     entry: &CatalogEntry,
     source: &SourceText,
+    tokens: &[Token],
     statements: &[Statement],
 ) -> Result<Vec<RuleFinding>, String> {
     let rule = entry.lint_rule.as_ref().expect("caller checks attachment");
-    let rule_source = &rule.source_text;
     let expected_parameters: &[&str] = match rule.view.as_str() {
         "task-declaration-order/1" => &["Int", "Int"],
         "task-state-machine/1" => &["Boolean", "Boolean"],
+        "token/1" | "declaration/1" => &["String", "String"],
         other => {
             return Err(format!(
                 "best-practice {} requires unsupported read-only view `{other}`",
@@ -1255,46 +1361,198 @@ fn topal_rule(
         }
     };
     validate_rule_text(
-        rule_source,
+        &rule.source_text,
         "<embedded lint rule>",
         &rule.entry_point,
         Some(expected_parameters),
     )?;
-    match rule.view.as_str() {
-        "task-declaration-order/1" => {
-            topal_task_declaration_order(entry, rule_source, &rule.entry_point, source, statements)
+    // Rule evaluation recurses on the native stack, so it runs where the
+    // stack holds every call the rule budget admits.
+    with_evaluation_stack(RULE_LIMITS, || {
+        let loaded = TopalRule::load(&rule.source_text, &rule.entry_point)?;
+        let mut findings = Vec::new();
+        let mut context = ApplicabilityContext::default();
+        match rule.view.as_str() {
+            "task-declaration-order/1" => visit_topal_task_order(
+                entry,
+                &loaded,
+                source,
+                statements,
+                &mut context,
+                &mut findings,
+            )?,
+            "task-state-machine/1" => visit_topal_task_state_machine(
+                entry,
+                &loaded,
+                source,
+                statements,
+                &mut context,
+                &mut findings,
+            )?,
+            "token/1" | "declaration/1" => {
+                if let Some(Statement::LanguageSelection {
+                    version, features, ..
+                }) = statements.first()
+                {
+                    context.select(source, *version, features);
+                }
+                if entry_applies(
+                    entry,
+                    context.version.as_deref(),
+                    &context.selected_features,
+                )? {
+                    if rule.view == "token/1" {
+                        check_topal_tokens(entry, &loaded, source, tokens, &mut findings)?;
+                    } else {
+                        visit_topal_declarations(
+                            entry,
+                            &loaded,
+                            source,
+                            statements,
+                            &mut findings,
+                        )?;
+                    }
+                }
+            }
+            _ => unreachable!("view checked above"),
         }
-        "task-state-machine/1" => {
-            topal_task_state_machine(entry, rule_source, &rule.entry_point, source, statements)
+        Ok(findings)
+    })
+}
+
+/// Apply a `token/1` rule to the kind and text of each non-trivia token.
+fn check_topal_tokens(
+    entry: &CatalogEntry,
+    rule: &TopalRule,
+    source: &SourceText,
+    tokens: &[Token],
+    findings: &mut Vec<RuleFinding>,
+) -> Result<(), String> {
+    for token in tokens.iter().filter(|token| !token.kind.is_trivia()) {
+        let text = source.slice(token.span);
+        if !rule.holds(
+            LanguageValue::String(token_view_kind(token.kind).to_owned()),
+            LanguageValue::String(text.to_owned()),
+        )? {
+            findings.push(RuleFinding {
+                span: token.span,
+                message: format!("token `{text}` does not follow this best-practice"),
+                suggestion: entry.recommendation.clone(),
+                rectification: None,
+            });
         }
-        _ => unreachable!("view checked above"),
     }
+    Ok(())
 }
 
-fn topal_task_state_machine(
+/// The stable kind a `token/1` rule sees for a non-trivia token. These names
+/// are part of the view contract, independent of the lexer's type names.
+const fn token_view_kind(kind: TokenKind) -> &'static str {
+    match kind {
+        TokenKind::Whitespace => "whitespace",
+        TokenKind::Newline => "newline",
+        TokenKind::Comment => "comment",
+        TokenKind::Documentation => "documentation",
+        TokenKind::Hashbang => "hashbang",
+        TokenKind::Identifier => "identifier",
+        TokenKind::Discard => "discard",
+        TokenKind::Boolean => "boolean",
+        TokenKind::Integer => "integer",
+        TokenKind::Rational => "rational",
+        TokenKind::Version => "version",
+        TokenKind::String => "string",
+        TokenKind::LeftParen => "left-paren",
+        TokenKind::RightParen => "right-paren",
+        TokenKind::LeftBrace => "left-brace",
+        TokenKind::RightBrace => "right-brace",
+        TokenKind::LeftBracket => "left-bracket",
+        TokenKind::RightBracket => "right-bracket",
+        TokenKind::Comma => "comma",
+        TokenKind::Colon => "colon",
+        TokenKind::Arrow => "arrow",
+        TokenKind::Equals => "equals",
+        TokenKind::Bang => "bang",
+        TokenKind::NotEquals => "not-equals",
+        TokenKind::Less => "less",
+        TokenKind::Greater => "greater",
+        TokenKind::LessEqual => "less-equal",
+        TokenKind::Compare => "compare",
+        TokenKind::Range => "range",
+        TokenKind::Ellipsis => "ellipsis",
+        TokenKind::GreaterEqual => "greater-equal",
+        TokenKind::Plus => "plus",
+        TokenKind::Minus => "minus",
+        TokenKind::Star => "star",
+        TokenKind::Slash => "slash",
+        TokenKind::SlashPercent => "slash-percent",
+        TokenKind::Percent => "percent",
+        TokenKind::Caret => "caret",
+        TokenKind::At => "at",
+        TokenKind::Dot => "dot",
+        TokenKind::Unknown => "unknown",
+    }
+}
+
+/// Apply a `declaration/1` rule to the kind and name of each named
+/// declaration, including those nested in implementations and bodies.
+fn visit_topal_declarations(
     entry: &CatalogEntry,
-    rule_source: &str,
-    entry_point: &str,
+    rule: &TopalRule,
     source: &SourceText,
     statements: &[Statement],
-) -> Result<Vec<RuleFinding>, String> {
-    let mut findings = Vec::new();
-    visit_topal_task_state_machine(
-        entry,
-        rule_source,
-        entry_point,
-        source,
-        statements,
-        &mut ApplicabilityContext::default(),
-        &mut findings,
-    )?;
-    Ok(findings)
+    findings: &mut Vec<RuleFinding>,
+) -> Result<(), String> {
+    for statement in statements {
+        let declaration = match statement {
+            Statement::Binding { name, .. } => Some(("binding", *name)),
+            Statement::Implementation { name, .. } => Some(("implementation", *name)),
+            Statement::StateField { name, .. } => Some(("state-field", *name)),
+            Statement::Function { name, .. } => Some(("function", *name)),
+            Statement::Generator { name, .. } => Some(("generator", *name)),
+            Statement::Union { name, .. } => Some(("union", *name)),
+            Statement::Interface { name, .. } => Some(("interface", *name)),
+            _ => None,
+        };
+        if let Some((kind, name)) = declaration {
+            let text = source.slice(name);
+            if !rule.holds(
+                LanguageValue::String(kind.to_owned()),
+                LanguageValue::String(text.to_owned()),
+            )? {
+                findings.push(RuleFinding {
+                    span: name,
+                    message: format!("{kind} `{text}` does not follow this best-practice"),
+                    suggestion: entry.recommendation.clone(),
+                    rectification: None,
+                });
+            }
+        }
+        match statement {
+            Statement::Published { declaration, .. } => visit_topal_declarations(
+                entry,
+                rule,
+                source,
+                std::slice::from_ref(declaration.as_ref()),
+                findings,
+            )?,
+            Statement::Implementation { declarations, .. }
+            | Statement::InterfaceImplementation { declarations, .. } => {
+                visit_topal_declarations(entry, rule, source, declarations, findings)?;
+            }
+            Statement::Function { body, .. }
+            | Statement::Generator { body, .. }
+            | Statement::Foreach { body, .. } => {
+                visit_topal_declarations(entry, rule, source, body, findings)?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
 }
 
 fn visit_topal_task_state_machine(
     entry: &CatalogEntry,
-    rule_source: &str,
-    entry_point: &str,
+    rule: &TopalRule,
     source: &SourceText,
     statements: &[Statement],
     context: &mut ApplicabilityContext,
@@ -1307,8 +1565,7 @@ fn visit_topal_task_state_machine(
             } => context.select(source, *version, features),
             Statement::Published { declaration, .. } => visit_topal_task_state_machine(
                 entry,
-                rule_source,
-                entry_point,
+                rule,
                 source,
                 std::slice::from_ref(declaration.as_ref()),
                 context,
@@ -1333,24 +1590,19 @@ fn visit_topal_task_state_machine(
                         Statement::Generator { body, .. } => contains_context_assignment(body),
                         _ => false,
                     });
-                    if !evaluate_topal_boolean_rule(
-                        rule_source,
-                        entry_point,
-                        has_state,
-                        has_transition,
-                    )? {
+                    if !evaluate_topal_boolean_rule(rule, has_state, has_transition)? {
                         findings.push(RuleFinding {
                             span: *name,
-                            message: "stateful task declares no explicit message transition",
-                            suggestion: "update task-owned state in the handler for each state-changing event",
+                            message: "stateful task declares no explicit message transition".into(),
+                            suggestion: "update task-owned state in the handler for each state-changing event"
+                                .into(),
                             rectification: None,
                         });
                     }
                 }
                 visit_topal_task_state_machine(
                     entry,
-                    rule_source,
-                    entry_point,
+                    rule,
                     source,
                     declarations,
                     &mut context.clone(),
@@ -1362,8 +1614,7 @@ fn visit_topal_task_state_machine(
             | Statement::Foreach { body, .. } => {
                 visit_topal_task_state_machine(
                     entry,
-                    rule_source,
-                    entry_point,
+                    rule,
                     source,
                     body,
                     &mut context.clone(),
@@ -1373,8 +1624,7 @@ fn visit_topal_task_state_machine(
             Statement::InterfaceImplementation { declarations, .. } => {
                 visit_topal_task_state_machine(
                     entry,
-                    rule_source,
-                    entry_point,
+                    rule,
                     source,
                     declarations,
                     &mut context.clone(),
@@ -1404,39 +1654,13 @@ fn contains_context_assignment(statements: &[Statement]) -> bool {
     })
 }
 
-fn evaluate_topal_boolean_rule(
-    rule_source: &str,
-    entry_point: &str,
-    left: bool,
-    right: bool,
-) -> Result<bool, String> {
-    evaluate_topal_rule_application(rule_source, entry_point, left, right)
-}
-
-fn topal_task_declaration_order(
-    entry: &CatalogEntry,
-    rule_source: &str,
-    entry_point: &str,
-    source: &SourceText,
-    statements: &[Statement],
-) -> Result<Vec<RuleFinding>, String> {
-    let mut findings = Vec::new();
-    visit_topal_task_order(
-        entry,
-        rule_source,
-        entry_point,
-        source,
-        statements,
-        &mut ApplicabilityContext::default(),
-        &mut findings,
-    )?;
-    Ok(findings)
+fn evaluate_topal_boolean_rule(rule: &TopalRule, left: bool, right: bool) -> Result<bool, String> {
+    rule.holds(LanguageValue::Boolean(left), LanguageValue::Boolean(right))
 }
 
 fn visit_topal_task_order(
     entry: &CatalogEntry,
-    rule_source: &str,
-    entry_point: &str,
+    rule: &TopalRule,
     source: &SourceText,
     statements: &[Statement],
     context: &mut ApplicabilityContext,
@@ -1449,8 +1673,7 @@ fn visit_topal_task_order(
             } => context.select(source, *version, features),
             Statement::Published { declaration, .. } => visit_topal_task_order(
                 entry,
-                rule_source,
-                entry_point,
+                rule,
                 source,
                 std::slice::from_ref(declaration.as_ref()),
                 context,
@@ -1461,18 +1684,11 @@ fn visit_topal_task_order(
                 if is_task_definition(source, declarations)
                     && entry_applies(entry, context.version.as_deref(), &features)?
                 {
-                    check_topal_task_order(
-                        rule_source,
-                        entry_point,
-                        source,
-                        declarations,
-                        findings,
-                    )?;
+                    check_topal_task_order(rule, source, declarations, findings)?;
                 }
                 visit_topal_task_order(
                     entry,
-                    rule_source,
-                    entry_point,
+                    rule,
                     source,
                     declarations,
                     &mut context.clone(),
@@ -1482,21 +1698,12 @@ fn visit_topal_task_order(
             Statement::Function { body, .. }
             | Statement::Generator { body, .. }
             | Statement::Foreach { body, .. } => {
-                visit_topal_task_order(
-                    entry,
-                    rule_source,
-                    entry_point,
-                    source,
-                    body,
-                    &mut context.clone(),
-                    findings,
-                )?;
+                visit_topal_task_order(entry, rule, source, body, &mut context.clone(), findings)?;
             }
             Statement::InterfaceImplementation { declarations, .. } => {
                 visit_topal_task_order(
                     entry,
-                    rule_source,
-                    entry_point,
+                    rule,
                     source,
                     declarations,
                     &mut context.clone(),
@@ -1510,56 +1717,119 @@ fn visit_topal_task_order(
 }
 
 fn check_topal_task_order(
-    rule_source: &str,
-    entry_point: &str,
+    rule: &TopalRule,
     source: &SourceText,
     declarations: &[Statement],
     findings: &mut Vec<RuleFinding>,
 ) -> Result<(), String> {
     let mut previous = None;
+    let mut seen = Vec::<(u8, &Statement)>::new();
     for declaration in declarations {
         let Some((phase, span, expected)) = declaration_phase(source, declaration) else {
             continue;
         };
         if let Some(previous_phase) = previous
-            && !evaluate_topal_phase_rule(rule_source, entry_point, previous_phase, phase)?
+            && !evaluate_topal_phase_rule(rule, previous_phase, phase)?
         {
             findings.push(RuleFinding {
                 span,
-                message: "task declaration is outside the recommended lifecycle section",
-                suggestion: expected,
-                rectification: None,
+                message: "task declaration is outside the recommended lifecycle section".into(),
+                suggestion: expected.into(),
+                rectification: seen
+                    .iter()
+                    .find(|(earlier, _)| *earlier > phase)
+                    .map(|(_, target)| move_declaration_before(source, target, declaration)),
             });
         }
         previous = Some(phase);
+        seen.push((phase, declaration));
     }
     Ok(())
 }
 
-fn evaluate_topal_phase_rule(
-    rule_source: &str,
-    entry_point: &str,
-    previous: u8,
-    current: u8,
-) -> Result<bool, String> {
-    evaluate_topal_rule_application(rule_source, entry_point, previous, current)
+/// Move the lines of `moved` to the line before `target`. Reordering may change which declaration a reader meets first, so
+/// the edit always requires review.
+fn move_declaration_before(
+    source: &SourceText,
+    target: &Statement,
+    moved: &Statement,
+) -> SourceEdit {
+    let text = source.as_str();
+    let start = declaration_lines(text, lint_statement_span(target)).start;
+    let moved = declaration_lines(text, lint_statement_span(moved));
+    let mut replacement = text[moved.clone()].to_owned();
+    if !replacement.ends_with('\n') {
+        replacement.push('\n');
+    }
+    replacement.push_str(&text[start..moved.start]);
+    if !text[..moved.end].ends_with('\n') {
+        replacement.pop();
+    }
+    SourceEdit {
+        span: Span::new(start, moved.end),
+        replacement,
+        safety: RectificationSafety::ReviewRequired,
+        diagnostic: None,
+    }
+}
+
+/// The whole lines of `span`.
+fn declaration_lines(text: &str, span: Span) -> std::ops::Range<usize> {
+    let start = text[..span.start]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1);
+    let end = text[span.end..]
+        .find('\n')
+        .map_or(text.len(), |newline| span.end + newline + 1);
+    start..end
+}
+
+fn evaluate_topal_phase_rule(rule: &TopalRule, previous: u8, current: u8) -> Result<bool, String> {
+    rule.holds(
+        LanguageValue::Int(previous.into()),
+        LanguageValue::Int(current.into()),
+    )
 }
 
-fn evaluate_topal_rule_application(
-    rule_source: &str,
-    entry_point: &str,
-    left: impl std::fmt::Display,
-    right: impl std::fmt::Display,
-) -> Result<bool, String> {
-    let program = format!("{}\n{left} {entry_point} {right}\n", rule_source.trim_end());
-    let value = Session::new()
-        .evaluate_source_file(&program, &mut std::io::sink())
-        .map_err(|diagnostic| format!("Topal lint rule failed: {diagnostic}"))?;
-    match value {
-        LanguageValue::Boolean(decision) => Ok(decision),
-        other => Err(format!(
-            "Topal lint rule `{entry_point}` returned {other}, expected Boolean"
-        )),
+/// A rule module loaded once into its own `lint`-variant session. Each
+/// application binds only the view values it is given, so the rule sees
+/// nothing of the inspected source beyond its declared view.
+struct TopalRule<'a> {
+    session: Session,
+    entry_point: &'a str,
+}
+
+impl<'a> TopalRule<'a> {
+    fn load(rule_source: &str, entry_point: &'a str) -> Result<Self, String> {
+        let mut session = Session::new();
+        session.set_resource_limits(RULE_LIMITS);
+        session
+            .evaluate_source_file(rule_source, &mut std::io::sink())
+            .map_err(|diagnostic| format!("Topal lint rule failed to load: {diagnostic}"))?;
+        Ok(Self {
+            session,
+            entry_point,
+        })
+    }
+
+    fn holds(&self, left: LanguageValue, right: LanguageValue) -> Result<bool, String> {
+        let mut session = self.session.clone();
+        session.set_resource_limits(RULE_LIMITS);
+        let bindings = BTreeMap::from([
+            ("view-left".to_owned(), left),
+            ("view-right".to_owned(), right),
+        ]);
+        let application = format!("view-left {} view-right", self.entry_point);
+        match session
+            .evaluate_in_context(&bindings, &application, &mut std::io::sink())
+            .map_err(|diagnostic| format!("Topal lint rule failed: {diagnostic}"))?
+        {
+            LanguageValue::Boolean(decision) => Ok(decision),
+            other => Err(format!(
+                "Topal lint rule `{}` returned {other}, expected Boolean",
+                self.entry_point
+            )),
+        }
     }
 }
 
@@ -1668,8 +1938,8 @@ fn parse_version(version: &str) -> Result<(u64, u64), String> {
 
 struct RuleFinding {
     span: Span,
-    message: &'static str,
-    suggestion: &'static str,
+    message: String,
+    suggestion: String,
     rectification: Option<SourceEdit>,
 }
 
@@ -1775,7 +2045,7 @@ mod tests {
             .filter(|path| path.extension().is_some_and(|extension| extension == "t"))
             .collect::<Vec<_>>();
         examples.sort();
-        assert_eq!(examples.len(), 192);
+        assert_eq!(examples.len(), 195);
         for example in examples {
             let source = std::fs::read_to_string(&example).unwrap();
             let report = lint_text(&source, &[]).unwrap();
@@ -1795,16 +2065,19 @@ mod tests {
                 span: Span::new(4, 5),
                 replacement: "B".into(),
                 safety: RectificationSafety::SyntaxPreserving,
+                diagnostic: None,
             },
             SourceEdit {
                 span: Span::new(0, 1),
                 replacement: "A".into(),
                 safety: RectificationSafety::SemanticsProven,
+                diagnostic: None,
             },
             SourceEdit {
                 span: Span::new(2, 3),
                 replacement: "ignored".into(),
                 safety: RectificationSafety::ReviewRequired,
+                diagnostic: None,
             },
         ];
         assert_eq!(apply_source_edits("01245", &edits).unwrap(), "A124B");
@@ -1816,6 +2089,7 @@ mod tests {
             span,
             replacement: String::new(),
             safety: RectificationSafety::PresentationOnly,
+            diagnostic: None,
         };
         assert!(
             apply_source_edits("abcd", &[edit(Span::new(0, 2)), edit(Span::new(1, 3))])
@@ -1911,11 +2185,11 @@ mod tests {
             .find(|entry| entry.identity.ends_with("declaration-order"))
             .unwrap();
         let rule = entry.lint_rule.unwrap();
-        let source = rule.source_text;
-        assert!(evaluate_topal_phase_rule(&source, &rule.entry_point, 0, 1).unwrap());
-        assert!(evaluate_topal_phase_rule(&source, &rule.entry_point, 2, 3).unwrap());
-        assert!(!evaluate_topal_phase_rule(&source, &rule.entry_point, 2, 1).unwrap());
-        assert!(!evaluate_topal_phase_rule(&source, &rule.entry_point, 3, 0).unwrap());
+        let rule = TopalRule::load(&rule.source_text, &rule.entry_point).unwrap();
+        assert!(evaluate_topal_phase_rule(&rule, 0, 1).unwrap());
+        assert!(evaluate_topal_phase_rule(&rule, 2, 3).unwrap());
+        assert!(!evaluate_topal_phase_rule(&rule, 2, 1).unwrap());
+        assert!(!evaluate_topal_phase_rule(&rule, 3, 0).unwrap());
     }
 
     #[test]
@@ -1926,17 +2200,10 @@ mod tests {
             .find(|entry| entry.identity.ends_with("state-machine"))
             .unwrap();
         let rule = entry.lint_rule.unwrap();
-        assert!(
-            evaluate_topal_boolean_rule(&rule.source_text, &rule.entry_point, false, false)
-                .unwrap()
-        );
-        assert!(
-            evaluate_topal_boolean_rule(&rule.source_text, &rule.entry_point, true, true).unwrap()
-        );
-        assert!(
-            !evaluate_topal_boolean_rule(&rule.source_text, &rule.entry_point, true, false)
-                .unwrap()
-        );
+        let rule = TopalRule::load(&rule.source_text, &rule.entry_point).unwrap();
+        assert!(evaluate_topal_boolean_rule(&rule, false, false).unwrap());
+        assert!(evaluate_topal_boolean_rule(&rule, true, true).unwrap());
+        assert!(!evaluate_topal_boolean_rule(&rule, true, false).unwrap());
     }
 
     #[test]
@@ -1950,12 +2217,146 @@ mod tests {
         let source = SourceText::new("use language ( version is v0.1 )\n").unwrap();
         let lexed = lex(&source);
         let parsed = parse(&source, &lexed);
-        let Err(error) = topal_rule(&entry, &source, &parsed.statements) else {
+        let Err(error) = topal_rule(&entry, &source, &lexed.tokens, &parsed.statements) else {
             panic!("unsupported view revision was accepted");
         };
         assert!(error.contains("unsupported read-only view"));
     }
 
+    fn external_rule(view: &str, rule_module: &str) -> CatalogEntry {
+        let mut entry = Catalog::builtin()
+            .entries
+            .into_iter()
+            .find(|entry| entry.identity.ends_with("state-machine"))
+            .unwrap();
+        entry.identity = "org.example best-practice names".into();
+        entry.required_features.clear();
+        let rule = entry.lint_rule.as_mut().unwrap();
+        rule.view = view.into();
+        rule.source_text = std::fs::read_to_string(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../../examples/linter")
+                .join(rule_module),
+        )
+        .unwrap();
+        entry
+    }
+
+    fn rule_findings(entry: &CatalogEntry, text: &str) -> Result<Vec<(String, String)>, String> {
+        let source = SourceText::new(text).unwrap();
+        let lexed = lex(&source);
+        let parsed = parse(&source, &lexed);
+        Ok(
+            topal_rule(entry, &source, &lexed.tokens, &parsed.statements)?
+                .into_iter()
+                .map(|finding| (source.slice(finding.span).to_owned(), finding.message))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn catalog_rules_use_generic_declaration_and_token_views() {
+        let text = "use language ( version is v0.1 )
+tmp is 1
+scale is fn (tmp : Int) -> Int
+  total is tmp
+  total
+";
+        let declarations = external_rule("declaration/1", "declaration-name-rule.t");
+        assert_eq!(
+            rule_findings(&declarations, text).unwrap(),
+            [(
+                "tmp".to_owned(),
+                "binding `tmp` does not follow this best-practice".to_owned()
+            )]
+        );
+
+        let mut tokens = declarations;
+        tokens.lint_rule.as_mut().unwrap().view = "token/1".into();
+        let findings = rule_findings(&tokens, text).unwrap();
+        assert_eq!(findings.len(), 3);
+        assert!(findings.iter().all(|(text, _)| text == "tmp"));
+        assert_eq!(
+            findings[0].1,
+            "token `tmp` does not follow this best-practice"
+        );
+    }
+
+    #[test]
+    fn token_view_supplies_stable_kind_names_to_rules_with_helpers() {
+        let mut entry = external_rule("token/1", "declaration-name-rule.t");
+        entry.lint_rule.as_mut().unwrap().source_text = "use language (
+  version is v0.1,
+  features is ( lint )
+)
+unbracketed? is fn static (kind : String) -> Boolean
+  kind != \"left-paren\"
+rule is fn static (kind : String, text : String) -> Boolean
+  unbracketed? kind
+"
+        .into();
+        let findings = rule_findings(
+            &entry,
+            "use language ( version is v0.1 )
+total is (1 + 2)
+",
+        )
+        .unwrap();
+        assert_eq!(
+            findings
+                .iter()
+                .map(|(text, _)| text.as_str())
+                .collect::<Vec<_>>(),
+            ["(", "("]
+        );
+        assert_eq!(token_view_kind(TokenKind::Identifier), "identifier");
+        assert_eq!(token_view_kind(TokenKind::NotEquals), "not-equals");
+    }
+
+    #[test]
+    fn rule_modules_load_under_the_rule_budget() {
+        let source = "use language (
+  version is v0.1
+)
+branch-count is fn (value : Int) -> Int
+  value
+    <= 0 then 1
+    otherwise (branch-count (value - 1)) + (branch-count (value - 2))
+warm is branch-count 12
+rule is fn (left : Int, right : Int) -> Boolean
+  left <= right
+";
+        let error = with_evaluation_stack(RULE_LIMITS, || {
+            TopalRule::load(source, "rule").err().unwrap()
+        });
+        assert!(error.contains("E-RESOURCE-FUEL"), "{error}");
+    }
+
+    #[test]
+    fn generic_views_respect_entry_applicability_and_rule_signatures() {
+        let mut entry = external_rule("declaration/1", "declaration-name-rule.t");
+        entry.required_features = vec!["task".into()];
+        assert!(
+            rule_findings(
+                &entry,
+                "use language ( version is v0.1 )
+tmp is 1
+"
+            )
+            .unwrap()
+            .is_empty()
+        );
+
+        let entry = external_rule("declaration/1", "task-declaration-order-rule.t");
+        let error = rule_findings(
+            &entry,
+            "use language ( version is v0.1 )
+",
+        )
+        .unwrap_err();
+        assert!(error.starts_with("L-RULE-SIGNATURE"), "{error}");
+    }
+
     #[test]
     fn in_memory_api_matches_shared_frontend_and_rule_diagnostics() {
         let syntax = lint_text("use language ( version is v0.1 )\nvalue is #\n", &[]).unwrap();
@@ -2008,6 +2409,35 @@ mod tests {
         assert!(report.diagnostics.is_empty(), "{:?}", report.diagnostics);
     }
 
+    #[test]
+    fn declaration_order_proposes_a_reviewed_move_and_a_suppression() {
+        let task = "use language (\n  version is v0.1\n)\nCounter is Task (queue-size is 2)\nservice is Counter\n  start is fn (initial : Nat) -> Completed\n    Completed\n  increment is fn (_ : MessageContext, amount : Nat) -> Unit\n    ()\n  count : Nat";
+        let report = lint_text(task, &["lang best-practice task declaration-order"]).unwrap();
+        assert_eq!(report.diagnostics.len(), 1);
+        let [edit] = report.rectifications.as_slice() else {
+            panic!("{:?}", report.rectifications);
+        };
+        assert_eq!(edit.diagnostic, Some(0));
+        assert!(!edit.is_eligible());
+        let mut moved = task.to_owned();
+        moved.replace_range(edit.span.start..edit.span.end, &edit.replacement);
+        assert!(moved.contains("service is Counter\n  count : Nat\n  start is"));
+        let report = lint_text(&moved, &["lang best-practice task declaration-order"]).unwrap();
+        assert!(report.diagnostics.is_empty(), "{:?}", report.diagnostics);
+
+        let start = task.find("count : Nat").unwrap();
+        let finding = Span::new(start, start + "count".len());
+        let suppression =
+            suppression_edit(task, "lang best-practice task declaration-order", finding).unwrap();
+        let suppressed = apply_source_edits(task, &[suppression]).unwrap();
+        assert!(suppressed.contains(
+            "  ()\n  lang disable-diagnostic ( lang best-practice task declaration-order )\n  count : Nat"
+        ));
+        let report =
+            lint_text(&suppressed, &["lang best-practice task declaration-order"]).unwrap();
+        assert!(report.diagnostics.is_empty(), "{:?}", report.diagnostics);
+    }
+
     #[test]
     fn in_memory_controls_share_selector_precedence_and_severity() {
         let source = "use language (\n  version is v0.1\n)\nCounter is Task (queue-size is 2)\nservice is Counter\n  count : Nat\n  start is fn (initial : Nat) -> Completed\n    @ count is initial\n    Completed\n  current is fn (_ : MessageContext, _ : Unit) -> Nat\n    @ count\n";
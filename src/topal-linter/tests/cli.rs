@@ -474,16 +474,18 @@ fn source_suppression_uses_identity_instead_of_configured_severity() {
 #[test]
 fn validates_explicit_lint_variant_rule_modules() {
     let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
-    let valid = Command::new(env!("CARGO_BIN_EXE_topal-lint"))
-        .arg("--check-rule")
-        .arg(root.join("examples/linter/task-declaration-order-rule.t"))
-        .output()
-        .unwrap();
-    assert!(
-        valid.status.success(),
-        "{}",
-        String::from_utf8_lossy(&valid.stderr)
-    );
+    for module in ["task-declaration-order-rule.t", "declaration-name-rule.t"] {
+        let valid = Command::new(env!("CARGO_BIN_EXE_topal-lint"))
+            .arg("--check-rule")
+            .arg(root.join("examples/linter").join(module))
+            .output()
+            .unwrap();
+        assert!(
+            valid.status.success(),
+            "{}",
+            String::from_utf8_lossy(&valid.stderr)
+        );
+    }
 
     let path = temporary_source(
         "missing-lint-variant",
@@ -0,0 +1,132 @@
+//! Code actions for best-practice findings: rectification quick fixes,
+//! structured suppressions, and a source action applying every safe
+//! rectification in the document.
+
+use serde_json::{Value, json};
+use topal_linter::{LintReport, SourceEdit, apply_source_edits, suppression_edit};
+use topal_source::{SourceText, Span};
+
+use crate::formatting::client_line_endings;
+use crate::navigation::{protocol_range, protocol_span};
+use crate::protocol_diagnostic;
+use crate::workspace::Document;
+
+const QUICK_FIX: &str = "quickfix";
+const FIX_ALL: &str = "source.fixAll";
+
+/// Answer `textDocument/codeAction` for the findings of `report` that the
+/// requested range touches, restricted to the kinds the client asked for.
+pub(super) fn code_actions(
+    uri: &str,
+    document: &Document,
+    report: &LintReport,
+    params: &Value,
+) -> Value {
+    let Ok(source) = SourceText::new(&document.text) else {
+        return json!([]);
+    };
+    let range = params
+        .get("range")
+        .map_or(Span::new(0, usize::MAX), |range| {
+            protocol_span(source.as_str(), range)
+        });
+    let only = params
+        .pointer("/context/only")
+        .and_then(Value::as_array)
+        .map(|kinds| kinds.iter().filter_map(Value::as_str).collect::<Vec<_>>());
+    let wanted = |kind: &str| {
+        only.as_ref().is_none_or(|only| {
+            only.iter().any(|requested| {
+                kind == *requested
+                    || kind
+                        .strip_prefix(requested)
+                        .is_some_and(|rest| rest.starts_with('.'))
+            })
+        })
+    };
+    let text = source.as_str();
+    let edit = |edits: &[&SourceEdit]| {
+        json!({
+            "changes": {
+                uri: edits
+                    .iter()
+                    .map(|edit| json!({
+                        "range": protocol_range(text, edit.span),
+                        "newText": client_line_endings(document, edit.replacement.clone())
+                    }))
+                    .collect::<Vec<_>>()
+            }
+        })
+    };
+
+    let mut actions = Vec::new();
+    if wanted(QUICK_FIX) {
+        for (index, diagnostic) in report.diagnostics.iter().enumerate() {
+            let (Some(best_practice), Some(span)) =
+                (&diagnostic.best_practice, diagnostic.source_span.as_deref())
+            else {
+                continue;
+            };
+            if span.end < range.start || range.end < span.start {
+                continue;
+            }
+            let published = protocol_diagnostic(text, diagnostic);
+            let suggestion = best_practice
+                .suggestion
+                .as_deref()
+                .unwrap_or("apply the rectification");
+            for rectification in report
+                .rectifications
+                .iter()
+                .filter(|edit| edit.diagnostic == Some(index))
+            {
+                let title = if rectification.is_eligible() {
+                    capitalized(suggestion)
+                } else {
+                    format!("{} (review required)", capitalized(suggestion))
+                };
+                actions.push(json!({
+                    "title": title,
+                    "kind": QUICK_FIX,
+                    "diagnostics": [published],
+                    "isPreferred": rectification.is_eligible(),
+                    "edit": edit(&[rectification])
+                }));
+            }
+            if let Some(suppression) = suppression_edit(text, &best_practice.identity, *span) {
+                actions.push(json!({
+                    "title": format!("Suppress `{}` for this statement", best_practice.identity),
+                    "kind": QUICK_FIX,
+                    "diagnostics": [published],
+                    "isPreferred": false,
+                    "edit": edit(&[&suppression])
+                }));
+            }
+        }
+    }
+    let safe = report
+        .rectifications
+        .iter()
+        .filter(|edit| edit.is_eligible())
+        .collect::<Vec<_>>();
+    // Offer the combined edit only when every safe rectification applies
+    // together.
+    if wanted(FIX_ALL)
+        && !safe.is_empty()
+        && apply_source_edits(text, &report.rectifications).is_ok()
+    {
+        actions.push(json!({
+            "title": "Fix all safe lint findings",
+            "kind": FIX_ALL,
+            "edit": edit(&safe)
+        }));
+    }
+    Value::Array(actions)
+}
+
+fn capitalized(text: &str) -> String {
+    let mut characters = text.chars();
+    characters.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(characters).collect()
+    })
+}
@@ -0,0 +1,52 @@
+//! Document and range formatting through the shared `topal-fmt` formatter.
+//!
+//! Edits keep the client's line endings: a document that uses CRLF receives
+//! CRLF text even though the formatter prints LF.
+
+use serde_json::{Value, json};
+use topal_fmt::{format_range as format_span, format_source};
+use topal_source::SourceText;
+
+use crate::navigation::{protocol_range, protocol_span};
+use crate::protocol_position;
+use crate::workspace::Document;
+
+/// Answer `textDocument/formatting` with one edit replacing a document that is
+/// not canonically formatted, or no edits when it already is.
+pub(super) fn format_document(document: &Document) -> Result<Value, String> {
+    let formatted = format_source(&document.text).map_err(|error| error.to_string())?;
+    let formatted = client_line_endings(document, formatted);
+    if formatted == document.text {
+        return Ok(json!([]));
+    }
+    Ok(json!([{
+        "range": {
+            "start": { "line": 0, "character": 0 },
+            "end": protocol_position(&document.text, document.text.len())
+        },
+        "newText": formatted
+    }]))
+}
+
+/// Answer `textDocument/rangeFormatting` by formatting the top-level
+/// statements the protocol range touches.
+pub(super) fn format_range(document: &Document, range: &Value) -> Result<Value, String> {
+    let source = SourceText::new(&document.text).map_err(|error| error.to_string())?;
+    let edit = format_span(&document.text, protocol_span(source.as_str(), range))
+        .map_err(|error| error.to_string())?;
+    if source.slice(edit.span) == edit.text {
+        return Ok(json!([]));
+    }
+    Ok(json!([{
+        "range": protocol_range(source.as_str(), edit.span),
+        "newText": client_line_endings(document, edit.text)
+    }]))
+}
+
+pub(super) fn client_line_endings(document: &Document, text: String) -> String {
+    if document.text.contains("\r\n") {
+        text.replace('\n', "\r\n")
+    } else {
+        text
+    }
+}
@@ -0,0 +1,309 @@
+//! Inlay hints: the parameter each operand of a declared function binds, and
+//! the inferred classifier of bindings declared without one.
+//!
+//! A classifier is inferred only where no evaluation is needed: literals,
+//! unlabeled products of inferable operands, names of classified or inferred
+//! bindings, and whole calls whose overloads share one concrete result.
+
+use std::collections::BTreeMap;
+
+use serde_json::{Value, json};
+use topal_source::{SourceText, Span};
+use topal_syntax::{DecisionMatcher, Expression, Statement};
+
+use crate::index::Index;
+use crate::protocol_position;
+use crate::signatures::{Call, applications, call};
+use crate::workspace::Workspace;
+
+const TYPE: u8 = 1;
+const PARAMETER: u8 = 2;
+
+/// Classifiers of the names visible at one point; `None` marks a name whose
+/// classifier is unknown, shadowing any outer binding.
+type Scope = BTreeMap<String, Option<String>>;
+
+/// Answer `textDocument/inlayHint` for the hints that start within `range`.
+pub(super) fn inlay_hints(uri: &str, range: Span, workspace: &Workspace) -> Value {
+    let index = Index::build(uri, workspace);
+    let Some(file) = index.file_index(uri) else {
+        return json!([]);
+    };
+    let analysis = &index.file(file).analysis;
+    let text = analysis.source.as_str();
+    let within = |offset: usize| range.start <= offset && offset <= range.end;
+    let mut hints = Vec::new();
+    for (_, items) in applications(&analysis.statements) {
+        let Some(call) = call(&index, file, items) else {
+            continue;
+        };
+        for (parameter, argument) in &call.arguments {
+            let start = argument.span().start;
+            let Some(name) = parameter_name(&call, *parameter) else {
+                continue;
+            };
+            if !within(start)
+                || matches!(argument, Expression::Identifier(span)
+                    if analysis.source.slice(*span) == name)
+            {
+                continue;
+            }
+            hints.push((
+                start,
+                json!({
+                    "position": protocol_position(text, start),
+                    "label": format!("{name}:"),
+                    "kind": PARAMETER,
+                    "paddingRight": true
+                }),
+            ));
+        }
+    }
+    let mut inference = Inference {
+        index: &index,
+        file,
+        source: &analysis.source,
+        hints: Vec::new(),
+    };
+    inference.statements(&analysis.statements, &mut Scope::new());
+    for (offset, classifier) in inference.hints {
+        if within(offset) {
+            hints.push((
+                offset,
+                json!({
+                    "position": protocol_position(text, offset),
+                    "label": format!(": {classifier}"),
+                    "kind": TYPE,
+                    "paddingLeft": true
+                }),
+            ));
+        }
+    }
+    hints.sort_by_key(|(offset, _)| *offset);
+    Value::Array(hints.into_iter().map(|(_, hint)| hint).collect())
+}
+
+/// The name every overload gives parameter `position`, unless it is a
+/// packaged operand whose fields are labeled at the call.
+fn parameter_name<'a>(call: &Call<'a>, position: usize) -> Option<&'a str> {
+    let mut parameters = call
+        .declarations
+        .iter()
+        .filter_map(|declaration| declaration.parameters.get(position));
+    let first = parameters.next()?;
+    (!first.syntax.starts_with('(') && parameters.all(|other| other.name == first.name))
+        .then_some(first.name.as_str())
+}
+
+/// The result classifier every overload of `call` declares, unless it
+/// mentions a type variable that the arguments bind.
+fn concrete_result(call: &Call<'_>) -> Option<String> {
+    let mut results = call
+        .declarations
+        .iter()
+        .map(|declaration| declaration.result.as_deref());
+    let result = results.next()??;
+    if !results.all(|other| other == Some(result)) {
+        return None;
+    }
+    let generic = call
+        .declarations
+        .iter()
+        .flat_map(|declaration| &declaration.parameters)
+        .filter_map(|parameter| parameter.syntax.split_once(':'))
+        .map(|(_, classifier)| classifier)
+        .filter(|classifier| classifier.contains(':') || classifier.contains("fn"))
+        .flat_map(words)
+        .collect::<Vec<_>>();
+    (!words(result).any(|word| generic.contains(&word))).then(|| result.to_owned())
+}
+
+fn words(classifier: &str) -> impl Iterator<Item = &str> {
+    classifier
+        .split(|character: char| !character.is_alphanumeric() && character != '-')
+        .filter(|word| word.starts_with(char::is_uppercase))
+}
+
+struct Inference<'a> {
+    index: &'a Index,
+    file: usize,
+    source: &'a SourceText,
+    /// Offsets after binding names with the classifier inferred for each.
+    hints: Vec<(usize, String)>,
+}
+
+impl Inference<'_> {
+    fn name(&self, span: Span) -> String {
+        self.source.slice(span).to_owned()
+    }
+
+    fn statements(&mut self, statements: &[Statement], scope: &mut Scope) {
+        for statement in statements {
+            self.statement(statement, scope);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement, scope: &mut Scope) {
+        match statement {
+            Statement::Published { declaration, .. } => self.statement(declaration, scope),
+            Statement::Binding {
+                name,
+                classifier,
+                value,
+            } => {
+                self.expression(value, scope);
+                let classifier = if let Some(classifier) = classifier {
+                    Some(self.name(*classifier))
+                } else {
+                    let inferred = self.infer(value, scope);
+                    if let Some(inferred) = &inferred {
+                        self.hints.push((name.end, inferred.clone()));
+                    }
+                    inferred
+                };
+                scope.insert(self.name(*name), classifier);
+            }
+            Statement::Function {
+                name,
+                parameters,
+                body,
+                ..
+            }
+            | Statement::Generator {
+                name,
+                parameters,
+                body,
+                ..
+            } => {
+                scope.insert(self.name(*name), None);
+                let mut local = scope.clone();
+                for parameter in parameters {
+                    let classifier = parameter
+                        .fields
+                        .is_empty()
+                        .then(|| self.name(parameter.classifier));
+                    local.insert(self.name(parameter.name), classifier);
+                    for field in &parameter.fields {
+                        local.insert(self.name(field.name), Some(self.name(field.classifier)));
+                    }
+                }
+                self.statements(body, &mut local);
+            }
+            Statement::Implementation {
+                name, declarations, ..
+            } => {
+                scope.insert(self.name(*name), None);
+                self.statements(declarations, &mut scope.clone());
+            }
+            Statement::InterfaceImplementation { declarations, .. } => {
+                self.statements(declarations, &mut scope.clone());
+            }
+            Statement::Foreach {
+                result,
+                source,
+                binding,
+                body,
+                ..
+            } => {
+                self.expression(source, scope);
+                let mut local = scope.clone();
+                local.insert(self.name(*binding), None);
+                self.statements(body, &mut local);
+                if let Some((name, _)) = result {
+                    scope.insert(self.name(*name), None);
+                }
+            }
+            Statement::Union { name, .. }
+            | Statement::Interface { name, .. }
+            | Statement::StateField { name, .. } => {
+                scope.insert(self.name(*name), None);
+            }
+            Statement::ContextAssignment { value, .. }
+            | Statement::Discard { value, .. }
+            | Statement::Return { value, .. }
+            | Statement::Expression(value) => self.expression(value, scope),
+            Statement::LanguageSelection { .. } | Statement::DiagnosticControl { .. } => {}
+        }
+    }
+
+    /// Visit the bindings nested in `expression`.
+    fn expression(&mut self, expression: &Expression, scope: &Scope) {
+        match expression {
+            Expression::Block { statements, .. } => {
+                self.statements(statements, &mut scope.clone());
+            }
+            Expression::Product { fields, .. } => {
+                for field in fields {
+                    self.expression(&field.value, scope);
+                }
+            }
+            Expression::DecisionTable { subject, rules, .. } => {
+                self.expression(subject, scope);
+                for rule in rules {
+                    let mut local = scope.clone();
+                    match &rule.matcher {
+                        DecisionMatcher::Union { binding, .. }
+                        | DecisionMatcher::Variant { binding, .. }
+                        | DecisionMatcher::Result { binding, .. }
+                        | DecisionMatcher::Optional {
+                            binding: Some(binding),
+                            ..
+                        } => {
+                            local.insert(self.name(*binding), None);
+                        }
+                        DecisionMatcher::ListEntry { first, rest, .. } => {
+                            local.insert(self.name(*first), None);
+                            local.insert(self.name(*rest), None);
+                        }
+                        _ => {}
+                    }
+                    self.expression(&rule.action, &local);
+                }
+            }
+            Expression::AnonymousFunction {
+                parameters, body, ..
+            } => {
+                let mut local = scope.clone();
+                for parameter in parameters {
+                    local.insert(self.name(*parameter), None);
+                }
+                self.expression(body, &local);
+            }
+            Expression::Application { items, .. } => {
+                for item in items {
+                    self.expression(item, scope);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn infer(&self, expression: &Expression, scope: &Scope) -> Option<String> {
+        match expression {
+            Expression::Integer(_) => Some("Int".into()),
+            Expression::Rational(_) => Some("Rational".into()),
+            Expression::String(_) => Some("String".into()),
+            Expression::Boolean(_) => Some("Boolean".into()),
+            Expression::Unit(_) => Some("Unit".into()),
+            Expression::Identifier(span) => scope.get(self.source.slice(*span)).cloned()?,
+            Expression::Product { fields, .. }
+                if fields.iter().all(|field| field.label.is_none()) =>
+            {
+                let classifiers = fields
+                    .iter()
+                    .map(|field| self.infer(&field.value, scope))
+                    .collect::<Option<Vec<_>>>()?;
+                match classifiers.as_slice() {
+                    [] => None,
+                    [classifier] => Some(classifier.clone()),
+                    classifiers => Some(format!("({})", classifiers.join(", "))),
+                }
+            }
+            Expression::Application { items, .. } => call(self.index, self.file, items)
+                .filter(|call| call.whole)
+                .as_ref()
+                .and_then(concrete_result),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,667 @@
+//! Workspace symbol index shared by navigation, references, and rename.
+//!
+//! The index covers the package tree that `load_module_tree` would load for a
+//! document, together with the package's entry files. Open editor content is
+//! authoritative over the filesystem. Every file is analyzed with the shared
+//! lexer and recoverable parser; nothing is executed.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use topal_language::module_sources;
+use topal_source::{SourceText, Span, case_fold};
+use topal_syntax::{
+    DecisionMatcher, DocumentedDeclaration, Expression, FunctionParameter, Statement, Token,
+    TokenKind, extract_documentation, lex, parse,
+};
+
+use crate::is_keyword;
+use crate::workspace::Workspace;
+
+/// Files that make a directory the root of a loadable package tree.
+const PACKAGE_ENTRIES: [&str; 3] = ["application.t", "package.t", "library.t"];
+
+pub(super) struct Index {
+    files: Vec<IndexedFile>,
+}
+
+pub(super) struct IndexedFile {
+    pub(super) uri: String,
+    /// Module path under which published declarations are visible, or `None`
+    /// for package entry files and documents outside any package.
+    pub(super) module: Option<Vec<String>>,
+    pub(super) analysis: Arc<Analysis>,
+}
+
+/// Lexed and parsed facts about one source text, independent of where the
+/// text is located.
+pub(super) struct Analysis {
+    pub(super) source: SourceText,
+    tokens: Vec<Token>,
+    pub(super) declarations: Vec<DocumentedDeclaration>,
+    pub(super) statements: Vec<Statement>,
+    binders: Vec<Binder>,
+    labels: Vec<Span>,
+}
+
+/// Analyses of unopened files, keyed by canonical path and revalidated
+/// against the file's modification time before use.
+#[derive(Default)]
+pub(super) struct DiskCache {
+    entries: Mutex<BTreeMap<PathBuf, (SystemTime, Arc<Analysis>)>>,
+}
+
+/// A local name that shadows top-level declarations within `scope`.
+struct Binder {
+    name: String,
+    span: Span,
+    scope: Span,
+}
+
+/// Every top-level declaration sharing one name in one indexed file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct Symbol {
+    pub(super) file: usize,
+    pub(super) name: String,
+}
+
+/// The declarations, modules, or built-in a referenced name denotes.
+#[derive(Default)]
+pub(super) struct Resolution {
+    pub(super) symbols: Vec<Symbol>,
+    pub(super) modules: Vec<usize>,
+    pub(super) builtin: Option<String>,
+}
+
+pub(super) struct Occurrence {
+    pub(super) file: usize,
+    pub(super) span: Span,
+    pub(super) declaration: bool,
+}
+
+impl Index {
+    /// Index the package containing `uri`, or only `uri` when it belongs to no
+    /// package.
+    pub(super) fn build(uri: &str, workspace: &Workspace) -> Self {
+        let open = workspace
+            .documents()
+            .filter_map(|(uri, text)| {
+                let path = uri_path(uri)?;
+                Some((fs::canonicalize(&path).unwrap_or(path), (uri, text)))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let mut index = Self { files: Vec::new() };
+        let mut add =
+            |uri: String, module: Option<Vec<String>>, analysis: Option<Arc<Analysis>>| {
+                if let Some(analysis) = analysis {
+                    index.files.push(IndexedFile {
+                        uri,
+                        module,
+                        analysis,
+                    });
+                }
+            };
+        if let Some(root) = uri_path(uri).as_deref().and_then(package_root) {
+            let entries = PACKAGE_ENTRIES
+                .iter()
+                .map(|entry| (root.join(entry), None))
+                .filter(|(path, _)| path.is_file());
+            let modules = module_sources(&root)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|source| (source.path, Some(source.module)));
+            for (path, module) in entries.chain(modules) {
+                if let Some((uri, text)) = open.get(&path) {
+                    add((*uri).to_owned(), module, Analysis::new(text).map(Arc::new));
+                } else {
+                    add(path_uri(&path), module, workspace.cache.analysis(&path));
+                }
+            }
+        }
+        if index.file_index(uri).is_none()
+            && let Some(document) = workspace.get(uri)
+        {
+            index.files.push(IndexedFile {
+                uri: uri.to_owned(),
+                module: None,
+                analysis: match Analysis::new(&document.text) {
+                    Some(analysis) => Arc::new(analysis),
+                    None => return index,
+                },
+            });
+        }
+        index
+    }
+
+    pub(super) fn file_index(&self, uri: &str) -> Option<usize> {
+        self.files.iter().position(|file| file.uri == uri)
+    }
+
+    pub(super) fn file(&self, index: usize) -> &IndexedFile {
+        &self.files[index]
+    }
+
+    /// Resolve the name at `offset`, preferring the longest qualified reading
+    /// so `std min` names the library function even when the document
+    /// declares its own `min`.
+    pub(super) fn resolve(&self, file: usize, offset: usize) -> Option<(Span, Resolution)> {
+        let (reference, path) = self.files[file].analysis.qualified_name_at(offset)?;
+        for first in 0..path.len() {
+            let candidate = &path[first..];
+            let mut resolution = Resolution::default();
+            match candidate {
+                [name]
+                    if !self.files[file].analysis.shadowed(name, reference)
+                        && self.files[file].analysis.declares(name) =>
+                {
+                    resolution.symbols.push(Symbol {
+                        file,
+                        name: name.clone(),
+                    });
+                }
+                [lang, name] if lang == "lang" => resolution.builtin = Some(name.clone()),
+                _ => {}
+            }
+            if resolution.symbols.is_empty() && resolution.builtin.is_none() {
+                self.resolve_qualified(candidate, &mut resolution);
+            }
+            if !resolution.symbols.is_empty()
+                || !resolution.modules.is_empty()
+                || resolution.builtin.is_some()
+            {
+                return Some((reference, resolution));
+            }
+        }
+        None
+    }
+
+    fn resolve_qualified(&self, path: &[String], resolution: &mut Resolution) {
+        let (member, module) = path.split_last().expect("qualified names are never empty");
+        for (index, file) in self.files.iter().enumerate() {
+            if file.module.as_deref() == Some(path) {
+                resolution.modules.push(index);
+            } else if file.module.as_deref() == Some(module)
+                && file
+                    .analysis
+                    .declarations
+                    .iter()
+                    .any(|declaration| declaration.published && declaration.name == *member)
+            {
+                resolution.symbols.push(Symbol {
+                    file: index,
+                    name: member.clone(),
+                });
+            }
+        }
+    }
+
+    /// Declarations denoted by `symbol`; only published ones are visible
+    /// through a module path.
+    pub(super) fn declarations<'a>(
+        &'a self,
+        symbol: &'a Symbol,
+        qualified: bool,
+    ) -> impl Iterator<Item = &'a DocumentedDeclaration> + 'a {
+        self.files[symbol.file]
+            .analysis
+            .declarations
+            .iter()
+            .filter(move |declaration| {
+                declaration.name == symbol.name && (declaration.published || !qualified)
+            })
+    }
+
+    /// Every use of `symbol`: unshadowed names in its own file and qualified
+    /// names that end with its module path elsewhere in the package.
+    pub(super) fn occurrences(&self, symbol: &Symbol) -> Vec<Occurrence> {
+        let declaring = &self.files[symbol.file];
+        let mut occurrences = Vec::new();
+        for token in declaring.analysis.identifiers(&symbol.name) {
+            let declaration = declaring
+                .analysis
+                .declarations
+                .iter()
+                .any(|declaration| declaration.name_span == Some(token.span));
+            if !declaration
+                && (declaring.analysis.labels.contains(&token.span)
+                    || declaring.analysis.shadowed(&symbol.name, token.span)
+                    || self.is_qualified_elsewhere(declaring, token.span))
+            {
+                continue;
+            }
+            occurrences.push(Occurrence {
+                file: symbol.file,
+                span: token.span,
+                declaration,
+            });
+        }
+        let Some(module) = &declaring.module else {
+            return occurrences;
+        };
+        let mut qualified = module.clone();
+        qualified.push(symbol.name.clone());
+        for (index, file) in self.files.iter().enumerate() {
+            if index == symbol.file {
+                continue;
+            }
+            for token in file.analysis.identifiers(&symbol.name) {
+                if file
+                    .analysis
+                    .qualified_name_at(token.span.start)
+                    .is_some_and(|(_, path)| path.ends_with(&qualified))
+                {
+                    occurrences.push(Occurrence {
+                        file: index,
+                        span: token.span,
+                        declaration: false,
+                    });
+                }
+            }
+        }
+        occurrences
+    }
+
+    fn is_qualified_elsewhere(&self, file: &IndexedFile, span: Span) -> bool {
+        let Some((_, path)) = file.analysis.qualified_name_at(span.start) else {
+            return false;
+        };
+        let prefix = &path[..path.len() - 1];
+        (0..prefix.len()).any(|first| {
+            self.files
+                .iter()
+                .any(|candidate| candidate.module.as_deref() == Some(&prefix[first..]))
+        })
+    }
+
+    /// Explain why renaming `symbol` to `name` would change what some name
+    /// denotes, comparing spellings by NFC and default case folding.
+    pub(super) fn rename_collision(
+        &self,
+        symbol: &Symbol,
+        name: &str,
+        occurrences: &[Occurrence],
+    ) -> Option<String> {
+        let folded = case_fold(name);
+        let same = |other: &str| other != symbol.name && case_fold(other) == folded;
+        let declaring = &self.files[symbol.file];
+        if let Some(existing) = declaring
+            .analysis
+            .declarations
+            .iter()
+            .find(|declaration| same(&declaration.name))
+        {
+            return Some(format!(
+                "`{name}` collides with the declaration `{}` in the same scope",
+                existing.name
+            ));
+        }
+        if let Some(module) = &declaring.module {
+            let mut child = module.clone();
+            child.push(name.to_owned());
+            if self.files.iter().any(|file| {
+                file.module.as_ref().is_some_and(|candidate| {
+                    candidate.len() == child.len()
+                        && candidate
+                            .iter()
+                            .zip(&child)
+                            .all(|(left, right)| case_fold(left) == case_fold(right))
+                })
+            }) {
+                return Some(format!(
+                    "`{name}` collides with the module `{}`",
+                    child.join(" ")
+                ));
+            }
+        }
+        for occurrence in occurrences {
+            let file = &self.files[occurrence.file];
+            if let Some(binder) = file.analysis.binders.iter().find(|binder| {
+                same(&binder.name)
+                    && binder.scope.start <= occurrence.span.start
+                    && occurrence.span.end <= binder.scope.end
+            }) {
+                return Some(format!(
+                    "`{name}` would be captured by the local name `{}`",
+                    binder.name
+                ));
+            }
+        }
+        None
+    }
+}
+
+impl Analysis {
+    fn new(text: &str) -> Option<Self> {
+        let source = SourceText::new(text).ok()?;
+        let lexed = lex(&source);
+        let parsed = parse(&source, &lexed);
+        let declarations = extract_documentation(&source, &lexed, &parsed);
+        let mut scopes = Scopes::default();
+        for statement in &parsed.statements {
+            scopes.top_level(&source, statement);
+        }
+        Some(Self {
+            tokens: lexed.tokens,
+            declarations,
+            statements: parsed.statements,
+            binders: scopes.binders,
+            labels: scopes.labels,
+            source,
+        })
+    }
+
+    fn identifiers<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Token> + 'a {
+        self.tokens.iter().filter(move |token| {
+            token.kind == TokenKind::Identifier && self.source.slice(token.span) == name
+        })
+    }
+
+    fn declares(&self, name: &str) -> bool {
+        self.declarations
+            .iter()
+            .any(|declaration| declaration.name == name)
+    }
+
+    fn shadowed(&self, name: &str, span: Span) -> bool {
+        self.binders.iter().any(|binder| {
+            binder.name == name
+                && (binder.span == span
+                    || binder.scope.start <= span.start && span.end <= binder.scope.end)
+        })
+    }
+
+    /// Return the identifier at `offset` and the run of adjacent identifiers
+    /// that precede it in the same flat application.
+    pub(super) fn qualified_name_at(&self, offset: usize) -> Option<(Span, Vec<String>)> {
+        let tokens = &self.tokens;
+        let index = tokens.iter().position(|token| {
+            token.kind == TokenKind::Identifier
+                && token.span.start <= offset
+                && offset <= token.span.end
+        })?;
+        let name = self.source.slice(tokens[index].span);
+        if is_keyword(name) {
+            return None;
+        }
+        let mut path = vec![name.to_owned()];
+        let mut cursor = index;
+        while cursor >= 2
+            && tokens[cursor - 1].kind == TokenKind::Whitespace
+            && tokens[cursor - 2].kind == TokenKind::Identifier
+            && !is_keyword(self.source.slice(tokens[cursor - 2].span))
+        {
+            cursor -= 2;
+            path.insert(0, self.source.slice(tokens[cursor].span).to_owned());
+        }
+        Some((tokens[index].span, path))
+    }
+}
+
+impl DiskCache {
+    /// Return the analysis of `path`, re-reading it when it changed on disk.
+    pub(super) fn analysis(&self, path: &Path) -> Option<Arc<Analysis>> {
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()?;
+        let mut entries = self.entries.lock().expect("index cache is not poisoned");
+        if let Some((cached, analysis)) = entries.get(path)
+            && *cached == modified
+        {
+            return Some(Arc::clone(analysis));
+        }
+        let analysis = Arc::new(Analysis::new(&fs::read_to_string(path).ok()?)?);
+        entries.insert(path.to_path_buf(), (modified, Arc::clone(&analysis)));
+        Some(analysis)
+    }
+
+    /// Analyze every Topal source below `folder`, skipping hidden and build
+    /// output directories.
+    pub(super) fn index_folder(&self, folder: &Path) {
+        let Ok(entries) = fs::read_dir(folder) else {
+            return;
+        };
+        let mut paths = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect::<Vec<_>>();
+        paths.sort();
+        for path in paths {
+            let hidden = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.') || name == "target");
+            if path.is_dir() && !hidden {
+                self.index_folder(&path);
+            } else if path.extension().is_some_and(|extension| extension == "t") {
+                let path = fs::canonicalize(&path).unwrap_or(path);
+                let _ = self.analysis(&path);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub(super) fn contains(&self, path: &Path) -> bool {
+        self.entries
+            .lock()
+            .expect("index cache is not poisoned")
+            .contains_key(path)
+    }
+}
+
+/// Local binders and label positions collected from one parsed file.
+#[derive(Default)]
+struct Scopes {
+    binders: Vec<Binder>,
+    labels: Vec<Span>,
+}
+
+impl Scopes {
+    fn top_level(&mut self, source: &SourceText, statement: &Statement) {
+        match statement {
+            Statement::Published { declaration, .. } => self.top_level(source, declaration),
+            Statement::Binding { value, .. } => self.expression(source, value, None),
+            _ => self.statement(source, statement, None),
+        }
+    }
+
+    fn bind(&mut self, source: &SourceText, span: Span, scope: Span) {
+        self.binders.push(Binder {
+            name: source.slice(span).to_owned(),
+            span,
+            scope,
+        });
+    }
+
+    fn parameters(&mut self, source: &SourceText, parameters: &[FunctionParameter], scope: Span) {
+        for parameter in parameters {
+            self.bind(source, parameter.name, scope);
+            self.parameters(source, &parameter.fields, scope);
+            if let Some(default) = &parameter.default {
+                self.expression(source, default, Some(scope));
+            }
+        }
+    }
+
+    fn statements(&mut self, source: &SourceText, statements: &[Statement], scope: Option<Span>) {
+        for statement in statements {
+            self.statement(source, statement, scope);
+        }
+    }
+
+    fn statement(&mut self, source: &SourceText, statement: &Statement, scope: Option<Span>) {
+        match statement {
+            Statement::Published { declaration, .. } => {
+                self.statement(source, declaration, scope);
+            }
+            Statement::Binding { name, value, .. } => {
+                if let Some(scope) = scope {
+                    self.bind(source, *name, scope);
+                }
+                self.expression(source, value, scope);
+            }
+            Statement::Function {
+                parameters,
+                body,
+                span,
+                ..
+            }
+            | Statement::Generator {
+                parameters,
+                body,
+                span,
+                ..
+            } => {
+                self.parameters(source, parameters, *span);
+                self.statements(source, body, Some(*span));
+            }
+            Statement::Implementation { declarations, .. }
+            | Statement::InterfaceImplementation { declarations, .. } => {
+                self.statements(source, declarations, scope);
+            }
+            Statement::Foreach {
+                source: iterated,
+                binding,
+                body,
+                span,
+                ..
+            } => {
+                self.expression(source, iterated, scope);
+                self.bind(source, *binding, *span);
+                self.statements(source, body, Some(*span));
+            }
+            Statement::ContextAssignment { value, .. }
+            | Statement::Discard { value, .. }
+            | Statement::Return { value, .. } => self.expression(source, value, scope),
+            Statement::Expression(expression) => self.expression(source, expression, scope),
+            Statement::LanguageSelection { .. }
+            | Statement::DiagnosticControl { .. }
+            | Statement::StateField { .. }
+            | Statement::Union { .. }
+            | Statement::Interface { .. } => {}
+        }
+    }
+
+    fn expression(&mut self, source: &SourceText, expression: &Expression, scope: Option<Span>) {
+        match expression {
+            Expression::Block { statements, span } => {
+                self.statements(source, statements, Some(scope.unwrap_or(*span)));
+            }
+            Expression::Product { fields, .. } => {
+                for field in fields {
+                    if let Some(label) = field.label {
+                        self.labels.push(label);
+                    }
+                    self.expression(source, &field.value, scope);
+                }
+            }
+            Expression::DecisionTable { subject, rules, .. } => {
+                self.expression(source, subject, scope);
+                for rule in rules {
+                    match &rule.matcher {
+                        DecisionMatcher::Union { binding, .. }
+                        | DecisionMatcher::Variant { binding, .. }
+                        | DecisionMatcher::Result { binding, .. }
+                        | DecisionMatcher::Optional {
+                            binding: Some(binding),
+                            ..
+                        } => self.bind(source, *binding, rule.span),
+                        DecisionMatcher::ListEntry { first, rest, .. } => {
+                            self.bind(source, *first, rule.span);
+                            self.bind(source, *rest, rule.span);
+                        }
+                        DecisionMatcher::Comparison { operand, .. } => {
+                            self.expression(source, operand, scope);
+                        }
+                        _ => {}
+                    }
+                    self.expression(source, &rule.action, scope);
+                }
+            }
+            Expression::AnonymousFunction {
+                parameters,
+                body,
+                span,
+            } => {
+                for parameter in parameters {
+                    self.bind(source, *parameter, *span);
+                }
+                self.expression(source, body, Some(*span));
+            }
+            Expression::Application { items, .. } => {
+                for item in items {
+                    self.expression(source, item, scope);
+                }
+            }
+            Expression::Unit(_)
+            | Expression::Boolean(_)
+            | Expression::Integer(_)
+            | Expression::Measured { .. }
+            | Expression::Rational(_)
+            | Expression::String(_)
+            | Expression::Identifier(_)
+            | Expression::ContextIdentifier(_)
+            | Expression::Discard(_)
+            | Expression::Callable { .. } => {}
+        }
+    }
+}
+
+/// The top-level module namespaces of the package containing `uri`, which
+/// the package loader binds before the document runs.
+pub(super) fn package_namespaces(uri: &str) -> BTreeSet<String> {
+    uri_path(uri)
+        .as_deref()
+        .and_then(package_root)
+        .and_then(|root| module_sources(&root).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|source| source.module.into_iter().next())
+        .collect()
+}
+
+/// Find the nearest enclosing directory whose module tree includes `path`.
+fn package_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|directory| {
+            PACKAGE_ENTRIES
+                .iter()
+                .any(|entry| directory.join(entry).is_file())
+        })
+        .map(|directory| fs::canonicalize(directory).unwrap_or_else(|_| directory.to_path_buf()))
+}
+
+pub(super) fn uri_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut remaining = encoded.as_bytes();
+    while let Some((&byte, rest)) = remaining.split_first() {
+        if byte == b'%'
+            && let Some(decoded) = rest
+                .get(..2)
+                .and_then(|digits| std::str::from_utf8(digits).ok())
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+        {
+            bytes.push(decoded);
+            remaining = &rest[2..];
+        } else {
+            bytes.push(byte);
+            remaining = rest;
+        }
+    }
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+pub(super) fn path_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            uri.push(char::from(byte));
+        } else {
+            let _ = write!(uri, "%{byte:02X}");
+        }
+    }
+    uri
+}
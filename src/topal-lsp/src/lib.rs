@@ -1,16 +1,29 @@
 //! Minimal editor-facing Topal language server state and protocol handling.
 
-use std::collections::BTreeMap;
+mod actions;
+mod formatting;
+mod hints;
+mod index;
+mod navigation;
+mod outline;
+mod references;
+mod signatures;
+mod workspace;
+
+use std::collections::BTreeSet;
 use std::fs;
 
+use navigation::protocol_span;
 use serde_json::{Value, json};
-use topal_linter::{LintControl, LintEngine};
-use topal_source::{Diagnostic, Severity, SourceText};
-use topal_syntax::{Statement, TokenKind, lex, parse};
+use topal_language::static_diagnostics;
+use topal_linter::{LintControl, LintEngine, LintReport};
+use topal_source::{Diagnostic, Severity, SourceText, Span};
+use topal_syntax::{Lexed, Statement, Token, TokenKind, lex, parse};
+use workspace::{Document, Workspace};
 
 #[derive(Default)]
 pub struct Server {
-    documents: BTreeMap<String, String>,
+    workspace: Workspace,
     lint_engine: LintEngine,
     lint_controls: Vec<LintControl>,
     shutdown: bool,
@@ -39,6 +52,48 @@ impl Server {
             Some("textDocument/semanticTokens/full") => {
                 vec![response(id, &self.semantic_tokens(message))]
             }
+            Some("textDocument/hover") => {
+                vec![response(id, &self.navigate(message, navigation::hover))]
+            }
+            Some("textDocument/definition") => {
+                vec![response(
+                    id,
+                    &self.navigate(message, navigation::definition),
+                )]
+            }
+            Some("textDocument/signatureHelp") => {
+                vec![response(
+                    id,
+                    &self.navigate(message, signatures::signature_help),
+                )]
+            }
+            Some("textDocument/inlayHint") => vec![response(id, &self.inlay_hints(message))],
+            Some("textDocument/references") => vec![response(id, &self.references(message))],
+            Some("textDocument/prepareRename") => {
+                vec![response(
+                    id,
+                    &self.navigate(message, references::prepare_rename),
+                )]
+            }
+            Some("textDocument/rename") => vec![self.rename(id, message)],
+            Some("textDocument/codeAction") => vec![response(id, &self.code_actions(message))],
+            Some("textDocument/formatting") => vec![self.format(id, message, false)],
+            Some("textDocument/rangeFormatting") => vec![self.format(id, message, true)],
+            Some("textDocument/documentSymbol") => {
+                vec![response(
+                    id,
+                    &self.outline(message, outline::document_symbols),
+                )]
+            }
+            Some("textDocument/foldingRange") => {
+                vec![response(
+                    id,
+                    &self.outline(message, outline::folding_ranges),
+                )]
+            }
+            Some("textDocument/selectionRange") => {
+                vec![response(id, &self.selection_ranges(message))]
+            }
             Some("textDocument/completion") => {
                 vec![response(
                     id,
@@ -67,13 +122,29 @@ impl Server {
         }
         self.lint_engine = lint_engine;
         self.lint_controls = controls;
+        self.workspace.index_folders(message);
         vec![response(
             id,
             &json!({
                 "capabilities": {
                     "positionEncoding": "utf-16",
-                    "textDocumentSync": { "openClose": true, "change": 1 },
+                    "textDocumentSync": { "openClose": true, "change": 2 },
                     "completionProvider": {},
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "signatureHelpProvider": {
+                        "triggerCharacters": [" ", "(", ","],
+                        "retriggerCharacters": [","]
+                    },
+                    "inlayHintProvider": true,
+                    "referencesProvider": true,
+                    "renameProvider": { "prepareProvider": true },
+                    "codeActionProvider": { "codeActionKinds": ["quickfix", "source.fixAll"] },
+                    "documentFormattingProvider": true,
+                    "documentRangeFormattingProvider": true,
+                    "documentSymbolProvider": true,
+                    "foldingRangeProvider": true,
+                    "selectionRangeProvider": true,
                     "semanticTokensProvider": {
                         "legend": {
                             "tokenTypes": [
@@ -106,9 +177,9 @@ impl Server {
         let Some(uri) = message["params"]["textDocument"]["uri"].as_str() else {
             return false;
         };
-        self.documents
+        self.workspace
             .get(uri)
-            .is_some_and(|text| source_has_feature(text, expected))
+            .is_some_and(|document| source_has_feature(&document.text, expected))
     }
 
     fn did_open(&mut self, message: &Value) -> Vec<Value> {
@@ -121,13 +192,14 @@ impl Server {
         ) else {
             return Vec::new();
         };
-        self.documents.insert(uri.to_owned(), text.to_owned());
-        vec![publish_diagnostics(
-            uri,
-            text,
-            &self.lint_engine,
-            &self.lint_controls,
-        )]
+        self.workspace.open(uri, text);
+        self.workspace
+            .get(uri)
+            .map(|document| {
+                publish_diagnostics(uri, document, &self.lint_engine, &self.lint_controls)
+            })
+            .into_iter()
+            .collect()
     }
 
     fn did_change(&mut self, message: &Value) -> Vec<Value> {
@@ -137,19 +209,22 @@ impl Server {
         else {
             return Vec::new();
         };
-        let Some(text) = message
+        let Some(changes) = message
             .pointer("/params/contentChanges")
             .and_then(Value::as_array)
-            .and_then(|changes| changes.last())
-            .and_then(|change| change.get("text"))
-            .and_then(Value::as_str)
         else {
             return Vec::new();
         };
-        self.documents.insert(uri.to_owned(), text.to_owned());
+        // A change that cannot be applied leaves the document and its previous
+        // diagnostics in place; the client resynchronizes by reopening the
+        // document. A batch that leaves the normalized source unchanged keeps
+        // the diagnostics already published for it.
+        let Ok(Some(document)) = self.workspace.change(uri, changes) else {
+            return Vec::new();
+        };
         vec![publish_diagnostics(
             uri,
-            text,
+            document,
             &self.lint_engine,
             &self.lint_controls,
         )]
@@ -162,7 +237,7 @@ impl Server {
         else {
             return Vec::new();
         };
-        self.documents.remove(uri);
+        self.workspace.close(uri);
         vec![json!({
             "jsonrpc": "2.0",
             "method": "textDocument/publishDiagnostics",
@@ -170,6 +245,149 @@ impl Server {
         })]
     }
 
+    /// Return the open document and byte offset named by a positional request.
+    fn document_position<'a>(&self, message: &'a Value) -> Option<(&'a str, usize)> {
+        let uri = message
+            .pointer("/params/textDocument/uri")
+            .and_then(Value::as_str)?;
+        let line = message
+            .pointer("/params/position/line")
+            .and_then(Value::as_u64)?;
+        let character = message
+            .pointer("/params/position/character")
+            .and_then(Value::as_u64)?;
+        let (source, _) = self.workspace.get(uri)?.lexed()?;
+        let offset = protocol_offset(
+            source.as_str(),
+            usize::try_from(line).unwrap_or(usize::MAX),
+            usize::try_from(character).unwrap_or(usize::MAX),
+        );
+        Some((uri, offset))
+    }
+
+    fn navigate(&self, message: &Value, answer: fn(&str, usize, &Workspace) -> Value) -> Value {
+        self.document_position(message)
+            .map_or(Value::Null, |(uri, offset)| {
+                answer(uri, offset, &self.workspace)
+            })
+    }
+
+    fn references(&self, message: &Value) -> Value {
+        let include_declaration = message
+            .pointer("/params/context/includeDeclaration")
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+        self.document_position(message)
+            .map_or(Value::Null, |(uri, offset)| {
+                references::references(uri, offset, include_declaration, &self.workspace)
+            })
+    }
+
+    fn inlay_hints(&self, message: &Value) -> Value {
+        let Some((uri, document)) = message
+            .pointer("/params/textDocument/uri")
+            .and_then(Value::as_str)
+            .and_then(|uri| Some((uri, self.workspace.get(uri)?)))
+        else {
+            return Value::Null;
+        };
+        let Some((source, _)) = document.lexed() else {
+            return json!([]);
+        };
+        let range = message
+            .pointer("/params/range")
+            .map_or(Span::new(0, usize::MAX), |range| {
+                protocol_span(source.as_str(), range)
+            });
+        hints::inlay_hints(uri, range, &self.workspace)
+    }
+
+    fn rename(&self, id: Option<Value>, message: &Value) -> Value {
+        let Some(new_name) = message.pointer("/params/newName").and_then(Value::as_str) else {
+            return error_response(id, -32602, "rename requires newName");
+        };
+        let Some((uri, offset)) = self.document_position(message) else {
+            return error_response(id, -32602, "rename requires an open document position");
+        };
+        match references::rename(uri, offset, new_name, &self.workspace) {
+            Ok(edit) => response(id, &edit),
+            Err((code, message)) => error_response(id, code, &message),
+        }
+    }
+
+    fn code_actions(&self, message: &Value) -> Value {
+        let Some((uri, document)) = message
+            .pointer("/params/textDocument/uri")
+            .and_then(Value::as_str)
+            .and_then(|uri| Some((uri, self.workspace.get(uri)?)))
+        else {
+            return Value::Null;
+        };
+        let Ok(report) = lint_document(document, &self.lint_engine, &self.lint_controls) else {
+            return json!([]);
+        };
+        actions::code_actions(uri, document, &report, &message["params"])
+    }
+
+    fn format(&self, id: Option<Value>, message: &Value, ranged: bool) -> Value {
+        let Some(document) = message
+            .pointer("/params/textDocument/uri")
+            .and_then(Value::as_str)
+            .and_then(|uri| self.workspace.get(uri))
+        else {
+            return error_response(id, -32602, "formatting requires an open document");
+        };
+        let edits = if ranged {
+            let Some(range) = message.pointer("/params/range") else {
+                return error_response(id, -32602, "range formatting requires a range");
+            };
+            formatting::format_range(document, range)
+        } else {
+            formatting::format_document(document)
+        };
+        match edits {
+            Ok(edits) => response(id, &edits),
+            Err(message) => error_response(id, -32803, &message),
+        }
+    }
+
+    fn outline(&self, message: &Value, answer: fn(&SourceText, &Lexed) -> Value) -> Value {
+        message
+            .pointer("/params/textDocument/uri")
+            .and_then(Value::as_str)
+            .and_then(|uri| self.workspace.get(uri))
+            .and_then(Document::lexed)
+            .map_or(Value::Null, |(source, lexed)| answer(source, lexed))
+    }
+
+    fn selection_ranges(&self, message: &Value) -> Value {
+        let Some((source, lexed)) = message
+            .pointer("/params/textDocument/uri")
+            .and_then(Value::as_str)
+            .and_then(|uri| self.workspace.get(uri))
+            .and_then(Document::lexed)
+        else {
+            return Value::Null;
+        };
+        let offsets = message
+            .pointer("/params/positions")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .map(|position| {
+                let coordinate = |name: &str| {
+                    position
+                        .get(name)
+                        .and_then(Value::as_u64)
+                        .and_then(|value| usize::try_from(value).ok())
+                        .unwrap_or(usize::MAX)
+                };
+                protocol_offset(source.as_str(), coordinate("line"), coordinate("character"))
+            })
+            .collect::<Vec<_>>();
+        outline::selection_ranges(source, lexed, &offsets)
+    }
+
     fn semantic_tokens(&self, message: &Value) -> Value {
         let Some(uri) = message
             .pointer("/params/textDocument/uri")
@@ -177,9 +395,13 @@ impl Server {
         else {
             return json!({ "data": [] });
         };
-        self.documents
+        self.workspace
             .get(uri)
-            .map_or_else(|| json!({ "data": [] }), |text| semantic_tokens(text))
+            .and_then(Document::lexed)
+            .map_or_else(
+                || json!({ "data": [] }),
+                |(source, lexed)| semantic_token_data(source, &lexed.tokens),
+            )
     }
 }
 
@@ -366,11 +588,12 @@ fn error_response(id: Option<Value>, code: i32, message: &str) -> Value {
 
 fn publish_diagnostics(
     uri: &str,
-    text: &str,
+    document: &Document,
     engine: &LintEngine,
     controls: &[LintControl],
 ) -> Value {
-    let diagnostics = diagnostics_with_lint(text, engine, controls);
+    let diagnostics =
+        diagnostics_with_lint(document, engine, controls, &index::package_namespaces(uri));
     json!({
         "jsonrpc": "2.0",
         "method": "textDocument/publishDiagnostics",
@@ -380,22 +603,69 @@ fn publish_diagnostics(
 
 #[cfg(test)]
 fn diagnostics(text: &str) -> Vec<Value> {
-    diagnostics_with_lint(text, &LintEngine::builtin(), &[])
+    let mut workspace = Workspace::default();
+    workspace.open("file:///diagnostics.topal", text);
+    diagnostics_with_lint(
+        workspace.get("file:///diagnostics.topal").unwrap(),
+        &LintEngine::builtin(),
+        &[],
+        &BTreeSet::new(),
+    )
 }
 
-fn diagnostics_with_lint(text: &str, engine: &LintEngine, controls: &[LintControl]) -> Vec<Value> {
-    match engine.lint_text(text, controls) {
+/// Lint the tokens and syntax tree the document already holds, or its text
+/// when that is not valid Topal source.
+fn lint_document(
+    document: &Document,
+    engine: &LintEngine,
+    controls: &[LintControl],
+) -> Result<LintReport, String> {
+    match document.parsed() {
+        Some((source, lexed, parsed)) => engine.lint_parsed(source, lexed, parsed, controls),
+        None => engine.lint_text(&document.text, controls),
+    }
+}
+
+/// Syntax and lint findings for `document`, followed by static semantic
+/// findings once the document parses cleanly. `namespaces` are bound by the
+/// package. Every finding is derived from the document's maintained analysis.
+fn diagnostics_with_lint(
+    document: &Document,
+    engine: &LintEngine,
+    controls: &[LintControl],
+    namespaces: &BTreeSet<String>,
+) -> Vec<Value> {
+    match lint_document(document, engine, controls) {
         Ok(report) => {
-            let normalized = SourceText::new(text).ok();
-            let coordinate_text = normalized.as_ref().map_or(text, SourceText::as_str);
+            let parsed = document.parsed();
+            let coordinate_text =
+                parsed.map_or(document.text.as_str(), |(source, _, _)| source.as_str());
+            let incomplete = parsed.and_then(|(_, _, parsed)| parsed.incomplete);
+            let semantic = parsed
+                .filter(|(_, _, parsed)| parsed.diagnostics.is_empty())
+                .map(|(source, _, parsed)| {
+                    static_diagnostics(source, &parsed.statements, namespaces)
+                })
+                .unwrap_or_default();
             report
                 .diagnostics
                 .iter()
-                .map(|diagnostic| protocol_diagnostic(coordinate_text, diagnostic))
+                .chain(&semantic)
+                .map(|diagnostic| {
+                    let mut result = protocol_diagnostic(coordinate_text, diagnostic);
+                    // A document that ends inside an open construct names the
+                    // continuation that would resolve its diagnostic.
+                    if let Some(incomplete) = incomplete.filter(|incomplete| {
+                        diagnostic.source_span.as_deref() == Some(&incomplete.span)
+                    }) {
+                        result["data"]["continuation"] = json!(incomplete.expected.as_str());
+                    }
+                    result
+                })
                 .collect()
         }
         Err(error) => vec![protocol_diagnostic(
-            text,
+            &document.text,
             &Diagnostic::error("L-LINT-ENGINE", 1, 1, error),
         )],
     }
@@ -533,6 +803,26 @@ fn protocol_position(text: &str, offset: usize) -> Value {
     json!({ "line": line, "character": character })
 }
 
+/// Convert a UTF-16 protocol position to a byte offset, clamping positions
+/// past the end of a line or document.
+fn protocol_offset(text: &str, line: usize, character: usize) -> usize {
+    let line_start = match line {
+        0 => 0,
+        _ => text
+            .match_indices('\n')
+            .nth(line - 1)
+            .map_or(text.len(), |(offset, _)| offset + 1),
+    };
+    let mut units = 0;
+    for (index, value) in text[line_start..].char_indices() {
+        if value == '\n' || units >= character {
+            return line_start + index;
+        }
+        units += value.len_utf16();
+    }
+    text.len()
+}
+
 fn protocol_coordinates(text: &str, offset: usize) -> (usize, usize) {
     let before = text.get(..offset).unwrap_or(text);
     let line = before.bytes().filter(|byte| *byte == b'\n').count();
@@ -540,12 +830,17 @@ fn protocol_coordinates(text: &str, offset: usize) -> (usize, usize) {
     (line, tail.encode_utf16().count())
 }
 
+#[cfg(test)]
 fn semantic_tokens(text: &str) -> Value {
-    let Ok(source) = SourceText::new(text) else {
-        return json!({ "data": [] });
-    };
+    SourceText::new(text).map_or_else(
+        |_| json!({ "data": [] }),
+        |source| semantic_token_data(&source, &lex(&source).tokens),
+    )
+}
+
+fn semantic_token_data(source: &SourceText, tokens: &[Token]) -> Value {
     let mut absolute = Vec::new();
-    for token in lex(&source).tokens {
+    for token in tokens {
         let Some(token_type) = semantic_token_type(token.kind, source.slice(token.span)) else {
             continue;
         };
@@ -576,27 +871,27 @@ fn semantic_tokens(text: &str) -> Value {
     json!({ "data": data })
 }
 
+fn is_keyword(lexeme: &str) -> bool {
+    matches!(
+        lexeme,
+        "fn" | "generator"
+            | "Interface"
+            | "language"
+            | "is"
+            | "otherwise"
+            | "resumes"
+            | "return"
+            | "static"
+            | "then"
+            | "use"
+            | "yield"
+            | "yields"
+    )
+}
+
 fn semantic_token_type(kind: TokenKind, lexeme: &str) -> Option<usize> {
     match kind {
-        TokenKind::Identifier
-            if matches!(
-                lexeme,
-                "fn" | "generator"
-                    | "Interface"
-                    | "language"
-                    | "is"
-                    | "otherwise"
-                    | "resumes"
-                    | "return"
-                    | "static"
-                    | "then"
-                    | "use"
-                    | "yield"
-                    | "yields"
-            ) =>
-        {
-            Some(4)
-        }
+        TokenKind::Identifier if is_keyword(lexeme) => Some(4),
         TokenKind::Identifier => Some(0),
         TokenKind::Integer | TokenKind::Rational | TokenKind::Version => Some(1),
         TokenKind::String => Some(2),
@@ -640,9 +935,10 @@ mod tests {
 
     use super::*;
     use topal_best_practices::Catalog;
+    use topal_linter::{LintReport, RectificationSafety, SourceEdit};
 
     #[test]
-    fn initializes_with_full_utf16_document_sync() {
+    fn initializes_with_incremental_utf16_document_sync() {
         let mut server = Server::default();
         let output = server.handle(&json!({
             "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}
@@ -653,7 +949,7 @@ mod tests {
         );
         assert_eq!(
             output[0]["result"]["capabilities"]["textDocumentSync"]["change"],
-            1
+            2
         );
         assert_eq!(
             output[0]["result"]["capabilities"]["completionProvider"],
@@ -661,6 +957,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn applies_incremental_utf16_changes_to_crlf_documents() {
+        let mut server = Server::default();
+        let original = "use language ( version is v0.1 )\r\n😀 is 40\r\n# total\r\n😀 + 2\r\n";
+        open(&mut server, "file:///incremental.t", original);
+        let output = server.handle(&json!({
+            "jsonrpc": "2.0", "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": "file:///incremental.t", "version": 2 },
+                "contentChanges": [
+                    {
+                        "range": {
+                            "start": { "line": 1, "character": 6 },
+                            "end": { "line": 1, "character": 8 }
+                        },
+                        "text": "41"
+                    },
+                    {
+                        "range": {
+                            "start": { "line": 3, "character": 3 },
+                            "end": { "line": 3, "character": 6 }
+                        },
+                        "text": "+ \"unterminated\r\nnext is 1"
+                    }
+                ]
+            }
+        }));
+        let expected = "use language ( version is v0.1 )\r\n😀 is 41\r\n# total\r\n😀 + \"unterminated\r\nnext is 1\r\n";
+        assert_eq!(
+            server.workspace.get("file:///incremental.t").unwrap().text,
+            expected
+        );
+        assert_eq!(
+            output[0]["params"]["diagnostics"],
+            Value::Array(diagnostics(expected))
+        );
+        let tokens = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 36, "method": "textDocument/semanticTokens/full",
+            "params": { "textDocument": { "uri": "file:///incremental.t" } }
+        }));
+        assert_eq!(tokens[0]["result"], semantic_tokens(expected));
+
+        let replaced = server.handle(&json!({
+            "jsonrpc": "2.0", "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": "file:///incremental.t", "version": 3 },
+                "contentChanges": [{ "text": "use language ( version is v0.1 )\n1\n" }]
+            }
+        }));
+        assert_eq!(replaced[0]["params"]["diagnostics"], json!([]));
+    }
+
+    #[test]
+    fn indexes_unopened_workspace_folders_in_the_background() {
+        let package = fs::canonicalize(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../../examples/applications/module-loading"),
+        )
+        .unwrap();
+        let mut server = Server::default();
+        let _ = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 37, "method": "initialize",
+            "params": { "workspaceFolders": [
+                { "uri": index::path_uri(&package), "name": "module-loading" }
+            ] }
+        }));
+        server.workspace.wait_for_index();
+        assert!(server.workspace.cache.contains(&package.join("math.t")));
+        assert!(
+            server
+                .workspace
+                .cache
+                .contains(&package.join("application.t"))
+        );
+    }
+
     #[test]
     fn completes_only_implemented_named_root_operations() {
         let mut server = Server::default();
@@ -733,6 +1105,749 @@ mod tests {
         );
     }
 
+    fn open(server: &mut Server, uri: &str, text: &str) {
+        let _ = server.handle(&json!({
+            "jsonrpc": "2.0", "method": "textDocument/didOpen",
+            "params": { "textDocument": {
+                "uri": uri, "languageId": "topal", "version": 1, "text": text
+            } }
+        }));
+    }
+
+    fn request_at(
+        server: &mut Server,
+        method: &str,
+        uri: &str,
+        line: usize,
+        character: usize,
+    ) -> Value {
+        server
+            .handle(&json!({
+                "jsonrpc": "2.0", "id": 30, "method": method,
+                "params": {
+                    "textDocument": { "uri": uri },
+                    "position": { "line": line, "character": character }
+                }
+            }))
+            .remove(0)["result"]
+            .take()
+    }
+
+    #[test]
+    fn hovers_document_declarations_and_lang_built_ins() {
+        let mut server = Server::default();
+        let source = "use language ( version is v0.1 )\n### Double an integer.\ndouble is fn (value : Int) -> Int\n  value * 2\ndouble 21\nlang Int\n";
+        open(&mut server, "file:///hover.t", source);
+
+        let hover = request_at(&mut server, "textDocument/hover", "file:///hover.t", 4, 2);
+        assert_eq!(
+            hover["contents"]["value"],
+            "```topal\ndouble is fn (value : Int) -> Int\n```\n\nDouble an integer."
+        );
+        assert_eq!(
+            hover["range"]["start"],
+            json!({ "line": 4, "character": 0 })
+        );
+        assert_eq!(hover["range"]["end"], json!({ "line": 4, "character": 6 }));
+
+        let definition = request_at(
+            &mut server,
+            "textDocument/definition",
+            "file:///hover.t",
+            4,
+            0,
+        );
+        assert_eq!(definition[0]["uri"], "file:///hover.t");
+        assert_eq!(
+            definition[0]["range"]["start"],
+            json!({ "line": 2, "character": 0 })
+        );
+
+        let built_in = request_at(&mut server, "textDocument/hover", "file:///hover.t", 5, 6);
+        assert!(
+            built_in["contents"]["value"]
+                .as_str()
+                .unwrap()
+                .contains("Arbitrary-precision signed integers.")
+        );
+        assert_eq!(
+            request_at(
+                &mut server,
+                "textDocument/definition",
+                "file:///hover.t",
+                5,
+                6
+            ),
+            Value::Null
+        );
+        assert_eq!(
+            request_at(&mut server, "textDocument/hover", "file:///hover.t", 2, 8),
+            Value::Null
+        );
+    }
+
+    #[test]
+    fn resolves_standard_library_names_through_the_module_tree() {
+        let library = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../library");
+        let application = library.join("application.t");
+        let uri = index::path_uri(&application);
+        let text = fs::read_to_string(&application).unwrap();
+        let mut server = Server::default();
+        open(&mut server, &uri, &text);
+        let line = text
+            .lines()
+            .position(|line| line == "min is std min")
+            .unwrap();
+
+        let definition = request_at(&mut server, "textDocument/definition", &uri, line, 12);
+        let target = index::uri_path(definition[0]["uri"].as_str().unwrap()).unwrap();
+        assert!(target.ends_with("std/module.t"));
+        let module = fs::read_to_string(&target).unwrap();
+        let declared = module
+            .lines()
+            .position(|line| line.starts_with("pub min is fn"))
+            .unwrap();
+        assert_eq!(
+            definition[0]["range"]["start"],
+            json!({ "line": declared, "character": 4 })
+        );
+        assert_eq!(definition.as_array().unwrap().len(), 1);
+
+        let hover = request_at(&mut server, "textDocument/hover", &uri, line, 12);
+        assert!(
+            hover["contents"]["value"]
+                .as_str()
+                .unwrap()
+                .contains("Return the smaller value.")
+        );
+
+        let overloads = text
+            .lines()
+            .position(|line| line == "sign is std sign")
+            .unwrap();
+        let definition = request_at(&mut server, "textDocument/definition", &uri, overloads, 13);
+        assert_eq!(definition.as_array().unwrap().len(), 2);
+
+        let module = request_at(&mut server, "textDocument/definition", &uri, line, 8);
+        assert!(
+            index::uri_path(module[0]["uri"].as_str().unwrap())
+                .unwrap()
+                .ends_with("std/module.t")
+        );
+    }
+
+    #[test]
+    fn finds_references_outside_shadowing_local_names() {
+        let mut server = Server::default();
+        let source = "use language ( version is v0.1 )\nlimit is 10\nclamp is fn (value : Int) -> Int\n  value\n    > limit then limit\n    otherwise value\ntimes is fn (limit : Int) -> Int\n  limit * 2\nclamp (times limit)\n";
+        open(&mut server, "file:///references.t", source);
+        let output = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 31, "method": "textDocument/references",
+            "params": {
+                "textDocument": { "uri": "file:///references.t" },
+                "position": { "line": 8, "character": 14 },
+                "context": { "includeDeclaration": false }
+            }
+        }));
+        let lines = output[0]["result"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|location| location["range"]["start"]["line"].as_u64().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines, [4, 4, 8]);
+
+        let prepared = request_at(
+            &mut server,
+            "textDocument/prepareRename",
+            "file:///references.t",
+            1,
+            0,
+        );
+        assert_eq!(prepared["placeholder"], "limit");
+        assert_eq!(
+            request_at(
+                &mut server,
+                "textDocument/prepareRename",
+                "file:///references.t",
+                7,
+                2
+            ),
+            Value::Null
+        );
+
+        let captured = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 32, "method": "textDocument/rename",
+            "params": {
+                "textDocument": { "uri": "file:///references.t" },
+                "position": { "line": 1, "character": 0 },
+                "newName": "value"
+            }
+        }));
+        assert_eq!(captured[0]["error"]["code"], -32803);
+        assert!(
+            captured[0]["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("captured")
+        );
+
+        let keyword = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 33, "method": "textDocument/rename",
+            "params": {
+                "textDocument": { "uri": "file:///references.t" },
+                "position": { "line": 1, "character": 0 },
+                "newName": "then"
+            }
+        }));
+        assert_eq!(keyword[0]["error"]["code"], -32602);
+    }
+
+    fn request(server: &mut Server, method: &str, uri: &str) -> Value {
+        server
+            .handle(&json!({
+                "jsonrpc": "2.0", "id": 31, "method": method,
+                "params": { "textDocument": { "uri": uri } }
+            }))
+            .remove(0)["result"]
+            .take()
+    }
+
+    fn symbol_names(symbols: &Value) -> Vec<(String, Value)> {
+        symbols
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|symbol| {
+                (
+                    symbol["name"].as_str().unwrap().to_owned(),
+                    symbol["kind"].clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn helps_signatures_and_hints_parameters_and_classifiers() {
+        let mut server = Server::default();
+        // An argument named like its parameter needs no hint.
+        let text = "use language ( version is v0.1 )\n### Scale a value.\nscale is fn (\n  ### The value to scale.\n  value : Int,\n  factor : Int\n) -> Int\n  value * factor\ndouble is fn ( value : Int ) -> Int\n  value * 2\nscaled is scale (4, 3)\ndoubled is double scaled\npair is (1, \"two\")\ninfix is 4 scale 5\nvalue is 7\nagain is double value\n";
+        open(&mut server, "file:///calls.t", text);
+
+        let help = request_at(
+            &mut server,
+            "textDocument/signatureHelp",
+            "file:///calls.t",
+            10,
+            20,
+        );
+        assert_eq!(
+            help["signatures"][0]["label"],
+            "scale is fn ( value : Int, factor : Int ) -> Int"
+        );
+        assert_eq!(
+            help["signatures"][0]["documentation"]["value"],
+            "Scale a value."
+        );
+        assert_eq!(
+            help["signatures"][0]["parameters"][0]["documentation"]["value"],
+            "The value to scale."
+        );
+        assert_eq!(help["activeParameter"], 1);
+        // Both operands of an infix call bind in declaration order.
+        for (character, parameter) in [(9, 0), (16, 1), (17, 1)] {
+            let infix = request_at(
+                &mut server,
+                "textDocument/signatureHelp",
+                "file:///calls.t",
+                13,
+                character,
+            );
+            assert_eq!(infix["activeParameter"], parameter, "{character}");
+        }
+        assert_eq!(
+            request_at(
+                &mut server,
+                "textDocument/signatureHelp",
+                "file:///calls.t",
+                14,
+                9
+            ),
+            Value::Null
+        );
+
+        let hints = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 40, "method": "textDocument/inlayHint",
+            "params": {
+                "textDocument": { "uri": "file:///calls.t" },
+                "range": {
+                    "start": { "line": 10, "character": 0 },
+                    "end": { "line": 16, "character": 0 }
+                }
+            }
+        }));
+        let labels = hints[0]["result"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hint| {
+                (
+                    hint["position"]["line"].as_u64().unwrap(),
+                    hint["position"]["character"].as_u64().unwrap(),
+                    hint["label"].as_str().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            [
+                (10, 6, ": Int"),
+                (10, 17, "value:"),
+                (10, 20, "factor:"),
+                (11, 7, ": Int"),
+                (11, 18, "value:"),
+                (12, 4, ": (Int, String)"),
+                (13, 5, ": Int"),
+                (13, 9, "value:"),
+                (13, 17, "factor:"),
+                (14, 5, ": Int"),
+                (15, 5, ": Int"),
+            ]
+        );
+    }
+
+    #[test]
+    fn formats_documents_and_ranges_in_client_line_endings() {
+        let mut server = Server::default();
+        let source = "use language ( version is v0.1 )\r\nf is fn ( value : Int ) -> Int\r\n    value\r\ng is ( 1,2 )\r\n";
+        open(&mut server, "file:///format.t", source);
+        let edits = request(&mut server, "textDocument/formatting", "file:///format.t");
+        assert_eq!(
+            edits,
+            json!([{
+                "range": {
+                    "start": { "line": 0, "character": 0 },
+                    "end": { "line": 4, "character": 0 }
+                },
+                "newText": "use language (\r\n  version is v0.1\r\n)\r\nf is fn (value : Int) -> Int\r\n  value\r\ng is (1, 2)\r\n"
+            }])
+        );
+
+        let output = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 33, "method": "textDocument/rangeFormatting",
+            "params": {
+                "textDocument": { "uri": "file:///format.t" },
+                "range": {
+                    "start": { "line": 2, "character": 4 },
+                    "end": { "line": 2, "character": 9 }
+                },
+                "options": { "tabSize": 2, "insertSpaces": true }
+            }
+        }));
+        assert_eq!(
+            output[0]["result"],
+            json!([{
+                "range": {
+                    "start": { "line": 1, "character": 0 },
+                    "end": { "line": 3, "character": 0 }
+                },
+                "newText": "f is fn (value : Int) -> Int\r\n  value\r\n"
+            }])
+        );
+
+        open(
+            &mut server,
+            "file:///invalid.t",
+            "f is fn (value) -> Int\n  value\n",
+        );
+        let output = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 34, "method": "textDocument/formatting",
+            "params": { "textDocument": { "uri": "file:///invalid.t" } }
+        }));
+        assert_eq!(output[0]["error"]["code"], -32803);
+    }
+
+    #[test]
+    fn offers_reviewed_rectifications_and_suppressions_as_quick_fixes() {
+        let mut server = Server::default();
+        let _ = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": { "initializationOptions": { "lint": { "enable": [
+                "lang best-practice task declaration-order"
+            ] } } }
+        }));
+        let source = "use language (\r\n  version is v0.1\r\n)\r\nCounter is Task (queue-size is 2)\r\nservice is Counter\r\n  start is fn (initial : Nat) -> Completed\r\n    Completed\r\n  count : Nat\r\n";
+        open(&mut server, "file:///order.t", source);
+        let actions = |server: &mut Server, only: Value| {
+            server
+                .handle(&json!({
+                    "jsonrpc": "2.0", "id": 36, "method": "textDocument/codeAction",
+                    "params": {
+                        "textDocument": { "uri": "file:///order.t" },
+                        "range": {
+                            "start": { "line": 7, "character": 3 },
+                            "end": { "line": 7, "character": 3 }
+                        },
+                        "context": { "diagnostics": [], "only": only }
+                    }
+                }))
+                .remove(0)["result"]
+                .take()
+        };
+        let quick_fixes = actions(&mut server, json!(["quickfix"]));
+        let [rectification, suppression] = quick_fixes.as_array().unwrap().as_slice() else {
+            panic!("{quick_fixes}");
+        };
+        assert_eq!(
+            rectification["title"],
+            "Move the state field before `start` and all message handlers (review required)"
+        );
+        assert_eq!(rectification["isPreferred"], false);
+        assert_eq!(
+            rectification["diagnostics"][0]["code"],
+            "L-TASK-DECLARATION-ORDER"
+        );
+        assert_eq!(
+            rectification["edit"]["changes"]["file:///order.t"],
+            json!([{
+                "range": {
+                    "start": { "line": 5, "character": 0 },
+                    "end": { "line": 8, "character": 0 }
+                },
+                "newText": "  count : Nat\r\n  start is fn (initial : Nat) -> Completed\r\n    Completed\r\n"
+            }])
+        );
+        assert_eq!(
+            suppression["title"],
+            "Suppress `lang best-practice task declaration-order` for this statement"
+        );
+        assert_eq!(
+            suppression["edit"]["changes"]["file:///order.t"][0]["newText"],
+            "  lang disable-diagnostic ( lang best-practice task declaration-order )\r\n"
+        );
+        assert_eq!(actions(&mut server, json!(["source"])), json!([]));
+    }
+
+    #[test]
+    fn fixes_every_safe_rectification_in_one_source_action() {
+        let mut workspace = Workspace::default();
+        workspace.open("file:///fix.t", "value is 1\nother is 2\n");
+        let edit = |start, end, replacement: &str, safety| SourceEdit {
+            span: topal_source::Span::new(start, end),
+            replacement: replacement.into(),
+            safety,
+            diagnostic: None,
+        };
+        let report = LintReport {
+            diagnostics: Vec::new(),
+            has_errors: false,
+            rectifications: vec![
+                edit(9, 10, "3", RectificationSafety::SemanticsProven),
+                edit(11, 16, "second", RectificationSafety::ReviewRequired),
+                edit(20, 21, "4", RectificationSafety::PresentationOnly),
+            ],
+        };
+        let actions = actions::code_actions(
+            "file:///fix.t",
+            workspace.get("file:///fix.t").unwrap(),
+            &report,
+            &json!({ "context": { "only": ["source.fixAll"] } }),
+        );
+        assert_eq!(
+            actions,
+            json!([{
+                "title": "Fix all safe lint findings",
+                "kind": "source.fixAll",
+                "edit": { "changes": { "file:///fix.t": [
+                    {
+                        "range": {
+                            "start": { "line": 0, "character": 9 },
+                            "end": { "line": 0, "character": 10 }
+                        },
+                        "newText": "3"
+                    },
+                    {
+                        "range": {
+                            "start": { "line": 1, "character": 9 },
+                            "end": { "line": 1, "character": 10 }
+                        },
+                        "newText": "4"
+                    }
+                ] } }
+            }])
+        );
+    }
+
+    #[test]
+    fn outlines_tasks_generators_and_layouts() {
+        let mut server = Server::default();
+        let task = include_str!("../../../examples/language/task-message-transactions.t");
+        open(&mut server, "file:///task.t", task);
+        let symbols = request(&mut server, "textDocument/documentSymbol", "file:///task.t");
+        assert_eq!(
+            symbol_names(&symbols),
+            [
+                ("Counter".to_owned(), json!(5)),
+                ("counter-service".to_owned(), json!(5)),
+                ("counter".to_owned(), json!(13)),
+                ("stream".to_owned(), json!(13)),
+            ]
+        );
+        let service = &symbols[1];
+        assert_eq!(service["detail"], "Counter");
+        assert_eq!(
+            symbol_names(&service["children"])
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>(),
+            [
+                "count",
+                "start",
+                "increment",
+                "current",
+                "values",
+                "terminate"
+            ]
+        );
+        let values = &service["children"][4];
+        assert_eq!(
+            values["detail"],
+            "generator ( _ : MessageContext, _ : Unit )"
+        );
+        assert_eq!(
+            values["range"]["start"],
+            json!({ "line": 17, "character": 2 })
+        );
+        assert_eq!(
+            values["range"]["end"],
+            json!({ "line": 22, "character": 6 })
+        );
+        assert_eq!(
+            values["selectionRange"]["end"],
+            json!({ "line": 17, "character": 8 })
+        );
+
+        let layouts = include_str!("../../../examples/language/external-layout-location.t");
+        open(&mut server, "file:///layouts.t", layouts);
+        let symbols = request(
+            &mut server,
+            "textDocument/documentSymbol",
+            "file:///layouts.t",
+        );
+        assert_eq!(
+            symbol_names(&symbols)[..6],
+            [
+                ("UInt32LE".to_owned(), json!(23)),
+                ("Utf8Text".to_owned(), json!(23)),
+                ("HeaderLayout".to_owned(), json!(23)),
+                ("MaybeNatLayout".to_owned(), json!(23)),
+                ("PairArrayLayout".to_owned(), json!(23)),
+                ("DeviceAddresses".to_owned(), json!(13)),
+            ]
+        );
+    }
+
+    #[test]
+    fn outlines_unions_interfaces_and_nested_functions() {
+        let mut server = Server::default();
+        let source = "use language ( version is v0.1 )\nShape is Union\n  Empty\n  Square : Int\n\nParser is Interface\n  parse is fn (source : String) -> Boolean\n\nouter is fn (value : Int) -> Int\n  local is value + 1\n  inner is fn (other : Int) -> Int\n    other\n  inner local\n";
+        open(&mut server, "file:///declarations.t", source);
+        let symbols = request(
+            &mut server,
+            "textDocument/documentSymbol",
+            "file:///declarations.t",
+        );
+        assert_eq!(
+            symbol_names(&symbols),
+            [
+                ("Shape".to_owned(), json!(10)),
+                ("Parser".to_owned(), json!(11)),
+                ("outer".to_owned(), json!(12)),
+            ]
+        );
+        assert_eq!(
+            symbol_names(&symbols[0]["children"]),
+            [
+                ("Empty".to_owned(), json!(22)),
+                ("Square".to_owned(), json!(22)),
+            ]
+        );
+        assert_eq!(symbols[0]["children"][1]["detail"], "Int");
+        assert_eq!(
+            symbols[1]["children"][0]["detail"],
+            "fn (source : String) -> Boolean"
+        );
+        assert_eq!(
+            symbol_names(&symbols[2]["children"]),
+            [("inner".to_owned(), json!(12))]
+        );
+    }
+
+    #[test]
+    fn folds_indented_bodies_brackets_and_comments_in_every_variant() {
+        let mut server = Server::default();
+        let task = include_str!("../../../examples/language/task-message-transactions.t");
+        open(&mut server, "file:///task.t", task);
+        let folds = |server: &mut Server, uri: &str| {
+            request(server, "textDocument/foldingRange", uri)
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|fold| {
+                    (
+                        fold["startLine"].as_u64().unwrap(),
+                        fold["endLine"].as_u64().unwrap(),
+                        fold["kind"].as_str().map(str::to_owned),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            folds(&mut server, "file:///task.t"),
+            [
+                (1, 3, None),
+                (4, 5, Some("comment".to_owned())),
+                (8, 24, None),
+                (10, 12, None),
+                (13, 14, None),
+                (15, 16, None),
+                (17, 22, None),
+                (23, 24, None),
+                (29, 30, None),
+            ]
+        );
+
+        let lint = include_str!("../../../examples/linter/task-state-machine-rule.t");
+        open(&mut server, "file:///rule.t", lint);
+        assert_eq!(
+            folds(&mut server, "file:///rule.t"),
+            [
+                (0, 3, None),
+                (4, 5, Some("comment".to_owned())),
+                (6, 9, None),
+                (7, 9, None),
+            ]
+        );
+
+        // A trailing comment and blank line do not extend a body, a line inside
+        // brackets does not close the body it continues, and a dedent to an
+        // equal indentation closes the sibling above it.
+        let debug = "use language ( version is v0.1, features is ( debug ) )\nhelper is fn (value : Int) -> Int\n  answer is (\nvalue,\n    1\n  )\n  answer\n  # Trailing.\n\nhelp helper\n";
+        open(&mut server, "file:///helper.debug", debug);
+        assert_eq!(
+            folds(&mut server, "file:///helper.debug"),
+            [(1, 6, None), (2, 5, None)]
+        );
+    }
+
+    #[test]
+    fn expands_selections_through_enclosing_syntax() {
+        let mut server = Server::default();
+        let source = "use language ( version is v0.1 )\ntotal is fn (value : Int) -> Int\n  doubled is value * 2\n  doubled + 1\n";
+        open(&mut server, "file:///selection.t", source);
+        let output = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 32, "method": "textDocument/selectionRange",
+            "params": {
+                "textDocument": { "uri": "file:///selection.t" },
+                "positions": [
+                    { "line": 2, "character": 14 },
+                    { "line": 1, "character": 15 }
+                ]
+            }
+        }));
+        let chain = |mut selection: &Value| {
+            let mut ranges = Vec::new();
+            while !selection.is_null() {
+                let range = &selection["range"];
+                ranges.push((
+                    range["start"]["line"].as_u64().unwrap(),
+                    range["start"]["character"].as_u64().unwrap(),
+                    range["end"]["line"].as_u64().unwrap(),
+                    range["end"]["character"].as_u64().unwrap(),
+                ));
+                selection = &selection["parent"];
+            }
+            ranges
+        };
+        assert_eq!(
+            chain(&output[0]["result"][0]),
+            [
+                (2, 13, 2, 18),
+                (2, 13, 2, 22),
+                (2, 2, 2, 22),
+                (1, 0, 3, 13),
+                (0, 0, 4, 0)
+            ]
+        );
+        assert_eq!(
+            chain(&output[0]["result"][1]),
+            [(1, 13, 1, 18), (1, 13, 1, 24), (1, 0, 3, 13), (0, 0, 4, 0)]
+        );
+    }
+
+    #[test]
+    fn renames_qualified_uses_across_the_module_tree() {
+        let package = fs::canonicalize(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../../examples/applications/module-loading"),
+        )
+        .unwrap();
+        let application = index::path_uri(&package.join("application.t"));
+        let math = index::path_uri(&package.join("math.t"));
+        let mut server = Server::default();
+        // Unsaved editor content is authoritative over the file on disk.
+        let edited = fs::read_to_string(package.join("application.t"))
+            .unwrap()
+            .replace("math answer", "(math answer) + (math answer)");
+        open(&mut server, &application, &edited);
+        let line = edited.lines().count() - 1;
+
+        let definition = request_at(
+            &mut server,
+            "textDocument/definition",
+            &application,
+            line,
+            8,
+        );
+        assert_eq!(definition[0]["uri"], math);
+
+        let output = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 34, "method": "textDocument/rename",
+            "params": {
+                "textDocument": { "uri": application },
+                "position": { "line": line, "character": 8 },
+                "newName": "reply"
+            }
+        }));
+        let changes = &output[0]["result"]["changes"];
+        assert_eq!(changes[&application].as_array().unwrap().len(), 2);
+        assert_eq!(changes[&math].as_array().unwrap().len(), 1);
+        assert_eq!(changes[&math][0]["newText"], "reply");
+        assert!(
+            changes[&application]
+                .as_array()
+                .unwrap()
+                .iter()
+                .all(|edit| edit["range"]["start"]["line"] == line)
+        );
+
+        let collision = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 35, "method": "textDocument/rename",
+            "params": {
+                "textDocument": { "uri": application },
+                "position": { "line": line, "character": 8 },
+                "newName": "Private-Value"
+            }
+        }));
+        assert_eq!(collision[0]["error"]["code"], -32803);
+        assert!(
+            collision[0]["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("private-value")
+        );
+    }
+
     #[test]
     fn completes_debug_commands_only_in_the_debug_variant() {
         let mut server = Server::default();
@@ -1058,6 +2173,7 @@ mod tests {
         }));
         let diagnostic = &output[0]["params"]["diagnostics"][0];
         assert_eq!(diagnostic["code"], "E-INCOMPLETE-ERROR-CODE-DECISION");
+        assert_eq!(diagnostic["data"]["continuation"], "decision-rules");
         assert!(
             diagnostic["message"]
                 .as_str()
@@ -1066,6 +2182,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn marks_only_end_of_document_diagnostics_as_continuations() {
+        let open = diagnostics("use language (\n  version is v0.1\n)\nvalue is (1,\n");
+        assert_eq!(open[0]["code"], "E-EXPECTED-RPAREN");
+        assert_eq!(open[0]["data"]["continuation"], "closing-parenthesis");
+        let closed = diagnostics("use language (\n  version is v0.1\n)\nvalue is (1 ]\n");
+        assert!(!closed.is_empty());
+        assert!(
+            closed
+                .iter()
+                .all(|diagnostic| diagnostic.get("data").is_none())
+        );
+    }
+
     #[test]
     fn publishes_duplicate_error_code_pattern_diagnostic() {
         let mut server = Server::default();
@@ -1108,6 +2238,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn publishes_static_dimension_mismatch_diagnostic() {
+        let source = include_str!("../../../examples/language/quantities.t");
+        assert!(diagnostics(source).is_empty());
+        let mismatched = format!("{source}mixed is 2[kg] + 1[s]\n");
+        let published = diagnostics(&mismatched);
+        assert_eq!(published[0]["code"], "E-DIMENSION-MISMATCH");
+        assert_eq!(published[0]["range"]["start"]["line"], 48);
+    }
+
+    #[test]
+    fn publishes_sensitive_output_diagnostic() {
+        let source = include_str!("../../../examples/language/sensitive-values.t");
+        assert!(diagnostics(source).is_empty());
+        let leaked = format!(
+            "{}\npassword\n",
+            source.trim_end().rsplit_once('\n').unwrap().0
+        );
+        let published = diagnostics(&leaked);
+        assert_eq!(published[0]["code"], "E-SENSITIVE-OUTPUT");
+        assert!(
+            !published[0]["message"]
+                .as_str()
+                .unwrap()
+                .contains("correct horse")
+        );
+    }
+
     #[test]
     fn publishes_rule_after_otherwise_diagnostic() {
         let mut server = Server::default();
@@ -1123,6 +2281,104 @@ mod tests {
         assert_eq!(diagnostic["range"]["start"]["line"], 3);
     }
 
+    #[test]
+    fn publishes_semantic_diagnostics_with_interpreter_codes() {
+        let package = fs::canonicalize(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../../examples/applications/module-loading"),
+        )
+        .unwrap();
+        let application = index::path_uri(&package.join("application.t"));
+        let source = fs::read_to_string(package.join("application.t")).unwrap();
+        let opened = Server::default().handle(&json!({
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": application, "text": source } }
+        }));
+        assert_eq!(opened[0]["params"]["diagnostics"], json!([]));
+
+        let published = diagnostics(&format!("{source}\ncount : Nat is -1\ncount + missing\n"));
+        assert_eq!(published.len(), 3);
+        // Outside its package the module namespace is unbound.
+        assert_eq!(published[0]["code"], "E-UNBOUND-NAME");
+        assert_eq!(published[0]["range"]["start"]["line"], 6);
+        assert_eq!(published[1]["code"], "E-BINDING-CLASSIFIER");
+        assert_eq!(published[2]["code"], "E-UNBOUND-NAME");
+        assert_eq!(published[2]["range"]["start"]["character"], 8);
+        assert!(
+            published
+                .iter()
+                .all(|diagnostic| diagnostic["source"] == "topal")
+        );
+
+        // Findings wait until the document parses.
+        let incomplete = diagnostics("value is missing + (1,\n");
+        assert!(!incomplete.is_empty());
+        assert!(
+            incomplete
+                .iter()
+                .all(|diagnostic| diagnostic["code"] != "E-UNBOUND-NAME")
+        );
+    }
+
+    #[test]
+    fn applies_a_change_batch_whole_and_keeps_analysis_of_unchanged_source() {
+        let mut server = Server::default();
+        let original = "use language ( version is v0.1 )\nvalue is 40\nvalue + 2\n";
+        open(&mut server, "file:///batch.t", original);
+        let change = |version: u64, changes: Value| {
+            json!({
+                "jsonrpc": "2.0", "method": "textDocument/didChange",
+                "params": {
+                    "textDocument": { "uri": "file:///batch.t", "version": version },
+                    "contentChanges": changes
+                }
+            })
+        };
+        let range = |line: u64, start: u64, end: u64| {
+            json!({
+                "start": { "line": line, "character": start },
+                "end": { "line": line, "character": end }
+            })
+        };
+
+        let rejected = server.handle(&change(
+            2,
+            json!([
+                { "range": range(1, 9, 11), "text": "41" },
+                { "range": range(2, 5, 1), "text": "-" }
+            ]),
+        ));
+        assert!(rejected.is_empty());
+        assert_eq!(
+            server.workspace.get("file:///batch.t").unwrap().text,
+            original
+        );
+
+        let restored = server.handle(&change(
+            3,
+            json!([
+                { "range": range(1, 9, 11), "text": "41" },
+                { "range": range(1, 9, 11), "text": "40" }
+            ]),
+        ));
+        assert!(restored.is_empty());
+
+        let edited = server.handle(&change(
+            4,
+            json!([{ "range": range(2, 6, 7), "text": "(1," }]),
+        ));
+        let expected = "use language ( version is v0.1 )\nvalue is 40\nvalue (1, 2\n";
+        let document = server.workspace.get("file:///batch.t").unwrap();
+        assert_eq!(document.text, expected);
+        let (source, lexed, parsed) = document.parsed().unwrap();
+        assert_eq!(*parsed, parse(source, lexed));
+        assert_eq!(lexed.tokens, lex(source).tokens);
+        assert_eq!(
+            edited[0]["params"]["diagnostics"],
+            Value::Array(diagnostics(expected))
+        );
+    }
+
     #[test]
     fn change_replaces_content_and_close_clears_diagnostics() {
         let mut server = Server::default();
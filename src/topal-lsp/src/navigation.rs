@@ -0,0 +1,151 @@
+//! Hover and go-to-definition over the workspace symbol index.
+//!
+//! A name resolves first against declarations in the open document, then
+//! against `lang` built-ins, and finally against the published declarations
+//! of the module tree that `load_module_tree` would load for the document's
+//! package, including nested `std` packages.
+
+use std::fmt::Write as _;
+
+use serde_json::{Value, json};
+use topal_language::lang_documentation;
+use topal_source::Span;
+
+use crate::index::{Index, Resolution};
+use crate::workspace::Workspace;
+use crate::{protocol_offset, protocol_position};
+
+/// One declaration a referenced name can denote.
+struct Target {
+    syntax: String,
+    documentation: Option<String>,
+    location: Option<(String, String, Span)>,
+}
+
+/// Answer `textDocument/hover` for the name at `offset` in `uri`.
+pub(super) fn hover(uri: &str, offset: usize, workspace: &Workspace) -> Value {
+    let index = Index::build(uri, workspace);
+    let Some((text, reference, targets)) = resolve(&index, uri, offset) else {
+        return Value::Null;
+    };
+    let mut contents = String::new();
+    for target in &targets {
+        if !contents.is_empty() {
+            contents.push_str("\n\n---\n\n");
+        }
+        let _ = write!(contents, "```topal\n{}\n```", target.syntax);
+        if let Some(documentation) = &target.documentation {
+            let _ = write!(contents, "\n\n{documentation}");
+        }
+    }
+    json!({
+        "contents": { "kind": "markdown", "value": contents },
+        "range": protocol_range(&text, reference)
+    })
+}
+
+/// Answer `textDocument/definition` with every overload the name denotes.
+pub(super) fn definition(uri: &str, offset: usize, workspace: &Workspace) -> Value {
+    let index = Index::build(uri, workspace);
+    let Some((_, _, targets)) = resolve(&index, uri, offset) else {
+        return Value::Null;
+    };
+    let locations = targets
+        .into_iter()
+        .filter_map(|target| target.location)
+        .map(|(uri, text, span)| json!({ "uri": uri, "range": protocol_range(&text, span) }))
+        .collect::<Vec<_>>();
+    if locations.is_empty() {
+        Value::Null
+    } else {
+        Value::Array(locations)
+    }
+}
+
+fn resolve(index: &Index, uri: &str, offset: usize) -> Option<(String, Span, Vec<Target>)> {
+    let file = index.file_index(uri)?;
+    let (reference, resolution) = index.resolve(file, offset)?;
+    let Resolution {
+        symbols,
+        modules,
+        builtin,
+    } = resolution;
+    let mut targets = Vec::new();
+    if let Some(name) = builtin {
+        let qualified = format!("lang {name}");
+        targets.extend(
+            lang_documentation()
+                .into_iter()
+                .filter(|declaration| declaration.name == qualified)
+                .map(|declaration| Target {
+                    syntax: declaration.syntax,
+                    documentation: declaration.documentation,
+                    location: None,
+                }),
+        );
+    }
+    for module in modules {
+        let module = index.file(module);
+        targets.push(Target {
+            syntax: format!(
+                "module {}",
+                module.module.as_deref().unwrap_or_default().join(" ")
+            ),
+            documentation: None,
+            location: Some((
+                module.uri.clone(),
+                module.analysis.source.as_str().to_owned(),
+                Span::new(0, 0),
+            )),
+        });
+    }
+    for symbol in &symbols {
+        let declaring = index.file(symbol.file);
+        targets.extend(
+            index
+                .declarations(symbol, symbol.file != file)
+                .map(|declaration| Target {
+                    syntax: declaration.syntax.clone(),
+                    documentation: declaration.documentation.clone(),
+                    location: declaration.name_span.map(|span| {
+                        (
+                            declaring.uri.clone(),
+                            declaring.analysis.source.as_str().to_owned(),
+                            span,
+                        )
+                    }),
+                }),
+        );
+    }
+    if targets.is_empty() {
+        return None;
+    }
+    Some((
+        index.file(file).analysis.source.as_str().to_owned(),
+        reference,
+        targets,
+    ))
+}
+
+pub(super) fn protocol_range(text: &str, span: Span) -> Value {
+    json!({
+        "start": protocol_position(text, span.start),
+        "end": protocol_position(text, span.end)
+    })
+}
+
+/// Normalized offsets of a protocol range, clamping missing or out-of-range
+/// coordinates to the end of the text.
+pub(super) fn protocol_span(text: &str, range: &Value) -> Span {
+    let offset = |name: &str| {
+        let coordinate = |field: &str| {
+            range
+                .pointer(&format!("/{name}/{field}"))
+                .and_then(Value::as_u64)
+                .and_then(|value| usize::try_from(value).ok())
+                .unwrap_or(usize::MAX)
+        };
+        protocol_offset(text, coordinate("line"), coordinate("character"))
+    };
+    Span::new(offset("start"), offset("end"))
+}
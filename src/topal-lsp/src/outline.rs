@@ -0,0 +1,543 @@
+//! Document symbols, folding ranges, and selection ranges derived from the
+//! shared syntax tree and token stream.
+
+use std::collections::BTreeMap;
+
+use serde_json::{Value, json};
+use topal_source::{SourceText, Span};
+use topal_syntax::{
+    Expression, FunctionParameter, Lexed, Statement, TokenKind, UnionAlternative, parse,
+};
+
+use crate::navigation::protocol_range;
+
+// `SymbolKind` values from the Language Server Protocol.
+const CLASS: u8 = 5;
+const METHOD: u8 = 6;
+const FIELD: u8 = 8;
+const ENUM: u8 = 10;
+const INTERFACE: u8 = 11;
+const FUNCTION: u8 = 12;
+const VARIABLE: u8 = 13;
+const ENUM_MEMBER: u8 = 22;
+const STRUCT: u8 = 23;
+
+/// One hierarchical outline entry before protocol conversion.
+struct Symbol {
+    name: Span,
+    kind: u8,
+    detail: Option<String>,
+    range: Span,
+    children: Vec<Self>,
+}
+
+impl Symbol {
+    const fn leaf(name: Span, kind: u8, detail: Option<String>, range: Span) -> Self {
+        Self {
+            name,
+            kind,
+            detail,
+            range,
+            children: Vec::new(),
+        }
+    }
+
+    fn to_json(&self, source: &SourceText) -> Value {
+        let mut symbol = json!({
+            "name": source.slice(self.name),
+            "kind": self.kind,
+            "range": protocol_range(source.as_str(), self.range),
+            "selectionRange": protocol_range(source.as_str(), self.name),
+            "children": self
+                .children
+                .iter()
+                .map(|child| child.to_json(source))
+                .collect::<Vec<_>>()
+        });
+        if let Some(detail) = &self.detail {
+            symbol["detail"] = json!(detail);
+        }
+        symbol
+    }
+}
+
+/// Answer `textDocument/documentSymbol` with the document's declarations.
+/// Function bodies contribute only nested declarations, not local values.
+pub(super) fn document_symbols(source: &SourceText, lexed: &Lexed) -> Value {
+    let parsed = parse(source, lexed);
+    Value::Array(
+        symbols(source, &parsed.statements, false)
+            .iter()
+            .map(|symbol| symbol.to_json(source))
+            .collect(),
+    )
+}
+
+fn symbols(source: &SourceText, statements: &[Statement], local: bool) -> Vec<Symbol> {
+    statements
+        .iter()
+        .filter_map(|statement| symbol(source, statement, local))
+        .collect()
+}
+
+#[allow(clippy::too_many_lines)] // One arm per outlined declaration form.
+fn symbol(source: &SourceText, statement: &Statement, local: bool) -> Option<Symbol> {
+    let symbol = match statement {
+        Statement::Published { declaration, span } => Symbol {
+            range: *span,
+            ..symbol(source, declaration, local)?
+        },
+        Statement::Function {
+            name, body, span, ..
+        }
+        | Statement::Generator {
+            name, body, span, ..
+        } => Symbol {
+            name: *name,
+            kind: FUNCTION,
+            detail: Some(header(source, *name, *span)),
+            range: *span,
+            children: symbols(source, body, true),
+        },
+        Statement::Union {
+            name,
+            alternatives,
+            span,
+        } => Symbol {
+            name: *name,
+            kind: ENUM,
+            detail: Some("Union".to_owned()),
+            range: *span,
+            children: alternatives
+                .iter()
+                .map(|alternative| {
+                    Symbol::leaf(
+                        alternative.name,
+                        ENUM_MEMBER,
+                        alternative
+                            .classifier
+                            .map(|classifier| source.slice(classifier).to_owned()),
+                        alternative_span(alternative),
+                    )
+                })
+                .collect(),
+        },
+        Statement::Interface {
+            name,
+            functions,
+            span,
+        } => Symbol {
+            name: *name,
+            kind: INTERFACE,
+            detail: Some("Interface".to_owned()),
+            range: *span,
+            children: functions
+                .iter()
+                .map(|function| {
+                    Symbol::leaf(
+                        function.name,
+                        METHOD,
+                        Some(header(source, function.name, function.span)),
+                        function.span,
+                    )
+                })
+                .collect(),
+        },
+        Statement::InterfaceImplementation {
+            interface,
+            declarations,
+            span,
+        } => Symbol {
+            name: *interface,
+            kind: CLASS,
+            detail: Some("implementation".to_owned()),
+            range: *span,
+            children: symbols(source, declarations, false),
+        },
+        Statement::Implementation {
+            name,
+            classifier,
+            declarations,
+            span,
+        } => Symbol {
+            name: *name,
+            kind: CLASS,
+            detail: Some(source.slice(classifier.span()).to_owned()),
+            range: *span,
+            children: symbols(source, declarations, false),
+        },
+        Statement::Binding {
+            name,
+            classifier,
+            value,
+        } => {
+            let kind = binding_kind(source, value);
+            if local && kind == VARIABLE {
+                return None;
+            }
+            Symbol::leaf(
+                *name,
+                kind,
+                classifier.map(|classifier| source.slice(classifier).to_owned()),
+                Span::new(name.start, value.span().end),
+            )
+        }
+        Statement::StateField { name, classifier } => Symbol::leaf(
+            *name,
+            FIELD,
+            Some(source.slice(*classifier).to_owned()),
+            Span::new(name.start, classifier.end),
+        ),
+        _ => return None,
+    };
+    Some(symbol)
+}
+
+/// Classify a binding by the first name of its value: layouts, task kinds,
+/// and variants are type-like declarations rather than plain values.
+fn binding_kind(source: &SourceText, value: &Expression) -> u8 {
+    match value {
+        Expression::AnonymousFunction { .. } => FUNCTION,
+        Expression::Application { items, .. } => items
+            .iter()
+            .find_map(|item| match item {
+                Expression::Identifier(span) => Some(match source.slice(*span) {
+                    "Layout" => STRUCT,
+                    "Task" => CLASS,
+                    "Variant" => ENUM,
+                    _ => VARIABLE,
+                }),
+                _ => None,
+            })
+            .unwrap_or(VARIABLE),
+        _ => VARIABLE,
+    }
+}
+
+/// The first line of a declaration after its `is`, such as
+/// `fn (source : String) -> Boolean`.
+fn header(source: &SourceText, name: Span, span: Span) -> String {
+    let text = source.slice(Span::new(name.end, span.end));
+    let line = text.lines().next().unwrap_or_default().trim();
+    line.strip_prefix("is").unwrap_or(line).trim().to_owned()
+}
+
+fn alternative_span(alternative: &UnionAlternative) -> Span {
+    Span::new(
+        alternative.name.start,
+        alternative
+            .classifier
+            .map_or(alternative.name.end, |classifier| classifier.end),
+    )
+}
+
+/// How one source line takes part in layout.
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Line {
+    Blank,
+    Comment,
+    /// A line whose first token is outside every bracket pair compares its
+    /// indentation with the layout stack; continuation lines do not.
+    Code {
+        indent: usize,
+        layout: bool,
+    },
+}
+
+/// Answer `textDocument/foldingRange`. Indented bodies fold from their header
+/// to the last code line before the dedent that closes them, following the
+/// layout rule that ignores lines inside brackets. Multi-line bracket pairs
+/// and runs of comment lines also fold.
+pub(super) fn folding_ranges(source: &SourceText, lexed: &Lexed) -> Value {
+    let text = source.as_str();
+    let starts = std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(offset, _)| offset + 1))
+        .collect::<Vec<_>>();
+    let line_of = |offset: usize| starts.partition_point(|start| *start <= offset) - 1;
+    let mut lines = vec![Line::Blank; starts.len()];
+    // Start line of each fold with its end line and optional kind.
+    let mut folds = BTreeMap::<usize, (usize, Option<&str>)>::new();
+    let mut fold = |start: usize, end: usize, kind: Option<&'static str>| {
+        if end > start {
+            let entry = folds.entry(start).or_insert((end, kind));
+            entry.0 = entry.0.max(end);
+        }
+    };
+    let mut brackets = Vec::new();
+    for token in &lexed.tokens {
+        if matches!(
+            token.kind,
+            TokenKind::Whitespace | TokenKind::Newline | TokenKind::Hashbang
+        ) {
+            continue;
+        }
+        let line = line_of(token.span.start);
+        if lines[line] == Line::Blank {
+            lines[line] = if matches!(token.kind, TokenKind::Comment | TokenKind::Documentation) {
+                Line::Comment
+            } else {
+                Line::Code {
+                    indent: token.span.start - starts[line],
+                    layout: brackets.is_empty(),
+                }
+            };
+        }
+        // Lines inside a multi-line token, such as a string, continue it.
+        for inner in &mut lines[line + 1..=line_of(token.span.end.saturating_sub(1)).max(line)] {
+            *inner = Line::Code {
+                indent: 0,
+                layout: false,
+            };
+        }
+        match token.kind {
+            TokenKind::LeftParen | TokenKind::LeftBrace | TokenKind::LeftBracket => {
+                brackets.push(line);
+            }
+            TokenKind::RightParen | TokenKind::RightBrace | TokenKind::RightBracket => {
+                if let Some(open) = brackets.pop() {
+                    fold(open, line.saturating_sub(1), None);
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut layout = Vec::<(usize, usize)>::new();
+    let mut last_code = 0;
+    let mut comments = None;
+    for (line, kind) in lines.iter().enumerate() {
+        if *kind == Line::Comment {
+            comments.get_or_insert(line);
+        } else if let Some(start) = comments.take() {
+            fold(start, line - 1, Some("comment"));
+        }
+        match *kind {
+            Line::Code {
+                indent,
+                layout: true,
+            } => {
+                while let Some(&(width, start)) = layout.last()
+                    && width >= indent
+                {
+                    layout.pop();
+                    fold(start, last_code, None);
+                }
+                layout.push((indent, line));
+                last_code = line;
+            }
+            Line::Code { .. } => last_code = line,
+            Line::Blank | Line::Comment => {}
+        }
+    }
+    if let Some(start) = comments {
+        fold(start, lines.len() - 1, Some("comment"));
+    }
+    for (_, start) in layout {
+        fold(start, last_code, None);
+    }
+    Value::Array(
+        folds
+            .into_iter()
+            .map(|(start, (end, kind))| {
+                let mut range = json!({ "startLine": start, "endLine": end });
+                if let Some(kind) = kind {
+                    range["kind"] = json!(kind);
+                }
+                range
+            })
+            .collect(),
+    )
+}
+
+/// Answer `textDocument/selectionRange` with, for each offset, the chain from
+/// the token at the offset through its enclosing syntax to the whole document.
+pub(super) fn selection_ranges(source: &SourceText, lexed: &Lexed, offsets: &[usize]) -> Value {
+    let parsed = parse(source, lexed);
+    Value::Array(
+        offsets
+            .iter()
+            .map(|&offset| {
+                let mut spans = vec![Span::new(0, source.as_str().len())];
+                for statement in &parsed.statements {
+                    enclosing_statement(statement, offset, &mut spans);
+                }
+                let token = lexed
+                    .tokens
+                    .iter()
+                    .filter(|token| {
+                        !matches!(token.kind, TokenKind::Whitespace | TokenKind::Newline)
+                    })
+                    .find(|token| token.span.start <= offset && offset <= token.span.end);
+                if let Some(token) = token {
+                    enclose(&mut spans, token.span, offset);
+                }
+                spans
+                    .iter()
+                    .fold(None, |parent, span| {
+                        let mut range = json!({ "range": protocol_range(source.as_str(), *span) });
+                        if let Some(parent) = parent {
+                            range["parent"] = parent;
+                        }
+                        Some(range)
+                    })
+                    .unwrap_or(Value::Null)
+            })
+            .collect(),
+    )
+}
+
+/// Push `span` when it holds `offset` and nests strictly within the innermost
+/// span so far; report whether it holds `offset`.
+fn enclose(spans: &mut Vec<Span>, span: Span, offset: usize) -> bool {
+    if span.start > offset || offset > span.end {
+        return false;
+    }
+    if let Some(last) = spans.last()
+        && *last != span
+        && last.start <= span.start
+        && span.end <= last.end
+    {
+        spans.push(span);
+    }
+    true
+}
+
+fn enclosing_statement(statement: &Statement, offset: usize, spans: &mut Vec<Span>) {
+    if !enclose(spans, statement_span(statement), offset) {
+        return;
+    }
+    match statement {
+        Statement::Published { declaration, .. } => {
+            enclosing_statement(declaration, offset, spans);
+        }
+        Statement::Binding { value, .. }
+        | Statement::ContextAssignment { value, .. }
+        | Statement::Discard { value, .. }
+        | Statement::Return { value, .. }
+        | Statement::Expression(value) => enclosing_expression(value, offset, spans),
+        Statement::Implementation {
+            classifier,
+            declarations,
+            ..
+        } => {
+            enclosing_expression(classifier, offset, spans);
+            for declaration in declarations {
+                enclosing_statement(declaration, offset, spans);
+            }
+        }
+        Statement::Function {
+            parameters, body, ..
+        }
+        | Statement::Generator {
+            parameters, body, ..
+        } => {
+            enclosing_parameters(parameters, offset, spans);
+            for statement in body {
+                enclosing_statement(statement, offset, spans);
+            }
+        }
+        Statement::Interface { functions, .. } => {
+            for function in functions {
+                if enclose(spans, function.span, offset) {
+                    enclosing_parameters(&function.parameters, offset, spans);
+                }
+            }
+        }
+        Statement::InterfaceImplementation { declarations, .. } => {
+            for declaration in declarations {
+                enclosing_statement(declaration, offset, spans);
+            }
+        }
+        Statement::Foreach { source, body, .. } => {
+            enclosing_expression(source, offset, spans);
+            for statement in body {
+                enclosing_statement(statement, offset, spans);
+            }
+        }
+        Statement::Union { alternatives, .. } => {
+            for alternative in alternatives {
+                enclose(spans, alternative_span(alternative), offset);
+            }
+        }
+        Statement::LanguageSelection { .. }
+        | Statement::DiagnosticControl { .. }
+        | Statement::StateField { .. } => {}
+    }
+}
+
+fn enclosing_parameters(parameters: &[FunctionParameter], offset: usize, spans: &mut Vec<Span>) {
+    for parameter in parameters {
+        let end = parameter
+            .fields
+            .last()
+            .map(|field| field.classifier.end)
+            .into_iter()
+            .chain(parameter.default.as_ref().map(|value| value.span().end))
+            .fold(parameter.classifier.end, usize::max);
+        if enclose(spans, Span::new(parameter.name.start, end), offset) {
+            enclosing_parameters(&parameter.fields, offset, spans);
+            if let Some(default) = &parameter.default {
+                enclosing_expression(default, offset, spans);
+            }
+        }
+    }
+}
+
+fn enclosing_expression(expression: &Expression, offset: usize, spans: &mut Vec<Span>) {
+    if !enclose(spans, expression.span(), offset) {
+        return;
+    }
+    match expression {
+        Expression::Block { statements, .. } => {
+            for statement in statements {
+                enclosing_statement(statement, offset, spans);
+            }
+        }
+        Expression::Product { fields, .. } => {
+            for field in fields {
+                let value = field.value.span();
+                let span = field
+                    .label
+                    .map_or(value, |label| Span::new(label.start, value.end));
+                if enclose(spans, span, offset) {
+                    enclosing_expression(&field.value, offset, spans);
+                }
+            }
+        }
+        Expression::DecisionTable { subject, rules, .. } => {
+            enclosing_expression(subject, offset, spans);
+            for rule in rules {
+                if enclose(spans, rule.span, offset) {
+                    enclosing_expression(&rule.action, offset, spans);
+                }
+            }
+        }
+        Expression::AnonymousFunction { body, .. } => enclosing_expression(body, offset, spans),
+        Expression::Application { items, .. } => {
+            for item in items {
+                enclosing_expression(item, offset, spans);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn statement_span(statement: &Statement) -> Span {
+    match statement {
+        Statement::LanguageSelection { span, .. }
+        | Statement::Published { span, .. }
+        | Statement::DiagnosticControl { span, .. }
+        | Statement::Implementation { span, .. }
+        | Statement::ContextAssignment { span, .. }
+        | Statement::Function { span, .. }
+        | Statement::Generator { span, .. }
+        | Statement::Union { span, .. }
+        | Statement::Interface { span, .. }
+        | Statement::InterfaceImplementation { span, .. }
+        | Statement::Foreach { span, .. }
+        | Statement::Discard { span, .. } => *span,
+        Statement::Binding { name, value, .. } => Span::new(name.start, value.span().end),
+        Statement::StateField { name, classifier } => Span::new(name.start, classifier.end),
+        Statement::Return { keyword, value } => Span::new(keyword.start, value.span().end),
+        Statement::Expression(expression) => expression.span(),
+    }
+}
@@ -0,0 +1,118 @@
+//! Find references and safe rename over the workspace symbol index.
+
+use std::collections::BTreeMap;
+
+use serde_json::{Value, json};
+use topal_source::{SourceText, Span, normalize_nfc};
+use topal_syntax::{TokenKind, lex};
+
+use crate::index::{Index, Occurrence, Symbol};
+use crate::is_keyword;
+use crate::navigation::protocol_range;
+use crate::workspace::Workspace;
+
+/// Answer `textDocument/references` for the declaration named at `offset`.
+pub(super) fn references(
+    uri: &str,
+    offset: usize,
+    include_declaration: bool,
+    workspace: &Workspace,
+) -> Value {
+    let index = Index::build(uri, workspace);
+    let Some((_, symbols)) = symbols_at(&index, uri, offset) else {
+        return Value::Null;
+    };
+    let locations = symbols
+        .iter()
+        .flat_map(|symbol| index.occurrences(symbol))
+        .filter(|occurrence| include_declaration || !occurrence.declaration)
+        .map(|occurrence| location(&index, &occurrence))
+        .collect();
+    Value::Array(locations)
+}
+
+/// Answer `textDocument/prepareRename` with the name's range, or `null` when
+/// it names a built-in, module, or local binder.
+pub(super) fn prepare_rename(uri: &str, offset: usize, workspace: &Workspace) -> Value {
+    let index = Index::build(uri, workspace);
+    let Some((reference, symbols)) = symbols_at(&index, uri, offset) else {
+        return Value::Null;
+    };
+    let file = index.file(
+        index
+            .file_index(uri)
+            .expect("resolved documents are indexed"),
+    );
+    json!({
+        "range": protocol_range(file.analysis.source.as_str(), reference),
+        "placeholder": symbols[0].name
+    })
+}
+
+/// Answer `textDocument/rename` with edits for every occurrence, or a protocol
+/// error code and message when the rename is invalid or unsafe.
+pub(super) fn rename(
+    uri: &str,
+    offset: usize,
+    new_name: &str,
+    workspace: &Workspace,
+) -> Result<Value, (i32, String)> {
+    let name = identifier(new_name)
+        .ok_or_else(|| (-32602, format!("`{new_name}` is not a Topal identifier")))?;
+    let index = Index::build(uri, workspace);
+    let (_, symbols) = symbols_at(&index, uri, offset).ok_or_else(|| {
+        (
+            -32602,
+            "no renameable declaration at this position".to_owned(),
+        )
+    })?;
+    let mut changes = BTreeMap::<String, Vec<Value>>::new();
+    for symbol in &symbols {
+        let occurrences = index.occurrences(symbol);
+        if let Some(collision) = index.rename_collision(symbol, &name, &occurrences) {
+            return Err((-32803, collision));
+        }
+        for occurrence in occurrences {
+            let file = index.file(occurrence.file);
+            changes.entry(file.uri.clone()).or_default().push(json!({
+                "range": protocol_range(file.analysis.source.as_str(), occurrence.span),
+                "newText": name
+            }));
+        }
+    }
+    Ok(json!({ "changes": changes }))
+}
+
+fn symbols_at(index: &Index, uri: &str, offset: usize) -> Option<(Span, Vec<Symbol>)> {
+    let file = index.file_index(uri)?;
+    let (reference, resolution) = index.resolve(file, offset)?;
+    (!resolution.symbols.is_empty()).then_some((reference, resolution.symbols))
+}
+
+fn location(index: &Index, occurrence: &Occurrence) -> Value {
+    let file = index.file(occurrence.file);
+    json!({
+        "uri": file.uri,
+        "range": protocol_range(file.analysis.source.as_str(), occurrence.span)
+    })
+}
+
+/// Normalize a requested spelling to NFC and accept it only when the shared
+/// lexer reads it as exactly one non-keyword identifier.
+fn identifier(name: &str) -> Option<String> {
+    let name = normalize_nfc(name);
+    let source = SourceText::new(&name).ok()?;
+    let lexed = lex(&source);
+    match lexed.tokens.as_slice() {
+        [token]
+            if token.kind == TokenKind::Identifier
+                && token.span == Span::new(0, name.len())
+                && lexed.diagnostics.is_empty()
+                && !is_keyword(&name)
+                && name != "lang" =>
+        {
+            Some(name)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,281 @@
+//! Signature help for applications of declared functions.
+//!
+//! A call is recognized in one flat application: the first name that resolves
+//! to function or generator declarations is the callee. Names before it that
+//! denote modules qualify it; otherwise, for a callee taking two or more
+//! parameters, the single operand before it is the left operand of an infix
+//! call. Operands after the callee bind the remaining parameters in order, and
+//! an unlabeled product spreads over them. Nothing is executed.
+
+use serde_json::{Value, json};
+use topal_source::Span;
+use topal_syntax::{DecisionMatcher, DocumentedDeclaration, Expression, Statement};
+
+use crate::index::Index;
+use crate::workspace::Workspace;
+
+/// One recognized application of a declared function.
+pub(super) struct Call<'a> {
+    pub(super) span: Span,
+    callee: Span,
+    /// Every overload the callee name denotes.
+    pub(super) declarations: Vec<&'a DocumentedDeclaration>,
+    /// Operands in source order with the parameter position each binds.
+    pub(super) arguments: Vec<(usize, &'a Expression)>,
+    /// Whether the application consists of the call alone.
+    pub(super) whole: bool,
+}
+
+/// Answer `textDocument/signatureHelp` for the innermost call around
+/// `offset`.
+pub(super) fn signature_help(uri: &str, offset: usize, workspace: &Workspace) -> Value {
+    let index = Index::build(uri, workspace);
+    let Some(file) = index.file_index(uri) else {
+        return Value::Null;
+    };
+    let text = index.file(file).analysis.source.as_str();
+    let Some(call) = applications(&index.file(file).analysis.statements)
+        .into_iter()
+        .filter(|(span, _)| span.start <= offset && offset <= span.end)
+        .filter_map(|(_, items)| call(&index, file, items))
+        .min_by_key(|call| call.span.end - call.span.start)
+    else {
+        return Value::Null;
+    };
+    let active = active_parameter(text, &call, offset);
+    let signatures = call
+        .declarations
+        .iter()
+        .map(|declaration| {
+            let mut signature = json!({
+                "label": signature_label(&declaration.syntax),
+                "parameters": declaration
+                    .parameters
+                    .iter()
+                    .map(|parameter| {
+                        let mut information = json!({ "label": parameter.syntax });
+                        if let Some(documentation) = &parameter.documentation {
+                            information["documentation"] =
+                                json!({ "kind": "markdown", "value": documentation });
+                        }
+                        information
+                    })
+                    .collect::<Vec<_>>()
+            });
+            if let Some(documentation) = &declaration.documentation {
+                signature["documentation"] = json!({ "kind": "markdown", "value": documentation });
+            }
+            signature
+        })
+        .collect::<Vec<_>>();
+    let active_signature = call
+        .declarations
+        .iter()
+        .position(|declaration| active < declaration.parameters.len())
+        .unwrap_or(0);
+    json!({
+        "signatures": signatures,
+        "activeSignature": active_signature,
+        "activeParameter": active
+    })
+}
+
+/// The declaration header on one line, without documentation and comment
+/// lines inside it, so every parameter's syntax appears in it verbatim.
+fn signature_label(syntax: &str) -> String {
+    syntax
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The parameter the operand at `offset` binds. Past the last operand, a
+/// separating comma or the callee after an infix left operand moves on to the
+/// next parameter.
+fn active_parameter(text: &str, call: &Call<'_>, offset: usize) -> usize {
+    if let Some((parameter, _)) = call
+        .arguments
+        .iter()
+        .find(|(_, argument)| offset <= argument.span().end)
+    {
+        return *parameter;
+    }
+    let Some((parameter, argument)) = call.arguments.last() else {
+        return 0;
+    };
+    let end = argument.span().end;
+    let next = end <= call.callee.start && call.callee.end <= offset
+        || text
+            .get(end..offset)
+            .is_some_and(|between| between.contains(','));
+    parameter + usize::from(next)
+}
+
+/// Recognize a call in the application `items`.
+pub(super) fn call<'a>(index: &'a Index, file: usize, items: &'a [Expression]) -> Option<Call<'a>> {
+    let (position, callee, declarations) =
+        items.iter().enumerate().find_map(|(position, item)| {
+            let Expression::Identifier(name) = item else {
+                return None;
+            };
+            let declarations = functions(index, file, *name);
+            (!declarations.is_empty()).then_some((position, *name, declarations))
+        })?;
+    let qualifiers = items[..position]
+        .iter()
+        .rev()
+        .take_while(|item| match item {
+            Expression::Identifier(name) => {
+                index
+                    .resolve(file, name.start)
+                    .is_some_and(|(_, resolution)| {
+                        resolution.symbols.is_empty() && !resolution.modules.is_empty()
+                    })
+            }
+            _ => false,
+        })
+        .count();
+    let start = position - qualifiers;
+    let arity = declarations
+        .iter()
+        .map(|declaration| declaration.parameters.len())
+        .max()
+        .unwrap_or(0);
+    let mut arguments = Vec::new();
+    let mut first = start;
+    if arity > 1
+        && let Some(left) = start.checked_sub(1).map(|left| &items[left])
+        && !matches!(left, Expression::Callable { .. })
+    {
+        arguments.push((0, left));
+        first -= 1;
+    }
+    let operands = items[position + 1..]
+        .iter()
+        .take_while(|item| !matches!(item, Expression::Callable { .. }))
+        .collect::<Vec<_>>();
+    let next = arguments.len();
+    match operands.as_slice() {
+        [Expression::Product { fields, .. }]
+            if arity - next > 1 && fields.iter().all(|field| field.label.is_none()) =>
+        {
+            arguments.extend(
+                fields
+                    .iter()
+                    .enumerate()
+                    .map(|(offset, field)| (next + offset, &field.value)),
+            );
+        }
+        operands => arguments.extend(
+            operands
+                .iter()
+                .enumerate()
+                .map(|(offset, operand)| (next + offset, *operand)),
+        ),
+    }
+    let end = operands
+        .last()
+        .map_or(callee.end, |operand| operand.span().end);
+    Some(Call {
+        span: Span::new(items[first].span().start, end),
+        callee,
+        declarations,
+        arguments,
+        whole: first == 0 && position + 1 + operands.len() == items.len(),
+    })
+}
+
+/// The function and generator declarations the name at `name` denotes.
+fn functions(index: &Index, file: usize, name: Span) -> Vec<&DocumentedDeclaration> {
+    let Some((reference, resolution)) = index.resolve(file, name.start) else {
+        return Vec::new();
+    };
+    if reference != name {
+        return Vec::new();
+    }
+    resolution
+        .symbols
+        .iter()
+        .flat_map(|symbol| {
+            index
+                .file(symbol.file)
+                .analysis
+                .declarations
+                .iter()
+                .filter(move |declaration| {
+                    declaration.name == symbol.name
+                        && (declaration.published || symbol.file == file)
+                })
+        })
+        .filter(|declaration| matches!(declaration.kind.as_str(), "function" | "generator"))
+        .collect()
+}
+
+/// Every application in `statements`, outermost first.
+pub(super) fn applications(statements: &[Statement]) -> Vec<(Span, &[Expression])> {
+    let mut applications = Vec::new();
+    let mut expressions = Vec::<&Expression>::new();
+    let mut pending = statements.iter().collect::<Vec<_>>();
+    while !pending.is_empty() || !expressions.is_empty() {
+        if let Some(expression) = expressions.pop() {
+            match expression {
+                Expression::Application { items, span } => {
+                    applications.push((*span, items.as_slice()));
+                    expressions.extend(items);
+                }
+                Expression::Block { statements, .. } => pending.extend(statements),
+                Expression::Product { fields, .. } => {
+                    expressions.extend(fields.iter().map(|field| &field.value));
+                }
+                Expression::DecisionTable { subject, rules, .. } => {
+                    expressions.push(subject);
+                    for rule in rules {
+                        if let DecisionMatcher::Comparison { operand, .. } = &rule.matcher {
+                            expressions.push(operand);
+                        }
+                        expressions.push(&rule.action);
+                    }
+                }
+                Expression::AnonymousFunction { body, .. } => expressions.push(body),
+                _ => {}
+            }
+            continue;
+        }
+        let Some(statement) = pending.pop() else {
+            break;
+        };
+        match statement {
+            Statement::Published { declaration, .. } => pending.push(declaration),
+            Statement::Binding { value, .. }
+            | Statement::ContextAssignment { value, .. }
+            | Statement::Discard { value, .. }
+            | Statement::Return { value, .. }
+            | Statement::Expression(value) => expressions.push(value),
+            Statement::Implementation {
+                classifier,
+                declarations,
+                ..
+            } => {
+                expressions.push(classifier);
+                pending.extend(declarations);
+            }
+            Statement::Function { body, .. }
+            | Statement::Generator { body, .. }
+            | Statement::InterfaceImplementation {
+                declarations: body, ..
+            } => pending.extend(body),
+            Statement::Foreach { source, body, .. } => {
+                expressions.push(source);
+                pending.extend(body);
+            }
+            Statement::LanguageSelection { .. }
+            | Statement::DiagnosticControl { .. }
+            | Statement::StateField { .. }
+            | Statement::Union { .. }
+            | Statement::Interface { .. } => {}
+        }
+    }
+    applications
+}
@@ -0,0 +1,219 @@
+//! Open documents with incremental synchronization, and the background index
+//! of unopened workspace files.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use serde_json::Value;
+use topal_source::{SourceText, Span};
+use topal_syntax::{Lexed, ParsedSource, lex, parse, relex};
+
+use crate::index::{DiskCache, uri_path};
+use crate::protocol_offset;
+
+/// Client text of one open document and its shared lexical and syntactic
+/// analysis.
+#[derive(Clone)]
+pub(super) struct Document {
+    /// Exact client text, including any CRLF line endings.
+    pub(super) text: String,
+    source: Option<SourceText>,
+    lexed: Lexed,
+    parsed: ParsedSource,
+}
+
+impl Document {
+    fn new(text: &str) -> Self {
+        let source = SourceText::new(text).ok();
+        let lexed = source.as_ref().map(lex).unwrap_or_default();
+        let parsed = source
+            .as_ref()
+            .map(|source| parse(source, &lexed))
+            .unwrap_or_default();
+        Self {
+            text: text.to_owned(),
+            source,
+            lexed,
+            parsed,
+        }
+    }
+
+    /// The normalized source and its tokens, or `None` when the text is not
+    /// valid Topal source.
+    pub(super) fn lexed(&self) -> Option<(&SourceText, &Lexed)> {
+        self.source.as_ref().map(|source| (source, &self.lexed))
+    }
+
+    /// The normalized source with its tokens and syntax tree, or `None` when
+    /// the text is not valid Topal source.
+    pub(super) fn parsed(&self) -> Option<(&SourceText, &Lexed, &ParsedSource)> {
+        self.source
+            .as_ref()
+            .map(|source| (source, &self.lexed, &self.parsed))
+    }
+
+    /// Reparse after a batch of changes, unless the batch left the normalized
+    /// source as it was. Returns whether the analysis changed.
+    fn reparse(&mut self, previous: Option<&SourceText>) -> bool {
+        if self.source.as_ref().map(SourceText::as_str) == previous.map(SourceText::as_str) {
+            return false;
+        }
+        self.parsed = self
+            .source
+            .as_ref()
+            .map(|source| parse(source, &self.lexed))
+            .unwrap_or_default();
+        true
+    }
+
+    /// Apply one `TextDocumentContentChangeEvent`. A change without a range
+    /// replaces the whole document; a ranged change replaces UTF-16 protocol
+    /// coordinates and re-lexes only the lines it affects.
+    fn apply(&mut self, change: &Value) -> Result<(), String> {
+        let text = change
+            .get("text")
+            .and_then(Value::as_str)
+            .ok_or("content change requires text")?;
+        let Some(range) = change.get("range") else {
+            *self = Self::new(text);
+            return Ok(());
+        };
+        let position = |name: &str| {
+            let line = range
+                .pointer(&format!("/{name}/line"))
+                .and_then(Value::as_u64);
+            let character = range
+                .pointer(&format!("/{name}/character"))
+                .and_then(Value::as_u64);
+            line.zip(character)
+                .and_then(|(line, character)| {
+                    Some((
+                        usize::try_from(line).ok()?,
+                        usize::try_from(character).ok()?,
+                    ))
+                })
+                .ok_or_else(|| format!("content change has an invalid {name} position"))
+        };
+        let (start, end) = (position("start")?, position("end")?);
+        let raw = protocol_offset(&self.text, start.0, start.1)
+            ..protocol_offset(&self.text, end.0, end.1);
+        if raw.start > raw.end {
+            return Err("content change range ends before it starts".into());
+        }
+        // Normalized coordinates of the same protocol range; CRLF and LF text
+        // agree on lines and characters.
+        let replaced = self.source.as_ref().map(|source| {
+            Span::new(
+                protocol_offset(source.as_str(), start.0, start.1),
+                protocol_offset(source.as_str(), end.0, end.1),
+            )
+        });
+        self.text.replace_range(raw, text);
+        let source = SourceText::new(&self.text).ok();
+        self.lexed = match (&source, &self.source, replaced) {
+            (Some(edited), Some(previous), Some(replaced)) => {
+                let inserted = edited.as_str().len() + (replaced.end - replaced.start)
+                    - previous.as_str().len();
+                relex(edited, &self.lexed, replaced, inserted)
+            }
+            (Some(edited), _, _) => lex(edited),
+            (None, _, _) => Lexed::default(),
+        };
+        self.source = source;
+        Ok(())
+    }
+}
+
+/// Open documents, which are authoritative over the filesystem, and the cache
+/// of unopened files analyzed in the background.
+#[derive(Default)]
+pub(super) struct Workspace {
+    documents: BTreeMap<String, Document>,
+    pub(super) cache: Arc<DiskCache>,
+    indexer: Option<JoinHandle<()>>,
+}
+
+impl Workspace {
+    pub(super) fn get(&self, uri: &str) -> Option<&Document> {
+        self.documents.get(uri)
+    }
+
+    pub(super) fn documents(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.documents
+            .iter()
+            .map(|(uri, document)| (uri.as_str(), document.text.as_str()))
+    }
+
+    pub(super) fn open(&mut self, uri: &str, text: &str) {
+        self.documents.insert(uri.to_owned(), Document::new(text));
+    }
+
+    /// Apply content changes in order. The batch is applied to a copy and
+    /// committed only when every change applies, so a rejected change leaves
+    /// the document as it was. Returns the committed document when its
+    /// normalized source changed and `None` when its analysis still holds.
+    pub(super) fn change(
+        &mut self,
+        uri: &str,
+        changes: &[Value],
+    ) -> Result<Option<&Document>, String> {
+        let document = self
+            .documents
+            .get_mut(uri)
+            .ok_or_else(|| format!("document `{uri}` is not open"))?;
+        let mut edited = document.clone();
+        for change in changes {
+            edited.apply(change)?;
+        }
+        let reanalyzed = edited.reparse(document.source.as_ref());
+        *document = edited;
+        Ok(reanalyzed.then_some(&*document))
+    }
+
+    pub(super) fn close(&mut self, uri: &str) {
+        self.documents.remove(uri);
+    }
+
+    /// Start analyzing every source file below the initialization's workspace
+    /// folders, or its root URI when no folders are given.
+    pub(super) fn index_folders(&mut self, message: &Value) {
+        let mut folders = message
+            .pointer("/params/workspaceFolders")
+            .and_then(Value::as_array)
+            .map(|folders| {
+                folders
+                    .iter()
+                    .filter_map(|folder| folder.get("uri").and_then(Value::as_str))
+                    .filter_map(uri_path)
+                    .collect::<Vec<PathBuf>>()
+            })
+            .unwrap_or_default();
+        if folders.is_empty()
+            && let Some(root) = message
+                .pointer("/params/rootUri")
+                .and_then(Value::as_str)
+                .and_then(uri_path)
+        {
+            folders.push(root);
+        }
+        if folders.is_empty() {
+            return;
+        }
+        let cache = Arc::clone(&self.cache);
+        self.indexer = Some(thread::spawn(move || {
+            for folder in folders {
+                cache.index_folder(&folder);
+            }
+        }));
+    }
+
+    /// Block until background indexing has finished.
+    #[cfg(test)]
+    pub(super) fn wait_for_index(&mut self) {
+        if let Some(indexer) = self.indexer.take() {
+            indexer.join().expect("background indexing does not panic");
+        }
+    }
+}
@@ -1658,11 +1658,38 @@ pub enum SemanticIdentity {
     Value(String),
 }
 
+/// Direct-information provenance from `TOPAL-SENSITIVE-PROVENANCE-001`.
+///
+/// Sensitivity never changes a classifier, equality, or overload selection.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Sensitivity {
+    #[default]
+    Public,
+    Sensitive,
+}
+
+impl Sensitivity {
+    /// The provenance of a value carrying information from both operands.
+    #[must_use]
+    pub const fn join(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Public, Self::Public) => Self::Public,
+            _ => Self::Sensitive,
+        }
+    }
+
+    #[must_use]
+    pub const fn is_sensitive(self) -> bool {
+        matches!(self, Self::Sensitive)
+    }
+}
+
 /// The reusable result of semantic analysis under `TOPAL-TYPE-JUDGE-001`.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct TypedObject {
     pub kind: ObjectKind,
     pub classifier: Option<TypeIdentity>,
+    pub sensitivity: Sensitivity,
     pub effects: EffectSet,
     pub relationships: Vec<Relationship>,
 }
@@ -1736,6 +1763,24 @@ mod tests {
         assert_eq!(left.iter().count(), 2);
     }
 
+    #[test]
+    fn sensitivity_survives_every_join() {
+        let copied = Sensitivity::Sensitive.join(Sensitivity::Public);
+        assert!(copied.is_sensitive());
+        assert_eq!(
+            Sensitivity::Public.join(Sensitivity::Public),
+            Sensitivity::default()
+        );
+        let object = TypedObject {
+            kind: ObjectKind::Value,
+            classifier: Some(TypeIdentity::Fundamental("String")),
+            sensitivity: copied,
+            effects: EffectSet::default(),
+            relationships: Vec::new(),
+        };
+        assert_eq!(object.classifier, Some(TypeIdentity::Fundamental("String")));
+    }
+
     #[test]
     fn declarations_retain_source_order() {
         let mut scope = SemanticScope::default();
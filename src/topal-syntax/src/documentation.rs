@@ -12,10 +12,16 @@ pub struct DocumentedParameter {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct DocumentedDeclaration {
     pub name: String,
+    /// Source span of the declared name; `None` for built-in declarations.
+    pub name_span: Option<Span>,
+    /// Whether the declaration is visible outside its module.
+    pub published: bool,
     pub kind: String,
     pub syntax: String,
     pub documentation: Option<String>,
     pub parameters: Vec<DocumentedParameter>,
+    /// Declared result classifier of a function or generator.
+    pub result: Option<String>,
 }
 
 #[must_use]
@@ -41,20 +47,25 @@ fn declaration(
     statement: &Statement,
     lower_bound: usize,
 ) -> Option<DocumentedDeclaration> {
-    let (inner, syntax_start) = match statement {
-        Statement::Published { declaration, span } => (declaration.as_ref(), span.start),
-        _ => (statement, statement_extent(statement).start),
+    let (inner, syntax_start, published) = match statement {
+        Statement::Published { declaration, span } => (declaration.as_ref(), span.start, true),
+        _ => (statement, statement_extent(statement).start, false),
     };
-    let (name_span, kind, syntax_end, parameters) = match inner {
+    let (name_span, kind, syntax_end, parameters, result) = match inner {
         Statement::Binding { name, value, .. } => (
             *name,
             "binding",
             line_end(source, value.span().end),
             &[][..],
+            None,
+        ),
+        Statement::StateField { name, classifier } => (
+            *name,
+            "field",
+            line_end(source, classifier.end),
+            &[][..],
+            None,
         ),
-        Statement::StateField { name, classifier } => {
-            (*name, "field", line_end(source, classifier.end), &[][..])
-        }
         Statement::Function {
             name,
             parameters,
@@ -66,6 +77,7 @@ fn declaration(
             "function",
             line_end(source, effect_bound.unwrap_or(*result).end),
             parameters.as_slice(),
+            Some(*result),
         ),
         Statement::Generator {
             name,
@@ -77,24 +89,38 @@ fn declaration(
             "generator",
             line_end(source, result.end),
             parameters.as_slice(),
+            Some(*result),
         ),
-        Statement::Union { name, span, .. } => (*name, "type", line_end(source, span.end), &[][..]),
-        Statement::Interface { name, span, .. } => {
-            (*name, "interface", line_end(source, span.end), &[][..])
-        }
-        Statement::Implementation { name, span, .. } => {
-            (*name, "implementation", line_end(source, span.end), &[][..])
+        Statement::Union { name, span, .. } => {
+            (*name, "type", line_end(source, span.end), &[][..], None)
         }
+        Statement::Interface { name, span, .. } => (
+            *name,
+            "interface",
+            line_end(source, span.end),
+            &[][..],
+            None,
+        ),
+        Statement::Implementation { name, span, .. } => (
+            *name,
+            "implementation",
+            line_end(source, span.end),
+            &[][..],
+            None,
+        ),
         _ => return None,
     };
     Some(DocumentedDeclaration {
         name: source.slice(name_span).to_owned(),
+        name_span: Some(name_span),
+        published,
         kind: kind.to_owned(),
         syntax: source.as_str()[syntax_start..syntax_end]
             .trim_end()
             .to_owned(),
         documentation: documentation_before(source, tokens, syntax_start, lower_bound),
         parameters: documented_parameters(source, tokens, parameters, name_span.end),
+        result: result.map(|result| source.slice(result).to_owned()),
     })
 }
 
@@ -213,6 +239,11 @@ mod tests {
         assert!(parsed.diagnostics.is_empty(), "{:?}", parsed.diagnostics);
         let declarations = extract_documentation(&source, &lexed, &parsed);
         assert_eq!(declarations.len(), 1);
+        assert!(declarations[0].published);
+        assert_eq!(
+            declarations[0].name_span.map(|span| source.slice(span)),
+            Some("compare")
+        );
         assert_eq!(
             declarations[0].documentation.as_deref(),
             Some("Compare values.")
@@ -222,5 +253,6 @@ mod tests {
             Some("Used when values tie.")
         );
         assert_eq!(declarations[0].parameters[1].documentation, None);
+        assert_eq!(declarations[0].result.as_deref(), Some("Int"));
     }
 }
@@ -8,9 +8,9 @@ mod documentation;
 mod parser;
 pub use documentation::{DocumentedDeclaration, DocumentedParameter, extract_documentation};
 pub use parser::{
-    CallableKind, DecisionMatcher, DecisionRule, DiagnosticControlKind, Expression,
-    FunctionParameter, InterfaceFunction, ParsedSource, ProductField, Statement, UnionAlternative,
-    parse,
+    CallableKind, Continuation, DecisionMatcher, DecisionRule, DiagnosticControlKind, Expression,
+    FunctionParameter, IncompleteInput, InterfaceFunction, ParsedSource, ProductField, Statement,
+    UnionAlternative, UnitExpression, parse,
 };
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -89,58 +89,136 @@ pub struct Lexed {
 
 #[must_use]
 pub fn lex(source: &SourceText) -> Lexed {
-    let text = source.as_str();
     let mut result = Lexed::default();
     let mut offset = 0;
-    while offset < text.len() {
-        let rest = &text[offset..];
-        let (kind, length) = if offset == 0 && rest.starts_with("#!") {
-            (TokenKind::Hashbang, rest.find('\n').unwrap_or(rest.len()))
-        } else {
-            let left_delimited = offset == 0
-                || text[..offset]
-                    .chars()
-                    .next_back()
-                    .is_some_and(is_token_boundary);
-            next_token(rest, left_delimited)
-        };
-        let span = Span::new(offset, offset + length);
-        result.tokens.push(Token { kind, span });
-        if kind == TokenKind::Unknown {
-            result.diagnostics.push(SyntaxDiagnostic {
-                code: "E-UNKNOWN-TOKEN",
-                span,
-                message: "character does not begin a token in design-0".into(),
-            });
-        }
-        if kind == TokenKind::String && !string_is_terminated(source.slice(span)) {
-            result.diagnostics.push(SyntaxDiagnostic {
-                code: "E-UNTERMINATED-STRING",
-                span,
-                message: "string literal has no matching closing delimiter".into(),
-            });
-        }
-        if kind == TokenKind::Identifier && !is_nfc(source.slice(span)) {
-            result.diagnostics.push(SyntaxDiagnostic {
-                code: "E-NON-NFC-TOKEN",
-                span,
-                message: "source token is not Unicode Normalization Form C".into(),
-            });
+    while offset < source.as_str().len() {
+        offset = lex_token(source, offset, &mut result).span.end;
+    }
+    result
+}
+
+/// Re-lex `source` after `replaced`, a span of the previously lexed text, was
+/// replaced by `inserted` bytes.
+///
+/// Lexing restarts at the start of the first edited line and stops as soon as
+/// a newline after the edit coincides with a previous newline; tokens and
+/// diagnostics outside that region are reused with shifted spans. The result
+/// is identical to lexing `source` from scratch.
+#[must_use]
+pub fn relex(source: &SourceText, previous: &Lexed, replaced: Span, inserted: usize) -> Lexed {
+    let restart = previous
+        .tokens
+        .iter()
+        .rev()
+        .find(|token| token.kind == TokenKind::Newline && token.span.end <= replaced.start)
+        .map_or(0, |token| token.span.end);
+    let mut result = Lexed {
+        tokens: previous
+            .tokens
+            .iter()
+            .take_while(|token| token.span.end <= restart)
+            .copied()
+            .collect(),
+        diagnostics: previous
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.span.end <= restart)
+            .cloned()
+            .collect(),
+    };
+    let edited_end = replaced.start + inserted;
+    let shift = |span: Span| {
+        Span::new(
+            span.start - replaced.end + edited_end,
+            span.end - replaced.end + edited_end,
+        )
+    };
+    let mut offset = restart;
+    while offset < source.as_str().len() {
+        let token = lex_token(source, offset, &mut result);
+        offset = token.span.end;
+        if token.kind != TokenKind::Newline || token.span.start < edited_end {
+            continue;
         }
-        if kind == TokenKind::String
-            && let Some(tag_span) = non_nfc_string_tag(source.slice(span), span.start)
-        {
-            result.diagnostics.push(SyntaxDiagnostic {
-                code: "E-NON-NFC-TOKEN",
-                span: tag_span,
-                message: "string literal tag is not Unicode Normalization Form C".into(),
-            });
+        let previous_start = token.span.start - edited_end + replaced.end;
+        let Ok(position) = previous
+            .tokens
+            .binary_search_by_key(&previous_start, |token| token.span.start)
+        else {
+            continue;
+        };
+        if previous.tokens[position].kind == TokenKind::Newline {
+            result
+                .tokens
+                .extend(previous.tokens[position + 1..].iter().map(|token| Token {
+                    kind: token.kind,
+                    span: shift(token.span),
+                }));
+            result.diagnostics.extend(
+                previous
+                    .diagnostics
+                    .iter()
+                    .filter(|diagnostic| diagnostic.span.start > previous_start)
+                    .map(|diagnostic| SyntaxDiagnostic {
+                        span: shift(diagnostic.span),
+                        ..diagnostic.clone()
+                    }),
+            );
+            break;
         }
-        offset += length;
     }
     result
 }
 
+fn lex_token(source: &SourceText, offset: usize, result: &mut Lexed) -> Token {
+    let text = source.as_str();
+    let rest = &text[offset..];
+    let (kind, length) = if offset == 0 && rest.starts_with("#!") {
+        (TokenKind::Hashbang, rest.find('\n').unwrap_or(rest.len()))
+    } else {
+        let left_delimited = offset == 0
+            || text[..offset]
+                .chars()
+                .next_back()
+                .is_some_and(is_token_boundary);
+        next_token(rest, left_delimited)
+    };
+    let span = Span::new(offset, offset + length);
+    let token = Token { kind, span };
+    result.tokens.push(token);
+    if kind == TokenKind::Unknown {
+        result.diagnostics.push(SyntaxDiagnostic {
+            code: "E-UNKNOWN-TOKEN",
+            span,
+            message: "character does not begin a token in design-0".into(),
+        });
+    }
+    if kind == TokenKind::String && !string_is_terminated(source.slice(span)) {
+        result.diagnostics.push(SyntaxDiagnostic {
+            code: "E-UNTERMINATED-STRING",
+            span,
+            message: "string literal has no matching closing delimiter".into(),
+        });
+    }
+    if kind == TokenKind::Identifier && !is_nfc(source.slice(span)) {
+        result.diagnostics.push(SyntaxDiagnostic {
+            code: "E-NON-NFC-TOKEN",
+            span,
+            message: "source token is not Unicode Normalization Form C".into(),
+        });
+    }
+    if kind == TokenKind::String
+        && let Some(tag_span) = non_nfc_string_tag(source.slice(span), span.start)
+    {
+        result.diagnostics.push(SyntaxDiagnostic {
+            code: "E-NON-NFC-TOKEN",
+            span: tag_span,
+            message: "string literal tag is not Unicode Normalization Form C".into(),
+        });
+    }
+    token
+}
+
 fn next_token(rest: &str, left_delimited: bool) -> (TokenKind, usize) {
     let first = rest.chars().next().expect("nonempty source");
     match first {
@@ -344,6 +422,28 @@ mod tests {
             .collect()
     }
 
+    #[test]
+    fn relexing_an_edit_matches_lexing_from_scratch() {
+        let original = "#!/usr/bin/env topal\nname is \"left\"\n# note\nvalue + 2\ntail is 3\n";
+        let previous = lex(&SourceText::new(original).unwrap());
+        for (start, end, replacement) in [
+            (22, 26, "renamed"),
+            (30, 35, "\"open"),
+            (42, 42, "\n"),
+            (47, 52, "-"),
+            (0, 2, "x"),
+            (original.len(), original.len(), "more\n"),
+        ] {
+            let edited = format!("{}{replacement}{}", &original[..start], &original[end..]);
+            let source = SourceText::new(&edited).unwrap();
+            assert_eq!(
+                relex(&source, &previous, Span::new(start, end), replacement.len()),
+                lex(&source),
+                "{edited:?}"
+            );
+        }
+    }
+
     #[test]
     fn preserves_trivia_and_symbolic_application() {
         assert_eq!(
@@ -22,7 +22,7 @@ pub enum Expression {
     Integer(Span),
     Measured {
         value: Span,
-        unit: Span,
+        unit: UnitExpression,
         span: Span,
     },
     Rational(Span),
@@ -87,6 +87,50 @@ impl Expression {
     }
 }
 
+/// A bracketed unit suffix. Operators associate left to right without
+/// precedence, as in ordinary applications; parentheses group.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UnitExpression {
+    Atom(Span),
+    Delta {
+        unit: Span,
+        span: Span,
+    },
+    Product {
+        left: Box<Self>,
+        right: Box<Self>,
+        span: Span,
+    },
+    Quotient {
+        left: Box<Self>,
+        right: Box<Self>,
+        span: Span,
+    },
+    Power {
+        base: Box<Self>,
+        exponent: Span,
+        span: Span,
+    },
+    Group {
+        inner: Box<Self>,
+        span: Span,
+    },
+}
+
+impl UnitExpression {
+    #[must_use]
+    pub const fn span(&self) -> Span {
+        match self {
+            Self::Atom(span)
+            | Self::Delta { span, .. }
+            | Self::Product { span, .. }
+            | Self::Quotient { span, .. }
+            | Self::Power { span, .. }
+            | Self::Group { span, .. } => *span,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ProductField {
     pub label: Option<Span>,
@@ -268,6 +312,48 @@ pub enum DiagnosticControlKind {
 pub struct ParsedSource {
     pub statements: Vec<Statement>,
     pub diagnostics: Vec<SyntaxDiagnostic>,
+    /// Present when the source ends inside an open construct, so that an
+    /// interactive reader can request more input instead of reporting the
+    /// accompanying diagnostic.
+    pub incomplete: Option<IncompleteInput>,
+}
+
+/// The construct an incomplete source still needs in order to close.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Continuation {
+    StringTerminator,
+    ClosingParenthesis,
+    ClosingBrace,
+    IndentedBody,
+    GeneratorHeader,
+    UnionAlternatives,
+    InterfaceOperations,
+    DecisionRules,
+}
+
+impl Continuation {
+    /// Stable name used by tool protocols.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::StringTerminator => "string-terminator",
+            Self::ClosingParenthesis => "closing-parenthesis",
+            Self::ClosingBrace => "closing-brace",
+            Self::IndentedBody => "indented-body",
+            Self::GeneratorHeader => "generator-header",
+            Self::UnionAlternatives => "union-alternatives",
+            Self::InterfaceOperations => "interface-operations",
+            Self::DecisionRules => "decision-rules",
+        }
+    }
+}
+
+/// Recovery state for source that ended before `expected` was supplied.
+/// `span` is the span of the diagnostic the continuation would resolve.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IncompleteInput {
+    pub expected: Continuation,
+    pub span: Span,
 }
 
 #[must_use]
@@ -279,6 +365,7 @@ pub fn parse(source: &SourceText, lexed: &Lexed) -> ParsedSource {
         delimiter_depth: 0,
         current_indent: 0,
         diagnostics: lexed.diagnostics.clone(),
+        incomplete: unterminated_string(source, lexed),
     };
     let mut statements = Vec::new();
     while parser.skip_separators() {
@@ -297,6 +384,22 @@ pub fn parse(source: &SourceText, lexed: &Lexed) -> ParsedSource {
     ParsedSource {
         statements,
         diagnostics: parser.diagnostics,
+        incomplete: parser.incomplete,
+    }
+}
+
+fn unterminated_string(source: &SourceText, lexed: &Lexed) -> Option<IncompleteInput> {
+    match lexed.diagnostics.as_slice() {
+        [diagnostic]
+            if diagnostic.code == "E-UNTERMINATED-STRING"
+                && diagnostic.span.end == source.as_str().len() =>
+        {
+            Some(IncompleteInput {
+                expected: Continuation::StringTerminator,
+                span: diagnostic.span,
+            })
+        }
+        _ => None,
     }
 }
 
@@ -307,9 +410,10 @@ struct Parser<'a> {
     delimiter_depth: usize,
     current_indent: usize,
     diagnostics: Vec<SyntaxDiagnostic>,
+    incomplete: Option<IncompleteInput>,
 }
 
-impl Parser<'_> {
+impl<'a> Parser<'a> {
     fn statement(&mut self) -> Option<Statement> {
         if self.foreach_separator_index().is_some() {
             return self.foreach_statement();
@@ -784,7 +888,12 @@ impl Parser<'_> {
         let body_start = self.cursor + 1;
         let indent = self.tokens.get(body_start)?;
         let width = indent.span.end - indent.span.start;
-        (indent.kind == TokenKind::Whitespace && width > self.current_indent)
+        // A line holding only spaces is blank and cannot begin a body.
+        let blank = self
+            .tokens
+            .get(body_start + 1)
+            .is_none_or(|token| token.kind == TokenKind::Newline);
+        (indent.kind == TokenKind::Whitespace && width > self.current_indent && !blank)
             .then_some((width, body_start))
     }
 
@@ -824,9 +933,10 @@ impl Parser<'_> {
             delimiter_depth: 0,
             current_indent: self.current_indent,
             diagnostics: Vec::new(),
+            incomplete: None,
         };
         let source_expression = source_parser.expression()?;
-        self.diagnostics.extend(source_parser.diagnostics);
+        self.absorb(source_parser, separator_index);
         self.cursor = separator_index + 1;
         let opening = self.take_nontrivia()?;
         let binding = self.take_nontrivia()?;
@@ -849,6 +959,7 @@ impl Parser<'_> {
                 span: closing.span,
                 message: "expected an indented foreach body on the next line".into(),
             });
+            self.continues_with(Continuation::IndentedBody);
             return None;
         }
         self.cursor += 1;
@@ -858,6 +969,7 @@ impl Parser<'_> {
                 span: closing.span,
                 message: "expected an indented foreach body on the next line".into(),
             });
+            self.continues_with(Continuation::IndentedBody);
             return None;
         };
         if indent.kind != TokenKind::Whitespace {
@@ -941,6 +1053,7 @@ impl Parser<'_> {
                 span: Span::new(result.end, result.end),
                 message: "expected an indented function body on the next line".into(),
             });
+            self.continues_with(Continuation::IndentedBody);
             return None;
         }
         self.cursor += 1;
@@ -950,6 +1063,7 @@ impl Parser<'_> {
                 span: Span::new(result.end, result.end),
                 message: "expected an indented function body on the next line".into(),
             });
+            self.continues_with(Continuation::IndentedBody);
             return None;
         };
         if indent.kind != TokenKind::Whitespace {
@@ -976,6 +1090,7 @@ impl Parser<'_> {
                 span: Span::new(body_end, body_end),
                 message: "expected a final expression producing the function result".into(),
             });
+            self.continues_with(Continuation::IndentedBody);
             return None;
         }
         Some(Statement::Function {
@@ -1081,6 +1196,7 @@ impl Parser<'_> {
                 span: union.span,
                 message: "a Union requires at least one alternative".into(),
             });
+            self.continues_with(Continuation::UnionAlternatives);
             return None;
         }
         Some(Statement::Union {
@@ -1098,11 +1214,13 @@ impl Parser<'_> {
                 "E-EXPECTED-INTERFACE-OPERATIONS",
                 "interface operations begin on following indented lines",
             );
+            self.continues_with(Continuation::InterfaceOperations);
             return None;
         }
         self.cursor += 1;
         let Some(indent) = self.peek() else {
             self.error_current("E-EMPTY-INTERFACE", "an interface requires an operation");
+            self.continues_with(Continuation::InterfaceOperations);
             return None;
         };
         if indent.kind != TokenKind::Whitespace {
@@ -1165,6 +1283,7 @@ impl Parser<'_> {
         }
         let Some(last) = functions.last() else {
             self.error_current("E-EMPTY-INTERFACE", "an interface requires an operation");
+            self.continues_with(Continuation::InterfaceOperations);
             return None;
         };
         Some(Statement::Interface {
@@ -1203,6 +1322,7 @@ impl Parser<'_> {
                 "E-EXPECTED-GENERATOR-BODY",
                 "expected an indented generator body",
             );
+            self.continues_with(Continuation::IndentedBody);
             return None;
         };
         if indent.kind != TokenKind::Whitespace {
@@ -1339,6 +1459,18 @@ impl Parser<'_> {
             let payload = self.generator_classifier()?;
             return Some(Span::new(first.span.start, payload.end));
         }
+        if self.source.slice(first.span) == "Sensitive" {
+            let payload_start = self.take_nontrivia()?;
+            let payload = self.classifier_from_first(payload_start)?;
+            return Some(Span::new(first.span.start, payload.end));
+        }
+        if self.source.slice(first.span) == "Delta" {
+            let dimension = self.take_nontrivia()?;
+            if dimension.kind != TokenKind::Identifier {
+                return None;
+            }
+            return Some(Span::new(first.span.start, dimension.span.end));
+        }
         if self.source.slice(first.span) == "Generator" {
             let _yielded = self.generator_classifier()?;
             let _resumed = self.generator_classifier()?;
@@ -1357,6 +1489,7 @@ impl Parser<'_> {
                 "E-UNSUPPORTED-GENERATOR-HEADER",
                 "generator header clauses must start on separate indented lines",
             );
+            self.continues_with(Continuation::GeneratorHeader);
             return None;
         }
         self.cursor += 1;
@@ -1368,6 +1501,7 @@ impl Parser<'_> {
                 "E-EXPECTED-INDENTED-GENERATOR-HEADER",
                 "generator header clauses must be indented",
             );
+            self.continues_with(Continuation::GeneratorHeader);
             return None;
         }
         self.cursor += 1;
@@ -1529,6 +1663,7 @@ impl Parser<'_> {
                 span: Span::new(subject.span().start, end),
                 message,
             });
+            self.continues_with(Continuation::DecisionRules);
             return None;
         }
         let end = rules.last().expect("complete decision has rules").span.end;
@@ -1931,9 +2066,10 @@ impl Parser<'_> {
             delimiter_depth: 0,
             current_indent: self.current_indent,
             diagnostics: Vec::new(),
+            incomplete: None,
         };
         let operand = parser.expression();
-        self.diagnostics.extend(parser.diagnostics);
+        self.absorb(parser, separator_index);
         self.cursor = separator_index + 1;
         operand
     }
@@ -2103,32 +2239,42 @@ impl Parser<'_> {
         let token = self.take_nontrivia()?;
         match token.kind {
             TokenKind::Boolean => Some(Expression::Boolean(token.span)),
-            TokenKind::Integer => {
+            TokenKind::Integer | TokenKind::Rational
                 if self
                     .peek_nontrivia()
-                    .is_some_and(|next| next.kind == TokenKind::LeftBracket)
-                {
-                    self.take_nontrivia();
-                    let unit = self.take_nontrivia()?;
-                    let closing = self.take_nontrivia()?;
-                    if unit.kind != TokenKind::Identifier || closing.kind != TokenKind::RightBracket
-                    {
+                    .is_some_and(|next| next.kind == TokenKind::LeftBracket) =>
+            {
+                self.take_nontrivia();
+                let unit = self.unit_expression();
+                let closing = self.take_nontrivia();
+                match (unit, closing) {
+                    (Some(unit), Some(closing)) if closing.kind == TokenKind::RightBracket => {
+                        Some(Expression::Measured {
+                            value: token.span,
+                            unit,
+                            span: Span::new(token.span.start, closing.span.end),
+                        })
+                    }
+                    (_, closing) => {
+                        let mut end = closing.map_or(token.span.end, |closing| closing.span.end);
+                        if closing.is_some_and(|closing| closing.kind != TokenKind::RightBracket) {
+                            while let Some(skipped) = self.take_nontrivia() {
+                                end = skipped.span.end;
+                                if skipped.kind == TokenKind::RightBracket {
+                                    break;
+                                }
+                            }
+                        }
                         self.diagnostics.push(SyntaxDiagnostic {
                             code: "E-MEASUREMENT-SUFFIX",
-                            span: Span::new(token.span.start, closing.span.end),
-                            message: "a size suffix has the form `[b]` or `[B]`".into(),
+                            span: Span::new(token.span.start, end),
+                            message: "a unit suffix has the form `[unit]`, combining unit atoms with `*`, `/`, `^ Integer`, `delta`, and parentheses".into(),
                         });
-                        return None;
+                        None
                     }
-                    Some(Expression::Measured {
-                        value: token.span,
-                        unit: unit.span,
-                        span: Span::new(token.span.start, closing.span.end),
-                    })
-                } else {
-                    Some(Expression::Integer(token.span))
                 }
             }
+            TokenKind::Integer => Some(Expression::Integer(token.span)),
             TokenKind::Rational => Some(Expression::Rational(token.span)),
             TokenKind::String => Some(Expression::String(token.span)),
             TokenKind::Identifier | TokenKind::Version => Some(Expression::Identifier(token.span)),
@@ -2247,6 +2393,19 @@ impl Parser<'_> {
                 span: opening.span,
                 message: "expected a closing brace".into(),
             });
+            // The open block extends to the end of input. Input ending inside
+            // a construct nested in it continues that construct first; input
+            // whose block content is otherwise complete needs only the brace.
+            let mut block_parser = self.block_parser(start, self.tokens.len());
+            block_parser.block_statements();
+            self.cursor = self.tokens.len();
+            match block_parser.incomplete {
+                Some(incomplete) => self.continues_with(incomplete.expected),
+                None if block_parser.diagnostics.is_empty() => {
+                    self.continues_with(Continuation::ClosingBrace);
+                }
+                None => {}
+            }
             return None;
         };
         if self.braces_introduce_anonymous_function(start, closing_index) {
@@ -2254,7 +2413,20 @@ impl Parser<'_> {
         }
 
         let closing = self.tokens[closing_index];
-        let block_indent = self.tokens[start..closing_index]
+        let mut block_parser = self.block_parser(start, closing_index);
+        let statements = block_parser.block_statements();
+        self.absorb(block_parser, closing_index);
+        self.cursor = closing_index + 1;
+        Some(Expression::Block {
+            statements,
+            span: Span::new(opening.span.start, closing.span.end),
+        })
+    }
+
+    /// A parser for the block statements between `start` and `end`, indented
+    /// as deeply as the least indented line among them.
+    fn block_parser(&self, start: usize, end: usize) -> Parser<'a> {
+        let block_indent = self.tokens[start..end]
             .windows(2)
             .filter_map(|tokens| {
                 (tokens[0].kind == TokenKind::Newline && tokens[1].kind == TokenKind::Whitespace)
@@ -2262,37 +2434,36 @@ impl Parser<'_> {
             })
             .min()
             .unwrap_or(self.current_indent);
-        let mut block_parser = Self {
+        Parser {
             source: self.source,
-            tokens: &self.tokens[start..closing_index],
+            tokens: &self.tokens[start..end],
             cursor: 0,
             delimiter_depth: 0,
             current_indent: block_indent,
             diagnostics: Vec::new(),
-        };
+            incomplete: None,
+        }
+    }
+
+    fn block_statements(&mut self) -> Vec<Statement> {
         let mut statements = Vec::new();
-        while block_parser.skip_separators() {
-            if let Some(statement) = block_parser.statement() {
+        while self.skip_separators() {
+            if let Some(statement) = self.statement() {
                 statements.push(statement);
             }
-            if block_parser
+            if self
                 .peek()
                 .is_some_and(|token| token.kind != TokenKind::Newline)
             {
-                block_parser.error_current(
+                self.error_current(
                     "E-UNEXPECTED-TOKEN",
                     "unexpected token after block statement",
                 );
-                block_parser.skip_to_newline();
+                self.skip_to_newline();
             }
         }
-        validate_diagnostic_controls(self.source, &statements, &mut block_parser.diagnostics);
-        self.diagnostics.extend(block_parser.diagnostics);
-        self.cursor = closing_index + 1;
-        Some(Expression::Block {
-            statements,
-            span: Span::new(opening.span.start, closing.span.end),
-        })
+        validate_diagnostic_controls(self.source, &statements, &mut self.diagnostics);
+        statements
     }
 
     fn braces_introduce_anonymous_function(&self, start: usize, closing: usize) -> bool {
@@ -2397,6 +2568,7 @@ impl Parser<'_> {
                 span: Span::new(opening.span.end, opening.span.end),
                 message: "expected expression and closing parenthesis".into(),
             });
+            self.continues_with(Continuation::ClosingParenthesis);
             return None;
         };
         let product = first.label.is_some()
@@ -2425,6 +2597,7 @@ impl Parser<'_> {
                         span: Span::new(opening.span.end, opening.span.end),
                         message: "expected product field or closing parenthesis".into(),
                     });
+                    self.continues_with(Continuation::ClosingParenthesis);
                     return None;
                 };
                 fields.push(field);
@@ -2437,6 +2610,7 @@ impl Parser<'_> {
                 span: Span::new(opening.span.end, opening.span.end),
                 message: "expected closing parenthesis".into(),
             });
+            self.continues_with(Continuation::ClosingParenthesis);
         }
         if product {
             let first_labeled = fields[0].label.is_some();
@@ -2481,6 +2655,74 @@ impl Parser<'_> {
             .map(|value| ProductField { label: None, value })
     }
 
+    /// Parse the inside of a unit suffix up to, but excluding, its `]`.
+    fn unit_expression(&mut self) -> Option<UnitExpression> {
+        let mut expression = self.unit_operand()?;
+        while let Some(operator) = self.peek_nontrivia().filter(|token| {
+            matches!(
+                token.kind,
+                TokenKind::Star | TokenKind::Slash | TokenKind::Caret
+            )
+        }) {
+            self.take_nontrivia();
+            let start = expression.span().start;
+            expression = if operator.kind == TokenKind::Caret {
+                let exponent = self
+                    .take_nontrivia()
+                    .filter(|token| token.kind == TokenKind::Integer)?;
+                UnitExpression::Power {
+                    base: Box::new(expression),
+                    exponent: exponent.span,
+                    span: Span::new(start, exponent.span.end),
+                }
+            } else {
+                let right = self.unit_operand()?;
+                let span = Span::new(start, right.span().end);
+                if operator.kind == TokenKind::Star {
+                    UnitExpression::Product {
+                        left: Box::new(expression),
+                        right: Box::new(right),
+                        span,
+                    }
+                } else {
+                    UnitExpression::Quotient {
+                        left: Box::new(expression),
+                        right: Box::new(right),
+                        span,
+                    }
+                }
+            };
+        }
+        Some(expression)
+    }
+
+    fn unit_operand(&mut self) -> Option<UnitExpression> {
+        let token = self.take_nontrivia()?;
+        match token.kind {
+            TokenKind::Identifier if self.source.slice(token.span) == "delta" => {
+                let unit = self
+                    .take_nontrivia()
+                    .filter(|unit| unit.kind == TokenKind::Identifier)?;
+                Some(UnitExpression::Delta {
+                    unit: unit.span,
+                    span: Span::new(token.span.start, unit.span.end),
+                })
+            }
+            TokenKind::Identifier => Some(UnitExpression::Atom(token.span)),
+            TokenKind::LeftParen => {
+                let inner = self.unit_expression()?;
+                let closing = self
+                    .take_nontrivia()
+                    .filter(|closing| closing.kind == TokenKind::RightParen)?;
+                Some(UnitExpression::Group {
+                    inner: Box::new(inner),
+                    span: Span::new(token.span.start, closing.span.end),
+                })
+            }
+            _ => None,
+        }
+    }
+
     fn skip_separators(&mut self) -> bool {
         while let Some(token) = self.tokens.get(self.cursor) {
             if token.kind.is_trivia() {
@@ -2530,6 +2772,40 @@ impl Parser<'_> {
         });
     }
 
+    /// Mark the most recent diagnostic as resolvable by `expected` when it is
+    /// the only diagnostic and no source remains after it. Earlier errors
+    /// cannot be repaired by further input.
+    fn continues_with(&mut self, expected: Continuation) {
+        if self.incomplete.is_none()
+            && self.diagnostics.len() == 1
+            && self.tokens[self.cursor..]
+                .iter()
+                .all(|token| token.kind.is_trivia())
+            && let Some(diagnostic) = self.diagnostics.last()
+        {
+            self.incomplete = Some(IncompleteInput {
+                expected,
+                span: diagnostic.span,
+            });
+        }
+    }
+
+    /// Adopt the diagnostics of a parser over this parser's tokens up to
+    /// `end`, and its continuation when only trivia follows `end`, since the
+    /// nested parser's input then also ends this parser's input.
+    fn absorb(&mut self, nested: Parser<'_>, end: usize) {
+        self.diagnostics.extend(nested.diagnostics);
+        if let Some(incomplete) = nested.incomplete
+            && self.incomplete.is_none()
+            && self.diagnostics.len() == 1
+            && self.tokens[end..]
+                .iter()
+                .all(|token| token.kind.is_trivia())
+        {
+            self.incomplete = Some(incomplete);
+        }
+    }
+
     fn skip_to_newline(&mut self) {
         while self
             .peek()
@@ -2713,6 +2989,62 @@ mod tests {
         assert!(parsed.diagnostics.is_empty());
     }
 
+    #[test]
+    fn parses_unit_suffixes_left_to_right_with_groups() {
+        let source = SourceText::new("9.81[kg * m / (s ^ 2)] 5[delta Celsius]").unwrap();
+        let parsed = parse(&source, &lex(&source));
+        assert!(parsed.diagnostics.is_empty());
+        let Statement::Expression(Expression::Application { items, .. }) = &parsed.statements[0]
+        else {
+            panic!("expected application");
+        };
+        let Expression::Measured {
+            unit: UnitExpression::Quotient { left, right, .. },
+            ..
+        } = &items[0]
+        else {
+            panic!("expected quotient unit");
+        };
+        assert!(matches!(**left, UnitExpression::Product { .. }));
+        let UnitExpression::Group { inner, .. } = &**right else {
+            panic!("expected grouped divisor");
+        };
+        assert!(matches!(**inner, UnitExpression::Power { .. }));
+        assert!(matches!(
+            items[1],
+            Expression::Measured {
+                unit: UnitExpression::Delta { .. },
+                ..
+            }
+        ));
+
+        let malformed = SourceText::new("1[kg * ] + 2").unwrap();
+        let parsed = parse(&malformed, &lex(&malformed));
+        assert_eq!(parsed.diagnostics[0].code, "E-MEASUREMENT-SUFFIX");
+    }
+
+    #[test]
+    fn parses_sensitive_classifiers_around_their_payload() {
+        let source = SourceText::new(
+            "secret : Sensitive List String is Empty\nstep : Delta Temperature is delta",
+        )
+        .unwrap();
+        let parsed = parse(&source, &lex(&source));
+        assert!(parsed.diagnostics.is_empty());
+        let classifiers = parsed
+            .statements
+            .iter()
+            .map(|statement| match statement {
+                Statement::Binding {
+                    classifier: Some(classifier),
+                    ..
+                } => source.slice(*classifier),
+                _ => panic!("expected classified binding"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(classifiers, ["Sensitive List String", "Delta Temperature"]);
+    }
+
     #[test]
     fn parses_the_zero_field_product_as_unit() {
         let source = SourceText::new("()").unwrap();
@@ -2781,6 +3113,81 @@ mod tests {
         let source = SourceText::new("(\n1,").unwrap();
         let parsed = parse(&source, &lex(&source));
         assert_eq!(parsed.diagnostics[0].code, "E-EXPECTED-RPAREN");
+        assert_eq!(
+            parsed.incomplete.map(|incomplete| incomplete.expected),
+            Some(Continuation::ClosingParenthesis)
+        );
+    }
+
+    #[test]
+    fn reports_the_continuation_of_input_ending_inside_a_construct() {
+        for (text, expected) in [
+            ("\"open\n", Continuation::StringTerminator),
+            ("(1,\n", Continuation::ClosingParenthesis),
+            ("total is {\n  value is 20\n", Continuation::ClosingBrace),
+            ("total is {\n  {\n", Continuation::ClosingBrace),
+            (
+                "total is {\n  value is (1,\n",
+                Continuation::ClosingParenthesis,
+            ),
+            (
+                "total is {\n  double is fn (value : Int) -> Int\n",
+                Continuation::IndentedBody,
+            ),
+            (
+                "double is fn (value : Int) -> Int\n",
+                Continuation::IndentedBody,
+            ),
+            ("values foreach { value }\n", Continuation::IndentedBody),
+            (
+                "count is generator (start : Int)\n",
+                Continuation::GeneratorHeader,
+            ),
+            (
+                "count is generator (start : Int)\n  yields Int\n  resumes Unit\n  -> Unit\n",
+                Continuation::IndentedBody,
+            ),
+            ("Shape is Union\n", Continuation::UnionAlternatives),
+            ("Reader is Interface\n", Continuation::InterfaceOperations),
+            (
+                "choose is fn (condition : Boolean) -> Int\n  condition\n    true then 1\n",
+                Continuation::DecisionRules,
+            ),
+        ] {
+            let source = SourceText::new(text).unwrap();
+            let parsed = parse(&source, &lex(&source));
+            let incomplete = parsed.incomplete.unwrap_or_else(|| panic!("{text:?}"));
+            assert_eq!(incomplete.expected, expected, "{text:?}");
+            assert!(
+                parsed
+                    .diagnostics
+                    .iter()
+                    .any(|diagnostic| diagnostic.span == incomplete.span),
+                "{text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn errors_before_the_end_of_input_are_not_continuations() {
+        for text in [
+            "1 \u{200b} 2\n",
+            "1 )\n",
+            "(1 ]\n",
+            "1 \u{200b} \"open\n",
+            "double is fn (value : Int) -> Int\nvalue\n",
+            "total is {\n  1 )\n",
+            "total is { (1, }\n",
+            "total is { double is fn (value : Int) -> Int }\n",
+            "values foreach { value }\n  value\n(1, foreach { value }\n",
+        ] {
+            let source = SourceText::new(text).unwrap();
+            let parsed = parse(&source, &lex(&source));
+            assert!(!parsed.diagnostics.is_empty(), "{text:?}");
+            assert_eq!(parsed.incomplete, None, "{text:?}");
+        }
+        let source = SourceText::new("(1, 2)\n").unwrap();
+        assert_eq!(parse(&source, &lex(&source)).incomplete, None);
     }
 
     #[test]
@@ -2888,6 +3295,20 @@ mod tests {
         assert_eq!(parsed.diagnostics[0].code, "E-RESERVED-BOOLEAN-LITERAL");
     }
 
+    #[test]
+    fn keeps_a_binding_followed_by_a_line_of_spaces() {
+        let source = SourceText::new("pair is (1,\n  2)\n  \n").unwrap();
+        let parsed = parse(&source, &lex(&source));
+        assert!(parsed.diagnostics.is_empty());
+        assert!(matches!(
+            parsed.statements.as_slice(),
+            [Statement::Binding {
+                value: Expression::Product { .. },
+                ..
+            }]
+        ));
+    }
+
     #[test]
     fn parses_static_nullary_function_with_indented_body() {
         let source = SourceText::new("answer is fn static () -> Int\n  40 + 2\nanswer ()").unwrap();